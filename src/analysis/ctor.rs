@@ -1,10 +1,15 @@
+use std::collections::BTreeMap;
+
 use anyhow::{bail, Context, Result};
 use ds_rom::rom::Arm9;
-use unarm::args::Argument;
+use unarm::{args::Argument, Endian};
 
-use crate::config::section::Sections;
+use crate::{
+    config::{section::Sections, symbol::InstructionMode},
+    util::bytes::FromSlice,
+};
 
-use super::functions::{Function, FunctionParseOptions, ParseFunctionResult};
+use super::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
 
 #[derive(Debug)]
 pub struct CtorRange {
@@ -13,9 +18,9 @@ pub struct CtorRange {
 }
 
 impl CtorRange {
-    fn find_last_function_call(function: Function, module_code: &[u8], base_address: u32) -> Option<u32> {
+    fn find_last_function_call(function: Function, module_code: &[u8], base_address: u32, endian: Endian) -> Option<u32> {
         let mut last_called_function = None;
-        for (address, _ins, parsed_ins) in function.parser(module_code, base_address) {
+        for (address, _ins, parsed_ins) in function.parser(module_code, base_address, endian) {
             if !parsed_ins.mnemonic.starts_with("bl") {
                 continue;
             }
@@ -28,7 +33,11 @@ impl CtorRange {
         last_called_function
     }
 
-    pub fn find_in_arm9(arm9: &Arm9) -> Result<Self> {
+    pub fn find_in_arm9(
+        arm9: &Arm9,
+        endian: Endian,
+        instruction_modes: Option<&BTreeMap<u32, InstructionMode>>,
+    ) -> Result<Self> {
         let code = arm9.code()?;
 
         let entry_addr = arm9.entry_function();
@@ -41,15 +50,15 @@ impl CtorRange {
             known_end_address: None,
             module_start_address: arm9.base_address(),
             module_end_address: arm9.end_address()?,
-            parse_options: Default::default(),
+            parse_options: ParseFunctionOptions { instruction_modes, endian, ..Default::default() },
         })?;
         let entry_func = match parse_result {
             ParseFunctionResult::Found(function) => function,
             _ => bail!("failed to analyze entrypoint function: {:?}", parse_result),
         };
 
-        let run_inits_addr =
-            Self::find_last_function_call(entry_func, entry_code, entry_addr).context("no function calls in entrypoint")?;
+        let run_inits_addr = Self::find_last_function_call(entry_func, entry_code, entry_addr, endian)
+            .context("no function calls in entrypoint")?;
         let run_inits_code = &code[(run_inits_addr - arm9.base_address()) as usize..];
         let parse_result = Function::parse_function(FunctionParseOptions {
             name: "run_inits".to_string(),
@@ -59,7 +68,7 @@ impl CtorRange {
             known_end_address: None,
             module_start_address: arm9.base_address(),
             module_end_address: arm9.end_address()?,
-            parse_options: Default::default(),
+            parse_options: ParseFunctionOptions { instruction_modes, endian, ..Default::default() },
         })?;
         let run_inits_func = match parse_result {
             ParseFunctionResult::Found(function) => function,
@@ -69,11 +78,11 @@ impl CtorRange {
         let p_ctor_start =
             run_inits_func.pool_constants().first().context("no pool constants found in static initializer function")?;
         let ctor_start_data = &code[(p_ctor_start - arm9.base_address()) as usize..];
-        let ctor_start = u32::from_le_bytes([ctor_start_data[0], ctor_start_data[1], ctor_start_data[2], ctor_start_data[3]]);
+        let ctor_start = u32::from_slice(ctor_start_data, endian);
 
         let num_ctors = code[(ctor_start - arm9.base_address()) as usize..]
             .chunks_exact(4)
-            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .map(|c| u32::from_slice(c, endian))
             .position(|ctor| ctor == 0)
             .unwrap();
 
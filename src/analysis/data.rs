@@ -3,14 +3,12 @@ use std::ops::Range;
 use anyhow::Result;
 use snafu::Snafu;
 
-use crate::{
-    config::{
-        module::{AnalysisOptions, Module, ModuleKind},
-        relocation::{Relocation, RelocationModule, Relocations},
-        section::{Section, SectionKind, Sections},
-        symbol::{SymBss, SymData, SymbolMap, SymbolMaps},
-    },
-    function,
+use crate::config::{
+    module::{AnalysisOptions, Module, ModuleKind},
+    overlay_groups::ExclusiveOverlayGroups,
+    relocation::{Relocation, RelocationModule, Relocations},
+    section::{Section, SectionKind, Sections},
+    symbol::{SymBss, SymData, SymbolMap, SymbolMaps},
 };
 
 use super::functions::Function;
@@ -35,7 +33,7 @@ pub fn find_local_data_from_pools(
     let FindLocalDataOptions { sections, module_kind, symbol_map, relocations, name_prefix, code, base_address, .. } = options;
     let address_range = None;
 
-    for pool_constant in function.iter_pool_constants(code, base_address) {
+    for pool_constant in function.iter_pool_constants(code, base_address, analysis_options.endian) {
         let pointer = pool_constant.value;
         let Some((_, section)) = sections.get_by_contained_address(pointer) else {
             // Not a pointer, or points to a different module
@@ -44,14 +42,13 @@ pub fn find_local_data_from_pools(
         if section.kind() == SectionKind::Code && symbol_map.get_function(pointer & !1)?.is_some() {
             // Relocate function pointer
             let reloc = relocations.add_load(pool_constant.address, pointer, 0, module_kind.try_into()?)?;
-            if analysis_options.provide_reloc_source {
-                reloc.source = Some(function!().to_string());
-            }
+            reloc.source = analysis_options.reloc_source(|| format!("function pointer in pool of {}", function.name()));
         } else {
             add_symbol_from_pointer(
                 section,
                 pool_constant.address,
                 pointer,
+                analysis_options.reloc_source(|| format!("pointer in pool of {}", function.name())),
                 FindLocalDataOptions {
                     sections,
                     module_kind,
@@ -62,11 +59,36 @@ pub fn find_local_data_from_pools(
                     base_address,
                     address_range: address_range.clone(),
                 },
+                PointerKind::Word,
                 analysis_options,
             )?;
         }
     }
 
+    for (&address, &pointer) in function.data_loads() {
+        let Some((_, section)) = sections.get_by_contained_address(pointer) else {
+            continue;
+        };
+        add_symbol_from_pointer(
+            section,
+            address,
+            pointer,
+            analysis_options.reloc_source(|| format!("address computed in {}", function.name())),
+            FindLocalDataOptions {
+                sections,
+                module_kind,
+                symbol_map,
+                relocations,
+                name_prefix,
+                code,
+                base_address,
+                address_range: address_range.clone(),
+            },
+            PointerKind::Adr { thumb: function.is_thumb() },
+            analysis_options,
+        )?;
+    }
+
     Ok(())
 }
 
@@ -79,15 +101,16 @@ pub fn find_local_data_from_section(
 
     let address_range = options.address_range.clone().unwrap_or(section.address_range());
 
-    for word in section.iter_words(code, Some(address_range.clone())) {
+    for word in section.iter_words(code, Some(address_range.clone()), analysis_options.endian) {
         let pointer = word.value;
-        let Some((_, section)) = options.sections.get_by_contained_address(pointer) else {
+        let Some((_, pointer_section)) = options.sections.get_by_contained_address(pointer) else {
             continue;
         };
         add_symbol_from_pointer(
-            section,
+            pointer_section,
             word.address,
             pointer,
+            analysis_options.reloc_source(|| format!("data pointer in {}", section.name())),
             FindLocalDataOptions {
                 sections,
                 module_kind,
@@ -98,17 +121,46 @@ pub fn find_local_data_from_section(
                 base_address,
                 address_range: Some(address_range.clone()),
             },
+            PointerKind::Word,
             analysis_options,
         )?;
     }
     Ok(())
 }
 
+/// How the pointer to a symbol was found, which decides the kind of relocation
+#[derive(Clone, Copy)]
+enum PointerKind {
+    /// 32-bit word in a pool or data section
+    Word,
+    /// Address computed with `add/sub *, pc, #*`
+    Adr { thumb: bool },
+}
+
+impl PointerKind {
+    fn add_relocation(
+        self,
+        relocations: &mut Relocations,
+        from: u32,
+        to: u32,
+        addend: i32,
+        module: RelocationModule,
+    ) -> Result<&mut Relocation> {
+        match self {
+            PointerKind::Word => relocations.add_load(from, to, addend, module),
+            PointerKind::Adr { thumb } => relocations.add_adr(from, to, addend, module, thumb),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn add_symbol_from_pointer(
     section: &Section,
     address: u32,
     pointer: u32,
+    source: Option<String>,
     options: FindLocalDataOptions,
+    pointer_kind: PointerKind,
     analysis_options: &AnalysisOptions,
 ) -> Result<()> {
     let FindLocalDataOptions { module_kind, symbol_map, relocations, name_prefix, .. } = options;
@@ -121,7 +173,7 @@ fn add_symbol_from_pointer(
             if let Some((function, _)) = symbol_map.get_function(pointer)? {
                 // Instruction mode must match
                 if function.mode.into_thumb() == Some(thumb) {
-                    relocations.add_load(address, pointer, 0, module_kind.try_into()?)?
+                    pointer_kind.add_relocation(relocations, address, pointer, 0, module_kind.try_into()?)?
                 } else {
                     return Ok(());
                 }
@@ -131,16 +183,19 @@ fn add_symbol_from_pointer(
         }
         SectionKind::Data => {
             symbol_map.add_data(Some(name), pointer, SymData::Any)?;
-            relocations.add_load(address, pointer, 0, module_kind.try_into()?)?
+            pointer_kind.add_relocation(relocations, address, pointer, 0, module_kind.try_into()?)?
         }
         SectionKind::Bss => {
-            symbol_map.add_bss(Some(name), pointer, SymBss { size: None })?;
-            relocations.add_load(address, pointer, 0, module_kind.try_into()?)?
+            // Pointers into a bss symbol of known size, such as a struct field, are relocated to the symbol itself
+            let symbol_address = match symbol_map.get_bss_containing(pointer) {
+                Some((_, symbol)) => symbol.addr,
+                None => symbol_map.add_bss(Some(name), pointer, SymBss { size: None })?.1.addr,
+            };
+            let addend = (pointer - symbol_address) as i32;
+            pointer_kind.add_relocation(relocations, address, symbol_address, addend, module_kind.try_into()?)?
         }
     };
-    if analysis_options.provide_reloc_source {
-        reloc.source = Some(function!().to_string());
-    }
+    reloc.source = source;
 
     Ok(())
 }
@@ -163,11 +218,16 @@ pub fn analyze_external_references(
         AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps },
         analysis_options,
     )?;
-    find_external_references_in_sections(modules, module_index, &mut result)?;
+    find_external_references_in_sections(modules, module_index, &mut result, analysis_options)?;
     Ok(result)
 }
 
-fn find_external_references_in_sections(modules: &[Module], module_index: usize, result: &mut RelocationResult) -> Result<()> {
+fn find_external_references_in_sections(
+    modules: &[Module],
+    module_index: usize,
+    result: &mut RelocationResult,
+    analysis_options: &AnalysisOptions,
+) -> Result<()> {
     for section in modules[module_index].sections().iter() {
         match section.kind() {
             SectionKind::Data => {}
@@ -175,8 +235,9 @@ fn find_external_references_in_sections(modules: &[Module], module_index: usize,
         }
 
         let code = section.code(modules[module_index].code(), modules[module_index].base_address())?.unwrap();
-        for word in section.iter_words(code, None) {
-            find_external_data(modules, module_index, word.address, word.value, result)?;
+        for word in section.iter_words(code, None, analysis_options.endian) {
+            let source = analysis_options.reloc_source(|| format!("data pointer in {}", section.name()));
+            find_external_data(modules, module_index, word.address, word.value, source, result, analysis_options)?;
         }
     }
     Ok(())
@@ -197,7 +258,7 @@ fn find_relocations_in_functions(
                 AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps },
                 analysis_options,
             )?;
-            find_external_data_from_pools(modules, module_index, function, result)?;
+            find_external_data_from_pools(modules, module_index, function, result, analysis_options)?;
         }
     }
     Ok(())
@@ -218,11 +279,14 @@ fn add_function_calls_as_relocations(
     let AnalyzeExternalReferencesOptions { modules, module_index, symbol_maps } = options;
 
     for (&address, &called_function) in function.function_calls() {
-        if called_function.ins.is_conditional() {
+        if called_function.ins.is_conditional() && !analysis_options.relocate_function_branches {
             // Dumb mwld linker bug removes the condition code from relocated call instructions
             continue;
         }
 
+        // Absolute jumps like `ldr pc, =*` have no branch offset to relocate, their pool constant is relocated instead
+        let is_absolute_jump = !matches!(called_function.ins.mnemonic(), "b" | "bl" | "blx");
+
         let local_module = &modules[module_index];
         let is_local = local_module.sections().get_by_contained_address(called_function.address).is_some();
 
@@ -231,6 +295,15 @@ fn add_function_calls_as_relocations(
             let symbol_map = symbol_maps.get_mut(module_kind);
             let symbol = match symbol_map.get_function_containing(called_function.address) {
                 Some((_, symbol)) => symbol,
+                None if is_absolute_jump => {
+                    log::debug!(
+                        "Absolute jump from {:#010x} in {} to {:#010x} leads to no function",
+                        address,
+                        module_kind,
+                        called_function.address
+                    );
+                    continue;
+                }
                 None => {
                     if !analysis_options.allow_unknown_function_calls {
                         let error =
@@ -259,16 +332,30 @@ fn add_function_calls_as_relocations(
 
             module_kind.try_into()?
         } else {
-            let candidates = modules.iter().filter(|&module| {
-                let symbol_map = symbol_maps.get(module.kind()).unwrap();
-                let Some((function, _)) = symbol_map.get_function(called_function.address).unwrap() else {
-                    return false;
-                };
-                function.mode.into_thumb() == Some(called_function.thumb)
-            });
-            RelocationModule::from_modules(candidates)?
+            let local_kind = local_module.kind();
+            let mut candidates = modules
+                .iter()
+                .filter(|&module| {
+                    if analysis_options.overlay_groups.are_exclusive(local_kind, module.kind()) {
+                        return false;
+                    }
+                    let symbol_map = symbol_maps.get(module.kind()).unwrap();
+                    let Some((function, _)) = symbol_map.get_function(called_function.address).unwrap() else {
+                        return false;
+                    };
+                    function.mode.into_thumb() == Some(called_function.thumb)
+                })
+                .collect::<Vec<_>>();
+            if candidates.iter().any(|module| module.kind().is_tcm()) {
+                candidates.retain(|module| module.kind().is_tcm());
+            }
+            RelocationModule::from_modules(candidates.into_iter())?
         };
 
+        if is_absolute_jump {
+            continue;
+        }
+
         if module == RelocationModule::None {
             log::warn!(
                 "No functions from {address:#010x} in {} to {:#010x}:",
@@ -278,15 +365,14 @@ fn add_function_calls_as_relocations(
         }
 
         if called_function.ins.mnemonic() == "b" {
-            result.relocations.push(Relocation::new_branch(address, called_function.address, module));
+            let source = analysis_options.reloc_source(|| format!("tail call from {}", function.name()));
+            result.relocations.push(Relocation::new_branch(address, called_function.address, module).with_source(source));
         } else {
-            result.relocations.push(Relocation::new_call(
-                address,
-                called_function.address,
-                module,
-                function.is_thumb(),
-                called_function.thumb,
-            ));
+            let source = analysis_options.reloc_source(|| format!("call from {}", function.name()));
+            result.relocations.push(
+                Relocation::new_call(address, called_function.address, module, function.is_thumb(), called_function.thumb)
+                    .with_source(source),
+            );
         }
     }
     Ok(())
@@ -297,10 +383,20 @@ fn find_external_data_from_pools(
     module_index: usize,
     function: &Function,
     result: &mut RelocationResult,
+    analysis_options: &AnalysisOptions,
 ) -> Result<()> {
     let module = &modules[module_index];
-    for pool_constant in function.iter_pool_constants(module.code(), module.base_address()) {
-        find_external_data(modules, module_index, pool_constant.address, pool_constant.value, result)?;
+    for pool_constant in function.iter_pool_constants(module.code(), module.base_address(), analysis_options.endian) {
+        let source = analysis_options.reloc_source(|| format!("pointer in pool of {}", function.name()));
+        find_external_data(
+            modules,
+            module_index,
+            pool_constant.address,
+            pool_constant.value,
+            source,
+            result,
+            analysis_options,
+        )?;
     }
     Ok(())
 }
@@ -310,7 +406,9 @@ fn find_external_data(
     module_index: usize,
     address: u32,
     pointer: u32,
+    source: Option<String>,
     result: &mut RelocationResult,
+    analysis_options: &AnalysisOptions,
 ) -> Result<()> {
     let local_module = &modules[module_index];
     let is_local = local_module.sections().get_by_contained_address(pointer).is_some();
@@ -318,7 +416,7 @@ fn find_external_data(
         return Ok(());
     }
 
-    let candidates = find_symbol_candidates(modules, module_index, pointer);
+    let candidates = find_symbol_candidates(modules, module_index, pointer, &analysis_options.overlay_groups);
     if candidates.is_empty() {
         // Probably not a pointer
         return Ok(());
@@ -327,19 +425,29 @@ fn find_external_data(
     let candidate_modules = candidates.iter().map(|c| &modules[c.module_index]);
     let module = RelocationModule::from_modules(candidate_modules)?;
 
-    result.relocations.push(Relocation::new_load(address, pointer, 0, module));
+    result.relocations.push(Relocation::new_load(address, pointer, 0, module).with_source(source));
     result.external_symbols.push(ExternalSymbol { candidates, address: pointer });
     Ok(())
 }
 
-fn find_symbol_candidates(modules: &[Module], module_index: usize, pointer: u32) -> Vec<SymbolCandidate> {
-    modules
+fn find_symbol_candidates(
+    modules: &[Module],
+    module_index: usize,
+    pointer: u32,
+    overlay_groups: &ExclusiveOverlayGroups,
+) -> Vec<SymbolCandidate> {
+    let local_kind = modules[module_index].kind();
+    let mut candidates = modules
         .iter()
         .enumerate()
         .filter_map(|(index, module)| {
             if index == module_index {
                 return None;
             }
+            // Overlays in the same group are never loaded together, so they can't refer to each other
+            if overlay_groups.are_exclusive(local_kind, module.kind()) {
+                return None;
+            }
             let (section_index, section) = module.sections().get_by_contained_address(pointer)?;
             if section.kind() == SectionKind::Code {
                 let function = section.functions().get(&(pointer & !1))?;
@@ -350,7 +458,13 @@ fn find_symbol_candidates(modules: &[Module], module_index: usize, pointer: u32)
             };
             Some(SymbolCandidate { module_index: index, section_index })
         })
-        .collect::<Vec<_>>()
+        .collect::<Vec<_>>();
+
+    // The ITCM and DTCM never get unloaded, so a pointer into their address range always refers to them
+    if candidates.iter().any(|candidate| modules[candidate.module_index].kind().is_tcm()) {
+        candidates.retain(|candidate| modules[candidate.module_index].kind().is_tcm());
+    }
+    candidates
 }
 
 pub struct RelocationResult {
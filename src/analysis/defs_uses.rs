@@ -0,0 +1,158 @@
+use unarm::{
+    args::{Argument as Arg, OffsetReg, Reg, RegList, Register, ShiftReg},
+    Ins, ParsedIns,
+};
+
+/// Callee-saved registers in ARM code. Compiled functions save these before using them.
+const ARM_CALLEE_SAVED: [Register; 8] =
+    [Register::R4, Register::R5, Register::R6, Register::R7, Register::R8, Register::R9, Register::R10, Register::R11];
+
+/// Callee-saved registers in Thumb code. The high registers are left out, since Thumb functions read them when moving them
+/// to low registers to save them.
+const THUMB_CALLEE_SAVED: [Register; 4] = [Register::R4, Register::R5, Register::R6, Register::R7];
+
+/// Detects functions which read a callee-saved register before saving or writing it, which compiled code never does. Such
+/// code is most likely data or the middle of another function. Handwritten assembly doesn't always follow the calling
+/// convention, so this check should be turned off for it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DefsUsesState {
+    thumb: bool,
+    /// Bit N is set if register N of the callee-saved registers has been defined
+    defined: u8,
+    illegal: bool,
+}
+
+impl DefsUsesState {
+    pub fn new(thumb: bool) -> Self {
+        Self { thumb, defined: 0, illegal: false }
+    }
+
+    fn callee_saved(&self) -> &'static [Register] {
+        if self.thumb {
+            &THUMB_CALLEE_SAVED
+        } else {
+            &ARM_CALLEE_SAVED
+        }
+    }
+
+    fn use_reg(&mut self, reg: Register) {
+        if let Some(index) = self.callee_saved().iter().position(|&saved| saved == reg) {
+            if self.defined & (1 << index) == 0 {
+                self.illegal = true;
+            }
+        }
+    }
+
+    fn define_reg(&mut self, reg: Register) {
+        if let Some(index) = self.callee_saved().iter().position(|&saved| saved == reg) {
+            self.defined |= 1 << index;
+        }
+    }
+
+    fn use_reg_list(&mut self, list: RegList) {
+        for &reg in self.callee_saved() {
+            if list.contains(reg) {
+                self.use_reg(reg);
+            }
+        }
+    }
+
+    fn define_reg_list(&mut self, list: RegList) {
+        for &reg in self.callee_saved() {
+            if list.contains(reg) {
+                self.define_reg(reg);
+            }
+        }
+    }
+
+    /// Marks every register argument from `skip` onwards as used.
+    fn use_args(&mut self, args: &[Arg], skip: usize) {
+        for arg in args.iter().skip(skip) {
+            match *arg {
+                Arg::Reg(Reg { reg, .. }) | Arg::ShiftReg(ShiftReg { reg, .. }) | Arg::OffsetReg(OffsetReg { reg, .. }) => {
+                    self.use_reg(reg)
+                }
+                Arg::RegList(list) => self.use_reg_list(list),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn handle(mut self, ins: Ins, parsed_ins: &ParsedIns) -> Self {
+        if self.illegal {
+            return self;
+        }
+
+        let args = &parsed_ins.args;
+        let mnemonic = ins.mnemonic();
+        match (mnemonic, args[0], args[1]) {
+            // Saving registers on the stack
+            ("push", Arg::RegList(list), _) => self.define_reg_list(list),
+            (_, Arg::Reg(Reg { reg: Register::Sp, .. }), Arg::RegList(list)) if mnemonic.starts_with("stm") => {
+                self.define_reg_list(list)
+            }
+
+            // Saving registers on the stack one at a time
+            ("strd", Arg::Reg(Reg { reg: first, .. }), Arg::Reg(Reg { reg: second, .. }))
+                if matches!(args[2], Arg::Reg(Reg { reg: Register::Sp, deref: true, .. })) =>
+            {
+                self.use_args(args, 3);
+                self.define_reg(first);
+                self.define_reg(second);
+            }
+            (_, Arg::Reg(Reg { reg, .. }), Arg::Reg(Reg { reg: Register::Sp, deref: true, .. }))
+                if mnemonic.starts_with("str") && mnemonic != "strd" =>
+            {
+                self.use_args(args, 2);
+                self.define_reg(reg);
+            }
+
+            // Loading registers
+            ("pop", Arg::RegList(list), _) => self.define_reg_list(list),
+            (_, Arg::Reg(Reg { reg: base, .. }), Arg::RegList(list)) if mnemonic.starts_with("ldm") => {
+                self.use_reg(base);
+                self.define_reg_list(list);
+            }
+            ("ldrd", Arg::Reg(Reg { reg: first, .. }), Arg::Reg(Reg { reg: second, .. }))
+            | ("umull", Arg::Reg(Reg { reg: first, .. }), Arg::Reg(Reg { reg: second, .. }))
+            | ("umulls", Arg::Reg(Reg { reg: first, .. }), Arg::Reg(Reg { reg: second, .. }))
+            | ("smull", Arg::Reg(Reg { reg: first, .. }), Arg::Reg(Reg { reg: second, .. }))
+            | ("smulls", Arg::Reg(Reg { reg: first, .. }), Arg::Reg(Reg { reg: second, .. })) => {
+                self.use_args(args, 2);
+                self.define_reg(first);
+                self.define_reg(second);
+            }
+            ("mrc", _, _) => {
+                for arg in args {
+                    if let Arg::Reg(Reg { reg, .. }) = *arg {
+                        self.define_reg(reg);
+                    }
+                }
+            }
+
+            // Instructions which only read their registers
+            (_, _, _)
+                if mnemonic.starts_with("str")
+                    || mnemonic.starts_with("stm")
+                    || matches!(mnemonic, "cmp" | "cmn" | "tst" | "teq" | "bx" | "blx" | "mcr" | "msr")
+                    || mnemonic.starts_with("smlal")
+                    || mnemonic.starts_with("umlal") =>
+            {
+                self.use_args(args, 0)
+            }
+
+            // Anything else writes its first register and reads the rest
+            (_, Arg::Reg(Reg { reg, deref: false, .. }), _) => {
+                self.use_args(args, 1);
+                self.define_reg(reg);
+            }
+            _ => self.use_args(args, 0),
+        }
+
+        self
+    }
+
+    pub fn is_illegal(self) -> bool {
+        self.illegal
+    }
+}
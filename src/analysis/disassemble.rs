@@ -0,0 +1,381 @@
+use std::{
+    fs::create_dir_all,
+    io::{self, Write},
+    ops::Range,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use path_slash::PathExt;
+use unarm::Endian;
+
+use crate::{
+    config::{
+        module::ModuleKind,
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, SymData, Symbol, SymbolKind, SymbolLookup, SymbolMap, SymbolMaps},
+    },
+    util::{address::AddressFormat, io::write_file},
+};
+
+use super::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
+
+/// Writes the assembly of a module's sections. Used by the `dis` command and [`disassemble_slice`].
+pub struct AssemblyWriter<'a> {
+    /// Symbols and relocations of the module
+    pub symbol_lookup: SymbolLookup<'a>,
+    /// Code of the whole module
+    pub code: &'a [u8],
+    pub base_address: u32,
+    /// Sections of the module, which contain its analyzed functions
+    pub sections: &'a Sections,
+    /// Emits GNU assembler `.type`, `.size`, `.arm`, `.thumb` and `.thumb_func` directives.
+    pub ual: bool,
+    /// Functions and data outside of this range are left out.
+    pub range: Range<u32>,
+    /// Writes large data symbols to binary files, if set.
+    pub incbin: Option<Incbin<'a>>,
+}
+
+/// Data symbols of at least `threshold` bytes are written to `<asm_path>/<dir>/<symbol>.bin` and included with `.incbin`,
+/// unless they contain relocations or other symbols.
+pub struct Incbin<'a> {
+    pub threshold: u32,
+    /// Assembly output path, relative to the directory which the assembler runs in
+    pub asm_path: &'a Path,
+    /// Directory of the binary files, relative to the assembly output path
+    pub dir: String,
+}
+
+impl AssemblyWriter<'_> {
+    /// Writes the section directive and contents of `section`, unless it's outside of [`Self::range`].
+    pub fn write_section<W: io::Write>(&self, writer: &mut W, section: &Section) -> Result<()> {
+        if !self.in_range(section.address_range()) {
+            return Ok(());
+        }
+
+        match section.name() {
+            ".text" => writeln!(writer, "    .text")?,
+            _ => writeln!(writer, "    .section {}, 4, 1, 4", section.name())?,
+        }
+
+        let symbol_map = self.symbol_lookup.symbol_map;
+        let code = section.code(self.code, self.base_address)?;
+        let mut offset = 0; // offset within section
+
+        // Assembler mode since the start of the section, only written in UAL mode
+        let mut current_mode = None;
+
+        let mut symbol_iter = symbol_map.iter_by_address(section.address_range()).peekable();
+        while let Some(symbol) = symbol_iter.next() {
+            debug_assert!(symbol.addr >= section.start_address() && symbol.addr < section.end_address());
+
+            // Functions and data partially in range are included whole
+            let symbol_end = match &symbol.kind {
+                SymbolKind::Function(sym_function) => symbol.addr + sym_function.size.max(1),
+                SymbolKind::Data(data) => {
+                    symbol.addr + data.size().unwrap_or_else(|| Self::size_to_next_symbol(section, symbol, symbol_iter.peek()))
+                }
+                SymbolKind::Bss(bss) => {
+                    symbol.addr + bss.size.unwrap_or_else(|| Self::size_to_next_symbol(section, symbol, symbol_iter.peek()))
+                }
+                _ => continue,
+            };
+            if !self.in_range(symbol.addr..symbol_end) {
+                offset = offset.max(symbol_end.min(section.end_address()) - section.start_address());
+                continue;
+            }
+
+            match &symbol.kind {
+                SymbolKind::Function(sym_function) => {
+                    if sym_function.unknown {
+                        let function_offset = symbol.addr - section.start_address();
+                        if offset < function_offset && self.in_range(Self::offset_range(section, offset, function_offset)) {
+                            write_bytes(writer, code.unwrap(), offset, function_offset)?;
+                            writeln!(writer)?;
+                            offset = function_offset;
+                        }
+
+                        if self.ual {
+                            Self::write_mode_directive(writer, sym_function.mode, &mut current_mode)?;
+                            symbol.write_type_directive(writer)?;
+                        }
+                        writeln!(writer, "    .global {}", symbol.name)?;
+                        match sym_function.mode {
+                            InstructionMode::Arm => writeln!(writer, "    arm_func_start {}", symbol.name)?,
+                            InstructionMode::Thumb => writeln!(writer, "    thumb_func_start {}", symbol.name)?,
+                        }
+                        if self.ual && sym_function.mode == InstructionMode::Thumb {
+                            writeln!(writer, "    .thumb_func")?;
+                        }
+                        writeln!(writer, "{}: ; {}", symbol.name, self.symbol_lookup.address_format.format(symbol.addr))?;
+
+                        // The function body is unknown, so write its bytes up to the next symbol
+                        let end_address = Self::unknown_function_end(section, symbol_map, symbol);
+                        let end_offset = end_address - section.start_address();
+                        write_bytes(writer, code.unwrap(), function_offset, end_offset)?;
+                        writeln!(writer)?;
+                        if self.ual {
+                            symbol.write_size_directive(writer, end_address)?;
+                        }
+                        offset = end_offset;
+                    } else {
+                        let function = self.get_function(symbol.addr).with_context(|| {
+                            format!(
+                                "Tried to disassemble function symbol '{}' at {:#010x} but the function was not found in the module",
+                                symbol.name, symbol.addr,
+                            )
+                        })?;
+
+                        let function_offset = function.start_address() - section.start_address();
+                        if offset < function_offset && self.in_range(Self::offset_range(section, offset, function_offset)) {
+                            write_bytes(writer, code.unwrap(), offset, function_offset)?;
+                            writeln!(writer)?;
+                        }
+
+                        if self.ual {
+                            Self::write_mode_directive(writer, sym_function.mode, &mut current_mode)?;
+                            symbol.write_type_directive(writer)?;
+                        }
+                        function.write_assembly(writer, &self.symbol_lookup, self.code, self.base_address, self.ual)?;
+                        if self.ual {
+                            symbol.write_size_directive(writer, function.end_address())?;
+                        }
+                        offset = function.end_address() - section.start_address();
+                    }
+                }
+                SymbolKind::Data(data) => {
+                    let start = (symbol.addr - section.start_address()) as usize;
+
+                    let size = data.size().unwrap_or_else(|| Self::size_to_next_symbol(section, symbol, symbol_iter.peek()));
+
+                    let end = start + size as usize;
+                    let bytes = &code.unwrap()[start..end];
+                    if self.ual {
+                        symbol.write_type_directive(writer)?;
+                    }
+                    write!(writer, "{}:", symbol.name)?;
+
+                    if symbol.ambiguous {
+                        write!(writer, " ; ambiguous")?;
+                    }
+                    writeln!(writer)?;
+
+                    match &self.incbin {
+                        Some(incbin) if self.is_incbin(incbin, symbol, data, size) => {
+                            Self::write_incbin(writer, incbin, symbol, bytes)?
+                        }
+                        _ => data.write_assembly(writer, symbol, bytes, &self.symbol_lookup)?,
+                    }
+                    if self.ual {
+                        symbol.write_size_directive(writer, symbol.addr + size)?;
+                    }
+                    offset = end as u32;
+                }
+                SymbolKind::Bss(bss) => {
+                    let size = bss.size.unwrap_or_else(|| Self::size_to_next_symbol(section, symbol, symbol_iter.peek()));
+                    if self.ual {
+                        symbol.write_type_directive(writer)?;
+                    }
+                    writeln!(writer, "{}: .space {:#x}", symbol.name, size)?;
+                    if self.ual {
+                        symbol.write_size_directive(writer, symbol.addr + size)?;
+                    }
+                    offset += size;
+                }
+                _ => {}
+            }
+        }
+
+        let end_offset = section.end_address() - section.start_address();
+        if offset < end_offset && self.in_range(Self::offset_range(section, offset, end_offset)) {
+            if let Some(code) = code {
+                write_bytes(writer, code, offset, end_offset)?;
+                writeln!(writer)?;
+            } else {
+                writeln!(writer, "    .space {:#x}", end_offset - offset)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_function(&self, address: u32) -> Option<&Function> {
+        self.sections.get_by_contained_address(address).and_then(|(_, section)| section.functions().get(&address))
+    }
+
+    /// Writes `.arm` or `.thumb` if `mode` differs from the current mode. Data in between functions doesn't change the
+    /// mode, as the assembler emits `$d` mapping symbols for it on its own.
+    fn write_mode_directive<W: io::Write>(
+        writer: &mut W,
+        mode: InstructionMode,
+        current_mode: &mut Option<InstructionMode>,
+    ) -> Result<()> {
+        if *current_mode != Some(mode) {
+            match mode {
+                InstructionMode::Arm => writeln!(writer, "    .arm")?,
+                InstructionMode::Thumb => writeln!(writer, "    .thumb")?,
+            }
+            *current_mode = Some(mode);
+        }
+        Ok(())
+    }
+
+    /// Returns whether a data symbol is large enough to be included as a binary file and can be written without symbols.
+    fn is_incbin(&self, incbin: &Incbin, symbol: &Symbol, data: &SymData, size: u32) -> bool {
+        if size < incbin.threshold || matches!(data, SymData::Struct { .. }) {
+            return false;
+        }
+        let range = symbol.addr..symbol.addr + size;
+        self.symbol_lookup.relocations.iter_range(range.clone()).next().is_none()
+            && self.symbol_lookup.symbol_map.iter_by_address(range.start + 1..range.end).next().is_none()
+    }
+
+    /// Writes the bytes of a data symbol to a binary file, and includes it with the same path that it was written to. The
+    /// assembler looks up `.incbin` paths from its working directory, so a relative assembly output path only works if the
+    /// assembler runs in the same directory as `dsd`, which is usually the build root.
+    fn write_incbin<W: io::Write>(writer: &mut W, incbin: &Incbin, symbol: &Symbol, bytes: &[u8]) -> Result<()> {
+        let path = incbin.asm_path.join(&incbin.dir).join(format!("{}.bin", symbol.name));
+        create_dir_all(path.parent().unwrap())?;
+        write_file(&path, bytes)?;
+        writeln!(writer, "    .incbin \"{}\"", path.to_slash_lossy())?;
+        Ok(())
+    }
+
+    /// Returns whether `range` overlaps with [`Self::range`].
+    fn in_range(&self, range: Range<u32>) -> bool {
+        range.start < self.range.end && range.end > self.range.start
+    }
+
+    fn offset_range(section: &Section, start_offset: u32, end_offset: u32) -> Range<u32> {
+        section.start_address() + start_offset..section.start_address() + end_offset
+    }
+
+    /// Returns the end address of an unknown function, which is the next function, data or bss symbol in the section.
+    fn unknown_function_end(section: &Section, symbol_map: &SymbolMap, symbol: &Symbol) -> u32 {
+        symbol_map
+            .iter_by_address(symbol.addr + 1..section.end_address())
+            .find(|s| matches!(s.kind, SymbolKind::Function(_) | SymbolKind::Data(_) | SymbolKind::Bss(_)))
+            .map_or(section.end_address(), |s| s.addr)
+    }
+
+    fn size_to_next_symbol(section: &Section, symbol: &Symbol, next: Option<&&Symbol>) -> u32 {
+        if let Some(next_symbol) = next {
+            next_symbol.addr.min(section.end_address()) - symbol.addr
+        } else {
+            section.end_address() - symbol.addr
+        }
+    }
+}
+
+pub struct DisassembleSliceOptions<'a> {
+    /// Module which the code belongs to. Its symbol map names the functions and data in the code.
+    pub module_kind: ModuleKind,
+    /// Address of the first byte of the code.
+    pub base_address: u32,
+    /// Whether functions without a function symbol are in Thumb mode.
+    pub thumb: bool,
+    /// Byte order of the code.
+    pub endian: Endian,
+    /// Name prefixes of functions without a function symbol.
+    pub name_prefixes: &'a NamePrefixes,
+    /// Emits GNU assembler directives, see [`AssemblyWriter::ual`].
+    pub ual: bool,
+}
+
+/// Disassembles `code` into the same assembly as the `dis` command, as one `.text` section. Functions are parsed one after
+/// another from the start of `code`, using the name, mode and size of function symbols in the module's symbol map where
+/// they exist. Bytes after the last function are written as `.byte` directives.
+pub fn disassemble_slice(
+    code: &[u8],
+    symbol_maps: &SymbolMaps,
+    relocations: &Relocations,
+    options: &DisassembleSliceOptions,
+) -> Result<String> {
+    let DisassembleSliceOptions { module_kind, base_address, thumb, endian, name_prefixes, ual } = *options;
+    let end_address = base_address + code.len() as u32;
+
+    // Labels and pool constants found during analysis are added to a copy, the caller's map is left untouched
+    let mut symbol_map = symbol_maps.get(module_kind).cloned().unwrap_or_else(SymbolMap::new);
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, base_address, end_address, 4)?)?;
+
+    let func_prefix = name_prefixes.func_prefix(module_kind);
+    let mut address = base_address;
+    while address < end_address {
+        let (name, function_thumb, known_end_address) = match symbol_map.get_function(address)? {
+            Some((function, symbol)) => (
+                symbol.name.clone(),
+                function.mode.into_thumb().unwrap_or(thumb),
+                (function.size > 0).then_some(symbol.addr + function.size),
+            ),
+            None => (format!("{func_prefix}{address:08x}"), thumb, None),
+        };
+
+        let function = match Function::parse_function(FunctionParseOptions {
+            name,
+            start_address: address,
+            base_address,
+            module_code: code,
+            known_end_address,
+            module_start_address: base_address,
+            module_end_address: end_address,
+            parse_options: ParseFunctionOptions { thumb: Some(function_thumb), endian, ..Default::default() },
+        })? {
+            ParseFunctionResult::Found(function) => function,
+            result => {
+                log::debug!("Stopped disassembling at {address:#010x}: {result:?}");
+                break;
+            }
+        };
+
+        if symbol_map.get_function(address)?.is_none() {
+            symbol_map.add_function(&function)?;
+        }
+        function.add_local_symbols_to_map(&mut symbol_map)?;
+        address = function.padded_end_address();
+        sections.add_function(function);
+    }
+
+    let writer = AssemblyWriter {
+        symbol_lookup: SymbolLookup {
+            module_kind,
+            symbol_map: &symbol_map,
+            symbol_maps,
+            relocations,
+            endian,
+            string_encoding: None,
+            address_format: AddressFormat::default(),
+        },
+        code,
+        base_address,
+        sections: &sections,
+        ual,
+        range: base_address..end_address,
+        incbin: None,
+    };
+    let mut assembly = vec![];
+    for section in sections.iter() {
+        writer.write_section(&mut assembly, section)?;
+    }
+
+    Ok(String::from_utf8(assembly)?)
+}
+
+/// Writes `code[offset..end_offset]` as `.byte` directives, 16 bytes per line.
+pub fn write_bytes<W: io::Write>(w: &mut W, code: &[u8], mut offset: u32, end_offset: u32) -> Result<()> {
+    while offset < end_offset {
+        write!(w, "    .byte ")?;
+        for i in 0..16.min(end_offset - offset) {
+            if i != 0 {
+                write!(w, ", ")?;
+            }
+            write!(w, "0x{:02x}", code[offset as usize])?;
+            offset += 1;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
@@ -1,8 +1,13 @@
 use unarm::{
     args::{Argument, OffsetReg, Reg, Register},
-    arm, thumb, Ins, ParsedIns,
+    arm, thumb, ArmVersion, Endian, Ins, ParseFlags, ParsedIns,
 };
 
+use crate::util::bytes::FromSlice;
+
+/// Max number of instructions in a Thumb leaf function, including the return.
+const THUMB_LEAF_MAX_INSTRUCTIONS: usize = 4;
+
 pub fn is_valid_function_start_arm(_address: u32, ins: arm::Ins, parsed_ins: &ParsedIns) -> bool {
     if ins.op == arm::Opcode::Illegal || parsed_ins.is_illegal() {
         return false;
@@ -112,10 +117,55 @@ pub fn is_valid_function_start_thumb(_address: u32, ins: thumb::Ins, parsed_ins:
     }
 }
 
-pub fn is_valid_function_start(address: u32, ins: Ins, parsed_ins: &ParsedIns) -> bool {
-    match ins {
+/// Returns whether a function can start with `ins`. Functions are usually told apart from the end of the previous function
+/// by their entry instruction, but Thumb leaf functions don't have one. If `leaf_code` is given, which is the code at
+/// `address`, the function must also be a Thumb leaf function.
+pub fn is_valid_function_start(address: u32, ins: Ins, parsed_ins: &ParsedIns, leaf_code: Option<(&[u8], Endian)>) -> bool {
+    let valid = match ins {
         Ins::Arm(ins) => is_valid_function_start_arm(address, ins, parsed_ins),
         Ins::Thumb(ins) => is_valid_function_start_thumb(address, ins, parsed_ins),
         Ins::Data => false,
+    };
+    match leaf_code {
+        Some((code, endian)) => {
+            valid && matches!(ins, Ins::Thumb(_)) && thumb_leaf_function_size(address, code, endian).is_some()
+        }
+        None => valid,
+    }
+}
+
+/// Returns the size of the Thumb leaf function at the start of `code`, if any. A leaf function doesn't call other
+/// functions, so it must return cleanly with `bx lr` or `pop {..., pc}` within a few instructions that don't branch or use
+/// SP/PC. Only the first instruction may push registers.
+pub fn thumb_leaf_function_size(address: u32, code: &[u8], endian: Endian) -> Option<u32> {
+    let parse_flags = ParseFlags { ual: false, version: ArmVersion::V5Te };
+    let mut pushed_lr = false;
+    for (i, ins_code) in code.chunks_exact(2).take(THUMB_LEAF_MAX_INSTRUCTIONS).enumerate() {
+        let ins_address = address + i as u32 * 2;
+        let ins = thumb::Ins::new(u16::from_slice(ins_code, endian) as u32, &parse_flags);
+        let parsed_ins = ins.parse(&parse_flags);
+        if !is_valid_function_start_thumb(ins_address, ins, &parsed_ins) || Ins::Thumb(ins).is_conditional() {
+            return None;
+        }
+
+        let args = &parsed_ins.args;
+        match (parsed_ins.mnemonic, args[0], args[1]) {
+            ("bx", Argument::Reg(Reg { reg: Register::Lr, .. }), Argument::None) if !pushed_lr => {
+                return Some(ins_address + 2 - address)
+            }
+            ("pop", Argument::RegList(list), _) if pushed_lr && list.contains(Register::Pc) => {
+                return Some(ins_address + 2 - address)
+            }
+            ("push", Argument::RegList(list), _) if i == 0 && list.contains(Register::Lr) => pushed_lr = true,
+            ("b" | "bx" | "blx" | "push" | "pop", _, _) => return None,
+            // Pool constant load
+            ("ldr", Argument::Reg(_), Argument::Reg(Reg { reg: Register::Pc, deref: true, .. })) => {}
+            _ => {
+                if args.iter().any(|arg| matches!(arg, Argument::Reg(Reg { reg: Register::Sp | Register::Pc, .. }))) {
+                    return None;
+                }
+            }
+        }
     }
+    None
 }
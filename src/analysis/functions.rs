@@ -1,36 +1,46 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     io,
+    ops::Range,
 };
 
 use anyhow::{bail, Result};
 use unarm::{
-    args::{Argument, Reg, Register},
+    args::{Argument, OffsetImm, Reg, Register},
     thumb, ArmVersion, DisplayOptions, Endian, Ins, ParseFlags, ParseMode, ParsedIns, Parser, RegNames,
 };
 
 use crate::{
     analysis::function_start::is_valid_function_start,
-    config::symbol::{SymbolLookup, SymbolMap},
+    config::{
+        data_regions::DataRegion,
+        function_overrides::FunctionOverride,
+        symbol::{InstructionMode, SymbolKind, SymbolLookup, SymbolMap},
+    },
     util::bytes::FromSlice,
 };
 
 use super::{
+    defs_uses::DefsUsesState,
     function_branch::FunctionBranchState,
     illegal_code::IllegalCodeState,
     inline_table::{InlineTable, InlineTableState},
-    jump_table::{JumpTable, JumpTableState},
+    jump_table::{offset_table_layout, read_offset_entry, JumpTable, JumpTableState},
     secure_area::SecureAreaState,
 };
 
 // All keys in the types below are instruction addresses
 pub type Labels = BTreeSet<u32>;
+/// Addresses of 4-byte pool words. An 8-byte constant loaded by `ldrd` occupies two consecutive words.
 pub type PoolConstants = BTreeSet<u32>;
 pub type JumpTables = BTreeMap<u32, JumpTable>;
 pub type InlineTables = BTreeMap<u32, InlineTable>;
 pub type FunctionCalls = BTreeMap<u32, CalledFunction>;
 pub type DataLoads = BTreeMap<u32, u32>;
 
+/// Largest constant pool which is searched past for the code of a function that starts with its pool.
+const MAX_PRE_CODE_POOL_SIZE: u32 = 0x40;
+
 #[derive(Debug, Clone)]
 pub struct Function {
     name: String,
@@ -43,6 +53,11 @@ pub struct Function {
     jump_tables: JumpTables,
     inline_tables: InlineTables,
     function_calls: FunctionCalls,
+    data_loads: DataLoads,
+    /// Jump table destinations outside of this function, which are separate functions
+    external_jump_targets: Labels,
+    /// Alignment `nop`s after the last instruction, either before the constant pool or past the end of the function
+    padding: Option<Range<u32>>,
 }
 
 impl Function {
@@ -50,14 +65,22 @@ impl Function {
         self.end_address - self.start_address
     }
 
-    fn is_thumb_function(address: u32, code: &[u8]) -> bool {
-        if (address & 3) != 0 {
+    fn is_thumb_function(
+        address: u32,
+        code: &[u8],
+        endian: Endian,
+        instruction_modes: Option<&BTreeMap<u32, InstructionMode>>,
+    ) -> bool {
+        if let Some(mode) = instruction_modes.and_then(|modes| modes.get(&address)) {
+            // Given by the user
+            *mode == InstructionMode::Thumb
+        } else if (address & 3) != 0 {
             // Not 4-aligned, must be Thumb
             true
         } else if code.len() < 4 {
             // Can't contain a full ARM instruction
             true
-        } else if code[3] & 0xf0 == 0xe0 {
+        } else if u32::from_slice(code, endian) >> 28 == 0xe {
             // First instruction has the AL condition code, must be ARM
             false
         } else {
@@ -85,6 +108,13 @@ impl Function {
             {
                 true
             }
+            // str lr, [sp, #-4]!
+            (
+                "str",
+                Argument::Reg(Reg { reg: Register::Lr, deref: false, .. }),
+                Argument::Reg(Reg { reg: Register::Sp, deref: true, writeback: true }),
+                Argument::OffsetImm(OffsetImm { post_indexed: false, value: -4 }),
+            ) => true,
             _ => false,
         }
     }
@@ -124,7 +154,9 @@ impl Function {
             }
             // subs pc, lr, *
             ("subs", Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::Reg(Reg { reg: Register::Lr, .. })) => true,
-            // ldr pc, *
+            // movs pc, *, including movs pc, lr
+            ("movs", Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::Reg(_)) => true,
+            // ldr pc, *, including ldr pc, [sp], #4
             ("ldr", Argument::Reg(Reg { reg: Register::Pc, .. }), _) => true,
             _ => false,
         }
@@ -138,11 +170,16 @@ impl Function {
         Some((address as i32 + dest).try_into().unwrap())
     }
 
-    fn is_pool_load(ins: Ins, parsed_ins: &ParsedIns, address: u32, thumb: bool) -> Option<u32> {
-        if ins.mnemonic() != "ldr" {
-            return None;
-        }
-        match (parsed_ins.args[0], parsed_ins.args[1], parsed_ins.args[2]) {
+    /// Returns the address and size of the pool constant loaded by `ldr *, [pc + *]` or `ldrd *, [pc + *]`.
+    fn is_pool_load(ins: Ins, parsed_ins: &ParsedIns, address: u32, thumb: bool) -> Option<(u32, u32)> {
+        let args = &parsed_ins.args;
+        let (size, base_index) = match ins.mnemonic() {
+            "ldr" => (4, 1),
+            // The second destination register is only an argument in UAL syntax
+            "ldrd" => (8, if matches!(args[1], Argument::Reg(Reg { deref: false, .. })) { 2 } else { 1 }),
+            _ => return None,
+        };
+        match (args[0], args[base_index], args[base_index + 1]) {
             (Argument::Reg(dest), Argument::Reg(base), Argument::OffsetImm(offset)) => {
                 if dest.reg == Register::Pc {
                     None
@@ -154,13 +191,61 @@ impl Function {
                     // ldr *, [pc + *]
                     let load_address = (address as i32 + offset.value) as u32 & !3;
                     let load_address = load_address + if thumb { 4 } else { 8 };
-                    Some(load_address)
+                    Some((load_address, size))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the address of the pool constant loaded by `ldr pc, [pc + *]`, which jumps to the address in the pool.
+    fn is_pool_jump(parsed_ins: &ParsedIns, address: u32) -> Option<u32> {
+        match (parsed_ins.mnemonic, parsed_ins.args[0], parsed_ins.args[1], parsed_ins.args[2]) {
+            (
+                "ldr",
+                Argument::Reg(Reg { reg: Register::Pc, .. }),
+                Argument::Reg(Reg { deref: true, reg: Register::Pc, .. }),
+                Argument::OffsetImm(offset),
+            ) if !offset.post_indexed => {
+                // Only ARM can load into PC
+                Some(((address as i32 + offset.value) as u32 & !3) + 8)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the destination register and address of `add/sub *, pc, #*`, also known as ADR.
+    fn is_pc_relative_address(parsed_ins: &ParsedIns, address: u32, thumb: bool) -> Option<(Register, u32)> {
+        let negative = match parsed_ins.mnemonic {
+            "add" => false,
+            "sub" => true,
+            _ => return None,
+        };
+        match (parsed_ins.args[0], parsed_ins.args[1], parsed_ins.args[2], parsed_ins.args[3]) {
+            (Argument::Reg(dest), Argument::Reg(base), Argument::UImm(offset), Argument::None) => {
+                if matches!(dest.reg, Register::Pc | Register::Lr) {
+                    // Computes a return address or jumps
+                    None
+                } else if base.deref || base.reg != Register::Pc {
+                    None
+                } else {
+                    let pc = if thumb { (address + 4) & !3 } else { address + 8 };
+                    let target = if negative { pc.wrapping_sub(offset) } else { pc.wrapping_add(offset) };
+                    Some((dest.reg, target))
                 }
             }
             _ => None,
         }
     }
 
+    fn is_register_jump(parsed_ins: &ParsedIns, register: Register) -> bool {
+        match (parsed_ins.mnemonic, parsed_ins.args[0], parsed_ins.args[1]) {
+            ("bx" | "blx", Argument::Reg(reg), Argument::None) => reg.reg == register,
+            ("mov", Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::Reg(reg)) => reg.reg == register,
+            _ => false,
+        }
+    }
+
     fn is_function_call(ins: Ins, parsed_ins: &ParsedIns, address: u32, thumb: bool) -> Option<CalledFunction> {
         let args = &parsed_ins.args;
         match (ins.mnemonic(), args[0], args[1]) {
@@ -178,15 +263,31 @@ impl Function {
     }
 
     fn function_parser_loop(mut parser: Parser<'_>, options: FunctionParseOptions) -> Result<ParseFunctionResult> {
-        let FunctionParseOptions { name, start_address, known_end_address, module_start_address, module_end_address, .. } =
-            options;
+        let FunctionParseOptions {
+            name,
+            start_address,
+            base_address,
+            module_code,
+            known_end_address,
+            module_start_address,
+            module_end_address,
+            parse_options,
+        } = options;
 
         let thumb = parser.mode == ParseMode::Thumb;
-        let mut context =
-            ParseFunctionContext::new(start_address, thumb, known_end_address, module_start_address, module_end_address);
+        let mut context = ParseFunctionContext::new(
+            start_address,
+            thumb,
+            known_end_address,
+            module_start_address,
+            module_end_address,
+            parse_options.check_defs_uses,
+            parse_options.relocate_function_branches,
+            LookaheadCode { code: module_code, base_address, endian: parse_options.endian },
+        );
 
         let Some((address, ins, parsed_ins)) = parser.next() else { return Ok(ParseFunctionResult::NoEpilogue) };
-        if !is_valid_function_start(address, ins, &parsed_ins) {
+        if !is_valid_function_start(address, ins, &parsed_ins, None) {
             return Ok(ParseFunctionResult::InvalidStart { address, ins, parsed_ins });
         }
 
@@ -196,7 +297,7 @@ impl Function {
         } else {
             loop {
                 let Some((address, ins, parsed_ins)) = parser.next() else {
-                    break context.into_function(ParseFunctionState::Done, name);
+                    break context.into_function(ParseFunctionState::EndOfCode, name);
                 };
                 let state = context.handle_ins(&mut parser, address, ins, parsed_ins);
                 if state.ended() {
@@ -229,14 +330,16 @@ impl Function {
     pub fn parse_function(options: FunctionParseOptions) -> Result<ParseFunctionResult> {
         let FunctionParseOptions { start_address, base_address, module_code, parse_options, .. } = &options;
 
-        let thumb = parse_options.thumb.unwrap_or(Function::is_thumb_function(*start_address, module_code));
+        let thumb = parse_options.thumb.unwrap_or_else(|| {
+            Function::is_thumb_function(*start_address, module_code, parse_options.endian, parse_options.instruction_modes)
+        });
         let parse_mode = if thumb { ParseMode::Thumb } else { ParseMode::Arm };
         let start = (start_address - base_address) as usize;
         let function_code = &module_code[start..];
         let parser = Parser::new(
             parse_mode,
             *start_address,
-            Endian::Little,
+            parse_options.endian,
             ParseFlags { version: ArmVersion::V5Te, ual: false },
             function_code,
         );
@@ -253,8 +356,21 @@ impl Function {
             module_start_address,
             module_end_address,
             search_options,
+            function_overrides,
+            data_regions,
+            handwritten_code,
+            instruction_modes,
+            no_epilogue_fallback,
+            endian,
         } = options;
 
+        // Handwritten code may read callee-saved registers without saving them first
+        let is_handwritten = |address: u32| {
+            handwritten_code.is_some_and(|regions| {
+                regions.range(..=address).next_back().is_some_and(|(_, region)| region.contains(address))
+            })
+        };
+
         let mut functions = BTreeMap::new();
 
         let start_address = search_options.start_address.unwrap_or(base_address);
@@ -267,53 +383,195 @@ impl Function {
 
         log::debug!("Searching for functions from {:#010x} to {:#010x}", start_address, end_address);
 
+        // Function symbols which are already known, such as ones added by the user, are guaranteed function starts which
+        // end the function before them and keep their instruction mode
+        let known_modes = symbol_map
+            .iter_by_address(start_address..end_address)
+            .filter_map(|symbol| match symbol.kind {
+                SymbolKind::Function(function) => Some((symbol.addr, function.mode)),
+                _ => None,
+            })
+            .collect::<BTreeMap<_, _>>();
+        let function_addresses = search_options
+            .function_addresses
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&address| address >= start_address && address < end_address)
+            .chain(known_modes.keys().copied())
+            .collect::<BTreeSet<_>>();
+        let is_thumb = |address: u32, code: &[u8]| match known_modes.get(&address) {
+            Some(&mode) => mode == InstructionMode::Thumb,
+            None => Function::is_thumb_function(address, code, endian, instruction_modes),
+        };
+        // Analysis which can't continue from an address resumes at the next known function start, so that none are missed
+        let next_known_function = |address: u32| function_addresses.range(address + 1..).next().copied();
+
         let mut last_function_address = search_options.last_function_address.unwrap_or(end_address);
         let mut address = start_address;
+        // Call and pointer targets of the functions found so far, which are real functions regardless of their size
+        let mut referenced = BTreeSet::new();
 
         while !function_code.is_empty() && address <= last_function_address {
-            let thumb = Function::is_thumb_function(address, function_code);
+            if let Some((_, region)) = data_regions.and_then(|regions| regions.range(..=address).next_back()) {
+                if region.contains(address) {
+                    log::debug!("Skipping data region from {:#010x} to {:#010x}", region.start, region.end);
+                    address = region.end.next_multiple_of(2);
+                    if address >= end_address {
+                        break;
+                    }
+                    function_code = &module_code[(address - base_address) as usize..];
+                    continue;
+                }
+            }
+
+            let function_override = function_overrides.and_then(|overrides| overrides.get(&address));
+            let thumb = match function_override {
+                Some(function_override) => function_override.mode == InstructionMode::Thumb,
+                None => is_thumb(address, function_code),
+            };
 
             let parse_mode = if thumb { ParseMode::Thumb } else { ParseMode::Arm };
-            let parser = Parser::new(
-                parse_mode,
-                address,
-                Endian::Little,
-                ParseFlags { version: ArmVersion::V5Te, ual: false },
-                function_code,
-            );
+            let check_defs_uses = search_options.check_defs_uses && function_override.is_none() && !is_handwritten(address);
 
-            let (name, new) = if let Some((_, symbol)) = symbol_map.by_address(address)? {
+            let (name, mut new) = if let Some((_, symbol)) = symbol_map.by_address(address)? {
                 (symbol.name.clone(), false)
             } else {
                 (format!("{}{:08x}", default_name_prefix, address), true)
             };
 
-            let function_result = Function::function_parser_loop(
-                parser,
-                FunctionParseOptions {
-                    name,
-                    start_address: address,
-                    base_address,
-                    module_code,
-                    known_end_address: None,
-                    module_start_address,
-                    module_end_address,
-                    parse_options: Default::default(),
-                },
-            )?;
+            let parse = |known_end_address: Option<u32>, function_code: &[u8]| {
+                let parser = Parser::new(
+                    parse_mode,
+                    address,
+                    endian,
+                    ParseFlags { version: ArmVersion::V5Te, ual: false },
+                    function_code,
+                );
+                Function::function_parser_loop(
+                    parser,
+                    FunctionParseOptions {
+                        name: name.clone(),
+                        start_address: address,
+                        base_address,
+                        module_code,
+                        known_end_address,
+                        module_start_address,
+                        module_end_address,
+                        parse_options: ParseFunctionOptions {
+                            thumb: Some(thumb),
+                            instruction_modes,
+                            endian,
+                            check_defs_uses,
+                            relocate_function_branches: search_options.relocate_function_branches,
+                        },
+                    },
+                )
+            };
+
+            let next_override =
+                function_overrides.and_then(|overrides| overrides.range(address + 1..).next()).map(|(&address, _)| address);
+            let next_data_region =
+                data_regions.and_then(|regions| regions.range(address + 1..).next()).map(|(&address, _)| address);
+            let next_function = next_known_function(address);
+
+            let mut function_result =
+                parse(function_override.map(|function_override| function_override.end_address), function_code)?;
+            if function_override.is_none() {
+                // Overridden and known functions must start where the user says and data regions must not be parsed as
+                // code, so end this function at the next override, known function or data region
+                let next_address = next_override.into_iter().chain(next_data_region).chain(next_function).min();
+                if let (ParseFunctionResult::Found(function), Some(next_address)) = (&function_result, next_address) {
+                    if function.end_address > next_address {
+                        log::debug!(
+                            "Function at {:#010x} was cut short to end at function override, known function or data region {:#010x}",
+                            address,
+                            next_address
+                        );
+                        function_result = parse(Some(next_address), function_code)?;
+                    }
+                }
+            }
+            if let (ParseFunctionResult::NoEpilogue, Some(max_distance)) = (&function_result, no_epilogue_fallback) {
+                // Handwritten functions like dispatch loops may never return, so end them at whatever comes next
+                let next_symbol = symbol_map
+                    .iter_by_address(address + 1..end_address)
+                    .find(|symbol| matches!(symbol.kind, SymbolKind::Function(_) | SymbolKind::Data(_)))
+                    .map(|symbol| symbol.addr);
+                let next_bound =
+                    next_symbol.into_iter().chain(next_override).chain(next_data_region).fold(end_address, u32::min);
+                if next_bound - address <= max_distance {
+                    log::debug!("Function at {:#010x} has no epilogue, ending it at {:#010x}", address, next_bound);
+                    function_result = parse(Some(next_bound), &function_code[..(next_bound - address) as usize])?;
+                }
+            }
+            if matches!(function_result, ParseFunctionResult::IllegalIns { .. } | ParseFunctionResult::InvalidStart { .. })
+                && function_override.is_none()
+                && (address & 3) == 0
+            {
+                // The function may begin with its own constant pool, so look for code after the pool which loads from it.
+                // Code at a known function start belongs to that function instead.
+                let pool_end = end_address.min(address + 4 + MAX_PRE_CODE_POOL_SIZE).min(next_function.unwrap_or(u32::MAX));
+                // The pool is folded into the function, so it's named after the pool address
+                let function_symbol = symbol_map
+                    .for_address(address)
+                    .and_then(|mut symbols| symbols.find(|(_, symbol)| matches!(symbol.kind, SymbolKind::Function(_))))
+                    .map(|(_, symbol)| symbol.name.clone());
+                for code_address in (address + 4..pool_end).step_by(4) {
+                    let code = &module_code[(code_address - base_address) as usize..];
+                    let thumb = is_thumb(code_address, code);
+                    let check_defs_uses = search_options.check_defs_uses && !is_handwritten(code_address);
+                    let result = Function::parse_function(FunctionParseOptions {
+                        name: function_symbol.clone().unwrap_or_else(|| format!("{}{:08x}", default_name_prefix, address)),
+                        start_address: code_address,
+                        base_address,
+                        module_code,
+                        known_end_address: None,
+                        module_start_address,
+                        module_end_address,
+                        parse_options: ParseFunctionOptions {
+                            thumb: Some(thumb),
+                            instruction_modes,
+                            endian,
+                            check_defs_uses,
+                            relocate_function_branches: search_options.relocate_function_branches,
+                        },
+                    })?;
+                    match result {
+                        // The pool was folded into the function, see `function_parser_loop`
+                        ParseFunctionResult::Found(function) if function.start_address == address => {
+                            log::debug!(
+                                "Skipped constant pool at {:#010x} before the code of function at {:#010x}",
+                                address,
+                                code_address
+                            );
+                            new = function_symbol.is_none();
+                            function_result = ParseFunctionResult::Found(function);
+                            break;
+                        }
+                        // Code which doesn't load from the pool, so the pool isn't part of it
+                        ParseFunctionResult::Found(_) => break,
+                        _ => {}
+                    }
+                }
+            }
+
             let function = match function_result {
                 ParseFunctionResult::Found(function) => function,
                 ParseFunctionResult::IllegalIns { address: illegal_address, ins, .. } => {
                     if search_options.keep_searching_for_valid_function_start {
                         // It's possible that we've attempted to analyze pool constants as code, which can happen if the
                         // function has a constant pool ahead of its code.
-                        let mut next_address = (address + 1).next_multiple_of(4);
-                        if let Some(function_addresses) = search_options.function_addresses.as_ref() {
-                            if let Some(&next_function) = function_addresses.range(address + 1..).next() {
-                                next_address = next_function;
-                            }
-                        }
-                        address = next_address;
+                        address = next_known_function(address).unwrap_or((address + 1).next_multiple_of(4));
+                        function_code = &module_code[(address - base_address) as usize..];
+                        continue;
+                    } else if let Some(next_function) = next_known_function(address) {
+                        log::debug!(
+                            "Illegal instruction at {:#010x}, resuming at known function {:#010x}",
+                            illegal_address,
+                            next_function
+                        );
+                        address = next_function;
                         function_code = &module_code[(address - base_address) as usize..];
                         continue;
                     } else {
@@ -334,6 +592,16 @@ impl Function {
                     }
                 }
                 ParseFunctionResult::NoEpilogue => {
+                    if let Some(next_function) = next_known_function(address) {
+                        log::debug!(
+                            "No epilogue in function starting from {:#010x}, resuming at known function {:#010x}",
+                            address,
+                            next_function
+                        );
+                        address = next_function;
+                        function_code = &module_code[(address - base_address) as usize..];
+                        continue;
+                    }
                     log::debug!(
                         "Terminating function analysis due to no epilogue in function starting from {:#010x}",
                         address
@@ -346,6 +614,15 @@ impl Function {
                         address += ins_size as u32;
                         function_code = &function_code[ins_size..];
                         continue;
+                    } else if let Some(next_function) = next_known_function(address) {
+                        log::debug!(
+                            "Invalid function start at {:#010x}, resuming at known function {:#010x}",
+                            start_address,
+                            next_function
+                        );
+                        address = next_function;
+                        function_code = &module_code[(address - base_address) as usize..];
+                        continue;
                     } else {
                         if thumb {
                             log::debug!(
@@ -367,17 +644,35 @@ impl Function {
                 }
             };
 
+            let known =
+                !new || function_override.is_some() || function_addresses.contains(&address) || referenced.contains(&address);
+            if !known && function.end_address - function.start_address < search_options.min_function_size {
+                // Tiny functions are likely to be misdecoded data, so skip over them and leave them to data analysis
+                log::debug!(
+                    "Function at {:#010x} is smaller than the minimum function size of {:#x} bytes",
+                    address,
+                    search_options.min_function_size
+                );
+                address = function.padded_end_address();
+                function_code = &module_code[(address - base_address) as usize..];
+                continue;
+            }
+
             if new {
-                symbol_map.add_function(&function);
+                symbol_map.add_function(&function)?;
             }
             function.add_local_symbols_to_map(symbol_map)?;
+            referenced.extend(function.function_calls().values().map(|called_function| called_function.address));
+            referenced.extend(
+                function.iter_pool_constants(module_code, base_address, endian).map(|pool_constant| pool_constant.value & !1),
+            );
 
-            address = function.end_address;
+            address = function.padded_end_address();
             function_code = &module_code[(address - base_address) as usize..];
 
             // Look for pointers to data in this module, to use as an upper bound for finding functions
             if search_options.use_data_as_upper_bound {
-                for pool_constant in function.iter_pool_constants(module_code, base_address) {
+                for pool_constant in function.iter_pool_constants(module_code, base_address, endian) {
                     let pointer_value = pool_constant.value & !1;
                     if pointer_value >= last_function_address {
                         continue;
@@ -385,16 +680,17 @@ impl Function {
                     if pointer_value >= start_address && pointer_value >= address {
                         let offset = (pointer_value - base_address) as usize;
                         if offset < module_code.len() {
-                            let thumb = Function::is_thumb_function(pointer_value, &module_code[offset..]);
+                            let thumb =
+                                Function::is_thumb_function(pointer_value, &module_code[offset..], endian, instruction_modes);
                             let mut parser = Parser::new(
                                 if thumb { ParseMode::Thumb } else { ParseMode::Arm },
                                 pointer_value,
-                                Endian::Little,
+                                endian,
                                 ParseFlags { ual: false, version: ArmVersion::V5Te },
                                 &module_code[offset..],
                             );
                             let (address, ins, parsed_ins) = parser.next().unwrap();
-                            if !is_valid_function_start(address, ins, &parsed_ins) {
+                            if !is_valid_function_start(address, ins, &parsed_ins, None) {
                                 // The pool constant points to data, limit the upper bound
                                 last_function_address = pointer_value;
                                 log::debug!(
@@ -411,6 +707,72 @@ impl Function {
 
             functions.insert(function.first_instruction_address, function);
         }
+
+        // Switch cases may be separate functions which are only reachable through a jump table, so they don't need to
+        // start like a function
+        let mut jump_targets = functions
+            .values()
+            .flat_map(|function| function.external_jump_targets().map(|&target| (target, function.thumb)))
+            .collect::<Vec<_>>();
+        while let Some((target, thumb)) = jump_targets.pop() {
+            if target < base_address || target >= end_address {
+                continue;
+            }
+            if data_regions
+                .is_some_and(|regions| regions.range(..=target).next_back().is_some_and(|(_, region)| region.contains(target)))
+            {
+                continue;
+            }
+            if functions.range(..=target).next_back().is_some_and(|(_, function)| target < function.end_address) {
+                continue;
+            }
+            if symbol_map.get_function(target)?.is_some() {
+                // Found by an earlier search
+                continue;
+            }
+
+            let next_function_address = functions.range(target..).next().map(|(_, function)| function.start_address);
+            let check_defs_uses = search_options.check_defs_uses && !is_handwritten(target);
+            let parse = |known_end_address: Option<u32>| {
+                Function::parse_function(FunctionParseOptions {
+                    name: format!("{}{:08x}", default_name_prefix, target),
+                    start_address: target,
+                    base_address,
+                    module_code,
+                    known_end_address,
+                    module_start_address,
+                    module_end_address,
+                    parse_options: ParseFunctionOptions {
+                        thumb: Some(thumb),
+                        instruction_modes,
+                        endian,
+                        check_defs_uses,
+                        relocate_function_branches: search_options.relocate_function_branches,
+                    },
+                })
+            };
+            let mut function_result = parse(None)?;
+            if let (ParseFunctionResult::Found(function), Some(next_address)) = (&function_result, next_function_address) {
+                if function.end_address > next_address {
+                    function_result = parse(Some(next_address))?;
+                }
+            }
+            let ParseFunctionResult::Found(function) = function_result else {
+                log::debug!(
+                    "Jump table destination {:#010x} could not be analyzed as a function: {:?}",
+                    target,
+                    function_result
+                );
+                continue;
+            };
+
+            log::debug!("Found function {} through a jump table", function.name);
+            symbol_map.add_function(&function)?;
+            function.add_local_symbols_to_map(symbol_map)?;
+            jump_targets.extend(function.external_jump_targets().map(|&target| (target, function.thumb)));
+            functions.insert(function.first_instruction_address, function);
+        }
+
         Ok(functions)
     }
 
@@ -434,6 +796,7 @@ impl Function {
         module_code: &[u8],
         base_addr: u32,
         symbol_map: &mut SymbolMap,
+        bios_call_names: bool,
     ) -> BTreeMap<u32, Function> {
         let mut functions = BTreeMap::new();
 
@@ -449,7 +812,7 @@ impl Function {
             state = state.handle(address, &parsed_ins);
             if let Some(function) = state.get_function() {
                 let function = Function {
-                    name: function.name().to_string(),
+                    name: if bios_call_names { function.bios_call_name() } else { function.name().to_string() },
                     start_address: function.start(),
                     end_address: function.end(),
                     first_instruction_address: function.start(),
@@ -459,8 +822,11 @@ impl Function {
                     jump_tables: JumpTables::new(),
                     inline_tables: InlineTables::new(),
                     function_calls: FunctionCalls::new(),
+                    data_loads: DataLoads::new(),
+                    external_jump_targets: Labels::new(),
+                    padding: None,
                 };
-                symbol_map.add_function(&function);
+                symbol_map.add_function(&function)?;
                 functions.insert(function.first_instruction_address, function);
             }
 
@@ -470,11 +836,11 @@ impl Function {
         functions
     }
 
-    pub fn parser<'a>(&'a self, module_code: &'a [u8], base_address: u32) -> Parser<'a> {
+    pub fn parser<'a>(&'a self, module_code: &'a [u8], base_address: u32, endian: Endian) -> Parser<'a> {
         Parser::new(
             if self.thumb { ParseMode::Thumb } else { ParseMode::Arm },
             self.start_address,
-            Endian::Little,
+            endian,
             ParseFlags { ual: false, version: ArmVersion::V5Te },
             self.code(module_code, base_address),
         )
@@ -498,6 +864,15 @@ impl Function {
         self.end_address
     }
 
+    /// Returns the end address including trailing padding, which is where the next function can start.
+    pub fn padded_end_address(&self) -> u32 {
+        self.padding.as_ref().map_or(self.end_address, |padding| padding.end.max(self.end_address))
+    }
+
+    pub fn padding(&self) -> Option<Range<u32>> {
+        self.padding.clone()
+    }
+
     pub fn first_instruction_address(&self) -> u32 {
         self.first_instruction_address
     }
@@ -510,6 +885,10 @@ impl Function {
         self.labels.iter()
     }
 
+    pub fn external_jump_targets(&self) -> impl Iterator<Item = &u32> {
+        self.external_jump_targets.iter()
+    }
+
     pub fn jump_tables(&self) -> impl Iterator<Item = &JumpTable> {
         self.jump_tables.values()
     }
@@ -534,11 +913,12 @@ impl Function {
         &'a self,
         module_code: &'a [u8],
         base_address: u32,
+        endian: Endian,
     ) -> impl Iterator<Item = PoolConstant> + 'a {
         self.pool_constants.iter().map(move |&address| {
             let start = (address - base_address) as usize;
             let bytes = &module_code[start..];
-            PoolConstant { address, value: u32::from_le_slice(bytes) }
+            PoolConstant { address, value: u32::from_slice(bytes, endian) }
         })
     }
 
@@ -546,6 +926,11 @@ impl Function {
         &self.function_calls
     }
 
+    /// Addresses computed relative to PC, keyed by instruction address.
+    pub fn data_loads(&self) -> &DataLoads {
+        &self.data_loads
+    }
+
     pub fn write_assembly<W: io::Write>(
         &self,
         w: &mut W,
@@ -558,7 +943,7 @@ impl Function {
         let mut parser = Parser::new(
             mode,
             self.start_address,
-            Endian::Little,
+            symbols.endian,
             ParseFlags { ual, version: ArmVersion::V5Te },
             self.code(module_code, base_address),
         );
@@ -575,10 +960,14 @@ impl Function {
                 writeln!(w, "    .global {}", self.name)?;
                 if self.thumb {
                     writeln!(w, "    thumb_func_start {}", self.name)?;
+                    if ual {
+                        // Marks the label as a Thumb function for interworking
+                        writeln!(w, "    .thumb_func")?;
+                    }
                 } else {
                     writeln!(w, "    arm_func_start {}", self.name)?;
                 }
-                writeln!(w, "{}: ; {:#010x}", self.name, self.first_instruction_address)?;
+                writeln!(w, "{}: ; {}", self.name, symbols.address_format.format(self.first_instruction_address))?;
             }
 
             let ins_size = parser.mode.instruction_size(0) as u32;
@@ -617,14 +1006,36 @@ impl Function {
             // write instruction
             match jump_table {
                 Some((table, sym)) if !table.code => {
-                    let (directive, value) =
-                        if self.thumb { (".short", ins.code() as i16 as i32) } else { (".word", ins.code() as i32) };
-                    let label_address = (sym.addr as i32 + value + 2) as u32;
-                    let Some(label) = symbols.symbol_map.get_label(label_address)? else {
-                        log::error!("Expected label for jump table destination {:#010x}", label_address);
-                        bail!("Expected label for jump table destination {:#010x}", label_address);
+                    // Each instruction can hold several entries, or the last entries and padding
+                    let (directive, width) = match table.entry_size {
+                        1 => (".byte", 4),
+                        2 => (".short", 6),
+                        _ => (".word", 10),
                     };
-                    write!(w, "    {directive} {} - {} - 2", label.name, sym.name)?;
+                    let (pc_offset, scale) = offset_table_layout(self.thumb, table.entry_size);
+                    for entry_address in (address..address + ins_size).step_by(table.entry_size as usize) {
+                        if entry_address >= sym.addr + table.size {
+                            let padding = read_offset_entry(ins.code(), address, entry_address, table.entry_size, false);
+                            writeln!(w, "    {directive} {padding:#0width$x}")?;
+                            continue;
+                        }
+                        let entry = read_offset_entry(ins.code(), address, entry_address, table.entry_size, table.signed);
+                        let label_address = ((sym.addr + pc_offset) as i32 + entry * scale as i32) as u32;
+                        let Some(label) = symbols.symbol_map.get_label(label_address)? else {
+                            log::error!("Expected label for jump table destination {:#010x}", label_address);
+                            bail!("Expected label for jump table destination {:#010x}", label_address);
+                        };
+                        let case = (entry_address - sym.addr) / table.entry_size;
+                        if scale == 1 {
+                            writeln!(w, "    {directive} {} - {} - {pc_offset} ; case {case}", label.name, sym.name)?;
+                        } else {
+                            writeln!(
+                                w,
+                                "    {directive} ({} - {} - {pc_offset}) / {scale} ; case {case}",
+                                label.name, sym.name
+                            )?;
+                        }
+                    }
                 }
                 _ => {
                     if parser.mode != ParseMode::Data {
@@ -646,21 +1057,41 @@ impl Function {
             }
 
             // write jump table case
-            if let Some((_table, sym)) = jump_table {
-                let case = (address - sym.addr) / ins_size;
-                writeln!(w, " ; case {case}")?;
-            } else {
-                writeln!(w)?;
+            match jump_table {
+                // Data entries end their own lines
+                Some((table, _)) if !table.code => {}
+                Some((_table, sym)) => {
+                    let case = (address - sym.addr) / ins_size;
+                    writeln!(w, " ; case {case}")?;
+                }
+                None => writeln!(w)?,
+            }
+
+            // write padding, which is not part of the function code but must keep its bytes
+            let mut next_address = address + ins_size;
+            if let Some(padding) =
+                self.padding.as_ref().filter(|padding| padding.start == next_address && padding.end <= self.end_address)
+            {
+                let directive = if self.thumb { ".short" } else { ".word" };
+                for pad_address in padding.clone().step_by(ins_size as usize) {
+                    let bytes = &module_code[(pad_address - base_address) as usize..];
+                    let value = if self.thumb {
+                        u16::from_slice(bytes, symbols.endian) as u32
+                    } else {
+                        u32::from_slice(bytes, symbols.endian)
+                    };
+                    writeln!(w, "    {directive} {value:#x} ; padding")?;
+                }
+                next_address = padding.end;
             }
 
             // write pool constants
-            let next_address = address + ins_size;
             for i in 0.. {
                 let pool_address = next_address + i * 4;
                 if self.pool_constants.contains(&pool_address) {
                     let start = pool_address - base_address;
                     let bytes = &module_code[start as usize..];
-                    let const_value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                    let const_value = u32::from_slice(bytes, symbols.endian);
 
                     let Some(pool_symbol) = symbols.symbol_map.get_pool_constant(pool_address)? else {
                         log::error!("Pool constant at {:#010x} in function {} has no symbol", pool_address, self.name);
@@ -705,7 +1136,7 @@ pub struct FunctionParseOptions<'a> {
     pub module_start_address: u32,
     pub module_end_address: u32,
 
-    pub parse_options: ParseFunctionOptions,
+    pub parse_options: ParseFunctionOptions<'a>,
 }
 
 pub struct FindFunctionsOptions<'a> {
@@ -717,9 +1148,28 @@ pub struct FindFunctionsOptions<'a> {
     pub module_end_address: u32,
 
     pub search_options: FunctionSearchOptions,
+    /// User-provided function boundaries, keyed by function address.
+    pub function_overrides: Option<&'a BTreeMap<u32, FunctionOverride>>,
+    /// User-provided ranges which must not be parsed as code, keyed by start address.
+    pub data_regions: Option<&'a BTreeMap<u32, DataRegion>>,
+    /// User-provided ranges of handwritten code, which is parsed without [`FunctionSearchOptions::check_defs_uses`].
+    pub handwritten_code: Option<&'a BTreeMap<u32, DataRegion>>,
+    /// User-provided instruction modes which are used instead of guessing, keyed by function address.
+    pub instruction_modes: Option<&'a BTreeMap<u32, InstructionMode>>,
+    /// If set, a function without a recognized return ends at the next symbol or address bound, as long as it's at most
+    /// this many bytes after the function start.
+    pub no_epilogue_fallback: Option<u32>,
+    pub endian: Endian,
 }
 
-struct ParseFunctionContext {
+/// Module code which the parser can look ahead in.
+struct LookaheadCode<'a> {
+    code: &'a [u8],
+    base_address: u32,
+    endian: Endian,
+}
+
+struct ParseFunctionContext<'a> {
     start_address: u32,
     thumb: bool,
     end_address: Option<u32>,
@@ -729,6 +1179,11 @@ struct ParseFunctionContext {
     jump_tables: JumpTables,
     inline_tables: InlineTables,
     function_calls: FunctionCalls,
+    data_loads: DataLoads,
+    /// Destinations of jump table entries
+    jump_table_targets: Labels,
+    /// Branch instructions in ARM jump tables, in case they lead outside the function
+    jump_table_branches: FunctionCalls,
 
     module_start_address: u32,
     module_end_address: u32,
@@ -745,19 +1200,30 @@ struct ParseFunctionContext {
     inline_table_state: InlineTableState,
     /// State machine for detecting illegal code sequences
     illegal_code_state: IllegalCodeState,
+    /// State machine for detecting reads of callee-saved registers before they're defined, if enabled
+    defs_uses_state: Option<DefsUsesState>,
+    /// Whether unconditional function branches are recorded as calls, see [`ParseFunctionOptions::relocate_function_branches`]
+    relocate_function_branches: bool,
+    /// Instruction address and destination register of the last PC-relative address computation
+    last_data_load: Option<(u32, Register)>,
 
     prev_ins: Option<Ins>,
     prev_parsed_ins: Option<ParsedIns>,
     prev_address: Option<u32>,
+
+    lookahead: LookaheadCode<'a>,
 }
 
-impl ParseFunctionContext {
+impl<'a> ParseFunctionContext<'a> {
     pub fn new(
         start_address: u32,
         thumb: bool,
         known_end_address: Option<u32>,
         module_start_address: u32,
         module_end_address: u32,
+        check_defs_uses: bool,
+        relocate_function_branches: bool,
+        lookahead: LookaheadCode<'a>,
     ) -> Self {
         Self {
             start_address,
@@ -769,6 +1235,9 @@ impl ParseFunctionContext {
             jump_tables: JumpTables::new(),
             inline_tables: InlineTables::new(),
             function_calls: FunctionCalls::new(),
+            data_loads: DataLoads::new(),
+            jump_table_targets: Labels::new(),
+            jump_table_branches: FunctionCalls::new(),
 
             module_start_address,
             module_end_address,
@@ -783,10 +1252,15 @@ impl ParseFunctionContext {
             function_branch_state: Default::default(),
             inline_table_state: Default::default(),
             illegal_code_state: Default::default(),
+            defs_uses_state: check_defs_uses.then(|| DefsUsesState::new(thumb)),
+            relocate_function_branches,
+            last_data_load: None,
 
             prev_ins: None,
             prev_parsed_ins: None,
             prev_address: None,
+
+            lookahead,
         }
     }
 
@@ -802,9 +1276,13 @@ impl ParseFunctionContext {
 
         self.jump_table_state = self.jump_table_state.handle(address, ins, parsed_ins, &mut self.jump_tables);
         self.last_conditional_destination = self.last_conditional_destination.max(self.jump_table_state.table_end_address());
-        if let Some(label) = self.jump_table_state.get_label(address, ins) {
-            self.labels.insert(label);
-            self.last_conditional_destination = self.last_conditional_destination.max(Some(label));
+        for label in self.jump_table_state.get_labels(address, ins) {
+            // A corrupt entry could point into the table itself, which is data and not code
+            if !self.is_in_jump_table(label) {
+                self.labels.insert(label);
+                self.jump_table_targets.insert(label);
+                self.last_conditional_destination = self.last_conditional_destination.max(Some(label));
+            }
         }
 
         if self.jump_table_state.is_numerical_jump_offset() {
@@ -812,6 +1290,13 @@ impl ParseFunctionContext {
             return ParseFunctionState::Continue;
         }
 
+        if let Some((load_address, register)) = self.last_data_load.take() {
+            if Some(load_address) == self.prev_address && Function::is_register_jump(parsed_ins, register) {
+                // The computed address is jumped to right away, so it points to code and not data
+                self.data_loads.remove(&load_address);
+            }
+        }
+
         let ins_size = if let Ins::Thumb(thumb_ins) = ins {
             if thumb_ins.op != thumb::Opcode::Bl && thumb_ins.op != thumb::Opcode::BlxI {
                 // Typical Thumb instruction
@@ -832,6 +1317,12 @@ impl ParseFunctionContext {
         if self.illegal_code_state.is_illegal() {
             return ParseFunctionState::IllegalIns { address, ins, parsed_ins: parsed_ins.clone() };
         }
+        if let Some(defs_uses_state) = self.defs_uses_state.as_mut() {
+            *defs_uses_state = defs_uses_state.handle(ins, parsed_ins);
+            if defs_uses_state.is_illegal() {
+                return ParseFunctionState::IllegalIns { address, ins, parsed_ins: parsed_ins.clone() };
+            }
+        }
 
         let in_conditional_block = Some(address) < self.last_conditional_destination;
         let is_return = Function::is_return(
@@ -850,6 +1341,11 @@ impl ParseFunctionContext {
                     // Tail call
                     self.function_calls.insert(address, CalledFunction { ins, address: destination, thumb: self.thumb });
                 }
+            } else if let Some(called_function) = self.absolute_jump(ins, parsed_ins, address) {
+                if called_function.address < self.start_address || called_function.address >= end_address {
+                    // Tail call to an absolute address
+                    self.function_calls.insert(address, called_function);
+                }
             }
 
             // We're not inside a conditional code block, so this is the final return instruction
@@ -879,6 +1375,15 @@ impl ParseFunctionContext {
             };
         }
 
+        if self.thumb
+            && self.known_end_address.is_none()
+            && address > self.start_address
+            && self.is_thumb_leaf_start(address, ins, parsed_ins)
+        {
+            self.end_address = Some(address);
+            return ParseFunctionState::Done;
+        }
+
         self.function_branch_state = self.function_branch_state.handle(ins, parsed_ins);
         if let Some(destination) = Function::is_branch(ins, parsed_ins, address) {
             let in_current_module = destination >= self.module_start_address && destination < self.module_end_address;
@@ -886,16 +1391,21 @@ impl ParseFunctionContext {
                 // Tail call
                 self.function_calls.insert(address, CalledFunction { ins, address: destination, thumb: self.thumb });
             } else if self.function_branch_state.is_function_branch() {
-                if !ins.is_conditional() && !in_conditional_block {
+                let is_unconditional = !ins.is_conditional() && !in_conditional_block;
+                if !is_unconditional || self.relocate_function_branches {
+                    // Conditional function branches are always calls, the unconditional one ending the function only if enabled
+                    self.function_calls.insert(address, CalledFunction { ins, address: destination, thumb: self.thumb });
+                }
+                if is_unconditional {
                     // This is an unconditional backwards function branch, which means this function has ended
                     self.end_address = Some(address + ins_size);
                     return ParseFunctionState::Done;
-                } else {
-                    // TODO: Always run this (move it outside of else block)
-                    // mwldarm manages to relocate conditional branches, but not unconditional ones like the if block above
-                    self.function_calls.insert(address, CalledFunction { ins, address: destination, thumb: self.thumb });
                 }
             } else {
+                if self.is_in_jump_table(address) {
+                    self.jump_table_targets.insert(destination);
+                    self.jump_table_branches.insert(address, CalledFunction { ins, address: destination, thumb: self.thumb });
+                }
                 // Normal branch instruction, insert a label
                 if let Some(state) = self.handle_label(destination, address, parser, ins_size) {
                     return state;
@@ -903,9 +1413,17 @@ impl ParseFunctionContext {
             }
         }
 
-        if let Some(pool_address) = Function::is_pool_load(ins, parsed_ins, address, self.thumb) {
-            self.pool_constants.insert(pool_address);
-            self.last_pool_address = self.last_pool_address.max(Some(pool_address));
+        if let Some((pool_address, size)) = Function::is_pool_load(ins, parsed_ins, address, self.thumb) {
+            // 8-byte constants are stored as two consecutive pool words
+            for word_address in (pool_address..pool_address + size).step_by(4) {
+                self.pool_constants.insert(word_address);
+            }
+            self.last_pool_address = self.last_pool_address.max(Some(pool_address + size - 4));
+        }
+
+        if let Some((register, target)) = Function::is_pc_relative_address(parsed_ins, address, self.thumb) {
+            self.data_loads.insert(address, target);
+            self.last_data_load = Some((address, register));
         }
 
         self.inline_table_state = self.inline_table_state.handle(self.thumb, address, parsed_ins);
@@ -929,6 +1447,73 @@ impl ParseFunctionContext {
         state
     }
 
+    /// Leaf functions don't push LR, so they can't be found by their entry instruction. Instead, a Thumb leaf function is
+    /// assumed to start after an unconditional tail call if the code is unreachable from this function, starts at an aligned
+    /// address or is called by this function, and returns shortly after.
+    fn is_thumb_leaf_start(&self, address: u32, ins: Ins, parsed_ins: &ParsedIns) -> bool {
+        let (Some(prev_ins), Some(prev_parsed_ins), Some(prev_address)) =
+            (self.prev_ins, self.prev_parsed_ins.as_ref(), self.prev_address)
+        else {
+            return false;
+        };
+        if prev_ins.is_conditional() || Function::is_branch(prev_ins, prev_parsed_ins, prev_address).is_none() {
+            return false;
+        }
+        if self.last_conditional_destination.is_some_and(|destination| destination >= address) {
+            // Still reachable by a branch in this function
+            return false;
+        }
+        let called = self.function_calls.values().any(|call| call.address == address);
+        if address % 4 != 0 && !called {
+            return false;
+        }
+
+        let LookaheadCode { code, base_address, endian } = self.lookahead;
+        let Some(code) = code.get((address - base_address) as usize..) else { return false };
+        is_valid_function_start(address, ins, parsed_ins, Some((code, endian)))
+    }
+
+    /// Returns the destination of a jump to a known absolute address: `mov pc, #*`, `ldr pc, =*`, or `ldr *, =*` followed
+    /// by `bx` or `mov pc` with the loaded register. Jumps through the pool also add the pool constant to this function.
+    fn absolute_jump(&mut self, ins: Ins, parsed_ins: &ParsedIns, address: u32) -> Option<CalledFunction> {
+        if let ("mov", Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::UImm(destination)) =
+            (parsed_ins.mnemonic, parsed_ins.args[0], parsed_ins.args[1])
+        {
+            // MOV doesn't change the instruction mode
+            return Some(CalledFunction { ins, address: destination & !1, thumb: self.thumb });
+        }
+
+        let (pool_address, thumb) = if let Some(pool_address) = Function::is_pool_jump(parsed_ins, address) {
+            (pool_address, None)
+        } else {
+            let (Some(prev_ins), Some(prev_parsed_ins), Some(prev_address)) =
+                (self.prev_ins, self.prev_parsed_ins.as_ref(), self.prev_address)
+            else {
+                return None;
+            };
+            let (pool_address, _) = Function::is_pool_load(prev_ins, prev_parsed_ins, prev_address, self.thumb)?;
+            let Argument::Reg(Reg { reg, .. }) = prev_parsed_ins.args[0] else { return None };
+            if !Function::is_register_jump(parsed_ins, reg) {
+                return None;
+            }
+            // Unlike BX, MOV doesn't change the instruction mode
+            let thumb = (parsed_ins.mnemonic == "mov").then_some(self.thumb);
+            (pool_address, thumb)
+        };
+
+        let LookaheadCode { code, base_address, endian } = self.lookahead;
+        let bytes = code.get(pool_address.checked_sub(base_address)? as usize..)?.get(..4)?;
+        let destination = u32::from_slice(bytes, endian);
+
+        self.pool_constants.insert(pool_address);
+        self.last_pool_address = self.last_pool_address.max(Some(pool_address));
+        Some(CalledFunction { ins, address: destination & !1, thumb: thumb.unwrap_or(destination & 1 != 0) })
+    }
+
+    fn is_in_jump_table(&self, address: u32) -> bool {
+        self.jump_tables.values().any(|table| address >= table.address && address < table.address + table.size)
+    }
+
     fn handle_label(
         &mut self,
         destination: u32,
@@ -974,7 +1559,7 @@ impl ParseFunctionContext {
         None
     }
 
-    fn into_function(self, state: ParseFunctionState, name: String) -> Result<ParseFunctionResult> {
+    fn into_function(mut self, state: ParseFunctionState, name: String) -> Result<ParseFunctionResult> {
         match state {
             ParseFunctionState::Continue => {
                 log::error!("Cannot turn parse context into function before parsing is done");
@@ -984,50 +1569,133 @@ impl ParseFunctionContext {
                 return Ok(ParseFunctionResult::IllegalIns { address, ins, parsed_ins })
             }
             ParseFunctionState::Done => {}
+            ParseFunctionState::EndOfCode => {
+                if let (None, Some(known_end_address)) = (self.end_address, self.known_end_address) {
+                    // The code was cut off at the known end without a return, such as by the no-epilogue fallback. Branches
+                    // past the end can't be labels in this function.
+                    self.end_address = Some(known_end_address);
+                    self.labels.retain(|&label| label < known_end_address);
+                }
+            }
         };
         let Some(end_address) = self.end_address else {
             return Ok(ParseFunctionResult::NoEpilogue);
         };
 
-        let end_address = self
-            .known_end_address
-            .unwrap_or(end_address.max(self.last_pool_address.map(|a| a + 4).unwrap_or(0)).next_multiple_of(4));
+        let padding = self.find_padding(end_address);
+        let end_address = match (self.known_end_address, &padding) {
+            (Some(known_end_address), _) => known_end_address,
+            // Only alignment padding follows the code, so it's excluded from the function
+            (None, Some(padding)) if !self.pool_constants.contains(&padding.end) => end_address,
+            (None, _) => end_address.max(self.last_pool_address.map(|a| a + 4).unwrap_or(0)).next_multiple_of(4),
+        };
         if end_address > self.module_end_address {
             return Ok(ParseFunctionResult::NoEpilogue);
         }
 
+        // Jump tables leading outside the function are calls to other functions, not labels
+        let mut labels = self.labels;
+        let mut function_calls = self.function_calls;
+        let mut external_jump_targets = Labels::new();
+        for target in self.jump_table_targets {
+            if target < self.start_address || target >= end_address {
+                labels.remove(&target);
+                external_jump_targets.insert(target);
+            }
+        }
+        for (address, branch) in self.jump_table_branches {
+            if external_jump_targets.contains(&branch.address) {
+                function_calls.insert(address, branch);
+            }
+        }
+
         Ok(ParseFunctionResult::Found(Function {
             name,
             start_address: self.start_address,
             end_address,
             first_instruction_address: self.start_address,
             thumb: self.thumb,
-            labels: self.labels,
+            labels,
             pool_constants: self.pool_constants,
             jump_tables: self.jump_tables,
             inline_tables: self.inline_tables,
-            function_calls: self.function_calls,
+            function_calls,
+            data_loads: self.data_loads,
+            external_jump_targets,
+            padding,
         }))
     }
+
+    /// Returns the `nop`s from `code_end` up to the constant pool, or up to the next 4-aligned address if no pool follows.
+    fn find_padding(&self, code_end: u32) -> Option<Range<u32>> {
+        let pool_address = self.pool_constants.range(code_end..).next().copied();
+        let padding_end = pool_address.unwrap_or(code_end.next_multiple_of(4));
+        if padding_end <= code_end || padding_end > self.module_end_address {
+            return None;
+        }
+
+        let LookaheadCode { code, base_address, endian } = self.lookahead;
+        let ins_size = if self.thumb { 2 } else { 4 };
+        let is_padding = (code_end..padding_end).step_by(ins_size).all(|address| {
+            let Some(bytes) = code.get((address - base_address) as usize..).and_then(|code| code.get(..ins_size)) else {
+                return false;
+            };
+            if self.thumb {
+                // mov r8, r8
+                u16::from_slice(bytes, endian) == 0x46c0
+            } else {
+                // mov r0, r0 or nop
+                matches!(u32::from_slice(bytes, endian), 0xe1a00000 | 0xe320f000)
+            }
+        });
+        is_padding.then_some(code_end..padding_end)
+    }
 }
 
-#[derive(Default)]
-pub struct ParseFunctionOptions {
+pub struct ParseFunctionOptions<'a> {
     /// Whether the function is in Thumb or ARM mode, or None if it should be detected automatically.
     pub thumb: Option<bool>,
+    /// User-provided instruction modes, keyed by function address, which take precedence over detecting the mode when
+    /// [`Self::thumb`] is None.
+    pub instruction_modes: Option<&'a BTreeMap<u32, InstructionMode>>,
+    /// Byte order of the code. Defaults to little-endian.
+    pub endian: Endian,
+    /// Ends the function with an illegal instruction if it reads a callee-saved register before saving or writing it. Off
+    /// by default, and should be off for handwritten code.
+    pub check_defs_uses: bool,
+    /// Records every function branch as a call, including the unconditional one ending the function. Off by default.
+    pub relocate_function_branches: bool,
+}
+
+impl Default for ParseFunctionOptions<'_> {
+    fn default() -> Self {
+        Self {
+            thumb: None,
+            instruction_modes: None,
+            endian: Endian::Little,
+            check_defs_uses: false,
+            relocate_function_branches: false,
+        }
+    }
 }
 
 enum ParseFunctionState {
     Continue,
-    IllegalIns { address: u32, ins: Ins, parsed_ins: ParsedIns },
+    IllegalIns {
+        address: u32,
+        ins: Ins,
+        parsed_ins: ParsedIns,
+    },
     Done,
+    /// Ran out of code before finding a return
+    EndOfCode,
 }
 
 impl ParseFunctionState {
     pub fn ended(&self) -> bool {
         match self {
             Self::Continue => false,
-            Self::IllegalIns { .. } | Self::Done => true,
+            Self::IllegalIns { .. } | Self::Done | Self::EndOfCode => true,
         }
     }
 }
@@ -1055,7 +1723,18 @@ pub struct FunctionSearchOptions {
     /// Guarantees that all these addresses will be analyzed, even if the function analysis would terminate before they are
     /// reached. Used for .init functions.
     /// Note: This will override `keep_searching_for_valid_function_start`, they are not intended to be used together.
+    /// Function symbols in the symbol map within the search range are treated the same way, and are analyzed in the
+    /// instruction mode of their symbol.
     pub function_addresses: Option<BTreeSet<u32>>,
+    /// Functions smaller than this many bytes are skipped as non-code, unless a symbol, function override or one of
+    /// [`Self::function_addresses`] is at their address, or an earlier function calls or points to them. Reduces false
+    /// functions in data.
+    pub min_function_size: u32,
+    /// Ends functions which read a callee-saved register before saving or writing it, see
+    /// [`ParseFunctionOptions::check_defs_uses`]. Not applied to function overrides or handwritten code.
+    pub check_defs_uses: bool,
+    /// Records every function branch as a call, see [`ParseFunctionOptions::relocate_function_branches`].
+    pub relocate_function_branches: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
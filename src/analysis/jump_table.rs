@@ -1,16 +1,61 @@
 use unarm::{
-    args::{Argument, OffsetImm, Reg, Register, Shift, ShiftImm},
+    args::{Argument, OffsetImm, OffsetReg, Reg, Register, Shift, ShiftImm},
     Ins, ParsedIns,
 };
 
 use super::functions::JumpTables;
 
-#[derive(Debug, Clone)]
+/// Furthest distance from the start of a jump table without a known size to a plausible case.
+const MAX_UNBOUNDED_CASE_DISTANCE: u32 = 0x1000;
+
+#[derive(Debug, Clone, Copy)]
 pub struct JumpTable {
     pub address: u32,
     pub size: u32,
     /// If true, the jump table entries are instructions. Otherwise, they are data.
     pub code: bool,
+    /// Size of each entry in bytes.
+    pub entry_size: u32,
+    /// If true, the entries of a data table are signed offsets.
+    pub signed: bool,
+}
+
+/// Returns the distance from an offset table to the PC which its entries are relative to, and how many bytes one unit of an
+/// entry jumps. A case is at `table_address + pc_offset + entry * scale`.
+pub fn offset_table_layout(thumb: bool, entry_size: u32) -> (u32, u32) {
+    match (thumb, entry_size) {
+        (true, 1) => (2, 2),
+        (true, _) => (2, 1),
+        (false, _) => (4, 4),
+    }
+}
+
+/// Reads the entry at `entry_address` of an offset table, where `code` is the little-endian chunk of the table at `address`.
+pub fn read_offset_entry(code: u32, address: u32, entry_address: u32, entry_size: u32, signed: bool) -> i32 {
+    let raw = code >> ((entry_address - address) * 8);
+    match (entry_size, signed) {
+        (1, false) => (raw & 0xff) as i32,
+        (1, true) => raw as u8 as i8 as i32,
+        (2, false) => (raw & 0xffff) as i32,
+        (2, true) => raw as u16 as i16 as i32,
+        _ => raw as i32,
+    }
+}
+
+/// Returns the cases of the entries of an offset table in the instruction-sized chunk at `address`.
+fn offset_table_labels(thumb: bool, table: &JumpTable, address: u32, ins_size: u32, code: u32) -> Vec<u32> {
+    let end = table.address + table.size;
+    if address < table.address || address >= end {
+        return vec![];
+    }
+    let (pc_offset, scale) = offset_table_layout(thumb, table.entry_size);
+    (address..end.min(address + ins_size))
+        .step_by(table.entry_size as usize)
+        .map(|entry_address| {
+            let entry = read_offset_entry(code, address, entry_address, table.entry_size, table.signed);
+            ((table.address + pc_offset) as i32 + entry * scale as i32) as u32
+        })
+        .collect()
 }
 
 #[derive(Clone, Copy)]
@@ -34,16 +79,16 @@ impl JumpTableState {
         }
     }
 
-    pub fn get_label(&self, address: u32, ins: Ins) -> Option<u32> {
+    pub fn get_labels(&self, address: u32, ins: Ins) -> Vec<u32> {
         match self {
-            Self::Arm(_) => None,
-            Self::Thumb(state) => state.get_label(address, ins),
+            Self::Arm(state) => state.get_labels(address, ins),
+            Self::Thumb(state) => state.get_labels(address, ins),
         }
     }
 
     pub fn is_numerical_jump_offset(&self) -> bool {
         match self {
-            Self::Arm(_) => false,
+            Self::Arm(state) => state.is_numerical_jump_offset(),
             Self::Thumb(state) => state.is_numerical_jump_offset(),
         }
     }
@@ -57,7 +102,8 @@ pub enum JumpTableStateArm {
 
     /// `...`                           other non-comparing instructions
     /// `addls pc, pc, index, lsl #0x2` jump to nearby branch instruction, OR
-    /// `bgt @skip`                     skip jump table if SIGNED index is out of bounds
+    /// `bgt @skip`                     skip jump table if SIGNED index is out of bounds, OR
+    /// `bhi @skip`                     skip offset table if index is out of bounds
     JumpOrBranchSigned { index: Register, limit: u32 },
 
     /// if index is signed:  
@@ -70,6 +116,32 @@ pub enum JumpTableStateArm {
 
     /// valid table detected, starts from `table_address` with a size of `limit`
     ValidJumpTable { table_address: u32, limit: u32 },
+
+    /// if `limit` was a register, the size of the jump table is unknown:
+    /// `...`                           other non-comparing instructions
+    /// `addls pc, pc, index, lsl #0x2` jump to nearby branch instruction
+    JumpUnbounded { index: Register },
+
+    /// table without a known size, starts from `table_address` and grows by one entry for every `b` to a later address
+    /// near the table. Ends at the first other instruction, or at the earliest case it has branched to.
+    ScanBranchTable { table_address: u32, size: u32, first_case: u32 },
+
+    /// if [`JumpTableStateArm::JumpOrBranchSigned`] was bhi:
+    /// `add offset, index, index`      multiply index by 2 for a halfword table, OR
+    /// `add table, pc, #imm`           get the address of the offset table
+    OffsetTableAddress { index: Register, limit: u32, entry_size: u32 },
+
+    /// `ldrb jump, [table, index]`     load unsigned 8-bit jump value from table, OR
+    /// `ldrsb jump, [table, index]`    load signed 8-bit jump value from table, OR
+    /// `ldrh jump, [table, offset]`    load unsigned 16-bit jump value from table, OR
+    /// `ldrsh jump, [table, offset]`   load signed 16-bit jump value from table
+    LoadOffsetEntry { index: Register, table: Register, table_address: u32, limit: u32, entry_size: u32 },
+
+    /// `add pc, pc, jump, lsl #0x2`    do the jump, the table follows right after
+    AddPcOffset { jump: Register, table_address: u32, limit: u32, entry_size: u32, signed: bool },
+
+    /// valid offset table detected, starts from `table_address` with `limit + 1` entries of `entry_size` bytes
+    ValidOffsetTable { table: JumpTable },
 }
 
 impl JumpTableStateArm {
@@ -79,11 +151,18 @@ impl JumpTableStateArm {
             ("cmp", Argument::Reg(Reg { reg, .. }), Argument::UImm(limit), Argument::None) if limit > 0 => {
                 Some(Self::JumpOrBranchSigned { index: reg, limit })
             }
+            ("cmp", Argument::Reg(Reg { reg, .. }), Argument::Reg(_), Argument::None) => {
+                Some(Self::JumpUnbounded { index: reg })
+            }
             _ => None,
         }
     }
 
     fn handle(self, address: u32, ins: Ins, parsed_ins: &ParsedIns, jump_tables: &mut JumpTables) -> Self {
+        if matches!(self, Self::ValidOffsetTable { table } if address < table.address + table.size) {
+            // Table entries are data, even if they happen to look like a comparison
+            return self;
+        }
         if let Some(start) = self.check_start(parsed_ins) {
             return start;
         };
@@ -108,12 +187,18 @@ impl JumpTableStateArm {
                     ) if reg == index => {
                         let table_address = address + 8;
                         let size = (limit + 1) * 4;
-                        jump_tables.insert(table_address, JumpTable { address: table_address, size, code: true });
+                        jump_tables.insert(
+                            table_address,
+                            JumpTable { address: table_address, size, code: true, entry_size: 4, signed: false },
+                        );
                         Self::ValidJumpTable { table_address: address + 8, limit }
                     }
                     ("bgt", Argument::BranchDest(_), Argument::None, Argument::None, Argument::None, Argument::None) => {
                         Self::SignedBaseline { index, limit }
                     }
+                    ("bhi", Argument::BranchDest(_), Argument::None, Argument::None, Argument::None, Argument::None) => {
+                        Self::OffsetTableAddress { index, limit, entry_size: 1 }
+                    }
                     _ if ins.updates_condition_flags() => Self::default(),
                     _ => self,
                 }
@@ -135,7 +220,10 @@ impl JumpTableStateArm {
                 ) if reg == index => {
                     let table_address = address + 8;
                     let size = (limit + 1) * 4;
-                    jump_tables.insert(table_address, JumpTable { address: table_address, size, code: true });
+                    jump_tables.insert(
+                        table_address,
+                        JumpTable { address: table_address, size, code: true, entry_size: 4, signed: false },
+                    );
                     Self::ValidJumpTable { table_address: address + 8, limit }
                 }
                 _ if ins.updates_condition_flags() => Self::default(),
@@ -149,15 +237,144 @@ impl JumpTableStateArm {
                     self
                 }
             }
+            Self::JumpUnbounded { index } => match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3], args[4]) {
+                (
+                    "addls",
+                    Argument::Reg(Reg { reg: Register::Pc, .. }),
+                    Argument::Reg(Reg { reg: Register::Pc, .. }),
+                    Argument::Reg(Reg { reg, .. }),
+                    Argument::ShiftImm(ShiftImm { imm: 2, op: Shift::Lsl }),
+                    Argument::None,
+                ) if reg == index => Self::ScanBranchTable { table_address: address + 8, size: 0, first_case: u32::MAX },
+                _ if ins.updates_condition_flags() => Self::default(),
+                _ => self,
+            },
+            Self::ScanBranchTable { table_address, size, first_case } => {
+                if address < table_address {
+                    // Branch to the default case, which is executed if the index is out of bounds
+                    return self;
+                }
+                let destination = match (parsed_ins.mnemonic, args[0]) {
+                    ("b", Argument::BranchDest(offset)) => Some((address as i32 + offset) as u32),
+                    _ => None,
+                };
+                match destination {
+                    Some(destination)
+                        if address == table_address + size
+                            && address < first_case
+                            && destination > address
+                            && destination - table_address <= MAX_UNBOUNDED_CASE_DISTANCE =>
+                    {
+                        let size = size + 4;
+                        jump_tables.insert(
+                            table_address,
+                            JumpTable { address: table_address, size, code: true, entry_size: 4, signed: false },
+                        );
+                        Self::ScanBranchTable { table_address, size, first_case: first_case.min(destination) }
+                    }
+                    _ => {
+                        if size > 0 {
+                            log::debug!(
+                                "Low confidence: jump table at {table_address:#010x} is bounded by a register, guessed {} \
+                                entries from its branches",
+                                size / 4
+                            );
+                        }
+                        Self::default()
+                    }
+                }
+            }
+            Self::OffsetTableAddress { index, limit, entry_size } => {
+                match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
+                    (
+                        "add",
+                        Argument::Reg(Reg { reg: offset, .. }),
+                        Argument::Reg(Reg { reg: a, .. }),
+                        Argument::Reg(Reg { reg: b, .. }),
+                        Argument::None,
+                    ) if entry_size == 1 && a == index && b == index => {
+                        Self::OffsetTableAddress { index: offset, limit, entry_size: 2 }
+                    }
+                    (
+                        "add",
+                        Argument::Reg(Reg { reg: table, .. }),
+                        Argument::Reg(Reg { reg: Register::Pc, .. }),
+                        Argument::UImm(value),
+                        Argument::None,
+                    ) if table != index => {
+                        let table_address = address + 8 + value;
+                        Self::LoadOffsetEntry { index, table, table_address, limit, entry_size }
+                    }
+                    _ => Self::default(),
+                }
+            }
+            Self::LoadOffsetEntry { index, table, table_address, limit, entry_size } => {
+                let signed = match (parsed_ins.mnemonic, entry_size) {
+                    ("ldrb", 1) | ("ldrh", 2) => false,
+                    ("ldrsb", 1) | ("ldrsh", 2) => true,
+                    _ => return Self::default(),
+                };
+                match (args[0], args[1], args[2], args[3]) {
+                    (
+                        Argument::Reg(Reg { reg: jump, .. }),
+                        Argument::Reg(Reg { reg: base_reg, deref: true, .. }),
+                        Argument::OffsetReg(OffsetReg { add: true, post_indexed: false, reg: offset_reg }),
+                        Argument::None,
+                    ) if base_reg == table && offset_reg == index => {
+                        Self::AddPcOffset { jump, table_address, limit, entry_size, signed }
+                    }
+                    _ => Self::default(),
+                }
+            }
+            Self::AddPcOffset { jump, table_address, limit, entry_size, signed } => {
+                match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3], args[4]) {
+                    (
+                        "add",
+                        Argument::Reg(Reg { reg: Register::Pc, .. }),
+                        Argument::Reg(Reg { reg: Register::Pc, .. }),
+                        Argument::Reg(Reg { reg, .. }),
+                        Argument::ShiftImm(ShiftImm { imm: 2, op: Shift::Lsl }),
+                        Argument::None,
+                    ) if reg == jump && table_address == address + 4 => {
+                        // The table is skipped by the PC being 8 bytes ahead, and offsets are relative to the PC
+                        let size = (limit + 1) * entry_size;
+                        let table = JumpTable { address: table_address, size, code: false, entry_size, signed };
+                        jump_tables.insert(table_address, table);
+                        Self::ValidOffsetTable { table }
+                    }
+                    _ => Self::default(),
+                }
+            }
+            Self::ValidOffsetTable { table } => {
+                if address >= table.address + table.size {
+                    Self::default()
+                } else {
+                    self
+                }
+            }
         }
     }
 
     pub fn table_end_address(&self) -> Option<u32> {
         match self {
             Self::ValidJumpTable { table_address, limit } => Some(table_address + (limit + 1) * 4),
+            // Keep the next possible entry in the conditional block, so that a backwards branch isn't seen as a return
+            Self::ScanBranchTable { table_address, size, .. } => Some(table_address + size + 4),
+            // Offset tables are padded to the next word
+            Self::ValidOffsetTable { table } => Some((table.address + table.size + 3) & !3),
             _ => None,
         }
     }
+
+    /// Returns the jump destinations of the offset table entries in the word at `address`.
+    pub fn get_labels(&self, address: u32, ins: Ins) -> Vec<u32> {
+        let Self::ValidOffsetTable { table } = self else { return vec![] };
+        offset_table_labels(false, table, address, 4, ins.code())
+    }
+
+    pub fn is_numerical_jump_offset(&self) -> bool {
+        matches!(self, JumpTableStateArm::ValidOffsetTable { .. })
+    }
 }
 
 #[derive(Clone, Copy, Default, Debug)]
@@ -187,15 +404,23 @@ pub enum JumpTableStateThumb {
     /// `bmi @skip`                     same as above but for subtraction
     BranchNegative { index: Register, limit: u32 },
 
-    /// `add offset, index, index`      multiply index by 2 to calculate jump table offset
+    /// `add offset, index, index`      multiply index by 2 to calculate jump table offset, OR
+    /// `add index, pc`                 turn index into a PC-relative address of a byte offset, OR
+    /// `add table, pc, #imm`           get the address of a signed byte table
     AddRegReg { index: Register, limit: u32 },
 
-    /// `add offset, pc`                turn jump table offset into a PC-relative address
+    /// `add offset, pc`                turn jump table offset into a PC-relative address, OR
+    /// `add table, pc, #imm`           get the address of a signed halfword table
     AddRegPc { offset: Register, limit: u32 },
 
     /// `ldrh jump, [offset, #imm]`     load 16-bit jump value from table
     LoadOffset { offset: Register, limit: u32, pc_base: u32 },
 
+    /// if the table address was taken with `add table, pc, #imm`:
+    /// `ldrsb jump, [table, index]`    load signed 8-bit jump value from table, OR
+    /// `ldrsh jump, [table, offset]`   load signed 16-bit jump value from table, which needs no sign extension
+    LoadSignedOffset { index: Register, table: Register, table_address: u32, limit: u32, entry_size: u32 },
+
     /// `lsl jump, jump, #0x10`         sign extend
     SignExtendLsl { jump: Register, table_address: u32, limit: u32 },
 
@@ -205,8 +430,18 @@ pub enum JumpTableStateThumb {
     /// `add pc, jump`                  do the jump
     AddPcReg { jump: Register, table_address: u32, limit: u32 },
 
-    /// valid table detected, starts from `table_address` with a size of `limit`
-    ValidJumpTable { table_address: u32, limit: u32 },
+    /// if [`JumpTableStateThumb::AddRegReg`] was `add index, pc`:
+    /// `ldrb jump, [offset, #imm]`     load unsigned 8-bit jump value from table
+    LoadByteOffset { offset: Register, limit: u32 },
+
+    /// `lsl jump, jump, #0x1`          scale the byte offset to halfwords
+    ScaleByteOffset { jump: Register, table_address: Option<u32>, limit: u32, signed: bool },
+
+    /// `add pc, jump`                  do the jump, the table follows right after
+    AddPcByteOffset { jump: Register, table_address: Option<u32>, limit: u32, signed: bool },
+
+    /// valid table detected, starts from `table_address` with `limit + 1` entries of `entry_size` bytes
+    ValidJumpTable { table: JumpTable },
 }
 
 impl JumpTableStateThumb {
@@ -221,6 +456,10 @@ impl JumpTableStateThumb {
     }
 
     fn handle(self, address: u32, ins: Ins, parsed_ins: &ParsedIns, jump_tables: &mut JumpTables) -> Self {
+        if matches!(self, Self::ValidJumpTable { table } if address < table.address + table.size) {
+            // Table entries are data, even if they happen to look like a comparison
+            return self;
+        }
         if let Some(start) = self.check_start(parsed_ins) {
             return start;
         };
@@ -272,6 +511,21 @@ impl JumpTableStateThumb {
                         Self::default()
                     }
                 }
+                ("add", Argument::Reg(Reg { reg, .. }), Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::None, _)
+                    if reg == index =>
+                {
+                    Self::LoadByteOffset { offset: index, limit }
+                }
+                (
+                    "add",
+                    Argument::Reg(Reg { reg: table, .. }),
+                    Argument::Reg(Reg { reg: Register::Pc, .. }),
+                    Argument::UImm(value),
+                    _,
+                ) if table != index => {
+                    let table_address = ((address + 4) & !3) + value;
+                    Self::LoadSignedOffset { index, table, table_address, limit, entry_size: 1 }
+                }
                 _ => Self::default(),
             },
             Self::AddRegPc { offset, limit } => match (parsed_ins.mnemonic, args[0], args[1], args[2]) {
@@ -282,8 +536,37 @@ impl JumpTableStateThumb {
                         Self::default()
                     }
                 }
+                (
+                    "add",
+                    Argument::Reg(Reg { reg: table, .. }),
+                    Argument::Reg(Reg { reg: Register::Pc, .. }),
+                    Argument::UImm(value),
+                ) if table != offset => {
+                    let table_address = ((address + 4) & !3) + value;
+                    Self::LoadSignedOffset { index: offset, table, table_address, limit, entry_size: 2 }
+                }
                 _ => Self::default(),
             },
+            Self::LoadSignedOffset { index, table, table_address, limit, entry_size } => {
+                match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
+                    (
+                        mnemonic @ ("ldrsb" | "ldrsh"),
+                        Argument::Reg(Reg { reg: jump, .. }),
+                        Argument::Reg(Reg { reg: base_reg, deref: true, .. }),
+                        Argument::OffsetReg(OffsetReg { add: true, post_indexed: false, reg: offset_reg }),
+                        Argument::None,
+                    ) if (base_reg, offset_reg) == (table, index) || (base_reg, offset_reg) == (index, table) => {
+                        match (mnemonic, entry_size) {
+                            ("ldrsb", 1) => {
+                                Self::ScaleByteOffset { jump, table_address: Some(table_address), limit, signed: true }
+                            }
+                            ("ldrsh", 2) => Self::AddPcReg { jump, table_address, limit },
+                            _ => Self::default(),
+                        }
+                    }
+                    _ => Self::default(),
+                }
+            }
             Self::LoadOffset { offset, limit, pc_base } => match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
                 (
                     "ldrh",
@@ -329,15 +612,56 @@ impl JumpTableStateThumb {
                 ("add", Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::Reg(Reg { reg, .. }), Argument::None)
                     if reg == jump =>
                 {
+                    // Halfword entries are always sign extended, either by the load or by the shifts
                     let size = (limit + 1) * 2;
-                    jump_tables.insert(table_address, JumpTable { address: table_address, size, code: false });
-                    Self::ValidJumpTable { table_address, limit }
+                    let table = JumpTable { address: table_address, size, code: false, entry_size: 2, signed: true };
+                    jump_tables.insert(table_address, table);
+                    Self::ValidJumpTable { table }
                 }
                 _ => Self::default(),
             },
-            Self::ValidJumpTable { table_address, limit } => {
-                let end = table_address + limit * 2;
-                if address > end {
+            Self::LoadByteOffset { offset, limit } => match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
+                (
+                    "ldrb",
+                    Argument::Reg(Reg { reg: jump, .. }),
+                    Argument::Reg(Reg { reg: base_reg, deref: true, .. }),
+                    Argument::OffsetImm(OffsetImm { post_indexed: false, .. }),
+                    Argument::None,
+                ) if base_reg == offset => Self::ScaleByteOffset { jump, table_address: None, limit, signed: false },
+                _ => Self::default(),
+            },
+            Self::ScaleByteOffset { jump, table_address, limit, signed } => {
+                match (parsed_ins.mnemonic, args[0], args[1], args[2], args[3]) {
+                    (
+                        "lsl",
+                        Argument::Reg(Reg { reg: dest_reg, .. }),
+                        Argument::Reg(Reg { reg: src_reg, .. }),
+                        Argument::UImm(1),
+                        Argument::None,
+                    ) if dest_reg == src_reg && dest_reg == jump => {
+                        Self::AddPcByteOffset { jump, table_address, limit, signed }
+                    }
+                    _ => Self::default(),
+                }
+            }
+            Self::AddPcByteOffset { jump, table_address, limit, signed } => {
+                match (parsed_ins.mnemonic, args[0], args[1], args[2]) {
+                    ("add", Argument::Reg(Reg { reg: Register::Pc, .. }), Argument::Reg(Reg { reg, .. }), Argument::None)
+                        if reg == jump && table_address.map_or(true, |table_address| table_address == address + 2) =>
+                    {
+                        // The table follows right after this instruction, and offsets are relative to the PC which is 4 bytes
+                        // ahead
+                        let table_address = address + 2;
+                        let size = limit + 1;
+                        let table = JumpTable { address: table_address, size, code: false, entry_size: 1, signed };
+                        jump_tables.insert(table_address, table);
+                        Self::ValidJumpTable { table }
+                    }
+                    _ => Self::default(),
+                }
+            }
+            Self::ValidJumpTable { table } => {
+                if address >= table.address + table.size {
                     Self::default()
                 } else {
                     self
@@ -348,23 +672,16 @@ impl JumpTableStateThumb {
 
     pub fn table_end_address(&self) -> Option<u32> {
         match self {
-            Self::ValidJumpTable { table_address, limit } => Some(table_address + (limit + 1) * 2),
+            // Byte tables with an odd number of entries are padded to the next halfword
+            Self::ValidJumpTable { table } => Some((table.address + table.size + 1) & !1),
             _ => None,
         }
     }
 
-    pub fn get_label(&self, address: u32, ins: Ins) -> Option<u32> {
-        match self {
-            Self::ValidJumpTable { table_address, limit } => {
-                let end = table_address + limit * 2;
-                if address < *table_address || address > end {
-                    None
-                } else {
-                    Some((*table_address as i32 + ins.code() as i16 as i32 + 2) as u32)
-                }
-            }
-            _ => None,
-        }
+    /// Returns the jump destinations of the table entries in the halfword at `address`.
+    pub fn get_labels(&self, address: u32, ins: Ins) -> Vec<u32> {
+        let Self::ValidJumpTable { table } = self else { return vec![] };
+        offset_table_labels(true, table, address, 2, ins.code())
     }
 
     pub fn is_numerical_jump_offset(&self) -> bool {
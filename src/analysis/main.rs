@@ -1,8 +1,15 @@
+use std::collections::BTreeMap;
+
 use anyhow::{bail, Context, Result};
 use ds_rom::rom::Arm9;
-use unarm::args::{Argument, OffsetImm, Reg, Register};
+use unarm::{
+    args::{Argument, OffsetImm, Reg, Register},
+    Endian,
+};
+
+use crate::{config::symbol::InstructionMode, util::bytes::FromSlice};
 
-use super::functions::{Function, FunctionParseOptions, ParseFunctionResult};
+use super::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
 
 #[derive(Clone, Copy)]
 pub struct MainFunction {
@@ -10,8 +17,8 @@ pub struct MainFunction {
 }
 
 impl MainFunction {
-    fn find_tail_call(function: Function, module_code: &[u8], base_address: u32) -> Result<u32> {
-        let mut parser = function.parser(module_code, base_address);
+    fn find_tail_call(function: Function, module_code: &[u8], base_address: u32, endian: Endian) -> Result<u32> {
+        let mut parser = function.parser(module_code, base_address, endian);
 
         let ins_size = parser.mode.instruction_size(0) as u32;
         let last_ins_addr =
@@ -26,7 +33,7 @@ impl MainFunction {
         };
 
         let mut p_tail_call = None;
-        for (address, _ins, parsed_ins) in function.parser(module_code, base_address) {
+        for (address, _ins, parsed_ins) in function.parser(module_code, base_address, endian) {
             if function.pool_constants().contains(&address) {
                 break;
             }
@@ -48,11 +55,15 @@ impl MainFunction {
 
         let function_code = function.code(module_code, base_address);
         let tail_call_data = &function_code[(p_tail_call - function.start_address()) as usize..];
-        let tail_call = u32::from_le_bytes([tail_call_data[0], tail_call_data[1], tail_call_data[2], tail_call_data[3]]);
+        let tail_call = u32::from_slice(tail_call_data, endian);
         Ok(tail_call & !1)
     }
 
-    pub fn find_in_arm9(arm9: &Arm9) -> Result<Self> {
+    pub fn find_in_arm9(
+        arm9: &Arm9,
+        endian: Endian,
+        instruction_modes: Option<&BTreeMap<u32, InstructionMode>>,
+    ) -> Result<Self> {
         let code = arm9.code()?;
 
         let entry_addr = arm9.entry_function();
@@ -65,14 +76,14 @@ impl MainFunction {
             known_end_address: None,
             module_start_address: arm9.base_address(),
             module_end_address: arm9.end_address()?,
-            parse_options: Default::default(),
+            parse_options: ParseFunctionOptions { instruction_modes, endian, ..Default::default() },
         })?;
         let entry_func = match parse_result {
             ParseFunctionResult::Found(function) => function,
             _ => bail!("failed to analyze entrypoint function: {:?}", parse_result),
         };
 
-        let main = Self::find_tail_call(entry_func, entry_code, entry_addr)?;
+        let main = Self::find_tail_call(entry_func, entry_code, entry_addr, endian)?;
         Ok(Self { address: main })
     }
 }
@@ -1,5 +1,7 @@
 pub mod ctor;
 pub mod data;
+pub mod defs_uses;
+pub mod disassemble;
 pub mod function_branch;
 pub mod function_start;
 pub mod functions;
@@ -8,4 +10,7 @@ pub mod inline_table;
 pub mod jump_table;
 pub mod main;
 pub mod overlay_groups;
+pub mod overlay_loads;
+pub mod rtti;
 pub mod secure_area;
+pub mod strings;
@@ -1,29 +1,62 @@
 use anyhow::{bail, Result};
 use ds_rom::rom::Overlay;
 
+use crate::config::overlay_groups::ExclusiveOverlayGroups;
+
 pub struct OverlayGroups {
     groups: Vec<OverlayGroup>,
 }
 
 pub type OverlayIndex = u16;
 
+/// Address range which an overlay is loaded at.
+#[derive(Clone, Copy)]
+pub struct OverlayRange {
+    pub id: OverlayIndex,
+    pub base_address: u32,
+    pub end_address: u32,
+}
+
 pub struct OverlayGroup {
     pub index: u16,
     pub start_address: u32,
     pub end_address: u32,
     pub overlays: Vec<OverlayIndex>,
     pub after: Vec<OverlayIndex>,
+    /// Fixed base address, for user-declared groups which don't start at the end of another overlay
+    pub origin: Option<u32>,
 }
 
 impl OverlayGroups {
-    pub fn analyze(main_end_address: u32, overlays: &[Overlay]) -> Result<OverlayGroups> {
+    pub fn analyze(
+        main_end_address: u32,
+        overlays: &[Overlay],
+        exclusive_groups: &ExclusiveOverlayGroups,
+    ) -> Result<OverlayGroups> {
+        let overlays = overlays
+            .iter()
+            .map(|overlay| OverlayRange {
+                id: overlay.id(),
+                base_address: overlay.base_address(),
+                end_address: overlay.end_address(),
+            })
+            .collect::<Vec<_>>();
+        Self::analyze_ranges(main_end_address, &overlays, exclusive_groups)
+    }
+
+    /// Groups overlays by their address ranges. Overlay IDs must be the same as their indices in `overlays`.
+    pub fn analyze_ranges(
+        main_end_address: u32,
+        overlays: &[OverlayRange],
+        exclusive_groups: &ExclusiveOverlayGroups,
+    ) -> Result<OverlayGroups> {
         let (first_group, first_group_end, mut ungrouped_overlays) =
             overlays.iter().fold((vec![], 0, vec![]), |(mut first_group, mut first_group_end, mut rest), overlay| {
-                if overlay.base_address() == main_end_address {
-                    first_group.push(overlay.id());
-                    first_group_end = first_group_end.max(overlay.end_address())
+                if overlay.base_address == main_end_address {
+                    first_group.push(overlay.id);
+                    first_group_end = first_group_end.max(overlay.end_address)
                 } else {
-                    rest.push(overlay.id());
+                    rest.push(overlay.id);
                 }
                 (first_group, first_group_end, rest)
             });
@@ -34,10 +67,49 @@ impl OverlayGroups {
             end_address: first_group_end,
             overlays: first_group,
             after: vec![],
+            origin: None,
         }];
 
         let mut new_group = vec![];
         let mut groups_to_connect = vec![0u16]; // list of groups (indices) which may be preceded by ungrouped overlays
+
+        for exclusive_group in exclusive_groups.iter() {
+            let mut base_addresses = exclusive_group.overlays().iter().map(|&id| match overlays.get(id as usize) {
+                Some(overlay) => Ok(overlay.base_address),
+                None => bail!("Overlay group {exclusive_group} refers to overlay {id} which doesn't exist"),
+            });
+            let base_address = base_addresses.next().unwrap()?;
+            for other_base_address in base_addresses {
+                if other_base_address? != base_address {
+                    bail!("Overlays in overlay group {exclusive_group} must have the same base address");
+                }
+            }
+
+            let follows_overlay =
+                base_address == main_end_address || overlays.iter().any(|overlay| overlay.end_address == base_address);
+            if follows_overlay {
+                // Will be grouped by base address below
+                continue;
+            }
+
+            // Place every overlay sharing the base address in the group, as they occupy the same memory
+            let (group_overlays, rest): (Vec<_>, Vec<_>) =
+                ungrouped_overlays.into_iter().partition(|&id| overlays[id as usize].base_address == base_address);
+            ungrouped_overlays = rest;
+            let end_address = group_overlays.iter().map(|&id| overlays[id as usize].end_address).max().unwrap_or(base_address);
+
+            let index = groups.len() as u16;
+            groups.push(OverlayGroup {
+                index,
+                start_address: base_address,
+                end_address,
+                overlays: group_overlays,
+                after: vec![],
+                origin: Some(base_address),
+            });
+            groups_to_connect.push(index);
+        }
+
         while !ungrouped_overlays.is_empty() {
             let Some(connect_index) = groups_to_connect.pop() else {
                 bail!("No more overlay groups to connect to, are there gaps between overlays?");
@@ -46,14 +118,14 @@ impl OverlayGroups {
 
             for i in 0..groups[connect_index].overlays.len() {
                 let grouped_overlay = &overlays[groups[connect_index].overlays[i] as usize];
-                let overlay_end = grouped_overlay.end_address();
+                let overlay_end = grouped_overlay.end_address;
 
                 let mut group_end = 0;
                 for j in (0..ungrouped_overlays.len()).rev() {
                     let overlay = &overlays[ungrouped_overlays[j] as usize];
-                    if overlay.base_address() == grouped_overlay.end_address() {
+                    if overlay.base_address == grouped_overlay.end_address {
                         new_group.push(ungrouped_overlays.remove(j));
-                        group_end = group_end.max(overlay.end_address());
+                        group_end = group_end.max(overlay.end_address);
                     }
                 }
 
@@ -62,7 +134,7 @@ impl OverlayGroups {
                         .overlays
                         .iter()
                         .cloned()
-                        .filter(|&id| overlays[id as usize].end_address() <= overlay_end)
+                        .filter(|&id| overlays[id as usize].end_address <= overlay_end)
                         .collect();
 
                     new_group.reverse();
@@ -74,6 +146,7 @@ impl OverlayGroups {
                         end_address: group_end,
                         overlays: new_group,
                         after,
+                        origin: None,
                     });
                     groups_to_connect.push(index);
 
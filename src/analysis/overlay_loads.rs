@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use unarm::{
+    args::{Argument, Reg, Register},
+    Endian,
+};
+
+use super::functions::Function;
+
+/// Register which holds the overlay ID argument of `FS_LoadOverlay(MIProcessor target, FSOverlayID id)` and
+/// `FS_UnloadOverlay(MIProcessor target, FSOverlayID id)`.
+const OVERLAY_ID_REGISTER: Register = Register::R1;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum OverlayLoadKind {
+    Load,
+    Unload,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OverlayLoad {
+    /// Address of the call instruction
+    pub address: u32,
+    pub kind: OverlayLoadKind,
+    /// Overlay ID argument, or `None` if it isn't a known constant at the call
+    pub overlay_id: Option<u16>,
+}
+
+/// Finds calls to overlay loader functions in `function`. `loaders` maps loader function addresses to what they do.
+///
+/// This is a best-effort analysis which only follows constants moved or loaded into registers, ignoring control flow.
+pub fn find_overlay_loads(
+    function: &Function,
+    module_code: &[u8],
+    base_address: u32,
+    endian: Endian,
+    loaders: &BTreeMap<u32, OverlayLoadKind>,
+) -> Vec<OverlayLoad> {
+    let mut loads = vec![];
+    let mut constants = RegisterConstants::default();
+
+    let pool_constants = function
+        .iter_pool_constants(module_code, base_address, endian)
+        .map(|constant| (constant.address, constant.value))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut parser = function.parser(module_code, base_address, endian);
+    while let Some((address, ins, parsed_ins)) = parser.next() {
+        if pool_constants.contains_key(&address) {
+            parser.seek_forward(address + 4);
+            continue;
+        }
+
+        if let Some(called_function) = function.function_calls().get(&address) {
+            if let Some(&kind) = loaders.get(&(called_function.address & !1)) {
+                let overlay_id = constants.get(OVERLAY_ID_REGISTER).and_then(|id| u16::try_from(id).ok());
+                loads.push(OverlayLoad { address, kind, overlay_id });
+            }
+            // Argument registers are not preserved across calls
+            constants.clear_arguments();
+            continue;
+        }
+
+        let args = &parsed_ins.args;
+        match (ins.mnemonic(), args[0], args[1], args[2]) {
+            ("mov" | "movs", Argument::Reg(Reg { reg, .. }), Argument::UImm(value), Argument::None) => {
+                constants.set(reg, value);
+            }
+            (
+                "ldr",
+                Argument::Reg(Reg { reg, .. }),
+                Argument::Reg(Reg { reg: Register::Pc, deref: true, .. }),
+                Argument::OffsetImm(offset),
+            ) => {
+                let pool_address = ((address as i32 + offset.value) as u32 & !3) + if function.is_thumb() { 4 } else { 8 };
+                match pool_constants.get(&pool_address) {
+                    Some(&value) => constants.set(reg, value),
+                    None => constants.clear(reg),
+                }
+            }
+            (mnemonic, Argument::Reg(Reg { reg, deref: false, .. }), _, _) if !is_non_writing(mnemonic) => {
+                constants.clear(reg);
+            }
+            _ => {}
+        }
+    }
+
+    loads
+}
+
+/// Returns true for instructions whose first register argument is not written to.
+fn is_non_writing(mnemonic: &str) -> bool {
+    mnemonic.starts_with("str")
+        || mnemonic.starts_with("stm")
+        || matches!(mnemonic, "cmp" | "cmn" | "tst" | "teq" | "b" | "bx" | "push")
+}
+
+#[derive(Default)]
+struct RegisterConstants {
+    values: Vec<(Register, u32)>,
+}
+
+impl RegisterConstants {
+    fn get(&self, register: Register) -> Option<u32> {
+        self.values.iter().find(|(reg, _)| *reg == register).map(|(_, value)| *value)
+    }
+
+    fn set(&mut self, register: Register, value: u32) {
+        self.clear(register);
+        self.values.push((register, value));
+    }
+
+    fn clear(&mut self, register: Register) {
+        self.values.retain(|(reg, _)| *reg != register);
+    }
+
+    fn clear_arguments(&mut self) {
+        self.values
+            .retain(|(reg, _)| !matches!(reg, Register::R0 | Register::R1 | Register::R2 | Register::R3 | Register::R12));
+    }
+}
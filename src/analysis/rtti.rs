@@ -0,0 +1,47 @@
+/// Longer strings are unlikely to be type names
+const MAX_TYPE_NAME_LEN: usize = 256;
+
+/// Reads the NUL-terminated type name at the start of `bytes`, without the terminator.
+pub fn read_type_name(bytes: &[u8]) -> Option<&str> {
+    let len = bytes.iter().take(MAX_TYPE_NAME_LEN + 1).position(|&byte| byte == 0)?;
+    if len == 0 {
+        return None;
+    }
+    std::str::from_utf8(&bytes[..len]).ok()
+}
+
+/// Turns the type name of an mwcc type info block into an identifier. Plain names like `Foo` are kept, mangled names like
+/// `3Foo` become `Foo` and qualified names like `Q23Foo3Bar` become `Foo_Bar`. Returns `None` for anything else, which is
+/// then not considered a type name.
+pub fn demangle_type_name(name: &str) -> Option<String> {
+    if let Some(qualified) = name.strip_prefix('Q').filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit())) {
+        let count = qualified[..1].parse::<usize>().ok()?;
+        let mut rest = &qualified[1..];
+        let mut parts = vec![];
+        for _ in 0..count {
+            let (part, next) = take_length_prefixed(rest)?;
+            parts.push(part);
+            rest = next;
+        }
+        return (rest.is_empty() && !parts.is_empty()).then(|| parts.join("_"));
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        let (part, rest) = take_length_prefixed(name)?;
+        return rest.is_empty().then(|| part.to_string());
+    }
+    is_identifier(name).then(|| name.to_string())
+}
+
+/// Splits a length-prefixed identifier like `3Foo` from the start of `name`.
+fn take_length_prefixed(name: &str) -> Option<(&str, &str)> {
+    let digits = name.len() - name.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let len = name[..digits].parse::<usize>().ok()?;
+    let rest = &name[digits..];
+    let part = rest.get(..len)?;
+    is_identifier(part).then_some((part, &rest[len..]))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -161,6 +161,11 @@ impl SecureAreaFunction {
         self.function.name(self.return_reg)
     }
 
+    /// Returns the name of the BIOS call function in the SDK, like `SVC_Div`.
+    pub fn bios_call_name(&self) -> String {
+        format!("SVC_{}", self.name())
+    }
+
     pub fn start(&self) -> u32 {
         self.start
     }
@@ -0,0 +1,78 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum StringEncoding {
+    /// Printable ASCII only.
+    Ascii,
+    /// ASCII plus half-width katakana and double-byte Shift-JIS characters.
+    #[value(alias = "sjis")]
+    #[serde(alias = "sjis")]
+    ShiftJis,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct FoundString {
+    pub address: u32,
+    pub text: String,
+}
+
+/// Finds null-terminated strings of at least `min_len` characters in `code`, which starts at `base_address`.
+pub fn find_strings(code: &[u8], base_address: u32, min_len: usize, encoding: StringEncoding) -> Vec<FoundString> {
+    let mut strings = vec![];
+    let mut offset = 0;
+    while offset < code.len() {
+        match string_length(&code[offset..], encoding) {
+            Ok((size, num_chars)) => {
+                if num_chars >= min_len.max(1) {
+                    let bytes = &code[offset..offset + size];
+                    strings.push(FoundString { address: base_address + offset as u32, text: decode(bytes, encoding) });
+                }
+                offset += size + 1;
+            }
+            Err(invalid_offset) => offset += invalid_offset + 1,
+        }
+    }
+    strings
+}
+
+/// Decodes the null-terminated string at the start of `bytes`, if there is one. Returns the size of the string in bytes,
+/// excluding the null terminator.
+pub fn decode_string(bytes: &[u8], encoding: StringEncoding) -> Option<(usize, String)> {
+    match string_length(bytes, encoding) {
+        Ok((size, num_chars)) if num_chars > 0 => Some((size, decode(&bytes[..size], encoding))),
+        _ => None,
+    }
+}
+
+/// Returns the size in bytes and number of characters of the string at the start of `bytes`, excluding the null
+/// terminator. Fails with the offset of the first byte which isn't part of a string.
+fn string_length(bytes: &[u8], encoding: StringEncoding) -> Result<(usize, usize), usize> {
+    let mut offset = 0;
+    let mut num_chars = 0;
+    while let Some(&byte) = bytes.get(offset) {
+        let char_size = match (encoding, byte) {
+            (_, 0) => return Ok((offset, num_chars)),
+            (_, 0x20..=0x7e | b'\t' | b'\n' | b'\r') => 1,
+            // Half-width katakana
+            (StringEncoding::ShiftJis, 0xa1..=0xdf) => 1,
+            (StringEncoding::ShiftJis, 0x81..=0x9f | 0xe0..=0xfc) => match bytes.get(offset + 1) {
+                Some(0x40..=0x7e | 0x80..=0xfc) => 2,
+                _ => return Err(offset),
+            },
+            _ => return Err(offset),
+        };
+        offset += char_size;
+        num_chars += 1;
+    }
+    // Not null-terminated
+    Err(offset.saturating_sub(1))
+}
+
+fn decode(bytes: &[u8], encoding: StringEncoding) -> String {
+    match encoding {
+        StringEncoding::Ascii => String::from_utf8_lossy(bytes).into_owned(),
+        StringEncoding::ShiftJis => encoding_rs::SHIFT_JIS.decode_without_bom_handling(bytes).0.into_owned(),
+    }
+}
@@ -0,0 +1,26 @@
+mod renames;
+
+pub use renames::*;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Subcommands for applying bulk changes to the config.
+#[derive(Args)]
+pub struct ApplyArgs {
+    #[command(subcommand)]
+    command: ApplyCommand,
+}
+
+impl ApplyArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            ApplyCommand::Renames(renames) => renames.run(),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ApplyCommand {
+    Renames(ApplyRenames),
+}
@@ -0,0 +1,196 @@
+use std::{
+    fmt::Display,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Result};
+use clap::Args;
+
+use crate::{
+    config::{
+        config::Config,
+        module::ModuleKind,
+        symbol::{SymbolMaps, SymbolRename},
+    },
+    util::{backup::write_with_backups, io::open_file, parse::parse_u32},
+};
+
+/// Renames symbols according to a mapping file with one `<old name> <new name>` or `<address> <new name>` entry per line.
+#[derive(Args)]
+pub struct ApplyRenames {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Path to the mapping file. Addresses must start with `0x`, and text after a `#` is ignored.
+    #[arg(long, short = 'f')]
+    pub file: PathBuf,
+
+    /// Only renames symbols in this module: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Writes nothing if any entry fails, instead of applying the entries that succeeded.
+    #[arg(long, short = 's')]
+    pub strict: bool,
+
+    /// Dry run, do not write any files but list the symbols which would be renamed.
+    #[arg(long, short = 'd', visible_alias = "dry-run")]
+    pub dry: bool,
+
+    /// Don't keep a backup of changed symbol files in `.dsd_backups`.
+    #[arg(long)]
+    pub no_backup: bool,
+}
+
+/// An entry of the mapping file which [`ApplyRenames`] could not apply.
+pub struct RenameFailure {
+    /// 1-based line number in the mapping file
+    pub line: usize,
+    pub entry: String,
+    pub reason: RenameFailureReason,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum RenameFailureReason {
+    /// The line doesn't have exactly two columns, or the address is invalid
+    Malformed,
+    NotFound,
+    /// The name or address matches more than one symbol
+    Ambiguous {
+        count: usize,
+    },
+}
+
+pub struct ApplyRenamesResult {
+    pub renames: Vec<SymbolRename>,
+    pub failures: Vec<RenameFailure>,
+}
+
+impl ApplyRenames {
+    pub fn run(&self) -> Result<()> {
+        let ApplyRenamesResult { renames, failures } = self.apply()?;
+
+        for failure in &failures {
+            log::error!("{}:{}: '{}': {}", self.file.display(), failure.line, failure.entry, failure.reason);
+        }
+        for rename in &renames {
+            log::info!("{}: {} -> {} at {:#010x}", rename.module_kind, rename.old_name, rename.new_name, rename.address);
+        }
+
+        if self.strict && !failures.is_empty() {
+            bail!("{} entries failed, no symbols were renamed", failures.len());
+        }
+        if self.dry {
+            log::info!("Dry run, {} symbol(s) would be renamed", renames.len());
+        } else {
+            log::info!("Renamed {} symbol(s), {} entries failed", renames.len(), failures.len());
+        }
+        Ok(())
+    }
+
+    /// Applies every entry of the mapping file in order, and returns the renamed symbols and the failed entries. Nothing
+    /// is written in a dry run, or if any entry failed with `--strict`.
+    pub fn apply(&self) -> Result<ApplyRenamesResult> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+        let mut renames = vec![];
+        let mut failures = vec![];
+
+        let reader = BufReader::new(open_file(&self.file)?);
+        for (row, line) in reader.lines().enumerate() {
+            let line = line?;
+            let entry = line.split('#').next().unwrap().trim();
+            if entry.is_empty() {
+                continue;
+            }
+
+            let (module_kind, address) = match self.find_symbol(&config, &symbol_maps, entry) {
+                Ok(symbol) => symbol,
+                Err(reason) => {
+                    failures.push(RenameFailure { line: row + 1, entry: entry.to_string(), reason });
+                    continue;
+                }
+            };
+            let new_name = entry.split_whitespace().nth(1).unwrap();
+
+            let symbol_map = symbol_maps.get_mut(module_kind);
+            let (_, symbol) = symbol_map.by_address(address)?.unwrap();
+            if symbol.name == new_name {
+                continue;
+            }
+            let old_name = symbol.name.clone();
+            symbol_map.rename_by_address(address, new_name)?;
+            renames.push(SymbolRename { module_kind, address, old_name, new_name: new_name.to_string() });
+        }
+
+        let write_allowed = !self.strict || failures.is_empty();
+        if !self.dry && write_allowed && !renames.is_empty() {
+            let write = || symbol_maps.to_files(&config, config_path);
+            if self.no_backup {
+                write()?;
+            } else {
+                write_with_backups(config.modules().map(|(_, module)| config_path.join(&module.symbols)), write)?;
+            }
+        }
+
+        Ok(ApplyRenamesResult { renames, failures })
+    }
+
+    /// Returns the module and address of the only symbol matching the first column of `entry`.
+    fn find_symbol(
+        &self,
+        config: &Config,
+        symbol_maps: &SymbolMaps,
+        entry: &str,
+    ) -> Result<(ModuleKind, u32), RenameFailureReason> {
+        let columns = entry.split_whitespace().collect::<Vec<_>>();
+        let &[key, _] = columns.as_slice() else {
+            return Err(RenameFailureReason::Malformed);
+        };
+        let address =
+            if key.starts_with("0x") { Some(parse_u32(key).map_err(|_| RenameFailureReason::Malformed)?) } else { None };
+
+        let mut matches = vec![];
+        for (module_kind, _) in config.modules() {
+            if self.module.is_some_and(|module| module != module_kind) {
+                continue;
+            }
+            let Some(symbol_map) = symbol_maps.get(module_kind) else { continue };
+            let addresses: Vec<u32> = match address {
+                Some(address) => {
+                    symbol_map.for_address(address).into_iter().flatten().map(|(_, symbol)| symbol.addr).collect()
+                }
+                None => symbol_map.for_name(key).into_iter().flatten().map(|(_, symbol)| symbol.addr).collect(),
+            };
+            matches.extend(addresses.into_iter().map(|address| (module_kind, address)));
+        }
+
+        let &[(module_kind, address)] = matches.as_slice() else {
+            return Err(match matches.len() {
+                0 => RenameFailureReason::NotFound,
+                count => RenameFailureReason::Ambiguous { count },
+            });
+        };
+
+        // A symbol found by name can still share its address with other symbols
+        let count = symbol_maps.get(module_kind).unwrap().for_address(address).map_or(0, |symbols| symbols.count());
+        if count > 1 {
+            return Err(RenameFailureReason::Ambiguous { count });
+        }
+        Ok((module_kind, address))
+    }
+}
+
+impl Display for RenameFailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenameFailureReason::Malformed => write!(f, "expected '<old name> <new name>' or '<address> <new name>'"),
+            RenameFailureReason::NotFound => write!(f, "no symbol found"),
+            RenameFailureReason::Ambiguous { count } => write!(f, "ambiguous, found {count} symbols"),
+        }
+    }
+}
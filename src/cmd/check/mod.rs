@@ -1,6 +1,12 @@
 mod modules;
+mod relocations;
+mod sections;
+mod symbols;
 
 pub use modules::*;
+pub use relocations::*;
+pub use sections::*;
+pub use symbols::*;
 
 use anyhow::Result;
 use clap::{Args, Subcommand};
@@ -16,6 +22,9 @@ impl CheckArgs {
     pub fn run(&self) -> Result<()> {
         match &self.command {
             CheckCommand::Modules(modules) => modules.run(),
+            CheckCommand::Relocations(relocations) => relocations.run(),
+            CheckCommand::Sections(sections) => sections.run(),
+            CheckCommand::Symbols(symbols) => symbols.run(),
         }
     }
 }
@@ -23,4 +32,7 @@ impl CheckArgs {
 #[derive(Subcommand)]
 enum CheckCommand {
     Modules(CheckModules),
+    Relocations(CheckRelocations),
+    Sections(CheckSections),
+    Symbols(CheckSymbols),
 }
@@ -5,15 +5,21 @@ use std::{
 
 use anyhow::{bail, Context, Result};
 use clap::Args;
+use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
 
 use crate::{
     config::{
         config::{Config, ConfigModule},
+        delinks::Delinks,
         module::ModuleKind,
+        section::Sections,
+        symbol::SymbolMap,
     },
-    util::io::{open_file, read_file},
+    util::{io::read_file, watch::watch},
 };
 
+use super::OVERLAY_ALIGNMENT;
+
 /// Verifies that built modules are matching the base ROM.
 #[derive(Args)]
 pub struct CheckModules {
@@ -24,61 +30,250 @@ pub struct CheckModules {
     /// Return failing exit code if a module doesn't pass the checks.
     #[arg(long, short = 'f')]
     pub fail: bool,
+
+    /// Color the check results.
+    #[arg(long)]
+    pub color: bool,
+
+    /// Keep running and check again whenever config.yaml or a file it refers to changes.
+    #[arg(long, short = 'w')]
+    pub watch: bool,
+
+    /// Suggest an `ALIGN` edit to the LCF when a module fails because the linker placed a section too early.
+    #[arg(long)]
+    pub fix_alignment: bool,
 }
 
-#[derive(PartialEq, Eq)]
 enum CheckResult {
-    ChecksumFailed,
+    ChecksumFailed(Option<ModuleDifference>, Option<AlignmentFix>),
     Ok, // OK
 }
 
-impl CheckModules {
-    pub fn run(&self) -> Result<()> {
-        let config: Config = serde_yml::from_reader(open_file(&self.config_path)?)?;
-        let config_path = self.config_path.parent().unwrap();
+/// First byte where a built module differs from the base module.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ModuleDifference {
+    /// Offset from the start of the module
+    pub offset: u32,
+    pub address: u32,
+    /// Section containing the address
+    pub section: Option<String>,
+    /// Closest symbol at or before the address in the same section, and the offset from it
+    pub symbol: Option<(String, u32)>,
+}
 
-        let mut success = true;
+/// Alignment which a section needs in the LCF so that it starts at the same address as in the base module.
+#[derive(PartialEq, Eq, Debug)]
+pub struct AlignmentFix {
+    pub module: ModuleKind,
+    pub section: String,
+    pub alignment: u32,
+}
 
-        success &= self.print_check_module(&config.main_module, ModuleKind::Arm9, config_path)?;
-        for autoload in &config.autoloads {
-            success &= self.print_check_module(&autoload.module, ModuleKind::Autoload(autoload.kind), config_path)?;
-        }
-        for overlay in &config.overlays {
-            success &= self.print_check_module(&overlay.module, ModuleKind::Overlay(overlay.id), config_path)?;
+/// Number of bytes after the missing padding which must match the base module for an [`AlignmentFix`] to be suggested.
+const ALIGNMENT_FIX_MATCH_SIZE: usize = 0x20;
+
+impl CheckModules {
+    pub fn run(&self) -> Result<()> {
+        if self.watch {
+            watch(|| self.watched_paths(), || self.check());
         }
 
+        let success = self.check()?;
         if self.fail && !success {
             bail!("Some module(s) didn't pass the checks.");
         }
-
         Ok(())
     }
 
-    fn print_check_module(&self, module: &ConfigModule, module_kind: ModuleKind, config_path: &Path) -> Result<bool> {
-        let result = self.check_module(module, config_path)?;
-        log::info!("Check {module_kind}: {result}");
-        Ok(result == CheckResult::Ok)
+    /// Checks and logs every module. Returns whether all of them passed.
+    pub fn check(&self) -> Result<bool> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let name_width = config.modules().map(|(module_kind, _)| module_kind.to_string().len()).max().unwrap_or(0);
+
+        // Only loaded once a module fails
+        let mut base_rom = None;
+
+        let mut success = true;
+        for (module_kind, module) in config.modules() {
+            let result = self.check_module(module, module_kind, config_path, &config, &mut base_rom)?;
+            let module_name = module_kind.to_string();
+            log::info!("Check {module_name:<name_width$} : {}", self.format_result(&result));
+            if let CheckResult::ChecksumFailed(_, Some(fix)) = &result {
+                log::info!("{:<name_width$}         {fix}", "");
+            }
+            success &= matches!(result, CheckResult::Ok);
+        }
+
+        Ok(success)
     }
 
-    fn check_module(&self, module: &ConfigModule, config_path: &Path) -> Result<CheckResult> {
+    /// Returns config.yaml and the built modules, delinks files and symbol files it refers to.
+    pub fn watched_paths(&self) -> Result<Vec<PathBuf>> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut paths = vec![self.config_path.clone()];
+        for (_, module) in config.modules() {
+            paths.extend([&module.object, &module.delinks, &module.symbols].map(|path| config_path.join(path)));
+        }
+        Ok(paths)
+    }
+
+    fn format_result(&self, result: &CheckResult) -> String {
+        if !self.color {
+            return result.to_string();
+        }
+        match result {
+            CheckResult::ChecksumFailed(..) => format!("\x1b[31m{result}\x1b[0m"),
+            CheckResult::Ok => format!("\x1b[32m{result}\x1b[0m"),
+        }
+    }
+
+    /// Checks the hash of one module. If it fails, the module is compared with the extracted base ROM at `rom_config` in
+    /// config.yaml, which is loaded into `base_rom` the first time.
+    fn check_module(
+        &self,
+        module: &ConfigModule,
+        module_kind: ModuleKind,
+        config_path: &Path,
+        config: &Config,
+        base_rom: &mut Option<Rom>,
+    ) -> Result<CheckResult> {
         let base_hash = u64::from_str_radix(&module.hash, 16).with_context(|| format!("Invalid hash '{}'", module.hash))?;
 
         let code = read_file(config_path.join(&module.object))?;
         let code_hash = fxhash::hash64(&code);
 
-        if code_hash != base_hash {
-            Ok(CheckResult::ChecksumFailed)
-        } else {
-            Ok(CheckResult::Ok)
+        if code_hash == base_hash {
+            return Ok(CheckResult::Ok);
+        }
+        let rom_config_path = config_path.join(&config.rom_config);
+        if !rom_config_path.exists() {
+            log::debug!("Base ROM config '{}' not found, can't compare failing modules", rom_config_path.display());
+            return Ok(CheckResult::ChecksumFailed(None, None));
+        }
+        if base_rom.is_none() {
+            *base_rom = Some(Rom::load(
+                &rom_config_path,
+                RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+            )?);
+        }
+        let base_rom = base_rom.as_ref().unwrap();
+
+        let base_code = Self::read_base_code(base_rom, &rom_config_path, module_kind)?;
+        let delinks = Delinks::from_file(config_path.join(&module.delinks), module_kind)?;
+        let symbol_map = SymbolMap::from_file(config_path.join(&module.symbols))?;
+        let difference = Self::find_difference(&base_code, &code, &delinks.sections, &symbol_map);
+        let fix = match &difference {
+            Some(difference) if self.fix_alignment => {
+                Self::find_alignment_fix(&base_code, &code, &delinks.sections, module_kind, difference)
+            }
+            _ => None,
+        };
+        Ok(CheckResult::ChecksumFailed(difference, fix))
+    }
+
+    fn read_base_code(base_rom: &Rom, rom_config_path: &Path, module_kind: ModuleKind) -> Result<Vec<u8>> {
+        let extract_path = rom_config_path.parent().unwrap();
+        let path = match module_kind {
+            ModuleKind::Arm9 => extract_path.join(&base_rom.config().arm9_bin),
+            ModuleKind::Autoload(AutoloadKind::Itcm) => extract_path.join(&base_rom.config().itcm.bin),
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => extract_path.join(&base_rom.config().dtcm.bin),
+            ModuleKind::Autoload(kind) => bail!("Unknown autoload kind {kind}"),
+            ModuleKind::Overlay(id) => {
+                let arm9_overlays = base_rom.config().arm9_overlays.as_ref().context("Base ROM has no ARM9 overlays")?;
+                extract_path.join(arm9_overlays).parent().unwrap().join(format!("ov{id:03}.bin"))
+            }
+        };
+        read_file(path)
+    }
+
+    /// Returns the first byte where `code` differs from `base_code`, including where one of them ends before the other.
+    /// The section and symbol containing the difference are resolved from `sections` and `symbol_map`.
+    pub fn find_difference(
+        base_code: &[u8],
+        code: &[u8],
+        sections: &Sections,
+        symbol_map: &SymbolMap,
+    ) -> Option<ModuleDifference> {
+        let offset = base_code
+            .iter()
+            .zip(code)
+            .position(|(base, built)| base != built)
+            .or_else(|| (base_code.len() != code.len()).then_some(base_code.len().min(code.len())))?
+            as u32;
+        let address = sections.base_address().unwrap_or(0) + offset;
+
+        let section = sections.get_by_contained_address(address).map(|(_, section)| section);
+        let symbol = section.and_then(|section| {
+            let symbol = symbol_map.iter_by_address(section.start_address()..address + 1).last()?;
+            Some((symbol.name.clone(), address - symbol.addr))
+        });
+
+        Some(ModuleDifference { offset, address, section: section.map(|section| section.name().to_string()), symbol })
+    }
+
+    /// Returns the alignment that the LCF is missing, if `difference` is where the built module skips the zero padding
+    /// before a section in the base module, and the bytes after the padding match the start of that section. The
+    /// alignment is the smallest one which moves the section to its address in the base module, up to 32 bytes.
+    pub fn find_alignment_fix(
+        base_code: &[u8],
+        code: &[u8],
+        sections: &Sections,
+        module_kind: ModuleKind,
+        difference: &ModuleDifference,
+    ) -> Option<AlignmentFix> {
+        let section = sections
+            .sorted_by_address()
+            .into_iter()
+            .find(|section| section.start_address() > difference.address)
+            .filter(|section| section.start_address() - difference.address < OVERLAY_ALIGNMENT)?;
+
+        let alignment = [8, 16, 32]
+            .into_iter()
+            .find(|&alignment| difference.address.next_multiple_of(alignment) == section.start_address())?;
+
+        let offset = difference.offset as usize;
+        let padding = (section.start_address() - difference.address) as usize;
+        let size = (section.size() as usize).min(ALIGNMENT_FIX_MATCH_SIZE);
+        let base_padding = base_code.get(offset..offset + padding)?;
+        let base_section = base_code.get(offset + padding..offset + padding + size)?;
+        let built_section = code.get(offset..offset + size)?;
+        if size == 0 || base_padding.iter().any(|&byte| byte != 0) || base_section != built_section {
+            return None;
         }
+
+        Some(AlignmentFix { module: module_kind, section: section.name().to_string(), alignment })
     }
 }
 
 impl Display for CheckResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            CheckResult::ChecksumFailed => write!(f, "checksum failed"),
+            CheckResult::ChecksumFailed(None, _) => write!(f, "checksum failed"),
+            CheckResult::ChecksumFailed(Some(difference), _) => write!(f, "checksum failed, {difference}"),
             CheckResult::Ok => write!(f, "OK"),
         }
     }
 }
+
+impl Display for ModuleDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "first difference at offset {:#x} ({:#010x})", self.offset, self.address)?;
+        if let Some(section) = &self.section {
+            write!(f, " in {section}")?;
+        }
+        if let Some((symbol, offset)) = &self.symbol {
+            write!(f, " at {symbol}+{offset:#x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for AlignmentFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is misaligned, add `. = ALIGN({});` before {} in the LCF", self.module, self.alignment, self.section)
+    }
+}
@@ -0,0 +1,265 @@
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+use unarm::{ArmVersion, Endian, ParseFlags, ParseMode, Parser};
+
+use crate::{
+    analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult, PoolConstants},
+    config::{
+        config::Config,
+        module::ModuleKind,
+        relocation::{Relocation, RelocationKind, Relocations},
+        symbol::{InstructionMode, SymbolMap, SymbolMaps},
+    },
+};
+
+/// Verifies relocations in relocs.txt against the code they patch.
+#[derive(Args)]
+pub struct CheckRelocations {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Check that each relocation kind fits the instruction or word at its source address, and that the addend is
+    /// representable.
+    #[arg(long, short = 'e')]
+    pub encodings: bool,
+
+    /// Return failing exit code if a relocation doesn't pass the checks.
+    #[arg(long, short = 'f')]
+    pub fail: bool,
+}
+
+/// Reason why a relocation can't patch the code at its source address.
+#[derive(PartialEq, Eq, Debug)]
+pub enum EncodingMismatch {
+    OutOfBounds,
+    Misaligned { alignment: u32 },
+    WrongMode { function: String },
+    WrongInstruction { mnemonic: String },
+    NotPoolConstant { function: String },
+    OutOfRange { offset: i64 },
+}
+
+impl CheckRelocations {
+    pub fn run(&self) -> Result<()> {
+        if !self.encodings {
+            log::warn!("No relocation checks selected, pass --encodings to check relocation kinds and addends");
+            return Ok(());
+        }
+
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let arm9 = rom.arm9();
+        let autoloads = arm9.autoloads()?;
+
+        let mut modules = vec![(ModuleKind::Arm9, arm9.code()?, arm9.base_address())];
+        for autoload in autoloads.iter() {
+            if let AutoloadKind::Unknown(_) = autoload.kind() {
+                continue;
+            }
+            modules.push((ModuleKind::Autoload(autoload.kind()), autoload.code(), autoload.base_address()));
+        }
+        for overlay in rom.arm9_overlays() {
+            let base_address = config.overlay_base_address(overlay.id(), overlay.base_address());
+            modules.push((ModuleKind::Overlay(overlay.id()), overlay.code(), base_address));
+        }
+
+        let mut num_checked = 0;
+        let mut num_failed = 0;
+        for (module_kind, module) in config.modules() {
+            let (_, code, base_address) = modules
+                .iter()
+                .find(|(kind, _, _)| *kind == module_kind)
+                .with_context(|| format!("{module_kind} not found in ROM"))?;
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let relocations = Relocations::from_file(config_path.join(&module.relocations), &symbol_maps)?;
+
+            let mut checker = EncodingChecker::new(code, *base_address, symbol_map, config.endian.into());
+            for relocation in relocations.iter() {
+                num_checked += 1;
+                if let Some(mismatch) = checker.check(relocation)? {
+                    log::error!(
+                        "Relocation from {:#010x} in {module_kind} of kind {}: {mismatch}",
+                        relocation.from_address(),
+                        relocation.kind()
+                    );
+                    num_failed += 1;
+                }
+            }
+        }
+        log::info!("Checked encodings of {num_checked} relocations, {num_failed} mismatched");
+
+        if self.fail && num_failed > 0 {
+            bail!("Some relocation(s) didn't pass the checks.");
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks relocations against the code of one module, see [`Self::check`].
+pub struct EncodingChecker<'a> {
+    module_code: &'a [u8],
+    base_address: u32,
+    symbol_map: &'a SymbolMap,
+    endian: Endian,
+    /// Pool constants of the functions analyzed so far, keyed by function address
+    pool_constants: BTreeMap<u32, PoolConstants>,
+}
+
+impl<'a> EncodingChecker<'a> {
+    /// Creates a checker for `module_code`, which starts at `base_address`.
+    pub fn new(module_code: &'a [u8], base_address: u32, symbol_map: &'a SymbolMap, endian: Endian) -> Self {
+        Self { module_code, base_address, symbol_map, endian, pool_constants: BTreeMap::new() }
+    }
+
+    /// Checks that `relocation` fits the code at its source address.
+    /// Calls and branches must patch a matching branch instruction in the right mode, PC-relative addresses must patch an
+    /// `add` or `sub` from PC, and loads must patch a word which is not an instruction.
+    pub fn check(&mut self, relocation: &Relocation) -> Result<Option<EncodingMismatch>> {
+        let module_code = self.module_code;
+        let from = relocation.from_address();
+        let kind = relocation.kind();
+
+        let alignment = match kind {
+            RelocationKind::ThumbCall | RelocationKind::ThumbCallArm | RelocationKind::ThumbAdr => 2,
+            _ => 4,
+        };
+        let Some(offset) = from.checked_sub(self.base_address).map(|offset| offset as usize) else {
+            return Ok(Some(EncodingMismatch::OutOfBounds));
+        };
+        if offset + 4 > module_code.len() {
+            return Ok(Some(EncodingMismatch::OutOfBounds));
+        }
+        if from % alignment != 0 {
+            return Ok(Some(EncodingMismatch::Misaligned { alignment }));
+        }
+
+        let function = self.symbol_map.get_function_containing(from);
+        let destination = relocation.to_address() as i64 + relocation.addend();
+
+        let (mode, expected_mnemonics, range, offset_alignment): (_, &[&str], _, _) = match kind {
+            RelocationKind::Load => {
+                if let Some((sym_function, symbol)) = function {
+                    if !sym_function.unknown && !self.is_pool_constant(from)? {
+                        return Ok(Some(EncodingMismatch::NotPoolConstant { function: symbol.name.clone() }));
+                    }
+                }
+                // The linker adds the addend to the symbol address, so the result must fit in a word
+                if !(0..=u32::MAX as i64).contains(&destination) {
+                    return Ok(Some(EncodingMismatch::OutOfRange { offset: destination }));
+                }
+                return Ok(None);
+            }
+            RelocationKind::ArmCall => (InstructionMode::Arm, &["bl"], 1 << 25, 4),
+            RelocationKind::ArmCallThumb => (InstructionMode::Arm, &["bl", "blx"], 1 << 25, 2),
+            RelocationKind::ArmBranch => (InstructionMode::Arm, &["b"], 1 << 25, 4),
+            RelocationKind::ThumbCall => (InstructionMode::Thumb, &["bl"], 1 << 22, 2),
+            RelocationKind::ThumbCallArm => (InstructionMode::Thumb, &["bl", "blx"], 1 << 22, 2),
+            // The linker turns `add` into `sub` for negative offsets
+            RelocationKind::ArmAdr => (InstructionMode::Arm, &["add", "sub"], 1 << 32, 1),
+            RelocationKind::ThumbAdr => (InstructionMode::Thumb, &["add"], 1 << 10, 2),
+        };
+
+        if let Some((sym_function, symbol)) = function {
+            if sym_function.mode != mode {
+                return Ok(Some(EncodingMismatch::WrongMode { function: symbol.name.clone() }));
+            }
+        }
+
+        let parse_mode = match mode {
+            InstructionMode::Arm => ParseMode::Arm,
+            InstructionMode::Thumb => ParseMode::Thumb,
+        };
+        let mut parser = Parser::new(
+            parse_mode,
+            from,
+            self.endian,
+            ParseFlags { ual: false, version: ArmVersion::V5Te },
+            &module_code[offset..],
+        );
+        let Some((_, ins, parsed_ins)) = parser.next() else {
+            return Ok(Some(EncodingMismatch::OutOfBounds));
+        };
+        if !expected_mnemonics.contains(&ins.mnemonic()) {
+            return Ok(Some(EncodingMismatch::WrongInstruction {
+                mnemonic: parsed_ins.display(Default::default()).to_string(),
+            }));
+        }
+
+        // `addend` includes the PC offset, so this is the offset encoded in the branch instruction
+        let branch_offset = destination - from as i64;
+        if !(-range..range).contains(&branch_offset) || branch_offset % offset_alignment != 0 {
+            return Ok(Some(EncodingMismatch::OutOfRange { offset: branch_offset }));
+        }
+        if kind == RelocationKind::ArmAdr && !Self::is_arm_immediate(branch_offset.unsigned_abs() as u32) {
+            return Ok(Some(EncodingMismatch::OutOfRange { offset: branch_offset }));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns whether `value` can be encoded as an ARM data processing immediate, which is 8 bits rotated by an even amount.
+    fn is_arm_immediate(value: u32) -> bool {
+        (0..16).any(|rotation| value.rotate_left(rotation * 2) <= 0xff)
+    }
+
+    /// Returns whether `address` is a pool constant of the function containing it. Each function is only analyzed once.
+    fn is_pool_constant(&mut self, address: u32) -> Result<bool> {
+        let Some((sym_function, symbol)) = self.symbol_map.get_function_containing(address) else { return Ok(false) };
+        if let Some(pool_constants) = self.pool_constants.get(&symbol.addr) {
+            return Ok(pool_constants.contains(&address));
+        }
+
+        let parse_result = Function::parse_function(FunctionParseOptions {
+            name: symbol.name.clone(),
+            start_address: symbol.addr,
+            base_address: self.base_address,
+            module_code: self.module_code,
+            known_end_address: Some(symbol.addr + sym_function.size),
+            module_start_address: self.base_address,
+            module_end_address: self.base_address + self.module_code.len() as u32,
+            parse_options: ParseFunctionOptions {
+                thumb: sym_function.mode.into_thumb(),
+                endian: self.endian,
+                ..Default::default()
+            },
+        })?;
+        let pool_constants = match &parse_result {
+            ParseFunctionResult::Found(function) => function.pool_constants().clone(),
+            _ => {
+                log::warn!("Failed to analyze function {} at {:#010x}: {parse_result:?}", symbol.name, symbol.addr);
+                PoolConstants::new()
+            }
+        };
+        let is_pool_constant = pool_constants.contains(&address);
+        self.pool_constants.insert(symbol.addr, pool_constants);
+        Ok(is_pool_constant)
+    }
+}
+
+impl Display for EncodingMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodingMismatch::OutOfBounds => write!(f, "source address is outside of the module"),
+            EncodingMismatch::Misaligned { alignment } => write!(f, "source address is not aligned to {alignment} bytes"),
+            EncodingMismatch::WrongMode { function } => write!(f, "wrong instruction mode for function {function}"),
+            EncodingMismatch::WrongInstruction { mnemonic } => write!(f, "can't patch instruction '{mnemonic}'"),
+            EncodingMismatch::NotPoolConstant { function } => {
+                write!(f, "source is an instruction in function {function}, not a pool constant")
+            }
+            EncodingMismatch::OutOfRange { offset } => write!(f, "offset {offset} can't be encoded"),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::{fmt::Display, path::PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Args;
+
+use crate::config::{config::Config, delinks::Delinks, module::ModuleKind};
+
+/// mwldarm places overlays at 32-byte aligned addresses.
+pub const OVERLAY_ALIGNMENT: u32 = 32;
+
+/// Verifies the alignment and contiguity of the sections in every delinks.txt.
+#[derive(Args)]
+pub struct CheckSections {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Don't report gaps between sections, for modules with intentional gaps.
+    #[arg(long, short = 'g')]
+    pub allow_gaps: bool,
+
+    /// Return failing exit code if a section doesn't pass the checks.
+    #[arg(long, short = 'f')]
+    pub fail: bool,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum SectionViolation {
+    /// The delinks file couldn't be loaded, for example because a section starts at a misaligned address or overlaps
+    /// with another section
+    Invalid {
+        message: String,
+    },
+    /// More bytes between two sections than the alignment of the second section requires
+    Gap {
+        before: String,
+        after: String,
+        start: u32,
+        end: u32,
+    },
+    MisalignedOverlay {
+        address: u32,
+    },
+}
+
+impl CheckSections {
+    pub fn run(&self) -> Result<()> {
+        let violations = self.check()?;
+        for (module_kind, violation) in &violations {
+            log::error!("{module_kind}: {violation}");
+        }
+        log::info!("Checked sections, found {} violation(s)", violations.len());
+
+        if self.fail && !violations.is_empty() {
+            bail!("Some section(s) didn't pass the checks.");
+        }
+        Ok(())
+    }
+
+    /// Returns every violation in the order of the modules in the config.
+    pub fn check(&self) -> Result<Vec<(ModuleKind, SectionViolation)>> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut violations = vec![];
+        for (module_kind, module) in config.modules() {
+            let delinks = match Delinks::from_file(config_path.join(&module.delinks), module_kind) {
+                Ok(delinks) => delinks,
+                Err(error) => {
+                    violations.push((module_kind, SectionViolation::Invalid { message: format!("{error:#}") }));
+                    continue;
+                }
+            };
+
+            let sections = delinks.sections.sorted_by_address();
+            if let (ModuleKind::Overlay(_), Some(first)) = (module_kind, sections.first()) {
+                if first.start_address() % OVERLAY_ALIGNMENT != 0 {
+                    violations.push((module_kind, SectionViolation::MisalignedOverlay { address: first.start_address() }));
+                }
+            }
+
+            if self.allow_gaps {
+                continue;
+            }
+            for pair in sections.windows(2) {
+                let (before, after) = (pair[0], pair[1]);
+                if before.end_address().next_multiple_of(after.alignment()) < after.start_address() {
+                    violations.push((
+                        module_kind,
+                        SectionViolation::Gap {
+                            before: before.name().to_string(),
+                            after: after.name().to_string(),
+                            start: before.end_address(),
+                            end: after.start_address(),
+                        },
+                    ));
+                }
+            }
+        }
+
+        Ok(violations)
+    }
+}
+
+impl Display for SectionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionViolation::Invalid { message } => write!(f, "{message}"),
+            SectionViolation::Gap { before, after, start, end } => {
+                write!(f, "gap of {:#x} bytes from {start:#010x} to {end:#010x} between {before} and {after}", end - start)
+            }
+            SectionViolation::MisalignedOverlay { address } => {
+                write!(f, "overlay starts at {address:#010x}, which is not aligned to {OVERLAY_ALIGNMENT} bytes")
+            }
+        }
+    }
+}
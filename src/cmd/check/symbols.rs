@@ -0,0 +1,324 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    ops::AddAssign,
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use object::{Object, ObjectSection, ObjectSymbol};
+
+use crate::{
+    config::{
+        config::Config,
+        module::ModuleKind,
+        symbol::{SymbolKind, SymbolMap, SymbolMaps},
+    },
+    util::{
+        io::{read_dir, read_file},
+        local_name::CompilerLocalName,
+        watch::watch,
+    },
+};
+
+/// Verifies that symbols in linked ELF files are at the same addresses as in symbols.txt.
+#[derive(Args)]
+pub struct CheckSymbols {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Path to built/linked ELF file. Can be repeated if modules are linked into separate ELF files.
+    #[arg(long = "elf-path", short = 'x', required_unless_present = "elf_dir")]
+    pub elf_paths: Vec<PathBuf>,
+
+    /// Directory of built/linked ELF files, all `.elf` and `.o` files in it are checked.
+    #[arg(long)]
+    pub elf_dir: Option<PathBuf>,
+
+    /// Return failing exit code if a symbol doesn't pass the checks.
+    #[arg(long, short = 'f')]
+    pub fail: bool,
+
+    /// Only print the number of checked, matched, missing and mismatched symbols, not every failing symbol.
+    #[arg(long, short = 's')]
+    pub summary: bool,
+
+    /// Maximum number of failing symbols to print before only printing the summary.
+    #[arg(long, short = 'l')]
+    pub limit: Option<usize>,
+
+    /// Keep running and check again whenever an ELF file, config.yaml or a symbol file changes.
+    #[arg(long, short = 'w')]
+    pub watch: bool,
+
+    /// Match compiler-generated local symbols like `@1304` by address and size if the ELF numbered them differently.
+    #[arg(long)]
+    pub normalize_locals: bool,
+}
+
+#[derive(PartialEq, Eq)]
+enum SymbolCheckResult {
+    Missing,
+    AddressMismatch { address: u32 },
+    SizeMismatch { size: u32 },
+    Ok,
+}
+
+/// Number of symbols per check result.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SymbolCheckSummary {
+    pub checked: usize,
+    pub matched: usize,
+    pub missing: usize,
+    pub address_mismatched: usize,
+    pub size_mismatched: usize,
+}
+
+/// Symbols in a linked ELF file, grouped by module.
+pub struct ElfSymbols {
+    modules: BTreeMap<ModuleKind, HashMap<String, Vec<ElfSymbol>>>,
+}
+
+struct ElfSymbol {
+    address: u32,
+    size: u32,
+}
+
+impl CheckSymbols {
+    pub fn run(&self) -> Result<()> {
+        if self.watch {
+            watch(|| self.watched_paths(), || self.check());
+        }
+
+        let success = self.check()?;
+        if self.fail && !success {
+            bail!("Some symbol(s) didn't pass the checks.");
+        }
+        Ok(())
+    }
+
+    /// Checks and logs the symbols of every module. Returns whether all of them passed.
+    pub fn check(&self) -> Result<bool> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let mut elf_symbols = ElfSymbols::new();
+        for elf_path in self.elf_files()? {
+            let file = read_file(&elf_path)?;
+            let object = object::File::parse(&*file).with_context(|| format!("Failed to parse '{}'", elf_path.display()))?;
+            let file_symbols = ElfSymbols::from_object(&object)?;
+            if file_symbols.modules.is_empty() {
+                log::warn!("No module sections found in '{}'", elf_path.display());
+            }
+            elf_symbols.extend(file_symbols);
+        }
+
+        let mut success = true;
+        let mut total = SymbolCheckSummary::default();
+        let mut num_printed = 0;
+        for (module_kind, _) in config.modules() {
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let num_overlaps = Self::check_overlaps(module_kind, symbol_map);
+            let summary = self.check_module(module_kind, symbol_map, &elf_symbols, &mut num_printed);
+            log::info!("Check symbols in {module_kind}: {summary}");
+            success &= summary.passed() && num_overlaps == 0;
+            total += summary;
+        }
+        log::info!("Check symbols in total: {total}");
+
+        Ok(success)
+    }
+
+    /// Returns the ELF files, config.yaml and the symbol files it refers to.
+    pub fn watched_paths(&self) -> Result<Vec<PathBuf>> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut paths = self.elf_files()?;
+        paths.push(self.config_path.clone());
+        paths.extend(config.modules().map(|(_, module)| config_path.join(&module.symbols)));
+        Ok(paths)
+    }
+
+    /// Returns the ELF files from `--elf-path` followed by the ones in `--elf-dir`, sorted by name.
+    pub fn elf_files(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = self.elf_paths.clone();
+        if let Some(elf_dir) = &self.elf_dir {
+            let mut dir_paths = vec![];
+            for entry in read_dir(elf_dir)? {
+                let path = entry?.path();
+                if path.is_file() && path.extension().is_some_and(|extension| extension == "elf" || extension == "o") {
+                    dir_paths.push(path);
+                }
+            }
+            dir_paths.sort();
+            paths.extend(dir_paths);
+        }
+        Ok(paths)
+    }
+
+    /// Logs functions which overlap with the next function. Returns the number of overlaps.
+    fn check_overlaps(module_kind: ModuleKind, symbol_map: &SymbolMap) -> usize {
+        let overlaps = symbol_map.overlapping_functions();
+        for &(first, second) in &overlaps {
+            log::error!(
+                "Function {} at {:#010x} in {module_kind} overlaps with function {} at {:#010x}",
+                first.name,
+                first.addr,
+                second.name,
+                second.addr
+            );
+        }
+        overlaps.len()
+    }
+
+    /// Checks the symbols of one module against the ELF symbols. Failing symbols are printed unless `--summary` is set or
+    /// `num_printed` has reached `--limit`.
+    pub fn check_module(
+        &self,
+        module_kind: ModuleKind,
+        symbol_map: &SymbolMap,
+        elf_symbols: &ElfSymbols,
+        num_printed: &mut usize,
+    ) -> SymbolCheckSummary {
+        let empty = HashMap::new();
+        let elf_symbols = elf_symbols.modules.get(&module_kind).unwrap_or(&empty);
+
+        let mut summary = SymbolCheckSummary::default();
+        for symbol in symbol_map.iter_by_address(0..u32::MAX) {
+            let (is_function, size) = match &symbol.kind {
+                SymbolKind::Function(function) if !function.unknown => (true, Some(function.size)),
+                SymbolKind::Data(data) => (false, data.size()),
+                SymbolKind::Bss(bss) => (false, bss.size),
+                _ => continue,
+            };
+            summary.checked += 1;
+
+            // Thumb function symbols have the Thumb bit set in the ELF
+            let address_mask = if is_function { !1 } else { !0 };
+            let candidates: Vec<&ElfSymbol> = match elf_symbols.get(&symbol.name) {
+                Some(candidates) => candidates.iter().collect(),
+                None if self.normalize_locals && CompilerLocalName::classify(&symbol.name).is_some() => {
+                    // The build may have numbered the local differently, so only look at symbols at the same address
+                    elf_symbols
+                        .iter()
+                        .filter(|(name, _)| CompilerLocalName::names_match(&symbol.name, name))
+                        .flat_map(|(_, candidates)| candidates)
+                        .filter(|candidate| candidate.address & address_mask == symbol.addr)
+                        .collect()
+                }
+                None => vec![],
+            };
+
+            let result = match candidates.as_slice() {
+                [] => SymbolCheckResult::Missing,
+                candidates => match candidates.iter().find(|candidate| candidate.address & address_mask == symbol.addr) {
+                    None => SymbolCheckResult::AddressMismatch { address: candidates[0].address & address_mask },
+                    Some(candidate) => match size {
+                        Some(size) if candidate.size != 0 && candidate.size != size => {
+                            SymbolCheckResult::SizeMismatch { size: candidate.size }
+                        }
+                        _ => SymbolCheckResult::Ok,
+                    },
+                },
+            };
+
+            match result {
+                SymbolCheckResult::Missing => summary.missing += 1,
+                SymbolCheckResult::AddressMismatch { .. } => summary.address_mismatched += 1,
+                SymbolCheckResult::SizeMismatch { .. } => summary.size_mismatched += 1,
+                SymbolCheckResult::Ok => {
+                    summary.matched += 1;
+                    continue;
+                }
+            }
+
+            if !self.summary && self.limit.map_or(true, |limit| *num_printed < limit) {
+                log::warn!("Symbol {} at {:#010x} in {module_kind}: {result}", symbol.name, symbol.addr);
+                *num_printed += 1;
+                if self.limit == Some(*num_printed) {
+                    log::warn!("Reached limit of {} failing symbols, only printing the summary from now on", *num_printed);
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+impl ElfSymbols {
+    pub fn new() -> Self {
+        Self { modules: BTreeMap::new() }
+    }
+
+    /// Adds the symbols of another ELF file, such as a separately linked overlay.
+    pub fn extend(&mut self, other: Self) {
+        for (module_kind, symbols) in other.modules {
+            let module_symbols = self.modules.entry(module_kind).or_default();
+            for (name, elf_symbols) in symbols {
+                module_symbols.entry(name).or_default().extend(elf_symbols);
+            }
+        }
+    }
+
+    pub fn from_object(object: &object::File<'_>) -> Result<Self> {
+        let mut modules = BTreeMap::<ModuleKind, HashMap<String, Vec<ElfSymbol>>>::new();
+        for section in object.sections() {
+            let Some(module_kind) = ModuleKind::from_elf_section_name(section.name()?)? else { continue };
+            let module_symbols = modules.entry(module_kind).or_default();
+            for symbol in object.symbols() {
+                if symbol.section_index() != Some(section.index()) {
+                    continue;
+                }
+                module_symbols
+                    .entry(symbol.name()?.to_string())
+                    .or_default()
+                    .push(ElfSymbol { address: symbol.address() as u32, size: symbol.size() as u32 });
+            }
+        }
+        Ok(Self { modules })
+    }
+}
+
+impl SymbolCheckSummary {
+    /// Returns true if every checked symbol matched.
+    pub fn passed(&self) -> bool {
+        self.matched == self.checked
+    }
+}
+
+impl AddAssign for SymbolCheckSummary {
+    fn add_assign(&mut self, rhs: Self) {
+        self.checked += rhs.checked;
+        self.matched += rhs.matched;
+        self.missing += rhs.missing;
+        self.address_mismatched += rhs.address_mismatched;
+        self.size_mismatched += rhs.size_mismatched;
+    }
+}
+
+impl Display for SymbolCheckSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} OK, {} missing, {} at wrong address, {} with wrong size",
+            self.matched, self.checked, self.missing, self.address_mismatched, self.size_mismatched
+        )
+    }
+}
+
+impl Display for SymbolCheckResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbolCheckResult::Missing => write!(f, "missing in ELF"),
+            SymbolCheckResult::AddressMismatch { address } => write!(f, "found at {address:#010x} in ELF"),
+            SymbolCheckResult::SizeMismatch { size } => write!(f, "size is {size:#x} in ELF"),
+            SymbolCheckResult::Ok => write!(f, "OK"),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde::Serialize;
+use serde_yml::{Mapping, Value};
+
+use crate::{
+    config::{
+        config::{ConfigEndian, CONFIG_VERSION},
+        name_prefixes::NamePrefixes,
+    },
+    util::{
+        backup::write_with_backups,
+        io::{create_file, open_file},
+    },
+};
+
+/// Upgrades a config to the latest config version, in place. The old config is backed up first.
+#[derive(Args)]
+pub struct MigrateConfig {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+}
+
+type Migration = fn(&mut Mapping) -> Result<()>;
+
+/// Migrations from version N to N + 1, starting at version 1.
+const MIGRATIONS: [Migration; (CONFIG_VERSION - 1) as usize] = [migrate_v1_to_v2];
+
+impl MigrateConfig {
+    pub fn run(&self) -> Result<()> {
+        let mut config: Mapping = serde_yml::from_reader(open_file(&self.config_path)?)?;
+
+        let version = match config.get("version") {
+            Some(version) => {
+                version.as_u64().and_then(|version| u32::try_from(version).ok()).context("Invalid config version")?
+            }
+            None => 1,
+        };
+        if version == 0 || version > CONFIG_VERSION {
+            bail!("Unknown config version {version}, the latest supported version is {CONFIG_VERSION}");
+        }
+        if version == CONFIG_VERSION {
+            log::info!("Config is already at version {CONFIG_VERSION}");
+            return Ok(());
+        }
+
+        for (from_version, migration) in (version..).zip(&MIGRATIONS[version as usize - 1..]) {
+            migration(&mut config).with_context(|| format!("Failed to migrate config from version {from_version}"))?;
+            log::info!("Migrated config from version {from_version} to {}", from_version + 1);
+        }
+
+        // Keep the version at the top of the file
+        let mut migrated = Mapping::new();
+        migrated.insert(Value::from("version"), Value::from(CONFIG_VERSION));
+        migrated.extend(config.into_iter().filter(|(key, _)| key.as_str() != Some("version")));
+
+        write_with_backups([&self.config_path], || {
+            serde_yml::to_writer(create_file(&self.config_path)?, &migrated)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// Version 2 adds the `version` field, which is written after the last migration, and makes the endianness and name
+/// prefixes configurable. The defaults which version 1 relied on are written out, so they stay the same for this project.
+fn migrate_v1_to_v2(config: &mut Mapping) -> Result<()> {
+    insert_default(config, "endian", ConfigEndian::default())?;
+    insert_default(config, "name_prefixes", NamePrefixes::default())?;
+    Ok(())
+}
+
+fn insert_default<T: Serialize>(config: &mut Mapping, key: &str, value: T) -> Result<()> {
+    if !config.contains_key(key) {
+        config.insert(Value::from(key), serde_yml::to_value(value)?);
+    }
+    Ok(())
+}
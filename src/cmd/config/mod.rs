@@ -0,0 +1,30 @@
+mod migrate;
+mod validate;
+
+pub use migrate::*;
+pub use validate::*;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Subcommands for inspecting and upgrading dsd configs.
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+impl ConfigArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            ConfigCommand::Validate(validate) => validate.run(),
+            ConfigCommand::Migrate(migrate) => migrate.run(),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    Validate(ValidateConfig),
+    Migrate(MigrateConfig),
+}
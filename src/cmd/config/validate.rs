@@ -0,0 +1,245 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+
+use crate::config::{
+    config::{Config, CONFIG_VERSION},
+    data_regions::DataRegions,
+    delinks::Delinks,
+    function_overrides::FunctionOverrides,
+    instruction_modes::InstructionModes,
+    memory_regions::MemoryRegion,
+    module::ModuleKind,
+    no_epilogue::NoEpilogueFallbacks,
+    overlay_groups::ExclusiveOverlayGroups,
+    relocation::Relocations,
+    small_data::SmallDataSection,
+    struct_layouts::StructLayouts,
+    symbol::{SymData, SymbolKind, SymbolMaps},
+};
+
+/// Loads a config and all files it refers to, and reports every problem found.
+#[derive(Args)]
+pub struct ValidateConfig {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+}
+
+impl ValidateConfig {
+    pub fn run(&self) -> Result<()> {
+        let problems = self.validate()?;
+        for problem in &problems {
+            log::error!("{problem}");
+        }
+        if !problems.is_empty() {
+            bail!("Found {} problem(s) in config", problems.len());
+        }
+        log::info!("Config is valid");
+        Ok(())
+    }
+
+    /// Returns a description of every problem found in the config. Only fails if config.yaml itself can't be loaded.
+    pub fn validate(&self) -> Result<Vec<String>> {
+        let config = Config::from_file_unchecked(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut errors = vec![];
+
+        if config.version > CONFIG_VERSION {
+            errors.push(anyhow!(
+                "Config version {} is newer than the latest supported version {CONFIG_VERSION}",
+                config.version
+            ));
+        }
+        if let Err(error) = config.name_prefixes.validate() {
+            errors.push(error);
+        }
+        if let Some(function_overrides) = &config.function_overrides {
+            if let Err(error) = FunctionOverrides::from_file(config_path.join(function_overrides)) {
+                errors.push(error);
+            }
+        }
+        if let Err(error) = DataRegions::from_regions(&config.data_regions) {
+            errors.push(error);
+        }
+        if let Err(error) = DataRegions::from_regions(&config.handwritten_code) {
+            errors.push(error);
+        }
+        if let Err(error) = InstructionModes::from_overrides(&config.instruction_modes) {
+            errors.push(error);
+        }
+        if let Err(error) = NoEpilogueFallbacks::from_fallbacks(&config.no_epilogue_fallbacks) {
+            errors.push(error);
+        }
+        if let Err(error) = ExclusiveOverlayGroups::from_groups(&config.overlay_groups) {
+            errors.push(error);
+        }
+        if let Err(error) = SmallDataSection::check_unique(&config.small_data_sections) {
+            errors.push(error);
+        }
+        if let Err(error) = MemoryRegion::check_unique(&config.memory_regions) {
+            errors.push(error);
+        }
+        for id in config.overlay_groups.iter().flat_map(|group| group.overlays()) {
+            if !config.overlays.iter().any(|overlay| overlay.id == *id) {
+                errors.push(anyhow!("Overlay group refers to overlay {id} which is not in the config"));
+            }
+        }
+        let struct_layouts = match &config.struct_layouts {
+            Some(struct_layouts) => match StructLayouts::from_file(config_path.join(struct_layouts)) {
+                Ok(struct_layouts) => Some(struct_layouts),
+                Err(error) => {
+                    errors.push(error);
+                    None
+                }
+            },
+            None => Some(StructLayouts::new()),
+        };
+        let rom_config_path = config_path.join(&config.rom_config);
+        if !rom_config_path.is_file() {
+            errors.push(anyhow!("ROM config '{}' does not exist", rom_config_path.display()));
+        }
+
+        let mut module_kinds = BTreeSet::new();
+        for (module_kind, _) in config.modules() {
+            if !module_kinds.insert(module_kind) {
+                errors.push(anyhow!("{module_kind} is listed more than once"));
+            }
+        }
+
+        // Load symbols first, as relocations may refer to symbols by name
+        let mut symbol_maps = SymbolMaps::new();
+        let mut symbols = BTreeMap::new();
+        for (module_kind, module) in config.modules() {
+            match symbol_maps.get_mut(module_kind).load_lenient(config_path.join(&module.symbols), &mut errors) {
+                Ok(module_symbols) => {
+                    symbols.insert(module_kind, module_symbols);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let mut ranges: BTreeMap<ModuleKind, Range<u32>> = BTreeMap::new();
+        for (module_kind, module) in config.modules() {
+            let delinks = match Delinks::from_file(config_path.join(&module.delinks), module_kind) {
+                Ok(delinks) => delinks,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+            match (delinks.sections.base_address(), delinks.sections.end_address()) {
+                (Some(start), Some(end)) => {
+                    ranges.insert(module_kind, start..end);
+                }
+                _ => errors.push(anyhow!("{}: no sections in {module_kind}", module.delinks.display())),
+            }
+            if config.memory_regions.is_empty() {
+                continue;
+            }
+            for section in delinks.sections.iter() {
+                let fits = config
+                    .memory_regions
+                    .iter()
+                    .any(|region| region.origin <= section.start_address() && section.end_address() <= region.end());
+                if !fits {
+                    errors.push(anyhow!(
+                        "{}: section {} ({:#010x}..{:#010x}) of {module_kind} is not inside any memory region",
+                        module.delinks.display(),
+                        section.name(),
+                        section.start_address(),
+                        section.end_address()
+                    ));
+                }
+            }
+        }
+
+        for (module_kind, module) in config.modules() {
+            let relocations_path = config_path.join(&module.relocations);
+            let relocations = match Relocations::from_file_lenient(&relocations_path, &symbol_maps, &mut errors) {
+                Ok(relocations) => relocations,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+            for relocation in relocations.iter() {
+                let from = relocation.from_address();
+                if ranges.get(&module_kind).is_some_and(|range| !range.contains(&from)) {
+                    errors.push(anyhow!(
+                        "{}: relocation from {from:#010x} is outside of {module_kind}",
+                        relocations_path.display()
+                    ));
+                }
+                let to_modules = relocation
+                    .module()
+                    .first_module()
+                    .into_iter()
+                    .chain(relocation.module().other_modules().into_iter().flatten());
+                for to_module in to_modules {
+                    if !module_kinds.contains(&to_module) {
+                        errors.push(anyhow!(
+                            "{}: relocation from {from:#010x} refers to {to_module} which is not in the config",
+                            relocations_path.display()
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (module_kind, module_symbols) in &symbols {
+            let Some(range) = ranges.get(module_kind) else { continue };
+            for (context, symbol) in module_symbols {
+                if !range.contains(&symbol.addr) {
+                    errors.push(anyhow!(
+                        "{context}: symbol {} at {:#010x} is outside of {module_kind} ({:#010x}..{:#010x})",
+                        symbol.name,
+                        symbol.addr,
+                        range.start,
+                        range.end
+                    ));
+                } else if let SymbolKind::Function(function) = &symbol.kind {
+                    if symbol.addr + function.size > range.end {
+                        errors.push(anyhow!(
+                            "{context}: function {} at {:#010x} ends after {module_kind} ({:#010x})",
+                            symbol.name,
+                            symbol.addr,
+                            range.end
+                        ));
+                    }
+                } else if let SymbolKind::Data(SymData::Struct { layout, .. }) = &symbol.kind {
+                    if struct_layouts.as_ref().is_some_and(|struct_layouts| struct_layouts.get(layout).is_none()) {
+                        errors.push(anyhow!("{context}: symbol {} has unknown struct layout '{layout}'", symbol.name));
+                    }
+                }
+            }
+        }
+
+        // Overlays are expected to share address space with each other, but not with any other module
+        let ranges = ranges.into_iter().collect::<Vec<_>>();
+        for (i, (module_kind, range)) in ranges.iter().enumerate() {
+            for (other_kind, other_range) in &ranges[i + 1..] {
+                if matches!((module_kind, other_kind), (ModuleKind::Overlay(_), ModuleKind::Overlay(_))) {
+                    continue;
+                }
+                if range.start < other_range.end && other_range.start < range.end {
+                    errors.push(anyhow!(
+                        "{module_kind} ({:#010x}..{:#010x}) overlaps with {other_kind} ({:#010x}..{:#010x})",
+                        range.start,
+                        range.end,
+                        other_range.start,
+                        other_range.end
+                    ));
+                }
+            }
+        }
+
+        Ok(errors.into_iter().map(|error| format!("{error:#}")).collect())
+    }
+}
@@ -1,7 +1,11 @@
 use std::{
     collections::BTreeMap,
-    io::BufWriter,
+    fs,
+    io::Write,
+    iter,
+    ops::Range,
     path::{Path, PathBuf},
+    ptr,
 };
 
 use anyhow::{bail, Context, Result};
@@ -9,17 +13,22 @@ use clap::Args;
 use ds_rom::rom::{Rom, RomLoadOptions};
 use object::{Architecture, BinaryFormat, Endianness, RelocationFlags};
 use serde::Serialize;
+use unarm::Endian;
 
 use crate::{
     config::{
-        config::{Config, ConfigAutoload, ConfigModule, ConfigOverlay},
+        config::Config,
         delinks::{DelinkFile, Delinks},
         module::{Module, ModuleKind},
-        relocation::Relocations,
-        section::SectionKind,
-        symbol::SymbolMaps,
+        relocation::{RelocationKind, RelocationSources, Relocations},
+        section::{Section, SectionKind},
+        symbol::{SymData, Symbol, SymbolKind, SymbolMap, SymbolMaps},
+    },
+    util::{
+        bytes::FromSlice,
+        io::{create_dir_all, create_file, open_file, write_file},
+        jobs,
     },
-    util::io::{create_dir_all, create_file, open_file},
 };
 
 /// Delinks an extracted ROM into relocatable ELF files.
@@ -28,17 +37,32 @@ pub struct Delink {
     /// Path to config.yaml.
     #[arg(long, short = 'c')]
     pub config_path: PathBuf,
+
+    /// Number of threads to delink with.
+    #[arg(long, short = 'j', default_value_t = 1)]
+    pub jobs: usize,
+
+    /// Only rewrites object files that changed since the last delink.
+    #[arg(long, short = 'i')]
+    pub incremental: bool,
 }
 
-#[derive(Default, Serialize)]
-struct DelinkResult {
-    num_files: usize,
-    num_gaps: usize,
+#[derive(Serialize)]
+pub struct DelinkResult {
+    pub num_files: usize,
+    pub num_gaps: usize,
+    /// Object files that were left untouched by `--incremental`
+    pub num_skipped: usize,
 }
 
+/// Maps each object file path, relative to the delinks path, to a hash of the inputs it was delinked from
+type DelinkManifest = BTreeMap<String, String>;
+
+pub const DELINK_MANIFEST_FILE_NAME: &str = "delink_manifest.yaml";
+
 impl Delink {
     pub fn run(&self) -> Result<()> {
-        let config: Config = serde_yml::from_reader(open_file(&self.config_path)?)?;
+        let config = Config::from_file(&self.config_path)?;
         let config_path = self.config_path.parent().unwrap();
 
         let mut symbol_maps = SymbolMaps::from_config(config_path, &config)?;
@@ -47,167 +71,202 @@ impl Delink {
             RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
         )?;
 
-        let elf_path = config_path.join(config.delinks_path);
-        let mut result = DelinkResult::default();
-
-        self.delink_arm9(&config.main_module, &rom, &elf_path, &mut symbol_maps, &mut result)?;
-        self.delink_autoloads(&config.autoloads, &rom, &elf_path, &mut symbol_maps, &mut result)?;
-        self.delink_overlays(&config.overlays, &rom, &elf_path, &mut symbol_maps, &mut result)?;
-
-        serde_yml::to_writer(create_file(elf_path.join("delink.yaml"))?, &result)?;
-
-        Ok(())
-    }
-
-    fn delink_arm9(
-        &self,
-        config: &ConfigModule,
-        rom: &Rom,
-        elf_path: &Path,
-        symbol_maps: &mut SymbolMaps,
-        result: &mut DelinkResult,
-    ) -> Result<()> {
-        let config_path = self.config_path.parent().unwrap();
-
-        let module_kind = ModuleKind::Arm9;
-        let delinks = Delinks::from_file(config_path.join(&config.delinks), module_kind)?;
-        let symbol_map = symbol_maps.get_mut(module_kind);
-        let relocations = Relocations::from_file(config_path.join(&config.relocations))?;
-
-        let code = rom.arm9().code()?;
-        let module = Module::new_arm9(config.name.clone(), symbol_map, relocations, delinks.sections, code)?;
-
-        for file in &delinks.files {
-            let (file_path, _) = file.split_file_ext();
-            Self::create_elf_file(&module, file, elf_path.join(format!("{file_path}.o")), symbol_maps)?;
-
-            if file.gap() {
-                result.num_gaps += 1;
-            } else {
-                result.num_files += 1;
-            }
-        }
+        let mut relocation_sources = RelocationSources::from_config(config_path, &config, &symbol_maps)?;
+        Self::add_rom_roots(&mut relocation_sources, &rom, &config)?;
 
-        Ok(())
-    }
+        let elf_path = config_path.join(&config.delinks_path);
 
-    fn delink_autoloads(
-        &self,
-        autoloads: &[ConfigAutoload],
-        rom: &Rom,
-        elf_path: &Path,
-        symbol_maps: &mut SymbolMaps,
-        result: &mut DelinkResult,
-    ) -> Result<()> {
         let rom_autoloads = rom.arm9().autoloads()?;
-        for autoload in autoloads {
-            let config_path = self.config_path.parent().unwrap();
-
-            let module_kind = ModuleKind::Autoload(autoload.kind);
-            let delinks = Delinks::from_file(config_path.join(&autoload.module.delinks), module_kind)?;
-            let symbol_map = symbol_maps.get_mut(module_kind);
-            let relocations = Relocations::from_file(config_path.join(&autoload.module.relocations))?;
-
+        let mut module_codes = vec![(ModuleKind::Arm9, &config.main_module, rom.arm9().code()?)];
+        for autoload in &config.autoloads {
             let code = rom_autoloads
                 .iter()
                 .find(|a| a.kind() == autoload.kind)
                 .with_context(|| format!("Autoload {} not present in ROM", autoload.kind))?
                 .code();
-            let module = Module::new_autoload(
-                autoload.module.name.clone(),
-                symbol_map,
+            module_codes.push((ModuleKind::Autoload(autoload.kind), &autoload.module, code));
+        }
+        for overlay in &config.overlays {
+            let code = rom.arm9_overlays()[overlay.id as usize].code();
+            module_codes.push((ModuleKind::Overlay(overlay.id), &overlay.module, code));
+        }
+
+        // Modules add their local symbols to the symbol maps, so they are all loaded before any file is delinked
+        let mut modules = vec![];
+        for (module_kind, module_config, code) in module_codes {
+            let delinks = Delinks::from_file(config_path.join(&module_config.delinks), module_kind)?;
+            let relocations = Relocations::from_file(config_path.join(&module_config.relocations), &symbol_maps)?;
+            let module = Module::new(
+                module_kind,
+                module_config.name.clone(),
+                symbol_maps.get_mut(module_kind),
                 relocations,
                 delinks.sections,
-                autoload.kind,
                 code,
+                &config.name_prefixes,
+                config.endian.into(),
             )?;
+            modules.push((module, delinks.files));
+        }
 
-            for file in &delinks.files {
-                let (file_path, _) = file.split_file_ext();
-                Self::create_elf_file(&module, file, elf_path.join(format!("{file_path}.o")), symbol_maps)?;
+        let result = self.delink_modules(&modules, &elf_path, &symbol_maps, &relocation_sources)?;
 
-                if file.gap() {
-                    result.num_gaps += 1;
-                } else {
-                    result.num_files += 1;
-                }
-            }
-        }
+        serde_yml::to_writer(create_file(elf_path.join("delink.yaml"))?, &result)?;
 
         Ok(())
     }
 
-    fn delink_overlays(
+    /// Writes one object file per delink file of each module to `elf_path`. The files are written on up to `--jobs`
+    /// threads, which doesn't affect their contents.
+    ///
+    /// A manifest with a hash of the inputs of every object is written alongside them, see [`Self::input_hash`]. With
+    /// `--incremental`, a file whose hash is unchanged since the previous manifest is not delinked again. Objects of files
+    /// that were since removed or renamed are deleted.
+    pub fn delink_modules(
         &self,
-        overlays: &[ConfigOverlay],
-        rom: &Rom,
+        modules: &[(Module, Vec<DelinkFile>)],
         elf_path: &Path,
-        symbol_maps: &mut SymbolMaps,
-        result: &mut DelinkResult,
-    ) -> Result<()> {
-        let config_path = self.config_path.parent().unwrap();
-
-        for overlay in overlays {
-            let module_kind = ModuleKind::Overlay(overlay.id);
-            let delinks = Delinks::from_file(config_path.join(&overlay.module.delinks), module_kind)?;
-            let symbol_map = symbol_maps.get_mut(module_kind);
-            let relocations = Relocations::from_file(config_path.join(&overlay.module.relocations))?;
-
-            let code = rom.arm9_overlays()[overlay.id as usize].code();
-            let module =
-                Module::new_overlay(overlay.module.name.clone(), symbol_map, relocations, delinks.sections, overlay.id, code)?;
+        symbol_maps: &SymbolMaps,
+        relocation_sources: &RelocationSources,
+    ) -> Result<DelinkResult> {
+        let files =
+            modules.iter().flat_map(|(module, files)| files.iter().map(move |file| (module, file))).collect::<Vec<_>>();
+
+        let manifest_path = elf_path.join(DELINK_MANIFEST_FILE_NAME);
+        let old_manifest: DelinkManifest = if self.incremental && manifest_path.exists() {
+            serde_yml::from_reader(open_file(&manifest_path)?)?
+        } else {
+            DelinkManifest::new()
+        };
+
+        let objects = jobs::try_map(self.jobs, &files, |&(module, file)| {
+            let (file_path, _) = file.split_file_ext();
+            let object_path = format!("{file_path}.o");
+            let hash = format!("{:016x}", Self::input_hash(symbol_maps, relocation_sources, module, file)?);
+
+            let path = elf_path.join(&object_path);
+            let skipped = old_manifest.get(&object_path) == Some(&hash) && path.exists();
+            if !skipped {
+                let contents = Self::delink(symbol_maps, relocation_sources, module, file)?.write()?;
+                create_dir_all(path.parent().unwrap())?;
+                write_file(&path, contents)?;
+            }
+            Ok((object_path, hash, skipped))
+        })?;
 
-            for file in &delinks.files {
-                let (file_path, _) = file.split_file_ext();
-                Self::create_elf_file(&module, file, elf_path.join(format!("{file_path}.o")), symbol_maps)?;
+        let num_skipped = objects.iter().filter(|(_, _, skipped)| *skipped).count();
+        let manifest = objects.into_iter().map(|(object_path, hash, _)| (object_path, hash)).collect::<DelinkManifest>();
 
-                if file.gap() {
-                    result.num_gaps += 1;
-                } else {
-                    result.num_files += 1;
-                }
+        for object_path in old_manifest.keys().filter(|object_path| !manifest.contains_key(*object_path)) {
+            let path = elf_path.join(object_path);
+            if path.exists() {
+                log::debug!("Removing stale object '{}'", path.display());
+                fs::remove_file(&path).with_context(|| format!("Failed to remove '{}'", path.display()))?;
             }
         }
 
-        Ok(())
+        create_dir_all(elf_path)?;
+        serde_yml::to_writer(create_file(&manifest_path)?, &manifest)?;
+
+        let num_gaps = files.iter().filter(|(_, file)| file.gap()).count();
+        Ok(DelinkResult { num_files: files.len() - num_gaps, num_gaps, num_skipped })
     }
 
-    fn create_elf_file<P: AsRef<Path>>(
+    /// Hashes everything that [`Self::delink`] reads to create the object of `delink_file`: its entry in `delinks.txt`, the
+    /// code, symbols and relocations in its sections, and the symbols which its relocations and pointers refer to. The
+    /// `dsd` version is included, since the object format may change between versions.
+    fn input_hash(
+        symbol_maps: &SymbolMaps,
+        relocation_sources: &RelocationSources,
         module: &Module,
         delink_file: &DelinkFile,
-        path: P,
-        symbol_maps: &SymbolMaps,
-    ) -> Result<()> {
-        let path = path.as_ref();
-
-        create_dir_all(path.parent().unwrap())?;
+    ) -> Result<u64> {
+        let symbol_map = symbol_maps.get(module.kind()).unwrap();
+        let file_ranges = delink_file.sections.iter().map(|section| section.address_range()).collect::<Vec<_>>();
+
+        let mut inputs = vec![];
+        writeln!(inputs, "{} {} {}", env!("CARGO_PKG_VERSION"), module.kind(), matches!(module.endian(), Endian::Big))?;
+        writeln!(inputs, "{delink_file}gap:{}", delink_file.gap())?;
+        for file_section in delink_file.sections.sorted_by_address() {
+            let code = file_section.code_from_module(module)?.unwrap_or_default();
+            inputs.extend_from_slice(code);
+            writeln!(inputs)?;
+
+            for symbol in symbol_map.iter_by_address(file_section.address_range()) {
+                let referenced = Self::is_referenced_outside(relocation_sources, module, symbol, &file_ranges);
+                writeln!(inputs, "{symbol} referenced:{referenced}")?;
+            }
+            for (_, relocation) in module.relocations().iter_range(file_section.address_range()) {
+                writeln!(inputs, "{relocation}")?;
+                let reloc_module = relocation.module().first_module().unwrap();
+                if let Some(target_map) = symbol_maps.get(reloc_module) {
+                    Self::write_target_symbols(&mut inputs, target_map, relocation.to_address())?;
+                }
+            }
+            for (from, to) in Self::unrelocated_pointers(symbol_map, module, file_section, code) {
+                writeln!(inputs, "pointer {from:#010x} {to:#010x}")?;
+                for target_map in symbol_maps.iter() {
+                    Self::write_target_symbols(&mut inputs, target_map, to)?;
+                }
+            }
+        }
 
-        let object = Self::delink(symbol_maps, module, delink_file)?;
-        let file = create_file(path)?;
-        let writer = BufWriter::new(file);
-        object.write_stream(writer).unwrap();
+        Ok(fxhash::hash64(&inputs))
+    }
 
+    /// Writes the symbols which a relocation or pointer to `address` may resolve to, for [`Self::input_hash`].
+    fn write_target_symbols(inputs: &mut Vec<u8>, symbol_map: &SymbolMap, address: u32) -> Result<()> {
+        for (_, symbol) in symbol_map.for_address(address).into_iter().flatten() {
+            writeln!(inputs, "  {symbol}")?;
+        }
+        if let Some((_, symbol)) = symbol_map.get_function(address)? {
+            writeln!(inputs, "  {symbol}")?;
+        }
         Ok(())
     }
 
-    fn delink<'a>(symbol_maps: &SymbolMaps, module: &Module, delink_file: &DelinkFile) -> Result<object::write::Object<'a>> {
+    /// Creates a relocatable object of one delink file, which is also what objdiff uses as its target object. Symbols are
+    /// only global if `relocation_sources` has references to them from other files.
+    pub fn delink<'a>(
+        symbol_maps: &SymbolMaps,
+        relocation_sources: &RelocationSources,
+        module: &Module,
+        delink_file: &DelinkFile,
+    ) -> Result<object::write::Object<'a>> {
         let symbol_map = symbol_maps.get(module.kind()).unwrap();
-        let mut object = object::write::Object::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Little);
+        let endianness = match module.endian() {
+            Endian::Little => Endianness::Little,
+            Endian::Big => Endianness::Big,
+        };
+        let mut object = object::write::Object::new(BinaryFormat::Elf, Architecture::Arm, endianness);
         object.elf_is_rela = Some(true);
 
         // Maps address to ObjSection/ObjSymbol
         let mut obj_sections = BTreeMap::new();
         let mut obj_symbols = BTreeMap::new();
 
+        // Words in `data(pointer)` symbols without a relocation, mapped by section address
+        let mut section_pointers = BTreeMap::new();
+        let mut pointer_symbols = BTreeMap::new();
+
         let mut error = false;
 
+        let file_ranges = delink_file.sections.iter().map(|section| section.address_range()).collect::<Vec<_>>();
+
         for file_section in delink_file.sections.iter() {
             // Get section data
-            let code = file_section.relocatable_code(module)?.unwrap_or_else(Vec::new);
+            let mut code = file_section.relocatable_code(module)?.unwrap_or_else(Vec::new);
+            let pointers = Self::unrelocated_pointers(symbol_map, module, file_section, &code);
+            for &(from, _) in &pointers {
+                let offset = (from - file_section.start_address()) as usize;
+                code[offset..offset + 4].fill(0);
+            }
+            section_pointers.insert(file_section.start_address(), pointers);
             let name = file_section.name().as_bytes().to_vec();
             let kind = match file_section.kind() {
                 SectionKind::Code => object::SectionKind::Text,
-                SectionKind::Data => object::SectionKind::Data, // TODO: use ReadOnlyData if .rodata?
+                SectionKind::Data if file_section.name() == ".rodata" => object::SectionKind::ReadOnlyData,
+                SectionKind::Data => object::SectionKind::Data,
                 SectionKind::Bss => object::SectionKind::UninitializedData,
             };
 
@@ -238,7 +297,14 @@ impl Delink {
                 // Get symbol data
                 let max_address = symbols.peek().map(|s| s.addr).unwrap_or(file_section.end_address());
                 let kind = symbol.kind.into_obj_symbol_kind();
-                let scope = symbol.kind.into_obj_symbol_scope();
+                let scope = match symbol.kind.into_obj_symbol_scope() {
+                    object::SymbolScope::Dynamic
+                        if symbol.local || !Self::is_referenced_outside(relocation_sources, module, symbol, &file_ranges) =>
+                    {
+                        object::SymbolScope::Compilation
+                    }
+                    scope => scope,
+                };
                 let value = (symbol.addr - file_section.start_address()) as u64;
 
                 // Create symbol
@@ -337,6 +403,29 @@ impl Delink {
                     },
                 )?;
             }
+
+            for &(from, to) in &section_pointers[&file_section.start_address()] {
+                // Pointers to Thumb functions have the Thumb bit set, so look up the address of the symbol instead
+                let target = Self::pointer_target_symbol(symbol_map, to).map_or(to, |symbol| symbol.addr);
+                let symbol_id = if let Some(obj_symbol_id) = obj_symbols.get(&(target, module.kind())) {
+                    *obj_symbol_id
+                } else if let Some(symbol_id) = pointer_symbols.get(&target) {
+                    *symbol_id
+                } else {
+                    let symbol_id = Self::add_pointer_target(&mut object, symbol_maps, module, from, to)?;
+                    pointer_symbols.insert(target, symbol_id);
+                    symbol_id
+                };
+                object.add_relocation(
+                    obj_section_id,
+                    object::write::Relocation {
+                        offset: (from - file_section.start_address()) as u64,
+                        symbol: symbol_id,
+                        addend: 0,
+                        flags: RelocationFlags::Elf { r_type: RelocationKind::Load.into_elf_relocation_type() },
+                    },
+                )?;
+            }
         }
 
         if error {
@@ -345,4 +434,141 @@ impl Delink {
 
         Ok(object)
     }
+
+    /// Returns the source and target of every word in the `data(pointer)` symbols of `section` which has no relocation in
+    /// relocs.txt. `code` must be the content of the section.
+    fn unrelocated_pointers(symbol_map: &SymbolMap, module: &Module, section: &Section, code: &[u8]) -> Vec<(u32, u32)> {
+        if code.is_empty() {
+            return vec![];
+        }
+        let mut pointers = vec![];
+        let mut symbols = symbol_map.iter_by_address(section.address_range()).peekable();
+        while let Some(symbol) = symbols.next() {
+            let SymbolKind::Data(data @ SymData::Pointer { .. }) = &symbol.kind else { continue };
+            let max_address = symbols.peek().map(|s| s.addr).unwrap_or(section.end_address());
+            let end = data.size().map_or(max_address, |size| (symbol.addr + size).min(section.end_address()));
+            for from in (symbol.addr..end).step_by(4).filter(|from| from + 4 <= end) {
+                if module.relocations().get(from).is_some() {
+                    continue;
+                }
+                let offset = (from - section.start_address()) as usize;
+                let to = u32::from_slice(&code[offset..offset + 4], module.endian());
+                pointers.push((from, to));
+            }
+        }
+        pointers
+    }
+
+    /// Adds an undefined symbol for the target of a `data(pointer)` word, named after the first symbol at the target
+    /// address in this module, or else in the other modules. If there is none, the symbol is named after the address and
+    /// left unresolved for the linker to report.
+    fn add_pointer_target(
+        object: &mut object::write::Object,
+        symbol_maps: &SymbolMaps,
+        module: &Module,
+        from: u32,
+        to: u32,
+    ) -> Result<object::write::SymbolId> {
+        let own_symbol_map = symbol_maps.get(module.kind()).unwrap();
+        let target = match Self::pointer_target_symbol(own_symbol_map, to) {
+            Some(symbol) => Some(symbol),
+            None => {
+                let mut candidates = symbol_maps
+                    .iter()
+                    .filter(|&symbol_map| !ptr::eq(symbol_map, own_symbol_map))
+                    .filter_map(|symbol_map| Self::pointer_target_symbol(symbol_map, to));
+                let target = candidates.next();
+                let others = candidates.map(|symbol| symbol.name.as_str()).collect::<Vec<_>>();
+                if let Some(symbol) = target.filter(|_| !others.is_empty()) {
+                    log::warn!(
+                        "Pointer at {from:#010x} in {} to {to:#010x} has symbols in several modules, using {} over {}",
+                        module.kind(),
+                        symbol.name,
+                        others.join(", ")
+                    );
+                }
+                target
+            }
+        };
+
+        let name = match target {
+            Some(symbol) => symbol.name.clone(),
+            None => {
+                log::warn!(
+                    "No symbol found for pointer at {from:#010x} in {} to {to:#010x}, leaving it unresolved",
+                    module.kind()
+                );
+                format!("{}{to:08x}", module.default_data_prefix)
+            }
+        };
+        // The target is defined in another object, so the linker must resolve it and must not replace it with zero
+        Ok(object.add_symbol(object::write::Symbol {
+            name: name.into_bytes(),
+            value: 0,
+            size: 0,
+            kind: RelocationKind::Load.into_obj_symbol_kind(),
+            scope: object::SymbolScope::Linkage,
+            weak: false,
+            section: object::write::SymbolSection::Undefined,
+            flags: object::SymbolFlags::None,
+        }))
+    }
+
+    /// Returns the first symbol at the target of a pointer in `symbol_map`. A pointer with the Thumb bit set may also point
+    /// to the Thumb function at the even address.
+    fn pointer_target_symbol(symbol_map: &SymbolMap, to: u32) -> Option<&Symbol> {
+        if to & 1 != 0 {
+            let function = symbol_map
+                .for_address(to & !1)
+                .and_then(|mut symbols| symbols.find(|(_, symbol)| matches!(symbol.kind, SymbolKind::Function(_))));
+            if let Some((_, symbol)) = function {
+                return Some(symbol);
+            }
+        }
+        symbol_map.for_address(to).and_then(|mut symbols| symbols.next()).map(|(_, symbol)| symbol)
+    }
+
+    /// Adds the addresses which the ROM refers to as roots, so that their symbols are global even if nothing else refers
+    /// to them: the entry function, build info and autoload callback of the ARM9 program, and the static initializers in
+    /// the overlay table.
+    fn add_rom_roots(relocation_sources: &mut RelocationSources, rom: &Rom, config: &Config) -> Result<()> {
+        let arm9 = rom.arm9();
+        relocation_sources.add_root(ModuleKind::Arm9, arm9.entry_function());
+        relocation_sources.add_root(ModuleKind::Arm9, arm9.base_address() + arm9.build_info_offset());
+        relocation_sources.add_root(ModuleKind::Arm9, arm9.autoload_callback());
+
+        let endian = config.endian.into();
+        for overlay in &config.overlays {
+            let rom_overlay = &rom.arm9_overlays()[overlay.id as usize];
+            // The overlay may have been analyzed at another base address than the one in the ROM
+            let offset = overlay.base_address.map_or(0, |base_address| base_address.wrapping_sub(rom_overlay.base_address()));
+            let (ctor_start, ctor_end) = (rom_overlay.ctor_start(), rom_overlay.ctor_end());
+            if ctor_start == ctor_end {
+                continue;
+            }
+            let code = rom_overlay.code();
+            let start = (ctor_start - rom_overlay.base_address()) as usize;
+            let end = (ctor_end - rom_overlay.base_address()) as usize;
+            let Some(ctors) = code.get(start..end) else {
+                bail!("Static initializers of overlay {} are outside of the overlay", overlay.id);
+            };
+            for pointer in ctors.chunks_exact(4).map(|word| u32::from_slice(word, endian)).filter(|&pointer| pointer != 0) {
+                relocation_sources.add_root(ModuleKind::Overlay(overlay.id), pointer.wrapping_add(offset) & !1);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_referenced_outside(
+        relocation_sources: &RelocationSources,
+        module: &Module,
+        symbol: &Symbol,
+        file_ranges: &[Range<u32>],
+    ) -> bool {
+        // Pointers to Thumb functions have the Thumb bit set
+        let thumb_address = matches!(symbol.kind, SymbolKind::Function(_)).then_some(symbol.addr | 1);
+        iter::once(symbol.addr)
+            .chain(thumb_address)
+            .any(|address| relocation_sources.is_referenced_outside(module.kind(), address, file_ranges))
+    }
 }
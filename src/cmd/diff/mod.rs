@@ -0,0 +1,26 @@
+mod symbols;
+
+pub use symbols::*;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Subcommands for comparing project files.
+#[derive(Args)]
+pub struct DiffArgs {
+    #[command(subcommand)]
+    command: DiffCommand,
+}
+
+impl DiffArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            DiffCommand::Symbols(symbols) => symbols.run(),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum DiffCommand {
+    Symbols(DiffSymbols),
+}
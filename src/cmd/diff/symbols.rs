@@ -0,0 +1,280 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde::Serialize;
+
+use crate::{
+    config::{
+        config::Config,
+        module::ModuleKind,
+        symbol::{Symbol, SymbolMap},
+    },
+    util::local_name::CompilerLocalName,
+};
+
+/// Compares two symbol maps and lists added, removed, renamed and retyped symbols.
+#[derive(Args)]
+pub struct DiffSymbols {
+    /// Path to config.yaml or symbols.txt with the old symbols.
+    #[arg(long, short = 'l')]
+    pub left: PathBuf,
+
+    /// Path to config.yaml or symbols.txt with the new symbols.
+    #[arg(long, short = 'r')]
+    pub right: PathBuf,
+
+    /// Module to compare when one side is a config and the other is a symbols.txt: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Output as JSON instead of text.
+    #[arg(long, short = 'j')]
+    pub json: bool,
+
+    /// Treat compiler-generated local symbols like `@1304` as unchanged if only their number differs, as long as the
+    /// address and kind are the same.
+    #[arg(long)]
+    pub normalize_locals: bool,
+}
+
+/// Symbol maps loaded from either a config or a single symbols.txt.
+enum SymbolSource {
+    Config(BTreeMap<ModuleKind, SymbolMap>),
+    File(SymbolMap),
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct SymbolDifference {
+    /// Missing if two symbols.txt files were compared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    pub address: u32,
+    #[serde(flatten)]
+    pub change: SymbolChange,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum SymbolChange {
+    Added {
+        name: String,
+        kind: String,
+    },
+    Removed {
+        name: String,
+        kind: String,
+    },
+    /// Same address, different name
+    Renamed {
+        old_name: String,
+        new_name: String,
+    },
+    /// Same address and name, different kind
+    Retyped {
+        name: String,
+        old_kind: String,
+        new_kind: String,
+    },
+}
+
+impl DiffSymbols {
+    pub fn run(&self) -> Result<()> {
+        let differences = self.diff()?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&differences)?);
+            return Ok(());
+        }
+
+        let mut module = None;
+        for difference in &differences {
+            if difference.module != module {
+                module = difference.module.clone();
+                if let Some(module) = &module {
+                    println!("{module}:");
+                }
+            }
+            println!("    {difference}");
+        }
+        let count = |f: fn(&SymbolChange) -> bool| differences.iter().filter(|d| f(&d.change)).count();
+        println!(
+            "{} added, {} removed, {} renamed, {} retyped",
+            count(|c| matches!(c, SymbolChange::Added { .. })),
+            count(|c| matches!(c, SymbolChange::Removed { .. })),
+            count(|c| matches!(c, SymbolChange::Renamed { .. })),
+            count(|c| matches!(c, SymbolChange::Retyped { .. })),
+        );
+
+        Ok(())
+    }
+
+    /// Returns the differences between the left and right symbols, grouped by module and sorted by address.
+    pub fn diff(&self) -> Result<Vec<SymbolDifference>> {
+        let left = Self::load(&self.left)?;
+        let right = Self::load(&self.right)?;
+
+        let mut differences = vec![];
+        match (left, right) {
+            (SymbolSource::Config(left), SymbolSource::Config(mut right)) => {
+                let empty = SymbolMap::new();
+                for (module_kind, left_map) in &left {
+                    if self.module.is_some_and(|module| module != *module_kind) {
+                        continue;
+                    }
+                    let right_map = right.remove(module_kind);
+                    let module = Some(module_kind.to_string());
+                    differences.extend(Self::diff_symbol_maps(
+                        module,
+                        left_map,
+                        right_map.as_ref().unwrap_or(&empty),
+                        self.normalize_locals,
+                    ));
+                }
+                for (module_kind, right_map) in &right {
+                    if self.module.is_some_and(|module| module != *module_kind) {
+                        continue;
+                    }
+                    differences.extend(Self::diff_symbol_maps(
+                        Some(module_kind.to_string()),
+                        &empty,
+                        right_map,
+                        self.normalize_locals,
+                    ));
+                }
+            }
+            (SymbolSource::File(left), SymbolSource::File(right)) => {
+                differences.extend(Self::diff_symbol_maps(None, &left, &right, self.normalize_locals));
+            }
+            (SymbolSource::Config(mut left), SymbolSource::File(right)) => {
+                let module_kind = self.config_module()?;
+                let left = left.remove(&module_kind).with_context(|| format!("{module_kind} not found in left config"))?;
+                differences.extend(Self::diff_symbol_maps(
+                    Some(module_kind.to_string()),
+                    &left,
+                    &right,
+                    self.normalize_locals,
+                ));
+            }
+            (SymbolSource::File(left), SymbolSource::Config(mut right)) => {
+                let module_kind = self.config_module()?;
+                let right = right.remove(&module_kind).with_context(|| format!("{module_kind} not found in right config"))?;
+                differences.extend(Self::diff_symbol_maps(
+                    Some(module_kind.to_string()),
+                    &left,
+                    &right,
+                    self.normalize_locals,
+                ));
+            }
+        }
+
+        Ok(differences)
+    }
+
+    fn config_module(&self) -> Result<ModuleKind> {
+        self.module.context("--module is required to compare a config with a symbols.txt")
+    }
+
+    fn load(path: &Path) -> Result<SymbolSource> {
+        let is_config = path.extension().is_some_and(|extension| extension == "yaml" || extension == "yml");
+        if !is_config {
+            return Ok(SymbolSource::File(SymbolMap::from_file(path)?));
+        }
+
+        let config = Config::from_file(path)?;
+        let config_path = path.parent().unwrap();
+        let mut symbol_maps = BTreeMap::new();
+        for (module_kind, module) in config.modules() {
+            if symbol_maps.insert(module_kind, SymbolMap::from_file(config_path.join(&module.symbols))?).is_some() {
+                bail!("{module_kind} is listed more than once in '{}'", path.display());
+            }
+        }
+        Ok(SymbolSource::Config(symbol_maps))
+    }
+
+    /// Compares the symbols at each address. Symbols with the same name are compared by kind, and the remaining symbols
+    /// are paired up as renames. Unpaired symbols were added or removed. With `normalize_locals`, compiler-generated
+    /// locals of the same kind whose names only differ by number are the same symbol.
+    pub fn diff_symbol_maps(
+        module: Option<String>,
+        left: &SymbolMap,
+        right: &SymbolMap,
+        normalize_locals: bool,
+    ) -> Vec<SymbolDifference> {
+        let mut addresses: BTreeMap<u32, (Vec<&Symbol>, Vec<&Symbol>)> = BTreeMap::new();
+        for symbol in left.iter() {
+            addresses.entry(symbol.addr).or_default().0.push(symbol);
+        }
+        for symbol in right.iter() {
+            addresses.entry(symbol.addr).or_default().1.push(symbol);
+        }
+
+        let mut changes = vec![];
+        for (address, (mut lefts, mut rights)) in addresses {
+            lefts.retain(|left| {
+                let same_local = |right: &&Symbol| {
+                    normalize_locals && right.kind == left.kind && CompilerLocalName::names_match(&left.name, &right.name)
+                };
+                let Some(index) =
+                    rights.iter().position(|right| right.name == left.name).or_else(|| rights.iter().position(same_local))
+                else {
+                    return true;
+                };
+                let right = rights.remove(index);
+                if right.kind != left.kind {
+                    changes.push((
+                        address,
+                        SymbolChange::Retyped {
+                            name: left.name.clone(),
+                            old_kind: left.kind.to_string(),
+                            new_kind: right.kind.to_string(),
+                        },
+                    ));
+                }
+                false
+            });
+
+            let renamed = lefts.len().min(rights.len());
+            for (left, right) in lefts.drain(..renamed).zip(rights.drain(..renamed)) {
+                changes.push((address, SymbolChange::Renamed { old_name: left.name.clone(), new_name: right.name.clone() }));
+                if right.kind != left.kind {
+                    changes.push((
+                        address,
+                        SymbolChange::Retyped {
+                            name: right.name.clone(),
+                            old_kind: left.kind.to_string(),
+                            new_kind: right.kind.to_string(),
+                        },
+                    ));
+                }
+            }
+
+            for left in lefts {
+                changes.push((address, SymbolChange::Removed { name: left.name.clone(), kind: left.kind.to_string() }));
+            }
+            for right in rights {
+                changes.push((address, SymbolChange::Added { name: right.name.clone(), kind: right.kind.to_string() }));
+            }
+        }
+
+        changes.into_iter().map(|(address, change)| SymbolDifference { module: module.clone(), address, change }).collect()
+    }
+}
+
+impl Display for SymbolDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let address = self.address;
+        match &self.change {
+            SymbolChange::Added { name, kind } => write!(f, "added   {address:#010x} {name} kind:{kind}"),
+            SymbolChange::Removed { name, kind } => write!(f, "removed {address:#010x} {name} kind:{kind}"),
+            SymbolChange::Renamed { old_name, new_name } => write!(f, "renamed {address:#010x} {old_name} -> {new_name}"),
+            SymbolChange::Retyped { name, old_kind, new_kind } => {
+                write!(f, "retyped {address:#010x} {name} kind:{old_kind} -> kind:{new_kind}")
+            }
+        }
+    }
+}
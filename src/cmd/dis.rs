@@ -1,23 +1,33 @@
 use std::{
-    fs::{create_dir_all, File},
-    io::{BufWriter, Write},
+    fs::create_dir_all,
+    io::{self, BufWriter},
+    ops::Range,
     path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::Args;
 use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
 
 use crate::{
+    analysis::{
+        disassemble::{AssemblyWriter, Incbin},
+        strings::StringEncoding,
+    },
     config::{
-        config::{Config, ConfigAutoload, ConfigModule, ConfigOverlay},
+        config::{Config, ConfigModule},
         delinks::{DelinkFile, Delinks},
         module::{Module, ModuleKind},
         relocation::Relocations,
-        section::Section,
-        symbol::{InstructionMode, Symbol, SymbolKind, SymbolLookup, SymbolMaps},
+        section::{Section, Sections},
+        symbol::{SymbolLookup, SymbolMaps},
+    },
+    util::{
+        address::AddressFormat,
+        io::{create_file, read_file},
+        jobs,
+        parse::parse_u32,
     },
-    util::io::{create_file, open_file, read_file},
 };
 
 /// Disassembles an extracted ROM.
@@ -31,14 +41,49 @@ pub struct Disassemble {
     #[arg(long, short = 'a')]
     pub asm_path: PathBuf,
 
-    /// Disassemble with Unified Assembler Language (UAL) syntax.
+    /// Disassemble with Unified Assembler Language (UAL) syntax. Also emits GNU assembler `.type`, `.size`, `.arm`,
+    /// `.thumb` and `.thumb_func` directives.
     #[arg(long, short = 'u')]
     pub ual: bool,
+
+    /// Only disassemble this module: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Only disassemble functions and data which end after this address.
+    #[arg(long, value_parser = parse_u32)]
+    pub start: Option<u32>,
+
+    /// Only disassemble functions and data which start before this address.
+    #[arg(long, value_parser = parse_u32)]
+    pub end: Option<u32>,
+
+    /// Number of threads to disassemble with.
+    #[arg(long, short = 'j', default_value_t = 1)]
+    pub jobs: usize,
+
+    /// Data symbols of at least this many bytes are written to a binary file and included with `.incbin`, unless they
+    /// contain relocations or other symbols.
+    #[arg(long, value_parser = parse_u32)]
+    pub incbin_threshold: Option<u32>,
+
+    /// Encoding of strings in data, which are decoded into comments next to the bytes. Defaults to `string_encoding` in
+    /// config.yaml, or no decoding if neither is given.
+    #[arg(long, short = 'e', value_enum)]
+    pub encoding: Option<StringEncoding>,
+
+    /// Writes one assembly file per module with all of its sections, instead of one per delink file.
+    #[arg(long)]
+    pub single_file: bool,
+
+    /// Format of addresses in comments, such as the address after each function label.
+    #[arg(long, value_enum, default_value_t)]
+    pub address_format: AddressFormat,
 }
 
 impl Disassemble {
     pub fn run(&self) -> Result<()> {
-        let config: Config = serde_yml::from_reader(open_file(&self.config_path)?)?;
+        let config = Config::from_file(&self.config_path)?;
         let config_path = self.config_path.parent().unwrap();
 
         let rom_paths_path = config_path.join(&config.rom_config);
@@ -48,128 +93,115 @@ impl Disassemble {
 
         let mut symbol_maps = SymbolMaps::from_config(config_path, &config)?;
 
-        self.disassemble_arm9(&config.main_module, &mut symbol_maps, &rom, extract_path)?;
-        self.disassemble_autoloads(&config.autoloads, &mut symbol_maps, &rom, extract_path)?;
-        if let Some(arm9_overlays) = &rom.config().arm9_overlays {
-            let overlays_path = extract_path.join(arm9_overlays);
-            let overlays_path = overlays_path.parent().unwrap();
-            self.disassemble_overlays(&config.overlays, &mut symbol_maps, overlays_path)?;
+        let module_codes = self.read_module_codes(&config, &rom, extract_path)?;
+
+        // Modules add their local symbols to the symbol maps, so they are all loaded before any file is disassembled
+        let mut modules = vec![];
+        for (module_kind, module_config, code) in &module_codes {
+            let delinks = Delinks::from_file(config_path.join(&module_config.delinks), *module_kind)?;
+            let relocations = Relocations::from_file(config_path.join(&module_config.relocations), &symbol_maps)?;
+            let module = Module::new(
+                *module_kind,
+                module_config.name.clone(),
+                symbol_maps.get_mut(*module_kind),
+                relocations,
+                delinks.sections,
+                code,
+                &config.name_prefixes,
+                config.endian.into(),
+            )?;
+            modules.push((module, delinks.files));
         }
 
-        Ok(())
+        let string_encoding = self.encoding.or(config.string_encoding);
+        self.disassemble_modules(&modules, &symbol_maps, string_encoding)
     }
 
-    fn disassemble_arm9(
+    fn read_module_codes<'a>(
         &self,
-        config: &ConfigModule,
-        symbol_maps: &mut SymbolMaps,
+        config: &'a Config,
         rom: &Rom,
         extract_path: &Path,
-    ) -> Result<()> {
-        let config_path = self.config_path.parent().unwrap();
-
-        let module_kind = ModuleKind::Arm9;
-        let delinks = Delinks::from_file(config_path.join(&config.delinks), module_kind)?;
-        let symbol_map = symbol_maps.get_mut(module_kind);
-        let relocations = Relocations::from_file(config_path.join(&config.relocations))?;
-
-        let code = read_file(extract_path.join(&rom.config().arm9_bin))?;
-        let module = Module::new_arm9(config.name.clone(), symbol_map, relocations, delinks.sections, &code)?;
+    ) -> Result<Vec<(ModuleKind, &'a ConfigModule, Vec<u8>)>> {
+        let mut module_codes = vec![];
 
-        for file in &delinks.files {
-            let (file_path, _) = file.split_file_ext();
-            self.create_assembly_file(
-                &module,
-                file,
-                self.asm_path.join(format!("{}/{file_path}.s", config.name)),
-                symbol_maps,
-            )?;
+        if self.includes_module(ModuleKind::Arm9) {
+            let code = read_file(extract_path.join(&rom.config().arm9_bin))?;
+            module_codes.push((ModuleKind::Arm9, &config.main_module, code));
         }
 
-        Ok(())
-    }
-
-    fn disassemble_autoloads(
-        &self,
-        autoloads: &[ConfigAutoload],
-        symbol_maps: &mut SymbolMaps,
-        rom: &Rom,
-        extract_path: &Path,
-    ) -> Result<()> {
-        for autoload in autoloads {
-            let config_path = self.config_path.parent().unwrap();
-
+        for autoload in &config.autoloads {
             let module_kind = ModuleKind::Autoload(autoload.kind);
-            let delinks = Delinks::from_file(config_path.join(&autoload.module.delinks), module_kind)?;
-            let symbol_map = symbol_maps.get_mut(module_kind);
-            let relocations = Relocations::from_file(config_path.join(&autoload.module.relocations))?;
-
+            if !self.includes_module(module_kind) {
+                continue;
+            }
             let autoload_path = match autoload.kind {
                 AutoloadKind::Itcm => &rom.config().itcm.bin,
                 AutoloadKind::Dtcm => &rom.config().dtcm.bin,
                 AutoloadKind::Unknown(_) => panic!("Unknown autoload kind"),
             };
-
             let code = read_file(extract_path.join(autoload_path))?;
-            let module = Module::new_autoload(
-                autoload.module.name.clone(),
-                symbol_map,
-                relocations,
-                delinks.sections,
-                autoload.kind,
-                &code,
-            )?;
+            module_codes.push((module_kind, &autoload.module, code));
+        }
 
-            for file in &delinks.files {
-                let (file_path, _) = file.split_file_ext();
-                self.create_assembly_file(
-                    &module,
-                    file,
-                    self.asm_path.join(format!("{}/{file_path}.s", autoload.module.name)),
-                    symbol_maps,
-                )?;
+        if let Some(arm9_overlays) = &rom.config().arm9_overlays {
+            let overlays_path = extract_path.join(arm9_overlays);
+            let overlays_path = overlays_path.parent().unwrap();
+            for overlay in &config.overlays {
+                let module_kind = ModuleKind::Overlay(overlay.id);
+                if !self.includes_module(module_kind) {
+                    continue;
+                }
+                let code = read_file(overlays_path.join(format!("ov{:03}.bin", overlay.id)))?;
+                module_codes.push((module_kind, &overlay.module, code));
             }
         }
 
-        Ok(())
+        Ok(module_codes)
     }
 
-    fn disassemble_overlays(
+    /// Writes one assembly file per delink file of each module to the assembly output path, or one per module with
+    /// `--single-file`. The files are written on up to `--jobs` threads, which doesn't affect their contents.
+    pub fn disassemble_modules(
         &self,
-        overlays: &[ConfigOverlay],
-        symbol_maps: &mut SymbolMaps,
-        overlays_path: &Path,
+        modules: &[(Module, Vec<DelinkFile>)],
+        symbol_maps: &SymbolMaps,
+        string_encoding: Option<StringEncoding>,
     ) -> Result<()> {
-        let config_path = self.config_path.parent().unwrap();
-
-        for overlay in overlays {
-            let module_kind = ModuleKind::Overlay(overlay.id);
-            let delinks = Delinks::from_file(config_path.join(&overlay.module.delinks), module_kind)?;
-            let symbol_map = symbol_maps.get_mut(module_kind);
-            let relocations = Relocations::from_file(config_path.join(&overlay.module.relocations))?;
-
-            let code = read_file(overlays_path.join(format!("ov{:03}.bin", overlay.id)))?;
-            let module = Module::new_overlay(
-                overlay.module.name.clone(),
-                symbol_map,
-                relocations,
-                delinks.sections,
-                overlay.id,
-                &code,
-            )?;
-
-            for file in &delinks.files {
-                let (file_path, _) = file.split_file_ext();
+        if self.single_file {
+            return jobs::try_for_each(self.jobs, modules, |(module, _)| {
                 self.create_assembly_file(
-                    &module,
-                    file,
-                    self.asm_path.join(format!("{}/{file_path}.s", overlay.module.name)),
+                    module,
+                    &Self::module_file(module)?,
+                    self.asm_path.join(format!("{}.s", module.name())),
                     symbol_maps,
-                )?;
-            }
+                    string_encoding,
+                )
+            });
         }
 
-        Ok(())
+        let files =
+            modules.iter().flat_map(|(module, files)| files.iter().map(move |file| (module, file))).collect::<Vec<_>>();
+
+        jobs::try_for_each(self.jobs, &files, |&(module, file)| {
+            let (file_path, _) = file.split_file_ext();
+            self.create_assembly_file(
+                module,
+                file,
+                self.asm_path.join(format!("{}/{file_path}.s", module.name())),
+                symbol_maps,
+                string_encoding,
+            )
+        })
+    }
+
+    /// Returns a delink file with every section of `module`, so that the whole module is disassembled in address order.
+    fn module_file(module: &Module) -> Result<DelinkFile> {
+        let mut sections = Sections::new();
+        for section in module.sections().sorted_by_address() {
+            sections.add(Section::inherit(section, section.start_address(), section.end_address())?)?;
+        }
+        Ok(DelinkFile::new(module.name().to_string(), sections, true))
     }
 
     fn create_assembly_file<P: AsRef<Path>>(
@@ -178,140 +210,70 @@ impl Disassemble {
         delink_file: &DelinkFile,
         path: P,
         symbol_maps: &SymbolMaps,
+        string_encoding: Option<StringEncoding>,
     ) -> Result<()> {
         let path = path.as_ref();
 
+        if !delink_file.sections.iter().any(|section| self.in_range(section.address_range())) {
+            return Ok(());
+        }
+
         create_dir_all(path.parent().unwrap())?;
         let asm_file = create_file(path)?;
         let mut writer = BufWriter::new(asm_file);
 
-        self.disassemble(module, delink_file, &mut writer, symbol_maps)?;
+        self.disassemble(module, delink_file, &mut writer, symbol_maps, string_encoding)?;
 
         Ok(())
     }
 
-    fn disassemble(
+    /// Writes the assembly of one delink file. Sections, functions and data outside of `--start`/`--end` are left out.
+    pub fn disassemble<W: io::Write>(
         &self,
         module: &Module,
         delink_file: &DelinkFile,
-        writer: &mut BufWriter<File>,
+        writer: &mut W,
         symbol_maps: &SymbolMaps,
+        string_encoding: Option<StringEncoding>,
     ) -> Result<()> {
         writeln!(writer, "    .include \"macros/function.inc\"")?;
         writeln!(writer)?;
 
-        let symbol_map = symbol_maps.get(module.kind()).unwrap();
-
+        let (file_path, _) = delink_file.split_file_ext();
+        let assembly_writer = AssemblyWriter {
+            symbol_lookup: SymbolLookup {
+                module_kind: module.kind(),
+                symbol_map: symbol_maps.get(module.kind()).unwrap(),
+                symbol_maps,
+                relocations: module.relocations(),
+                endian: module.endian(),
+                string_encoding,
+                address_format: self.address_format,
+            },
+            code: module.code(),
+            base_address: module.base_address(),
+            sections: module.sections(),
+            ual: self.ual,
+            range: self.start.unwrap_or(0)..self.end.unwrap_or(u32::MAX),
+            incbin: self.incbin_threshold.map(|threshold| Incbin {
+                threshold,
+                asm_path: &self.asm_path,
+                dir: format!("{}/{file_path}", module.name()),
+            }),
+        };
         for section in delink_file.sections.sorted_by_address() {
-            // write section directive
-            match section.name() {
-                ".text" => writeln!(writer, "    .text")?,
-                _ => writeln!(writer, "    .section {}, 4, 1, 4", section.name())?,
-            }
-
-            let code = section.code_from_module(module)?;
-            let mut offset = 0; // offset within section
-
-            let symbol_lookup =
-                SymbolLookup { module_kind: module.kind(), symbol_map, symbol_maps, relocations: module.relocations() };
-
-            let mut symbol_iter = symbol_map.iter_by_address(section.address_range()).peekable();
-            while let Some(symbol) = symbol_iter.next() {
-                debug_assert!(symbol.addr >= section.start_address() && symbol.addr < section.end_address());
-                match symbol.kind {
-                    SymbolKind::Function(sym_function) => {
-                        if sym_function.unknown {
-                            let function_offset = symbol.addr - section.start_address();
-                            if offset < function_offset {
-                                Self::dump_bytes(code.unwrap(), offset, function_offset, writer)?;
-                                writeln!(writer)?;
-                                offset = function_offset;
-                            }
-
-                            writeln!(writer, "    .global {}", symbol.name)?;
-                            match sym_function.mode {
-                                InstructionMode::Arm => writeln!(writer, "    arm_func_start {}", symbol.name)?,
-                                InstructionMode::Thumb => writeln!(writer, "    thumb_func_start {}", symbol.name)?,
-                            }
-                            writeln!(writer, "{}: ; {:#010x}", symbol.name, symbol.addr)?;
-                        } else {
-                            let function = module.get_function(symbol.addr).with_context(|| format!(
-                                "Tried to disassemble function symbol '{}' at {:#010x} but the function was not found in the module",
-                                symbol.name,
-                                symbol.addr,
-                            ))?;
-
-                            let function_offset = function.start_address() - section.start_address();
-                            if offset < function_offset {
-                                Self::dump_bytes(code.unwrap(), offset, function_offset, writer)?;
-                                writeln!(writer)?;
-                            }
-
-                            function.write_assembly(writer, &symbol_lookup, module.code(), module.base_address(), self.ual)?;
-                            offset = function.end_address() - section.start_address();
-                        }
-                    }
-                    SymbolKind::Data(data) => {
-                        let start = (symbol.addr - section.start_address()) as usize;
-
-                        let size =
-                            data.size().unwrap_or_else(|| Self::size_to_next_symbol(section, symbol, symbol_iter.peek()));
-
-                        let end = start + size as usize;
-                        let bytes = &code.unwrap()[start..end];
-                        write!(writer, "{}:", symbol.name)?;
-
-                        if symbol.ambiguous {
-                            write!(writer, " ; ambiguous")?;
-                        }
-                        writeln!(writer)?;
-
-                        data.write_assembly(writer, symbol, bytes, &symbol_lookup)?;
-                        offset = end as u32;
-                    }
-                    SymbolKind::Bss(bss) => {
-                        let size = bss.size.unwrap_or_else(|| Self::size_to_next_symbol(section, symbol, symbol_iter.peek()));
-                        writeln!(writer, "{}: .space {:#x}", symbol.name, size)?;
-                        offset += size;
-                    }
-                    _ => {}
-                }
-            }
-
-            let end_offset = section.end_address() - section.start_address();
-            if offset < end_offset {
-                if let Some(code) = code {
-                    Self::dump_bytes(code, offset, end_offset, writer)?;
-                    writeln!(writer)?;
-                } else {
-                    writeln!(writer, "    .space {:#x}", end_offset - offset)?;
-                }
-            }
+            assembly_writer.write_section(writer, section)?;
         }
 
         Ok(())
     }
 
-    fn size_to_next_symbol(section: &Section, symbol: &Symbol, next: Option<&&Symbol>) -> u32 {
-        if let Some(next_symbol) = next {
-            next_symbol.addr.min(section.end_address()) - symbol.addr
-        } else {
-            section.end_address() - symbol.addr
-        }
+    fn includes_module(&self, module_kind: ModuleKind) -> bool {
+        self.module.map_or(true, |module| module == module_kind)
     }
 
-    fn dump_bytes(code: &[u8], mut offset: u32, end_offset: u32, writer: &mut BufWriter<File>) -> Result<()> {
-        while offset < end_offset {
-            write!(writer, "    .byte ")?;
-            for i in 0..16.min(end_offset - offset) {
-                if i != 0 {
-                    write!(writer, ", ")?;
-                }
-                write!(writer, "0x{:02x}", code[offset as usize])?;
-                offset += 1;
-            }
-            writeln!(writer)?;
-        }
-        Ok(())
+    /// Returns whether `range` overlaps with the `--start`/`--end` range.
+    fn in_range(&self, range: Range<u32>) -> bool {
+        range.start < self.end.unwrap_or(u32::MAX) && range.end > self.start.unwrap_or(0)
     }
 }
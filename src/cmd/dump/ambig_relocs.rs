@@ -0,0 +1,134 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::Result;
+use clap::Args;
+use serde::Serialize;
+
+use crate::config::{
+    config::Config,
+    module::ModuleKind,
+    relocation::{RelocationModule, Relocations},
+    symbol::{Symbol, SymbolKind, SymbolMap, SymbolMaps},
+};
+
+/// Lists relocations which may point to more than one overlay, grouped by destination address.
+#[derive(Args)]
+pub struct DumpAmbigRelocs {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Only list relocations from this module: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Maximum number of destinations to list.
+    #[arg(long, short = 'l')]
+    pub limit: Option<usize>,
+
+    /// Output as JSON instead of a table.
+    #[arg(long, short = 'j')]
+    pub json: bool,
+}
+
+/// Ambiguous relocations which share a destination address.
+#[derive(Serialize)]
+pub struct AmbiguousDestination {
+    pub destination: u32,
+    pub sources: Vec<AmbiguousSource>,
+    /// Every overlay that any of the relocations may point to, in ascending order
+    pub candidates: Vec<AmbiguousCandidate>,
+}
+
+#[derive(Serialize)]
+pub struct AmbiguousSource {
+    pub module: String,
+    pub address: u32,
+}
+
+#[derive(Serialize)]
+pub struct AmbiguousCandidate {
+    pub module: String,
+    /// Name of the symbol at the destination, if the overlay has one
+    pub symbol: Option<String>,
+}
+
+impl DumpAmbigRelocs {
+    pub fn run(&self) -> Result<()> {
+        let destinations = self.find_destinations()?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&destinations)?);
+            return Ok(());
+        }
+
+        for destination in &destinations {
+            let candidates = destination
+                .candidates
+                .iter()
+                .map(|candidate| format!("{}: {}", candidate.module, candidate.symbol.as_deref().unwrap_or("?")))
+                .collect::<Vec<_>>();
+            println!("{:#010x} -> {}", destination.destination, candidates.join(", "));
+            for source in &destination.sources {
+                println!("    {:#010x} in {}", source.address, source.module);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the ambiguous relocations grouped by destination in ascending order, at most `limit` destinations.
+    pub fn find_destinations(&self) -> Result<Vec<AmbiguousDestination>> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let mut groups: BTreeMap<u32, (Vec<AmbiguousSource>, Vec<u16>)> = BTreeMap::new();
+        for (module_kind, module) in config.modules() {
+            if self.module.is_some_and(|module| module != module_kind) {
+                continue;
+            }
+            let relocations = Relocations::from_file(config_path.join(&module.relocations), &symbol_maps)?;
+            for relocation in relocations.iter() {
+                let RelocationModule::Overlays { ids } = relocation.module() else { continue };
+                let (sources, overlays) = groups.entry(relocation.to_address()).or_default();
+                sources.push(AmbiguousSource { module: module_kind.to_string(), address: relocation.from_address() });
+                overlays.extend(ids);
+            }
+        }
+
+        let mut destinations = vec![];
+        for (destination, (sources, mut overlays)) in groups.into_iter().take(self.limit.unwrap_or(usize::MAX)) {
+            overlays.sort_unstable();
+            overlays.dedup();
+
+            let mut candidates = vec![];
+            for id in overlays {
+                let module_kind = ModuleKind::Overlay(id);
+                let symbol = match symbol_maps.get(module_kind) {
+                    Some(symbol_map) => match Self::candidate_symbol(symbol_map, destination) {
+                        Some(symbol) => Some(symbol.name.clone()),
+                        None => symbol_map.get_function(destination)?.map(|(_, symbol)| symbol.name.clone()),
+                    },
+                    None => None,
+                };
+                candidates.push(AmbiguousCandidate { module: module_kind.to_string(), symbol });
+            }
+            destinations.push(AmbiguousDestination { destination, sources, candidates });
+        }
+
+        Ok(destinations)
+    }
+
+    /// Returns the symbol which a relocation to `destination` would most likely refer to. If there are several, labels and
+    /// pool constants are passed over in favor of functions and data.
+    fn candidate_symbol(symbol_map: &SymbolMap, destination: u32) -> Option<&Symbol> {
+        let symbols = symbol_map.for_address(destination)?.map(|(_, symbol)| symbol).collect::<Vec<_>>();
+        symbols
+            .iter()
+            .find(|symbol| !matches!(symbol.kind, SymbolKind::Label(_) | SymbolKind::PoolConstant))
+            .or(symbols.first())
+            .copied()
+    }
+}
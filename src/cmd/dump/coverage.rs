@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::Serialize;
+
+use crate::config::{
+    config::Config,
+    delinks::Delinks,
+    module::ModuleKind,
+    section::Sections,
+    symbol::{SymbolMap, SymbolMaps},
+};
+
+/// Lists how many bytes of each module are covered by symbols, and the ranges which aren't.
+#[derive(Args)]
+pub struct DumpCoverage {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Only report coverage of this module: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Output as JSON instead of a table.
+    #[arg(long, short = 'j')]
+    pub json: bool,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct ModuleCoverage {
+    pub module: String,
+    pub total: u32,
+    pub covered: u32,
+    pub uncovered: u32,
+    pub uncovered_ranges: Vec<UncoveredRange>,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct UncoveredRange {
+    pub start: u32,
+    pub end: u32,
+    /// Section containing the range, or `None` if the range is between sections.
+    pub section: Option<String>,
+}
+
+impl DumpCoverage {
+    pub fn run(&self) -> Result<()> {
+        let coverages = self.coverage()?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&coverages)?);
+        } else {
+            println!("{:<12} {:>10} {:>10} {:>10} {:>7}", "module", "total", "covered", "uncovered", "percent");
+            for coverage in &coverages {
+                let percent =
+                    if coverage.total == 0 { 100.0 } else { coverage.covered as f64 * 100.0 / coverage.total as f64 };
+                println!(
+                    "{:<12} {:>#10x} {:>#10x} {:>#10x} {:>6.2}%",
+                    coverage.module, coverage.total, coverage.covered, coverage.uncovered, percent
+                );
+                for range in &coverage.uncovered_ranges {
+                    println!(
+                        "    {:#010x}..{:#010x} {:>#8x} {}",
+                        range.start,
+                        range.end,
+                        range.end - range.start,
+                        range.section.as_deref().unwrap_or("(no section)")
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the coverage of every module in the config, or only of `self.module` if set.
+    pub fn coverage(&self) -> Result<Vec<ModuleCoverage>> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let mut coverages = vec![];
+        for (module_kind, module_config) in config.modules() {
+            if self.module.is_some_and(|module| module != module_kind) {
+                continue;
+            }
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let delinks = Delinks::from_file(config_path.join(&module_config.delinks), module_kind)?;
+            coverages.push(module_coverage(module_kind, &delinks.sections, symbol_map));
+        }
+        Ok(coverages)
+    }
+}
+
+/// Returns which bytes from the start of the first section to the end of the last section are covered by a symbol. Data
+/// and bss symbols without a known size extend to the next symbol.
+pub fn module_coverage(module_kind: ModuleKind, sections: &Sections, symbol_map: &SymbolMap) -> ModuleCoverage {
+    let base_address = sections.base_address().unwrap_or(0);
+    let end_address = sections.end_address().unwrap_or(0);
+
+    let mut uncovered_ranges = vec![];
+    let mut cursor = base_address;
+    for section in sections.sorted_by_address() {
+        if section.start_address() > cursor {
+            uncovered_ranges.push(UncoveredRange { start: cursor, end: section.start_address(), section: None });
+        }
+
+        let symbols = symbol_map.iter_by_address(section.address_range()).collect::<Vec<_>>();
+        let mut covered_until = section.start_address().max(cursor);
+        for (i, symbol) in symbols.iter().enumerate() {
+            let next_address = symbols[i + 1..]
+                .iter()
+                .map(|next| next.addr)
+                .find(|&addr| addr > symbol.addr)
+                .unwrap_or(section.end_address());
+            if symbol.addr > covered_until {
+                uncovered_ranges.push(UncoveredRange {
+                    start: covered_until,
+                    end: symbol.addr,
+                    section: Some(section.name().to_string()),
+                });
+            }
+            let symbol_end = symbol.addr.saturating_add(symbol.size(next_address)).min(section.end_address());
+            covered_until = covered_until.max(symbol_end);
+        }
+        if covered_until < section.end_address() {
+            uncovered_ranges.push(UncoveredRange {
+                start: covered_until,
+                end: section.end_address(),
+                section: Some(section.name().to_string()),
+            });
+        }
+
+        cursor = cursor.max(section.end_address());
+    }
+
+    let total = end_address - base_address;
+    let uncovered = uncovered_ranges.iter().map(|range| range.end - range.start).sum();
+    ModuleCoverage { module: module_kind.to_string(), total, covered: total - uncovered, uncovered, uncovered_ranges }
+}
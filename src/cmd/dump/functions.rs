@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+
+use crate::config::{config::Config, module::ModuleKind, symbol::SymbolMaps};
+
+/// Lists all functions with their address, instruction mode and size.
+#[derive(Args)]
+pub struct DumpFunctions {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Only list functions in this module: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Order in which to list the functions.
+    #[arg(long, short = 's', value_enum, default_value_t = FunctionSortOrder::Address)]
+    pub sort: FunctionSortOrder,
+
+    /// Output as JSON instead of a table.
+    #[arg(long, short = 'j')]
+    pub json: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionSortOrder {
+    /// Ascending address.
+    Address,
+    /// Descending size.
+    Size,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct FunctionRow {
+    pub module: String,
+    pub address: u32,
+    pub name: String,
+    pub mode: String,
+    pub size: u32,
+    /// Whether the function still has its default name, like `func_02001234`.
+    pub auto_named: bool,
+}
+
+impl DumpFunctions {
+    pub fn run(&self) -> Result<()> {
+        let rows = self.functions()?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        } else {
+            let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0).max(4);
+            println!("{:<12} {:<10} {:<name_width$} {:<5} {:>8} auto", "module", "address", "name", "mode", "size");
+            for row in &rows {
+                println!(
+                    "{:<12} {:#010x} {:<name_width$} {:<5} {:>#8x} {}",
+                    row.module,
+                    row.address,
+                    row.name,
+                    row.mode,
+                    row.size,
+                    if row.auto_named { "yes" } else { "no" }
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the functions of every module in the config, or only of `self.module` if set, in `self.sort` order.
+    pub fn functions(&self) -> Result<Vec<FunctionRow>> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let mut rows = vec![];
+        for (module_kind, _) in config.modules() {
+            if self.module.is_some_and(|module| module != module_kind) {
+                continue;
+            }
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let default_prefix = config.name_prefixes.func_prefix(module_kind);
+            for (function, symbol) in symbol_map.functions() {
+                rows.push(FunctionRow {
+                    module: module_kind.to_string(),
+                    address: symbol.addr,
+                    name: symbol.name.clone(),
+                    mode: function.mode.to_string(),
+                    size: function.size,
+                    auto_named: symbol.name.starts_with(&default_prefix),
+                });
+            }
+        }
+
+        if self.sort == FunctionSortOrder::Size {
+            // Stable sort, so functions of equal size stay in address order
+            rows.sort_by(|a, b| b.size.cmp(&a.size));
+        }
+
+        Ok(rows)
+    }
+}
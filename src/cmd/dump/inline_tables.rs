@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+use serde::Serialize;
+
+use crate::config::{
+    config::Config,
+    delinks::Delinks,
+    module::{Module, ModuleKind},
+    relocation::Relocations,
+    symbol::SymbolMaps,
+};
+
+/// Lists the data tables found inside functions, with their address, size and owning function.
+#[derive(Args)]
+pub struct DumpInlineTables {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Only list inline tables in this module: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Output as JSON instead of a table.
+    #[arg(long, short = 'j')]
+    pub json: bool,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct InlineTableRow {
+    pub module: String,
+    pub address: u32,
+    pub size: u32,
+    pub function: String,
+}
+
+impl DumpInlineTables {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let mut symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let arm9 = rom.arm9();
+        let autoloads = arm9.autoloads()?;
+
+        let mut codes = vec![(ModuleKind::Arm9, arm9.code()?)];
+        for autoload in autoloads.iter() {
+            if let AutoloadKind::Unknown(_) = autoload.kind() {
+                continue;
+            }
+            codes.push((ModuleKind::Autoload(autoload.kind()), autoload.code()));
+        }
+        for overlay in rom.arm9_overlays() {
+            codes.push((ModuleKind::Overlay(overlay.id()), overlay.code()));
+        }
+
+        let mut rows = vec![];
+        for (module_kind, module_config) in config.modules() {
+            if self.module.is_some_and(|module| module != module_kind) {
+                continue;
+            }
+            let (_, code) = codes
+                .iter()
+                .find(|(kind, _)| *kind == module_kind)
+                .with_context(|| format!("{module_kind} not found in ROM"))?;
+            let delinks = Delinks::from_file(config_path.join(&module_config.delinks), module_kind)?;
+            let module = Module::new(
+                module_kind,
+                module_config.name.clone(),
+                symbol_maps.get_mut(module_kind),
+                Relocations::new(),
+                delinks.sections,
+                code,
+                &config.name_prefixes,
+                config.endian.into(),
+            )?;
+            rows.extend(Self::inline_tables(&module));
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        } else {
+            println!("{:<12} {:<10} {:>8} function", "module", "address", "size");
+            for row in &rows {
+                println!("{:<12} {:#010x} {:>#8x} {}", row.module, row.address, row.size, row.function);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the inline tables of every function in `module`, in address order.
+    pub fn inline_tables(module: &Module) -> Vec<InlineTableRow> {
+        let mut rows = vec![];
+        for function in module.sections().functions() {
+            for table in function.inline_tables().values() {
+                rows.push(InlineTableRow {
+                    module: module.kind().to_string(),
+                    address: table.address,
+                    size: table.size,
+                    function: function.name().to_string(),
+                });
+            }
+        }
+        rows.sort_by_key(|row| row.address);
+        rows
+    }
+}
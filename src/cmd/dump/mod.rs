@@ -0,0 +1,46 @@
+mod ambig_relocs;
+mod coverage;
+mod functions;
+mod inline_tables;
+mod overlays;
+mod strings;
+
+pub use ambig_relocs::*;
+pub use coverage::*;
+pub use functions::*;
+pub use inline_tables::*;
+pub use overlays::*;
+pub use strings::*;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Subcommands for dumping information from a dsd project.
+#[derive(Args)]
+pub struct DumpArgs {
+    #[command(subcommand)]
+    command: DumpCommand,
+}
+
+impl DumpArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            DumpCommand::AmbigRelocs(ambig_relocs) => ambig_relocs.run(),
+            DumpCommand::Coverage(coverage) => coverage.run(),
+            DumpCommand::Functions(functions) => functions.run(),
+            DumpCommand::InlineTables(inline_tables) => inline_tables.run(),
+            DumpCommand::Overlays(overlays) => overlays.run(),
+            DumpCommand::Strings(strings) => strings.run(),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum DumpCommand {
+    AmbigRelocs(DumpAmbigRelocs),
+    Coverage(DumpCoverage),
+    Functions(DumpFunctions),
+    InlineTables(DumpInlineTables),
+    Overlays(DumpOverlays),
+    Strings(DumpStrings),
+}
@@ -0,0 +1,221 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+use unarm::Endian;
+
+use crate::{
+    analysis::{
+        functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult},
+        overlay_loads::{self, OverlayLoad, OverlayLoadKind},
+    },
+    config::{
+        config::Config,
+        module::ModuleKind,
+        symbol::{SymbolMap, SymbolMaps},
+    },
+};
+
+/// Lists overlays and their address ranges, or which modules load which overlays.
+#[derive(Args)]
+pub struct DumpOverlays {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// List calls to the overlay loader functions instead, as a graph of which modules load which overlays.
+    #[arg(long, short = 'g')]
+    pub graph: bool,
+
+    /// Output the graph in DOT format.
+    #[arg(long, short = 'd', requires = "graph")]
+    pub dot: bool,
+
+    /// Name of the function which loads an overlay.
+    #[arg(long, default_value = "FS_LoadOverlay")]
+    pub load_function: String,
+
+    /// Name of the function which unloads an overlay.
+    #[arg(long, default_value = "FS_UnloadOverlay")]
+    pub unload_function: String,
+}
+
+struct ModuleOverlayLoad {
+    module_kind: ModuleKind,
+    function_name: String,
+    load: OverlayLoad,
+}
+
+impl DumpOverlays {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+
+        if self.graph {
+            let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+            let loads = self.find_loads(&rom, &config, &symbol_maps)?;
+            if self.dot {
+                Self::print_dot(&loads);
+            } else {
+                Self::print_loads(&loads);
+            }
+        } else {
+            Self::print_overlays(&rom, &config);
+        }
+
+        Ok(())
+    }
+
+    fn print_overlays(rom: &Rom, config: &Config) {
+        let overlays = rom
+            .arm9_overlays()
+            .iter()
+            .map(|overlay| {
+                let base_address = config.overlay_base_address(overlay.id(), overlay.base_address());
+                (overlay.id(), base_address, base_address + overlay.end_address() - overlay.base_address())
+            })
+            .collect::<Vec<_>>();
+        println!("{:<7} {:<10} {:<10} shares address space with", "overlay", "start", "end");
+        for &(id, start, end) in &overlays {
+            let shared = overlays
+                .iter()
+                .filter(|&&(other_id, other_start, other_end)| other_id != id && other_start < end && start < other_end)
+                .map(|(other_id, _, _)| other_id.to_string())
+                .collect::<Vec<_>>();
+            println!("{:<7} {:#010x} {:#010x} {}", id, start, end, shared.join(", "));
+        }
+    }
+
+    fn loaders(&self, symbol_maps: &SymbolMaps) -> Result<BTreeMap<u32, OverlayLoadKind>> {
+        let main_symbols = symbol_maps.get(ModuleKind::Arm9).context("Symbol map not found for main module")?;
+
+        let mut loaders = BTreeMap::new();
+        for (name, kind) in [(&self.load_function, OverlayLoadKind::Load), (&self.unload_function, OverlayLoadKind::Unload)] {
+            match main_symbols.by_name(name)? {
+                Some((_, symbol)) => {
+                    loaders.insert(symbol.addr & !1, kind);
+                }
+                None => log::warn!("Function '{name}' not found in main module"),
+            }
+        }
+        if loaders.is_empty() {
+            bail!(
+                "No overlay loader functions found, name them '{}' and '{}' in symbols.txt",
+                self.load_function,
+                self.unload_function
+            );
+        }
+        Ok(loaders)
+    }
+
+    fn find_loads(&self, rom: &Rom, config: &Config, symbol_maps: &SymbolMaps) -> Result<Vec<ModuleOverlayLoad>> {
+        let loaders = self.loaders(symbol_maps)?;
+        let endian = config.endian.into();
+
+        let arm9 = rom.arm9();
+        let autoloads = arm9.autoloads()?;
+
+        let mut modules = vec![(ModuleKind::Arm9, arm9.code()?, arm9.base_address())];
+        for autoload in autoloads.iter() {
+            if let AutoloadKind::Unknown(_) = autoload.kind() {
+                continue;
+            }
+            modules.push((ModuleKind::Autoload(autoload.kind()), autoload.code(), autoload.base_address()));
+        }
+        for overlay in rom.arm9_overlays() {
+            let base_address = config.overlay_base_address(overlay.id(), overlay.base_address());
+            modules.push((ModuleKind::Overlay(overlay.id()), overlay.code(), base_address));
+        }
+
+        let mut loads = vec![];
+        for (module_kind, code, base_address) in modules {
+            let Some(symbol_map) = symbol_maps.get(module_kind) else {
+                continue;
+            };
+            Self::find_loads_in_module(module_kind, symbol_map, code, base_address, endian, &loaders, &mut loads)?;
+        }
+        Ok(loads)
+    }
+
+    fn find_loads_in_module(
+        module_kind: ModuleKind,
+        symbol_map: &SymbolMap,
+        code: &[u8],
+        base_address: u32,
+        endian: Endian,
+        loaders: &BTreeMap<u32, OverlayLoadKind>,
+        loads: &mut Vec<ModuleOverlayLoad>,
+    ) -> Result<()> {
+        let end_address = base_address + code.len() as u32;
+        for (sym_function, symbol) in symbol_map.functions() {
+            if sym_function.unknown || symbol.addr < base_address || symbol.addr + sym_function.size > end_address {
+                continue;
+            }
+            let parse_result = Function::parse_function(FunctionParseOptions {
+                name: symbol.name.clone(),
+                start_address: symbol.addr,
+                base_address,
+                module_code: code,
+                known_end_address: Some(symbol.addr + sym_function.size),
+                module_start_address: base_address,
+                module_end_address: end_address,
+                parse_options: ParseFunctionOptions { thumb: sym_function.mode.into_thumb(), endian, ..Default::default() },
+            })?;
+            let ParseFunctionResult::Found(function) = parse_result else {
+                log::warn!("Function {} in {module_kind} could not be analyzed: {parse_result:?}", symbol.name);
+                continue;
+            };
+
+            for load in overlay_loads::find_overlay_loads(&function, code, base_address, endian, loaders) {
+                loads.push(ModuleOverlayLoad { module_kind, function_name: symbol.name.clone(), load });
+            }
+        }
+        Ok(())
+    }
+
+    fn print_loads(loads: &[ModuleOverlayLoad]) {
+        for ModuleOverlayLoad { module_kind, function_name, load } in loads {
+            let kind = match load.kind {
+                OverlayLoadKind::Load => "loads",
+                OverlayLoadKind::Unload => "unloads",
+            };
+            match load.overlay_id {
+                Some(id) => println!("{module_kind} {kind} overlay {id} in {function_name} at {:#010x}", load.address),
+                None => println!("{module_kind} {kind} unknown overlay in {function_name} at {:#010x}", load.address),
+            }
+        }
+    }
+
+    fn print_dot(loads: &[ModuleOverlayLoad]) {
+        let edges = loads
+            .iter()
+            .filter_map(|ModuleOverlayLoad { module_kind, load, .. }| {
+                load.overlay_id.map(|id| (*module_kind, ModuleKind::Overlay(id), load.kind))
+            })
+            .collect::<BTreeSet<_>>();
+
+        let num_unknown = loads.iter().filter(|load| load.load.overlay_id.is_none()).count();
+        if num_unknown > 0 {
+            log::warn!("{num_unknown} overlay loader call(s) with unknown overlay ID left out of the graph");
+        }
+
+        println!("digraph overlays {{");
+        for (from, to, kind) in edges {
+            let style = match kind {
+                OverlayLoadKind::Load => "solid",
+                OverlayLoadKind::Unload => "dashed",
+            };
+            println!("    \"{from}\" -> \"{to}\" [style={style}];");
+        }
+        println!("}}");
+    }
+}
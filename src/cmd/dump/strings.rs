@@ -0,0 +1,140 @@
+use std::{ops::Range, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+use serde::Serialize;
+
+use crate::{
+    analysis::strings::{find_strings, StringEncoding},
+    config::{
+        config::Config,
+        delinks::Delinks,
+        module::ModuleKind,
+        section::{SectionKind, Sections},
+        symbol::{SymbolMap, SymbolMaps},
+    },
+};
+
+/// Lists null-terminated strings in data sections and in code that isn't part of any function.
+#[derive(Args)]
+pub struct DumpStrings {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Only list strings in this module: main, itcm, dtcm or overlay:N.
+    #[arg(long, short = 'm')]
+    pub module: Option<ModuleKind>,
+
+    /// Minimum number of characters in a string.
+    #[arg(long, short = 'n', default_value_t = 4)]
+    pub min_len: usize,
+
+    /// Text encoding of the strings.
+    #[arg(long, short = 'e', value_enum, default_value_t = StringEncoding::Ascii)]
+    pub encoding: StringEncoding,
+
+    /// Output as JSON instead of a table.
+    #[arg(long, short = 'j')]
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct StringRow {
+    module: String,
+    address: u32,
+    text: String,
+}
+
+impl DumpStrings {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let arm9 = rom.arm9();
+        let autoloads = arm9.autoloads()?;
+
+        let mut modules = vec![(ModuleKind::Arm9, arm9.code()?, arm9.base_address())];
+        for autoload in autoloads.iter() {
+            if let AutoloadKind::Unknown(_) = autoload.kind() {
+                continue;
+            }
+            modules.push((ModuleKind::Autoload(autoload.kind()), autoload.code(), autoload.base_address()));
+        }
+        for overlay in rom.arm9_overlays() {
+            let base_address = config.overlay_base_address(overlay.id(), overlay.base_address());
+            modules.push((ModuleKind::Overlay(overlay.id()), overlay.code(), base_address));
+        }
+
+        let mut rows = vec![];
+        for (module_kind, module) in config.modules() {
+            if self.module.is_some_and(|module| module != module_kind) {
+                continue;
+            }
+            let (_, code, base_address) = modules
+                .iter()
+                .find(|(kind, _, _)| *kind == module_kind)
+                .with_context(|| format!("{module_kind} not found in ROM"))?;
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let delinks = Delinks::from_file(config_path.join(&module.delinks), module_kind)?;
+
+            for range in Self::string_ranges(&delinks.sections, symbol_map) {
+                let start = range.start.saturating_sub(*base_address) as usize;
+                let end = (range.end.saturating_sub(*base_address) as usize).min(code.len());
+                if start >= end {
+                    continue;
+                }
+                for string in find_strings(&code[start..end], range.start, self.min_len, self.encoding) {
+                    rows.push(StringRow { module: module_kind.to_string(), address: string.address, text: string.text });
+                }
+            }
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        } else {
+            println!("{:<12} {:<10} text", "module", "address");
+            for row in &rows {
+                println!("{:<12} {:#010x} {:?}", row.module, row.address, row.text);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the address ranges which may contain strings: all data sections, and the parts of code sections which
+    /// aren't covered by a function. The ranges are in ascending order.
+    pub fn string_ranges(sections: &Sections, symbol_map: &SymbolMap) -> Vec<Range<u32>> {
+        let mut ranges = vec![];
+        for section in sections.sorted_by_address() {
+            match section.kind() {
+                SectionKind::Data => ranges.push(section.address_range()),
+                SectionKind::Code => {
+                    let mut start = section.start_address();
+                    for (function, symbol) in symbol_map.functions() {
+                        if function.unknown || !section.address_range().contains(&symbol.addr) {
+                            continue;
+                        }
+                        if start < symbol.addr {
+                            ranges.push(start..symbol.addr);
+                        }
+                        start = start.max(symbol.addr + function.size);
+                    }
+                    if start < section.end_address() {
+                        ranges.push(start..section.end_address());
+                    }
+                }
+                SectionKind::Bss => {}
+            }
+        }
+        ranges
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use ds_rom::rom::raw::AutoloadKind;
+
+use crate::{
+    config::{config::Config, module::ModuleKind, symbol::SymbolMaps},
+    util::io::create_file,
+};
+
+/// Exports the symbols of every module to one file, annotating each symbol with its module.
+#[derive(Args)]
+pub struct ExportCombinedSymbols {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Path to the combined symbol file to write.
+    pub output_path: PathBuf,
+}
+
+impl ExportCombinedSymbols {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let mut symbols = vec![];
+        for (module_kind, _) in config.modules() {
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            symbols.extend(symbol_map.iter().map(|symbol| (module_kind, symbol)));
+        }
+        // Stable sort, so symbols at the same address stay in module order
+        symbols.sort_by_key(|(_, symbol)| symbol.addr);
+
+        let mut writer = BufWriter::new(create_file(&self.output_path)?);
+        for (module_kind, symbol) in symbols {
+            writeln!(writer, "{symbol} module:{}", module_annotation(module_kind))?;
+        }
+        writer.flush()?;
+
+        log::info!("Wrote combined symbols to '{}'", self.output_path.display());
+        Ok(())
+    }
+}
+
+/// Returns the module name accepted by `--module` options, like `main` or `overlay:5`.
+pub fn module_annotation(module_kind: ModuleKind) -> String {
+    match module_kind {
+        ModuleKind::Arm9 => "main".to_string(),
+        ModuleKind::Autoload(AutoloadKind::Itcm) => "itcm".to_string(),
+        ModuleKind::Autoload(AutoloadKind::Dtcm) => "dtcm".to_string(),
+        ModuleKind::Autoload(kind) => kind.to_string(),
+        ModuleKind::Overlay(id) => format!("overlay:{id}"),
+    }
+}
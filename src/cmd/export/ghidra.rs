@@ -0,0 +1,115 @@
+use std::{
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::{
+    config::{
+        config::{Config, ConfigModule},
+        module::ModuleKind,
+        symbol::{SymbolKind, SymbolMaps},
+    },
+    util::io::create_file,
+};
+
+/// Exports function and data symbols to a Ghidra Python script which names them.
+#[derive(Args)]
+pub struct ExportGhidra {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Path to the Ghidra script to write.
+    pub output_path: PathBuf,
+}
+
+const SCRIPT_BODY: &str = r#"
+def get_address(space, offset):
+    factory = currentProgram.getAddressFactory()
+    address_space = factory.getAddressSpace(space) if space else factory.getDefaultAddressSpace()
+    if address_space is None:
+        return None
+    return address_space.getAddress(offset)
+
+context = currentProgram.getProgramContext()
+t_mode = context.getRegister("TMode")
+for space, offset, name, kind in SYMBOLS:
+    address = get_address(space, offset)
+    if address is None:
+        print("Skipping %s, address space %s not found" % (name, space))
+        continue
+    if kind == "data":
+        createLabel(address, name, True, SourceType.USER_DEFINED)
+        continue
+    if kind == "thumb" and t_mode is not None:
+        context.setValue(t_mode, address, address, BigInteger.ONE)
+    function = getFunctionAt(address)
+    if function is None:
+        disassemble(address)
+        function = createFunction(address, name)
+    if function is None:
+        createLabel(address, name, True, SourceType.USER_DEFINED)
+    else:
+        function.setName(name, SourceType.USER_DEFINED)
+"#;
+
+impl ExportGhidra {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let mut writer = BufWriter::new(create_file(&self.output_path)?);
+        writeln!(writer, "# Names functions and data exported from dsd. Run it from the Ghidra script manager.")?;
+        writeln!(writer, "#@category dsd")?;
+        writeln!(writer, "from java.math import BigInteger")?;
+        writeln!(writer, "from ghidra.program.model.symbol import SourceType")?;
+        writeln!(writer)?;
+        writeln!(writer, "SYMBOLS = [")?;
+        for (module_kind, module) in config.modules() {
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let space = ghidra_space_name(module_kind, module).unwrap_or_default();
+            for symbol in symbol_map.iter() {
+                let kind = match &symbol.kind {
+                    SymbolKind::Function(function) if function.mode.into_thumb() == Some(true) => "thumb",
+                    SymbolKind::Function(_) => "arm",
+                    SymbolKind::Data(_) | SymbolKind::Bss(_) => "data",
+                    _ => continue,
+                };
+                writeln!(writer, "    ({space:?}, {:#010x}, {:?}, {kind:?}),", symbol.addr, symbol.name)?;
+            }
+        }
+        writeln!(writer, "]")?;
+        write!(writer, "{SCRIPT_BODY}")?;
+        writer.flush()?;
+
+        log::info!("Wrote Ghidra script to '{}'", self.output_path.display());
+        Ok(())
+    }
+}
+
+/// Returns the name of the Ghidra overlay address space of a module, which is the module name for overlays. Other modules
+/// are in Ghidra's default address space, so they have no name.
+pub fn ghidra_space_name(module_kind: ModuleKind, module: &ConfigModule) -> Option<&str> {
+    match module_kind {
+        ModuleKind::Overlay(_) => Some(&module.name),
+        ModuleKind::Arm9 | ModuleKind::Autoload(_) => None,
+    }
+}
+
+/// Returns the overlay in Ghidra's overlay address space `space`. The space may be named after the module, like `ov005`,
+/// or end in the overlay ID, like `overlay_5`.
+pub fn ghidra_space_module(config: &Config, space: &str) -> Option<ModuleKind> {
+    let overlays = || config.modules().filter(|(module_kind, _)| matches!(module_kind, ModuleKind::Overlay(_)));
+    if let Some((module_kind, _)) = overlays().find(|(_, module)| module.name.eq_ignore_ascii_case(space)) {
+        return Some(module_kind);
+    }
+    let digits = space.trim_start_matches(|c: char| !c.is_ascii_digit());
+    let id: u16 = digits.parse().ok()?;
+    overlays().map(|(module_kind, _)| module_kind).find(|&module_kind| module_kind == ModuleKind::Overlay(id))
+}
@@ -0,0 +1,34 @@
+mod combined_symbols;
+mod ghidra;
+mod sym;
+
+pub use combined_symbols::*;
+pub use ghidra::*;
+pub use sym::*;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Subcommands for exporting config data to other tools.
+#[derive(Args)]
+pub struct ExportArgs {
+    #[command(subcommand)]
+    command: ExportCommand,
+}
+
+impl ExportArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            ExportCommand::CombinedSymbols(combined_symbols) => combined_symbols.run(),
+            ExportCommand::Ghidra(ghidra) => ghidra.run(),
+            ExportCommand::Sym(sym) => sym.run(),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    CombinedSymbols(ExportCombinedSymbols),
+    Ghidra(ExportGhidra),
+    Sym(ExportSym),
+}
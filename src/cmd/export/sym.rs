@@ -0,0 +1,87 @@
+use std::{
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+
+use crate::{
+    config::{
+        config::Config,
+        symbol::{InstructionMode, SymbolKind, SymbolMaps},
+    },
+    util::io::create_file,
+};
+
+/// Exports function and data symbols to a symbol file for debuggers and emulators.
+#[derive(Args)]
+pub struct ExportSym {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Format of the symbol file.
+    #[arg(long, short = 'f', value_enum, default_value_t = SymFormat::Nocash)]
+    pub format: SymFormat,
+
+    /// Path to the symbol file to write.
+    pub output_path: PathBuf,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum SymFormat {
+    /// no$gba `.sym` file, one `ADDRESS name` line per symbol.
+    Nocash,
+}
+
+impl ExportSym {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        // Symbols are already at their load address, so overlays sharing address space will list the same addresses
+        let mut symbols = vec![];
+        for (module_kind, _) in config.modules() {
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            symbols.extend(symbol_map.iter().filter_map(|symbol| {
+                let mode = match symbol.kind {
+                    SymbolKind::Function(function) => Some(function.mode),
+                    SymbolKind::Data(_) | SymbolKind::Bss(_) => None,
+                    _ => return None,
+                };
+                Some((symbol.addr, mode, symbol.name.as_str()))
+            }));
+        }
+        // Stable sort, so symbols at the same address stay in module order
+        symbols.sort_by_key(|&(address, _, _)| address);
+
+        let mut writer = BufWriter::new(create_file(&self.output_path)?);
+        match self.format {
+            SymFormat::Nocash => {
+                for (address, mode, name) in symbols {
+                    // Functions are preceded by a mode marker, and Thumb functions have the Thumb bit set
+                    let address = match mode {
+                        Some(InstructionMode::Thumb) => {
+                            writeln!(writer, "{address:08X} .thumb")?;
+                            address | 1
+                        }
+                        Some(_) => {
+                            writeln!(writer, "{address:08X} .arm")?;
+                            address
+                        }
+                        None => address,
+                    };
+                    writeln!(writer, "{address:08X} {name}")?;
+                }
+            }
+        }
+        writer.flush()?;
+
+        log::info!("Wrote symbols to '{}'", self.output_path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::BTreeSet, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
+
+use crate::{
+    config::{
+        config::Config,
+        delinks::{DelinkFile, Delinks},
+        module::ModuleKind,
+        relocation::Relocations,
+        section::{SectionKind, Sections},
+        symbol::{SymBss, SymbolKind, SymbolMap, SymbolMaps},
+    },
+    util::backup::write_with_backups,
+};
+
+/// Changes auto-named data symbols at the end of .data which are all zeros to bss symbols, moving the start of .bss. This
+/// shrinks the module binary, so the built ROM no longer matches byte for byte.
+#[derive(Args)]
+pub struct FixDataToBss {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Dry run, do not write any files.
+    #[arg(long, short = 'd')]
+    pub dry: bool,
+
+    /// Don't keep a backup of changed symbol and delinks files in `.dsd_backups`.
+    #[arg(long)]
+    pub no_backup: bool,
+
+    /// Change the symbols even though the built modules will no longer match the ROM. Without this, the symbols which
+    /// would be changed are only logged.
+    #[arg(long, short = 'f')]
+    pub force: bool,
+}
+
+impl FixDataToBss {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let rom = Rom::load(
+            config_path.join(&config.rom_config),
+            RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
+        )?;
+        let mut symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        let arm9 = rom.arm9();
+        let autoloads = arm9.autoloads()?;
+
+        let mut modules = vec![(ModuleKind::Arm9, arm9.code()?, arm9.base_address())];
+        for autoload in autoloads.iter() {
+            if let AutoloadKind::Unknown(_) = autoload.kind() {
+                continue;
+            }
+            modules.push((ModuleKind::Autoload(autoload.kind()), autoload.code(), autoload.base_address()));
+        }
+        for overlay in rom.arm9_overlays() {
+            let base_address = config.overlay_base_address(overlay.id(), overlay.base_address());
+            modules.push((ModuleKind::Overlay(overlay.id()), overlay.code(), base_address));
+        }
+
+        // Symbols which are referenced must keep their kind
+        let mut relocation_targets = BTreeSet::new();
+        let mut module_relocations = vec![];
+        for (_, module) in config.modules() {
+            let relocations = Relocations::from_file(config_path.join(&module.relocations), &symbol_maps)?;
+            for relocation in relocations.iter() {
+                let to_modules = relocation
+                    .module()
+                    .first_module()
+                    .into_iter()
+                    .chain(relocation.module().other_modules().into_iter().flatten());
+                for to_module in to_modules {
+                    relocation_targets.insert((to_module, relocation.to_address()));
+                }
+            }
+            module_relocations.push(relocations);
+        }
+
+        for ((module_kind, module), relocations) in config.modules().zip(module_relocations.iter()) {
+            let (_, code, base_address) = modules
+                .iter()
+                .find(|(kind, _, _)| *kind == module_kind)
+                .with_context(|| format!("{module_kind} not found in ROM"))?;
+            let delinks_path = config_path.join(&module.delinks);
+            let mut delinks = Delinks::from_file(&delinks_path, module_kind)?;
+            let data_prefix = config.name_prefixes.data_prefix(module_kind);
+            let symbol_map = symbol_maps.get_mut(module_kind);
+
+            let converted = Self::data_to_bss(
+                symbol_map,
+                &mut delinks.sections,
+                &delinks.files,
+                code,
+                *base_address,
+                module_kind,
+                &relocation_targets,
+                relocations,
+                &data_prefix,
+                self.force,
+            )?;
+            if converted == 0 || self.dry {
+                continue;
+            }
+            let symbols_path = config_path.join(&module.symbols);
+            let write = || {
+                symbol_map.to_file(&symbols_path)?;
+                delinks.write_to_file(&delinks_path)
+            };
+            if self.no_backup {
+                write()?;
+            } else {
+                write_with_backups([&symbols_path, &delinks_path], write)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Changes the data symbols at the end of the `.data` section of a module to bss symbols of the same size, if their
+    /// bytes in `code` are all zeros, and moves the start of the `.bss` section which follows `.data` to the first changed
+    /// symbol. Symbols which were named by the user, are the target of a relocation or contain the source of a relocation
+    /// are kept, along with every symbol before them. Nothing is changed if a file in `files` has `.data` past the new
+    /// boundary. Returns the number of changed symbols.
+    ///
+    /// Moving zeroed data to .bss removes it from the module binary, which then no longer matches the ROM byte for byte.
+    /// Unless `force` is set, the symbols which would be changed are only logged and nothing is changed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn data_to_bss(
+        symbol_map: &mut SymbolMap,
+        sections: &mut Sections,
+        files: &[DelinkFile],
+        code: &[u8],
+        base_address: u32,
+        module_kind: ModuleKind,
+        relocation_targets: &BTreeSet<(ModuleKind, u32)>,
+        relocations: &Relocations,
+        data_prefix: &str,
+        force: bool,
+    ) -> Result<usize> {
+        let Some(data_index) = (0..sections.len()).find(|&index| {
+            let section = sections.get(index);
+            section.kind() == SectionKind::Data && section.name() == ".data"
+        }) else {
+            return Ok(0);
+        };
+        let data = sections.get(data_index);
+        let Some(bss_index) = (0..sections.len()).find(|&index| {
+            let section = sections.get(index);
+            section.kind() == SectionKind::Bss && section.start_address() == data.end_address()
+        }) else {
+            log::debug!("No .bss section follows .data in {module_kind}, so no data symbols can be changed to bss");
+            return Ok(0);
+        };
+        let bss = sections.get(bss_index);
+        let Some(section_code) = data.code(code, base_address)? else { return Ok(0) };
+
+        // Symbols can only become bss by moving the start of .bss before them, so stop at the last symbol which stays data
+        let mut boundary = data.end_address();
+        let mut converted = vec![];
+        let symbols = symbol_map.iter_by_address(data.address_range()).collect::<Vec<_>>();
+        for symbol in symbols.into_iter().rev() {
+            let SymbolKind::Data(sym_data) = &symbol.kind else { break };
+            let Some(size) = sym_data.size() else { break };
+            if symbol.ambiguous
+                || symbol.name != format!("{data_prefix}{:08x}", symbol.addr)
+                || symbol.addr + size > boundary
+                || relocation_targets.contains(&(module_kind, symbol.addr))
+                || relocations.iter_range(symbol.addr..boundary).next().is_some()
+                || symbol_map.for_address(symbol.addr).is_some_and(|mut symbols| symbols.nth(1).is_some())
+            {
+                break;
+            }
+
+            // Padding after the symbol moves along with it
+            let start = (symbol.addr - data.start_address()) as usize;
+            let end = (boundary - data.start_address()) as usize;
+            if !section_code[start..end].iter().all(|&byte| byte == 0) {
+                break;
+            }
+            converted.push((symbol.addr, size));
+            boundary = symbol.addr;
+        }
+        while boundary % bss.alignment() != 0 {
+            converted.pop();
+            boundary = converted.last().map_or(data.end_address(), |&(address, _)| address);
+        }
+        if converted.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(file) = files
+            .iter()
+            .filter(|file| !file.gap())
+            .find(|file| file.sections.by_name(data.name()).is_some_and(|file_section| file_section.end_address() > boundary))
+        {
+            log::warn!(
+                "Can't change zeroed data symbols from {boundary:#010x} in {module_kind} to bss, since they are in .data of \
+                file '{}'. Please move them to .bss of the file manually.",
+                file.name
+            );
+            return Ok(0);
+        }
+
+        if !force {
+            log::warn!(
+                "Not changing {} zeroed data symbols from {boundary:#010x} in {module_kind} to bss, since the module would no \
+                longer match the ROM byte for byte. Use --force to change them anyway.",
+                converted.len()
+            );
+            return Ok(0);
+        }
+        log::warn!(
+            "Changing {} zeroed data symbols to bss in {module_kind}, moving .bss from {:#010x} to {boundary:#010x}. The \
+            module will no longer match the ROM byte for byte.",
+            converted.len(),
+            bss.start_address()
+        );
+        let (data_start, bss_end) = (data.start_address(), bss.end_address());
+        for &(address, size) in &converted {
+            log::debug!("{data_prefix}{address:08x} in {module_kind} is now bss(size={size:#x})");
+            symbol_map.set_kind_by_address(address, SymbolKind::Bss(SymBss { size: Some(size) }))?;
+        }
+        sections.get_mut(data_index).set_range(data_start, boundary)?;
+        sections.get_mut(bss_index).set_range(boundary, bss_end)?;
+        Ok(converted.len())
+    }
+}
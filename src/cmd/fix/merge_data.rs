@@ -0,0 +1,169 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::{
+    config::{
+        config::Config,
+        delinks::Delinks,
+        module::ModuleKind,
+        relocation::Relocations,
+        section::SectionKind,
+        symbol::{SymData, SymbolKind, SymbolMap, SymbolMaps},
+    },
+    util::backup::write_with_backups,
+};
+
+/// Merges runs of adjacent auto-named data symbols of the same type in symbols.txt into arrays.
+#[derive(Args)]
+pub struct FixMergeData {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Dry run, do not write any files.
+    #[arg(long, short = 'd')]
+    pub dry: bool,
+
+    /// Don't keep a backup of changed symbol files in `.dsd_backups`.
+    #[arg(long)]
+    pub no_backup: bool,
+}
+
+impl FixMergeData {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        // Symbols which are referenced must keep their address and name
+        let mut relocation_targets = BTreeSet::new();
+        for (_, module) in config.modules() {
+            let relocations = Relocations::from_file(config_path.join(&module.relocations), &symbol_maps)?;
+            for relocation in relocations.iter() {
+                let to_modules = relocation
+                    .module()
+                    .first_module()
+                    .into_iter()
+                    .chain(relocation.module().other_modules().into_iter().flatten());
+                for to_module in to_modules {
+                    relocation_targets.insert((to_module, relocation.to_address()));
+                }
+            }
+        }
+
+        for (module_kind, module) in config.modules() {
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let delinks = Delinks::from_file(config_path.join(&module.delinks), module_kind)?;
+            let data_prefix = config.name_prefixes.data_prefix(module_kind);
+
+            let Some(symbol_map) = Self::merge_data(symbol_map, &delinks, module_kind, &relocation_targets, &data_prefix)
+            else {
+                continue;
+            };
+
+            if self.dry {
+                continue;
+            }
+            let symbols_path = config_path.join(&module.symbols);
+            let write = || symbol_map.to_file(&symbols_path);
+            if self.no_backup {
+                write()?;
+            } else {
+                write_with_backups([&symbols_path], write)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a new symbol map with the merged symbols, or `None` if nothing was merged.
+    fn merge_data(
+        symbol_map: &SymbolMap,
+        delinks: &Delinks,
+        module_kind: ModuleKind,
+        relocation_targets: &BTreeSet<(ModuleKind, u32)>,
+        data_prefix: &str,
+    ) -> Option<SymbolMap> {
+        // Symbols must not be merged across files
+        let file_boundaries = delinks
+            .files
+            .iter()
+            .flat_map(|file| file.sections.iter().map(|section| section.start_address()))
+            .collect::<BTreeSet<_>>();
+
+        let mergeable = |address: u32| -> Option<SymData> {
+            let mut symbols = symbol_map.for_address(address)?;
+            let (_, symbol) = symbols.next()?;
+            if symbols.next().is_some() || symbol.ambiguous || symbol.name != format!("{data_prefix}{address:08x}") {
+                return None;
+            }
+            match &symbol.kind {
+                SymbolKind::Data(data) if data.count().is_some() => Some(data.clone()),
+                _ => None,
+            }
+        };
+
+        // Maps address of first symbol in a run to the merged data type
+        let mut merged = BTreeMap::new();
+        let mut removed = BTreeSet::new();
+        for section in delinks.sections.iter().filter(|section| section.kind() == SectionKind::Data) {
+            let mut run: Option<(u32, SymData)> = None;
+            for symbol in symbol_map.iter_by_address(section.address_range()) {
+                let data = mergeable(symbol.addr);
+                if let (Some((start, run_data)), Some(data)) = (&mut run, &data) {
+                    let end = *start + run_data.size().unwrap();
+                    if end == symbol.addr
+                        && !relocation_targets.contains(&(module_kind, symbol.addr))
+                        && !file_boundaries.contains(&symbol.addr)
+                    {
+                        if let Some(combined) = Self::combine(run_data, data) {
+                            merged.insert(*start, combined.clone());
+                            *run_data = combined;
+                            removed.insert(symbol.addr);
+                            continue;
+                        }
+                    }
+                }
+                run = data.map(|data| (symbol.addr, data));
+            }
+        }
+
+        if removed.is_empty() {
+            return None;
+        }
+        log::info!("Merging {} data symbols into {} arrays in {module_kind}", removed.len() + merged.len(), merged.len());
+
+        let symbols = symbol_map
+            .iter()
+            .filter(|symbol| !removed.contains(&symbol.addr))
+            .map(|symbol| {
+                let mut symbol = symbol.clone();
+                if let Some(data) = merged.get(&symbol.addr) {
+                    log::debug!("{} in {module_kind} is now {data}", symbol.name);
+                    symbol.kind = SymbolKind::Data(data.clone());
+                }
+                symbol
+            })
+            .collect();
+        Some(SymbolMap::from_symbols(symbols))
+    }
+
+    /// Combines two data types into one array, if they have the same element type.
+    fn combine(a: &SymData, b: &SymData) -> Option<SymData> {
+        let count = Some(a.count()? + b.count()?);
+        match (a, b) {
+            (SymData::Byte { .. }, SymData::Byte { .. }) => Some(SymData::Byte { count }),
+            (SymData::Short { .. }, SymData::Short { .. }) => Some(SymData::Short { count }),
+            (SymData::Word { .. }, SymData::Word { .. }) => Some(SymData::Word { count }),
+            (SymData::Pointer { .. }, SymData::Pointer { .. }) => Some(SymData::Pointer { count }),
+            _ => None,
+        }
+    }
+}
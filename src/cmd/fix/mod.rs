@@ -0,0 +1,34 @@
+mod data_to_bss;
+mod merge_data;
+mod sections;
+
+pub use data_to_bss::*;
+pub use merge_data::*;
+pub use sections::*;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Subcommands for fixing up config files after manual edits.
+#[derive(Args)]
+pub struct FixArgs {
+    #[command(subcommand)]
+    command: FixCommand,
+}
+
+impl FixArgs {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            FixCommand::Sections(sections) => sections.run(),
+            FixCommand::MergeData(merge_data) => merge_data.run(),
+            FixCommand::DataToBss(data_to_bss) => data_to_bss.run(),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum FixCommand {
+    Sections(FixSections),
+    MergeData(FixMergeData),
+    DataToBss(FixDataToBss),
+}
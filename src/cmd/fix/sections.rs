@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::{
+    config::{
+        config::Config,
+        delinks::Delinks,
+        module::ModuleKind,
+        section::{SectionKind, Sections},
+        symbol::{SymbolKind, SymbolMap, SymbolMaps},
+    },
+    util::backup::write_with_backups,
+};
+
+/// Moves section boundaries in delinks.txt to match the functions and data in symbols.txt.
+#[derive(Args)]
+pub struct FixSections {
+    /// Path to config.yaml.
+    #[arg(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// Dry run, do not write any files.
+    #[arg(long, short = 'd')]
+    pub dry: bool,
+
+    /// Don't keep a backup of changed delinks files in `.dsd_backups`.
+    #[arg(long)]
+    pub no_backup: bool,
+}
+
+impl FixSections {
+    pub fn run(&self) -> Result<()> {
+        let config = Config::from_file(&self.config_path)?;
+        let config_path = self.config_path.parent().unwrap();
+
+        let symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+
+        for (module_kind, module) in config.modules() {
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("Symbol map not found for {module_kind}"))?;
+            let delinks_path = config_path.join(&module.delinks);
+            let mut delinks = Delinks::from_file(&delinks_path, module_kind)?;
+
+            if !Self::fix_sections(&mut delinks.sections, symbol_map, module_kind)? {
+                continue;
+            }
+            Self::warn_files_outside_sections(&delinks, module_kind);
+
+            if self.dry {
+                continue;
+            }
+            let write = || delinks.write_to_file(&delinks_path);
+            if self.no_backup {
+                write()?;
+            } else {
+                write_with_backups([&delinks_path], write)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves the boundaries between adjacent code and data sections. Returns true if any boundary was moved.
+    fn fix_sections(sections: &mut Sections, symbol_map: &SymbolMap, module_kind: ModuleKind) -> Result<bool> {
+        let mut indices = (0..sections.len()).collect::<Vec<_>>();
+        indices.sort_unstable_by_key(|&index| sections.get(index).start_address());
+
+        let mut changed = false;
+        for pair in indices.windows(2) {
+            let (prev, next) = (sections.get(pair[0]), sections.get(pair[1]));
+            if prev.end_address() != next.start_address() {
+                continue;
+            }
+            let range = prev.start_address()..next.end_address();
+
+            let boundary = match (prev.kind(), next.kind()) {
+                (SectionKind::Code, SectionKind::Data) => {
+                    // Code ends after the last function
+                    let Some(code_end) = symbol_map
+                        .functions()
+                        .filter(|(function, symbol)| !function.unknown && range.contains(&symbol.addr))
+                        .map(|(function, symbol)| symbol.addr + function.size)
+                        .max()
+                    else {
+                        continue;
+                    };
+                    code_end.next_multiple_of(next.alignment())
+                }
+                (SectionKind::Data, SectionKind::Code) => {
+                    // Code starts at the first function
+                    let Some(code_start) = symbol_map
+                        .functions()
+                        .filter(|(_, symbol)| range.contains(&symbol.addr))
+                        .map(|(_, symbol)| symbol.addr)
+                        .min()
+                    else {
+                        continue;
+                    };
+                    if code_start % next.alignment() != 0 {
+                        log::warn!(
+                            "Can't move start of {} in {module_kind} to {code_start:#010x} as it is not aligned to {}",
+                            next.name(),
+                            next.alignment()
+                        );
+                        continue;
+                    }
+                    code_start
+                }
+                _ => continue,
+            };
+            if boundary == next.start_address() {
+                continue;
+            }
+            if !range.contains(&boundary) {
+                log::warn!(
+                    "Can't move boundary between {} and {} in {module_kind} to {boundary:#010x} as it is outside {:#010x}..{:#010x}",
+                    prev.name(),
+                    next.name(),
+                    range.start,
+                    range.end
+                );
+                continue;
+            }
+
+            for symbol in symbol_map.iter_by_address(range.clone()) {
+                let size = match &symbol.kind {
+                    SymbolKind::Function(function) => function.size,
+                    SymbolKind::Data(data) => data.size().unwrap_or(0),
+                    _ => continue,
+                };
+                if symbol.addr < boundary && symbol.addr + size > boundary {
+                    log::warn!(
+                        "Symbol {} ({:#010x}..{:#010x}) straddles the new boundary between {} and {} at {boundary:#010x}",
+                        symbol.name,
+                        symbol.addr,
+                        symbol.addr + size,
+                        prev.name(),
+                        next.name()
+                    );
+                }
+            }
+
+            log::info!(
+                "Moving boundary between {} and {} in {module_kind} from {:#010x} to {boundary:#010x}",
+                prev.name(),
+                next.name(),
+                next.start_address()
+            );
+            let (prev_start, next_end) = (prev.start_address(), next.end_address());
+            sections.get_mut(pair[0]).set_range(prev_start, boundary)?;
+            sections.get_mut(pair[1]).set_range(boundary, next_end)?;
+            changed = true;
+        }
+
+        Ok(changed)
+    }
+
+    fn warn_files_outside_sections(delinks: &Delinks, module_kind: ModuleKind) {
+        for file in delinks.files.iter().filter(|file| !file.gap()) {
+            for file_section in file.sections.iter() {
+                let Some(section) = delinks.sections.by_name(file_section.name()) else { continue };
+                if file_section.start_address() < section.start_address() || file_section.end_address() > section.end_address()
+                {
+                    log::warn!(
+                        "{} of file '{}' in {module_kind} is no longer contained in the module's {}, please adjust it manually",
+                        file_section.name(),
+                        file.name,
+                        section.name()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 mod symbols;
 
-use symbols::*;
+pub use symbols::*;
 
 use anyhow::Result;
 use clap::{Args, Subcommand};
@@ -1,49 +1,144 @@
-use std::{borrow::Cow, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    io::{BufRead, BufReader},
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use clap::Args;
-use ds_rom::rom::raw::AutoloadKind;
 use object::{Object, ObjectSection, ObjectSymbol};
 
 use crate::{
-    config::{config::Config, module::ModuleKind, symbol::SymbolMaps},
-    util::io::{open_file, read_file},
+    cmd::ghidra_space_module,
+    config::{
+        config::Config,
+        delinks::Delinks,
+        module::ModuleKind,
+        symbol::{MergePolicy, SymData, SymFunction, SymbolKind, SymbolMap, SymbolMaps, SymbolRename},
+    },
+    util::{
+        backup::write_with_backups,
+        dwarf::{read_dwarf_symbols, DwarfSymbol, DwarfSymbolKind},
+        io::{open_file, read_file},
+    },
 };
 
-/// Imports symbol names from a previously built ELF file.
+/// Imports symbol names from a previously built ELF file, a no$gba `.sym` file, DWARF debug info, a Ghidra symbol table or
+/// the symbol maps of another copy of the project.
 #[derive(Args, Clone)]
 pub struct ImportSymbols {
     /// Path to config.yaml.
     #[arg(long, short = 'c')]
-    config_path: PathBuf,
+    pub config_path: PathBuf,
 
     /// Path to built/linked ELF file.
-    #[arg(long, short = 'x')]
-    elf_path: PathBuf,
+    #[arg(
+        long,
+        short = 'x',
+        required_unless_present_any = ["sym_path", "dwarf_path", "ghidra_csv_path", "map_config_path"],
+        conflicts_with_all = ["sym_path", "dwarf_path", "ghidra_csv_path", "map_config_path"]
+    )]
+    pub elf_path: Option<PathBuf>,
+
+    /// Path to no$gba `.sym` file. Addresses without a symbol become functions.
+    #[arg(long = "sym", short = 's', conflicts_with_all = ["dwarf_path", "ghidra_csv_path", "map_config_path"])]
+    pub sym_path: Option<PathBuf>,
+
+    /// Path to ELF file with DWARF debug info. Imports names, function sizes and data types of variables. Falls back to
+    /// the symbol table if there is no DWARF.
+    #[arg(long = "from-dwarf", conflicts_with_all = ["ghidra_csv_path", "map_config_path"])]
+    pub dwarf_path: Option<PathBuf>,
+
+    /// Path to a symbol table exported from Ghidra as CSV. Renames functions and labels at addresses which have a symbol.
+    #[arg(long = "ghidra-csv", conflicts_with = "map_config_path")]
+    pub ghidra_csv_path: Option<PathBuf>,
+
+    /// Path to config.yaml of another copy of the project. Its symbols are merged into this project's symbol maps, adding
+    /// missing symbols and resolving different names at the same address by `--merge-policy`.
+    #[arg(long = "map")]
+    pub map_config_path: Option<PathBuf>,
+
+    /// How to resolve symbols with different names at the same address when merging with `--map`.
+    #[arg(long, value_enum, default_value_t = MergePolicy::PreferNamed)]
+    pub merge_policy: MergePolicy,
 
     /// Includes symbols with default names like `func_ov12_0211514c`.
     #[arg(long, short = 'D')]
-    include_default_names: bool,
+    pub include_default_names: bool,
 
-    /// Dry run, do not write any files.
-    #[arg(long, short = 'd')]
-    dry: bool,
+    /// Dry run, do not write any files but list the symbols which would be renamed.
+    #[arg(long, short = 'd', visible_alias = "dry-run")]
+    pub dry: bool,
+
+    /// Don't keep a backup of changed symbol files in `.dsd_backups`.
+    #[arg(long)]
+    pub no_backup: bool,
 }
 
 impl ImportSymbols {
     pub fn run(&self) -> Result<()> {
-        let config: Config = serde_yml::from_reader(open_file(&self.config_path)?)?;
+        let renames = self.import()?;
+        if self.dry {
+            for rename in &renames {
+                log::info!("{}: {} -> {} at {:#010x}", rename.module_kind, rename.old_name, rename.new_name, rename.address);
+            }
+            log::info!("Dry run, {} symbol(s) would be renamed", renames.len());
+        } else {
+            log::info!("Renamed {} symbol(s)", renames.len());
+        }
+        Ok(())
+    }
+
+    /// Renames symbols to match the ELF, `.sym` file, DWARF, Ghidra symbol table or other symbol maps, and returns the
+    /// renamed symbols. Nothing is written in a dry run.
+    pub fn import(&self) -> Result<Vec<SymbolRename>> {
+        let config = Config::from_file(&self.config_path)?;
         let config_path = self.config_path.parent().unwrap();
 
         let mut symbol_maps = SymbolMaps::from_config(config_path, &config)?;
+        let renames = match (&self.elf_path, &self.sym_path, &self.dwarf_path, &self.ghidra_csv_path, &self.map_config_path) {
+            (Some(elf_path), _, _, _, _) => self.import_elf(elf_path, &config, &mut symbol_maps)?,
+            (None, Some(sym_path), _, _, _) => self.import_sym(sym_path, &config, &mut symbol_maps)?,
+            (None, None, Some(dwarf_path), _, _) => self.import_dwarf(dwarf_path, &config, &mut symbol_maps)?,
+            (None, None, None, Some(csv_path), _) => self.import_ghidra_csv(csv_path, &config, &mut symbol_maps)?,
+            (None, None, None, None, Some(map_config_path)) => self.import_map(map_config_path, &config, &mut symbol_maps)?,
+            (None, None, None, None, None) => {
+                bail!("Either an ELF file, a .sym file, an ELF file with DWARF, a Ghidra CSV file or a config must be given")
+            }
+        };
+
+        if !self.dry {
+            let write = || symbol_maps.to_files(&config, config_path);
+            if self.no_backup {
+                write()?;
+            } else {
+                write_with_backups(config.modules().map(|(_, module)| config_path.join(&module.symbols)), write)?;
+            }
+        }
+
+        Ok(renames)
+    }
 
-        let file = read_file(&self.elf_path)?;
+    fn import_elf(&self, elf_path: &Path, config: &Config, symbol_maps: &mut SymbolMaps) -> Result<Vec<SymbolRename>> {
+        let file = read_file(elf_path)?;
         let object = object::File::parse(&*file)?;
+        self.import_elf_symbols(&object, config, symbol_maps)
+    }
+
+    fn import_elf_symbols(
+        &self,
+        object: &object::File,
+        config: &Config,
+        symbol_maps: &mut SymbolMaps,
+    ) -> Result<Vec<SymbolRename>> {
+        let mut renames = vec![];
 
         for section in object.sections() {
             let section_name = section.name()?;
             log::debug!("Section: {section_name}");
-            let Some(module_kind) = self.parse_module_kind(section_name)? else { continue };
+            let Some(module_kind) = ModuleKind::from_elf_section_name(section_name)? else { continue };
             let symbol_map = symbol_maps.get_mut(module_kind);
             log::debug!("Module: {module_kind}");
             for symbol in object.symbols() {
@@ -63,13 +158,15 @@ impl ImportSymbols {
                     continue;
                 }
 
-                let is_default_name = name.starts_with("func_") || name.starts_with("data_");
+                // Older default names of overlay symbols weren't zero-padded, like `func_ov12_0211514c`
+                let padded_name = self.pad_default_symbol(name)?;
+                let is_default_name = config.name_prefixes.is_default_name(&padded_name, module_kind);
 
                 let name = if is_default_name {
                     if !self.include_default_names {
                         continue;
                     } else {
-                        self.pad_default_symbol(name)?
+                        padded_name
                     }
                 } else {
                     name.into()
@@ -80,34 +177,293 @@ impl ImportSymbols {
                     continue;
                 }
 
+                let old_name = symbol_map.by_address(address).ok().flatten().map(|(_, symbol)| symbol.name.clone());
                 let result = symbol_map.rename_by_address(address, &name);
                 if !is_default_name {
                     log::debug!("{address:#x} {name}");
                     result?;
+                } else if result.is_err() {
+                    continue;
+                }
+                if let Some(old_name) = old_name.filter(|old_name| *old_name != name) {
+                    renames.push(SymbolRename { module_kind, address, old_name, new_name: name.into_owned() });
                 }
             }
         }
 
-        if !self.dry {
-            symbol_maps.to_files(&config, config_path)?;
+        Ok(renames)
+    }
+
+    /// Imports functions and variables from DWARF debug info. Each one is applied to whichever module has a symbol at its
+    /// address. Function sizes are taken from the debug info, and data symbols of unknown type get the variable's type if
+    /// it's a byte, short or word array.
+    fn import_dwarf(&self, dwarf_path: &Path, config: &Config, symbol_maps: &mut SymbolMaps) -> Result<Vec<SymbolRename>> {
+        let file = read_file(dwarf_path)?;
+        let object = object::File::parse(&*file)?;
+        let Some(dwarf_symbols) = read_dwarf_symbols(&object)? else {
+            log::warn!("No DWARF debug info found in {}, importing the symbol table instead", dwarf_path.display());
+            return self.import_elf_symbols(&object, config, symbol_maps);
+        };
+
+        let mut renames = vec![];
+        let mut num_resized = 0;
+        for dwarf_symbol in dwarf_symbols {
+            let DwarfSymbol { name, address, kind } = dwarf_symbol;
+
+            let existing = config
+                .modules()
+                .filter(|&(module_kind, _)| {
+                    symbol_maps.get(module_kind).is_some_and(|symbol_map| symbol_map.for_address(address).is_some())
+                })
+                .map(|(module_kind, _)| module_kind)
+                .collect::<Vec<_>>();
+            let module_kind = match existing.as_slice() {
+                [] => {
+                    log::debug!("Skipping '{name}' at {address:#010x}, no symbol there");
+                    continue;
+                }
+                &[module_kind] => module_kind,
+                _ => {
+                    log::warn!("Skipping '{name}' at {address:#010x}, found symbols there in {} modules", existing.len());
+                    continue;
+                }
+            };
+            let symbol_map = symbol_maps.get_mut(module_kind);
+            let Ok(Some((_, symbol))) = symbol_map.by_address(address) else {
+                log::warn!("Skipping '{name}' at {address:#010x}, found multiple symbols there");
+                continue;
+            };
+
+            let new_kind = match (&symbol.kind, kind) {
+                (SymbolKind::Function(function), DwarfSymbolKind::Function { size }) if function.size != size => {
+                    Some(SymbolKind::Function(SymFunction { size, ..*function }))
+                }
+                (SymbolKind::Data(SymData::Any), DwarfSymbolKind::Variable { ty: Some(ty) }) => {
+                    let count = Some(ty.count);
+                    match ty.element_size {
+                        1 => Some(SymbolKind::Data(SymData::Byte { count })),
+                        2 => Some(SymbolKind::Data(SymData::Short { count })),
+                        4 => Some(SymbolKind::Data(SymData::Word { count })),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            let old_name = symbol.name.clone();
+
+            if let Some(new_kind) = new_kind {
+                symbol_map.set_kind_by_address(address, new_kind)?;
+                num_resized += 1;
+            }
+            let is_default_name = config.name_prefixes.is_default_name(&name, module_kind);
+            if old_name != name && (self.include_default_names || !is_default_name) {
+                symbol_map.rename_by_address(address, &name)?;
+                renames.push(SymbolRename { module_kind, address, old_name, new_name: name });
+            }
         }
 
-        Ok(())
+        if num_resized > 0 {
+            log::info!("Changed the size or type of {num_resized} symbol(s)");
+        }
+        Ok(renames)
     }
 
-    fn parse_module_kind(&self, s: &str) -> Result<Option<ModuleKind>> {
-        if s == "ARM9" {
-            Ok(Some(ModuleKind::Arm9))
-        } else if s == "ITCM" {
-            Ok(Some(ModuleKind::Autoload(AutoloadKind::Itcm)))
-        } else if s == "DTCM" {
-            Ok(Some(ModuleKind::Autoload(AutoloadKind::Dtcm)))
-        } else if let Some(overlay_number) = s.strip_prefix("ov") {
-            let overlay_id = overlay_number.parse()?;
-            Ok(Some(ModuleKind::Overlay(overlay_id)))
-        } else {
-            Ok(None)
+    /// Imports `ADDRESS name` lines of a no$gba `.sym` file. The address is renamed in whichever module has a symbol
+    /// there, or else added as a function to the module containing it. New functions are Thumb if the address has the Thumb
+    /// bit set or is marked with `.thumb`, otherwise ARM.
+    fn import_sym(&self, sym_path: &Path, config: &Config, symbol_maps: &mut SymbolMaps) -> Result<Vec<SymbolRename>> {
+        let module_ranges = self.module_ranges(config)?;
+
+        let mut entries = vec![];
+        let mut thumb_markers = BTreeSet::new();
+        let reader = BufReader::new(open_file(sym_path)?);
+        for (row, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.split(';').next().unwrap().trim();
+            let Some((address, name)) = line.split_once(char::is_whitespace) else { continue };
+            let name = name.trim();
+            let address = u32::from_str_radix(address, 16)
+                .with_context(|| format!("{}:{}: failed to parse address '{address}'", sym_path.display(), row + 1))?;
+            // Skip other no$gba directives like `.arm` and `.byt:0004`
+            if name == ".thumb" {
+                thumb_markers.insert(address & !1);
+            } else if !name.starts_with('.') {
+                entries.push((address, name.to_string()));
+            }
+        }
+
+        let mut renames = vec![];
+        let mut num_added = 0;
+        for (address, name) in entries {
+            let thumb = address & 1 != 0 || thumb_markers.contains(&(address & !1));
+            let address = address & !1;
+
+            let is_default_name =
+                config.modules().any(|(module_kind, _)| config.name_prefixes.is_default_name(&name, module_kind));
+            if is_default_name && !self.include_default_names {
+                continue;
+            }
+
+            let existing = config
+                .modules()
+                .filter(|&(module_kind, _)| {
+                    symbol_maps.get(module_kind).is_some_and(|symbol_map| symbol_map.for_address(address).is_some())
+                })
+                .map(|(module_kind, _)| module_kind)
+                .collect::<Vec<_>>();
+            match existing.as_slice() {
+                [] => {
+                    let containing = module_ranges
+                        .iter()
+                        .filter(|(_, range)| range.contains(&address))
+                        .map(|&(module_kind, _)| module_kind)
+                        .collect::<Vec<_>>();
+                    let [module_kind] = containing.as_slice() else {
+                        log::warn!("Skipping '{name}' at {address:#010x}, found {} modules containing it", containing.len());
+                        continue;
+                    };
+                    symbol_maps.get_mut(*module_kind).add_unknown_function(name, address, thumb);
+                    num_added += 1;
+                }
+                &[module_kind] => {
+                    let symbol_map = symbol_maps.get_mut(module_kind);
+                    let Ok(Some((_, symbol))) = symbol_map.by_address(address) else {
+                        log::warn!("Skipping '{name}' at {address:#010x}, found multiple symbols there");
+                        continue;
+                    };
+                    if symbol.name == name {
+                        continue;
+                    }
+                    let old_name = symbol.name.clone();
+                    symbol_map.rename_by_address(address, &name)?;
+                    renames.push(SymbolRename { module_kind, address, old_name, new_name: name });
+                }
+                _ => log::warn!("Skipping '{name}' at {address:#010x}, found symbols there in {} modules", existing.len()),
+            }
+        }
+
+        if num_added > 0 {
+            log::info!("Added {num_added} function(s) not in any symbols.txt");
         }
+        Ok(renames)
+    }
+
+    /// Imports the `Name` and `Location` columns of a symbol table exported from Ghidra. A location in an overlay address
+    /// space, like `ov005::0211514c`, is renamed in that overlay. A location in the default address space is renamed in the
+    /// main module or autoload containing it, or else in the only overlay containing it.
+    fn import_ghidra_csv(&self, csv_path: &Path, config: &Config, symbol_maps: &mut SymbolMaps) -> Result<Vec<SymbolRename>> {
+        let module_ranges = self.module_ranges(config)?;
+
+        let mut lines = BufReader::new(open_file(csv_path)?).lines();
+        let header = split_csv_line(&lines.next().context("Ghidra CSV file is empty")??);
+        let column = |name: &str| header.iter().position(|column| column == name);
+        let name_column = column("Name").context("Ghidra CSV file has no Name column")?;
+        let location_column = column("Location").context("Ghidra CSV file has no Location column")?;
+        let type_column = column("Type");
+        let source_column = column("Source");
+
+        let mut renames = vec![];
+        for line in lines {
+            let row = split_csv_line(&line?);
+            let field = |column: usize| row.get(column).map(|field| field.as_str()).unwrap_or_default();
+            if type_column.is_some_and(|column| !matches!(field(column), "Function" | "Label")) {
+                continue;
+            }
+            // Names generated by Ghidra, like FUN_02001234
+            if source_column.is_some_and(|column| field(column) == "Default") && !self.include_default_names {
+                continue;
+            }
+            let name = field(name_column);
+            let location = field(location_column);
+
+            let (space, address) = match location.split_once("::") {
+                Some((space, address)) => (Some(space), address),
+                None => (None, location),
+            };
+            let Ok(address) = u32::from_str_radix(address.trim_start_matches("0x"), 16) else {
+                log::debug!("Skipping '{name}' at unknown location '{location}'");
+                continue;
+            };
+
+            let module_kind = match space {
+                Some(space) => ghidra_space_module(config, space),
+                None => {
+                    let containing = |overlays: bool| {
+                        module_ranges
+                            .iter()
+                            .filter(|(module_kind, range)| {
+                                matches!(module_kind, ModuleKind::Overlay(_)) == overlays && range.contains(&address)
+                            })
+                            .map(|&(module_kind, _)| module_kind)
+                            .collect::<Vec<_>>()
+                    };
+                    match (containing(false).as_slice(), containing(true).as_slice()) {
+                        (&[module_kind], _) | ([], &[module_kind]) => Some(module_kind),
+                        _ => None,
+                    }
+                }
+            };
+            let Some(module_kind) = module_kind else {
+                log::warn!("Skipping '{name}' at '{location}', no single module contains it");
+                continue;
+            };
+
+            let symbol_map = symbol_maps.get_mut(module_kind);
+            let Ok(Some((_, symbol))) = symbol_map.by_address(address) else {
+                log::debug!("Skipping '{name}' at {address:#010x} in {module_kind}, no single symbol there");
+                continue;
+            };
+            if symbol.name == name {
+                continue;
+            }
+            let old_name = symbol.name.clone();
+            let is_default_name = config.name_prefixes.is_default_name(name, module_kind);
+            if is_default_name && !self.include_default_names {
+                continue;
+            }
+            symbol_map.rename_by_address(address, name)?;
+            renames.push(SymbolRename { module_kind, address, old_name, new_name: name.to_string() });
+        }
+
+        Ok(renames)
+    }
+
+    /// Merges the symbol maps of another copy of the project, see [`SymbolMaps::merge`]. Default names from the other
+    /// project are only added or taken with `--include-default-names`.
+    fn import_map(&self, map_config_path: &Path, config: &Config, symbol_maps: &mut SymbolMaps) -> Result<Vec<SymbolRename>> {
+        let map_config = Config::from_file(map_config_path)?;
+        let mut other = SymbolMaps::from_config(map_config_path.parent().unwrap(), &map_config)?;
+        if !self.include_default_names {
+            for (module_kind, _) in map_config.modules() {
+                let other_map = other.get_mut(module_kind);
+                let named = other_map
+                    .iter()
+                    .filter(|symbol| !config.name_prefixes.is_default_name(&symbol.name, module_kind))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                *other_map = SymbolMap::from_symbols(named);
+            }
+        }
+        symbol_maps.merge(
+            &other,
+            config.modules().map(|(module_kind, _)| module_kind),
+            self.merge_policy,
+            &config.name_prefixes,
+        )
+    }
+
+    /// Returns the address range of every module, from its delinks file.
+    fn module_ranges(&self, config: &Config) -> Result<Vec<(ModuleKind, Range<u32>)>> {
+        let config_path = self.config_path.parent().unwrap();
+
+        let mut module_ranges = vec![];
+        for (module_kind, module) in config.modules() {
+            let delinks = Delinks::from_file(config_path.join(&module.delinks), module_kind)?;
+            if let (Some(start), Some(end)) = (delinks.sections.base_address(), delinks.sections.end_address()) {
+                module_ranges.push((module_kind, start..end));
+            }
+        }
+        Ok(module_ranges)
     }
 
     fn pad_default_symbol<'a>(&self, name: &'a str) -> Result<Cow<'a, str>> {
@@ -133,3 +489,24 @@ impl ImportSymbols {
         Ok(format!("{prefix}_ov{overlay_id:03}_{suffix}").into())
     }
 }
+
+/// Splits a line of a CSV file into its fields. Fields may be quoted, where `""` is an escaped quote.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
@@ -5,16 +5,29 @@ use clap::Args;
 use ds_rom::rom::{raw::AutoloadKind, Rom, RomConfig, RomLoadOptions};
 use path_slash::PathBufExt;
 use pathdiff::diff_paths;
+use unarm::Endian;
 
 use crate::{
     config::{
-        config::{Config, ConfigAutoload, ConfigModule, ConfigOverlay},
+        config::{Config, ConfigAutoload, ConfigEndian, ConfigModule, ConfigOverlay, CONFIG_VERSION},
+        data_regions::{DataRegion, DataRegions},
         delinks::Delinks,
+        function_overrides::FunctionOverrides,
+        instruction_modes::{InstructionModeOverride, InstructionModes},
         module::{AnalysisOptions, Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        no_epilogue::{NoEpilogueFallback, NoEpilogueFallbacks},
+        overlay_base_addresses::{OverlayBaseAddress, OverlayBaseAddresses},
+        overlay_groups::{ExclusiveOverlayGroup, ExclusiveOverlayGroups},
         program::Program,
+        relocation::RelocCommentTemplate,
+        small_data::SmallDataSection,
         symbol::SymbolMaps,
     },
-    util::io::{create_dir_all, create_file, open_file},
+    util::{
+        io::{create_dir_all, create_file, open_file},
+        progress::Progress,
+    },
 };
 
 /// Generates a config for the given extracted ROM.
@@ -36,6 +49,48 @@ pub struct Init {
     #[arg(long, short = 'b')]
     pub build_path: PathBuf,
 
+    /// Path to a function overrides file, which forces function boundaries and instruction modes during analysis.
+    #[arg(long, short = 'f')]
+    pub function_overrides: Option<PathBuf>,
+
+    /// Address range which is never analyzed as code, formatted as `<module>:<start>:<end>`. Can be repeated.
+    #[arg(long = "data-region", value_name = "MODULE:START:END")]
+    pub data_regions: Vec<DataRegion>,
+
+    /// Address range of handwritten code, formatted as `<module>:<start>:<end>`. Functions in it aren't ended early for
+    /// reading a callee-saved register before saving it, which overlay analysis otherwise does to find the end of code.
+    /// Can be repeated.
+    #[arg(long = "handwritten-code", value_name = "MODULE:START:END")]
+    pub handwritten_code: Vec<DataRegion>,
+
+    /// Forces a function to be analyzed as ARM or Thumb, formatted as `<module>:<address>:<arm|thumb>`. Can be
+    /// repeated.
+    #[arg(long = "instruction-mode", value_name = "MODULE:ADDRESS:MODE")]
+    pub instruction_modes: Vec<InstructionModeOverride>,
+
+    /// Ends functions without a recognized return at the next symbol in a module, if it's at most max distance bytes
+    /// after the function start. Formatted as `<module>:<max distance>`. Can be repeated.
+    #[arg(long = "no-epilogue-fallback", value_name = "MODULE:MAX_DISTANCE")]
+    pub no_epilogue_fallbacks: Vec<NoEpilogueFallback>,
+
+    /// Comma-separated IDs of overlays which share an address region and are never loaded at the same time. Used to
+    /// resolve relocations which would otherwise be ambiguous. Can be repeated.
+    #[arg(long = "overlay-group", value_name = "IDS")]
+    pub overlay_groups: Vec<ExclusiveOverlayGroup>,
+
+    /// Analyzes an overlay as if it was loaded at another base address than the one in the ROM, such as an overlay
+    /// relocated by a patch. Formatted as `<overlay ID>=<address>`. Can be repeated.
+    #[arg(long = "overlay-base-address", value_name = "ID=ADDRESS")]
+    pub overlay_base_addresses: Vec<OverlayBaseAddress>,
+
+    /// Small data section which is split from `.data` or `.bss`, formatted as `<module>:<section>:<start>:<end>` where
+    /// section is `.sdata` or `.sbss`. Can be repeated.
+    #[arg(long = "small-data", value_name = "MODULE:SECTION:START:END")]
+    pub small_data_sections: Vec<SmallDataSection>,
+
+    #[command(flatten)]
+    pub name_prefixes: NamePrefixes,
+
     /// Skips relocation analysis across modules. symbols.txt and relocs.txt will be incomplete.
     #[arg(long, hide = true)]
     pub skip_reloc_analysis: bool,
@@ -45,9 +100,55 @@ pub struct Init {
     #[arg(long, hide = true)]
     pub allow_unknown_function_calls: bool,
 
+    /// Finds vtables in data sections, which are runs of function pointers. Adds functions which are only reachable
+    /// through a vtable.
+    #[arg(long)]
+    pub find_vtables: bool,
+
+    /// Finds mwcc type info blocks in data sections, which point to a type name and a base class list. Names them after
+    /// their type.
+    #[arg(long)]
+    pub find_rtti: bool,
+
+    /// Adds functions which are only reachable through a function pointer in a literal pool, such as callbacks loaded
+    /// with `ldr rN, [pc, #imm]`.
+    #[arg(long)]
+    pub find_pool_function_pointers: bool,
+
+    /// Minimum size in bytes of a discovered function. Smaller functions are treated as data, unless a symbol or function
+    /// override is at their address or another function calls or points to them. Reduces false functions in data, 0 by
+    /// default.
+    #[arg(long, default_value_t = 0)]
+    pub min_function_size: u32,
+
+    /// Ends functions outside the main module and ITCM which read a callee-saved register before saving or writing it, since
+    /// compiled code never does that. Reduces functions which run into data, but handwritten code must be listed with
+    /// `--handwritten-code`.
+    #[arg(long)]
+    pub check_defs_uses: bool,
+
+    /// Records every function branch as a call and relocates conditional calls. mwldarm drops the condition code of
+    /// relocated calls, so only use this with a linker which keeps it.
+    #[arg(long)]
+    pub relocate_function_branches: bool,
+
+    /// Analyzes the first 0x800 bytes of the ARM9 main module as ordinary code and data, for homebrew ROMs which have no
+    /// secure area.
+    #[arg(long)]
+    pub no_secure_area: bool,
+
+    /// Names the BIOS call functions in the secure area like the SDK does, such as `SVC_WaitByLoop` instead of
+    /// `WaitByLoop`.
+    #[arg(long)]
+    pub arm9_bios_calls: bool,
+
     /// Adds a comment to every relocation in relocs.txt explaining where/why it was generated.
-    #[arg(long, hide = true)]
-    pub provide_reloc_source: bool,
+    #[arg(long, alias = "provide-reloc-source")]
+    pub annotate_relocs: bool,
+
+    /// Template of the relocation comments added by --annotate-relocs, where `{source}` is replaced by the explanation.
+    #[arg(long, default_value = "{source}", requires = "annotate_relocs")]
+    pub reloc_comment: RelocCommentTemplate,
 }
 
 impl Init {
@@ -63,24 +164,76 @@ impl Init {
 
         let mut symbol_maps = SymbolMaps::new();
 
+        let function_overrides = match &self.function_overrides {
+            Some(path) => FunctionOverrides::from_file(path)?,
+            None => FunctionOverrides::new(),
+        };
+
+        self.name_prefixes.validate()?;
+        symbol_maps.set_label_prefix(&self.name_prefixes.label_prefix);
+        SmallDataSection::check_unique(&self.small_data_sections)?;
+
         let analysis_options = AnalysisOptions {
             allow_unknown_function_calls: self.allow_unknown_function_calls,
-            provide_reloc_source: self.provide_reloc_source,
+            reloc_comment: self.annotate_relocs.then(|| self.reloc_comment.clone()),
+            function_overrides,
+            data_regions: DataRegions::from_regions(&self.data_regions)?,
+            handwritten_code: DataRegions::from_regions(&self.handwritten_code)?,
+            check_defs_uses: self.check_defs_uses,
+            relocate_function_branches: self.relocate_function_branches,
+            instruction_modes: InstructionModes::from_overrides(&self.instruction_modes)?,
+            no_epilogue_fallbacks: NoEpilogueFallbacks::from_fallbacks(&self.no_epilogue_fallbacks)?,
+            overlay_groups: ExclusiveOverlayGroups::from_groups(&self.overlay_groups)?,
+            overlay_base_addresses: OverlayBaseAddresses::from_overrides(&self.overlay_base_addresses)?,
+            small_data_sections: self.small_data_sections.clone(),
+            endian: Endian::Little,
+            name_prefixes: self.name_prefixes.clone(),
+            find_vtables: self.find_vtables,
+            find_rtti: self.find_rtti,
+            find_pool_function_pointers: self.find_pool_function_pointers,
+            min_function_size: self.min_function_size,
+            has_secure_area: !self.no_secure_area,
+            arm9_bios_calls: self.arm9_bios_calls,
         };
 
+        let autoloads = rom.arm9().autoloads()?;
+        let arm9 = rom.arm9();
+        let mut module_ranges = vec![(
+            ModuleKind::Arm9,
+            arm9.base_address()..arm9.base_address() + (arm9.code()?.len() + arm9.bss()?.len()) as u32,
+        )];
+        for autoload in autoloads.iter() {
+            let end_address = autoload.base_address() + autoload.code().len() as u32 + autoload.bss_size();
+            module_ranges.push((ModuleKind::Autoload(autoload.kind()), autoload.base_address()..end_address));
+        }
+        analysis_options.overlay_base_addresses.check_overlaps(
+            |id| rom.arm9_overlays().get(id as usize).map(|overlay| overlay.code().len() as u32 + overlay.bss_size()),
+            &module_ranges,
+        )?;
+
+        let mut progress = Progress::stderr(1 + rom.arm9_overlays().len() + autoloads.len());
+
         let main = Module::analyze_arm9(rom.arm9(), &mut symbol_maps, &analysis_options)?;
+        progress.advance()?;
         let overlays = rom
             .arm9_overlays()
             .iter()
-            .map(|ov| Module::analyze_overlay(ov, &mut symbol_maps, &analysis_options))
+            .map(|ov| {
+                let module = Module::analyze_overlay(ov, &mut symbol_maps, &analysis_options)?;
+                progress.advance()?;
+                Ok(module)
+            })
             .collect::<Result<Vec<_>>>()?;
-        let autoloads = rom.arm9().autoloads()?;
         let autoloads = autoloads
             .iter()
-            .map(|autoload| match autoload.kind() {
-                AutoloadKind::Itcm => Module::analyze_itcm(autoload, &mut symbol_maps, &analysis_options),
-                AutoloadKind::Dtcm => Module::analyze_dtcm(autoload, &mut symbol_maps, &analysis_options),
-                AutoloadKind::Unknown(_) => bail!("unknown autoload kind"),
+            .map(|autoload| {
+                let module = match autoload.kind() {
+                    AutoloadKind::Itcm => Module::analyze_itcm(autoload, &mut symbol_maps, &analysis_options)?,
+                    AutoloadKind::Dtcm => Module::analyze_dtcm(autoload, &mut symbol_maps, &analysis_options)?,
+                    AutoloadKind::Unknown(_) => bail!("unknown autoload kind"),
+                };
+                progress.advance()?;
+                Ok(module)
             })
             .collect::<Result<Vec<_>>>()?;
 
@@ -149,9 +302,23 @@ impl Init {
         }
 
         Ok(Config {
+            version: CONFIG_VERSION,
             rom_config: Self::make_path(&self.rom_config, path),
             build_path: Self::make_path(&self.build_path, path),
             delinks_path: Self::make_path(self.build_path.join("delinks"), path),
+            function_overrides: self.function_overrides.as_ref().map(|overrides| Self::make_path(overrides, path)),
+            struct_layouts: None,
+            data_regions: self.data_regions.clone(),
+            handwritten_code: self.handwritten_code.clone(),
+            instruction_modes: self.instruction_modes.clone(),
+            no_epilogue_fallbacks: self.no_epilogue_fallbacks.clone(),
+            overlay_groups: self.overlay_groups.clone(),
+            small_data_sections: self.small_data_sections.clone(),
+            memory_regions: vec![],
+            string_encoding: None,
+            name_prefixes: self.name_prefixes.clone(),
+            endian: ConfigEndian::Little,
+            has_secure_area: !self.no_secure_area,
             main_module: ConfigModule {
                 name: "main".to_string(),
                 object: Self::make_path(&rom_config.arm9_bin, path),
@@ -259,6 +426,7 @@ impl Init {
                     relocations: Self::make_path(relocs_path, root),
                 },
                 id,
+                base_address: self.overlay_base_addresses.iter().find(|base| base.id == id).map(|base| base.address),
             });
         }
 
@@ -1,12 +1,13 @@
 use std::{
     borrow::Cow,
+    collections::BTreeMap,
     fs::File,
     io::{BufWriter, Write},
     path::{Path, PathBuf},
 };
 
 use anyhow::{bail, Result};
-use clap::Args;
+use clap::{Args, ValueEnum};
 use ds_rom::rom::{raw::AutoloadKind, Rom, RomLoadOptions};
 
 use crate::{
@@ -14,10 +15,13 @@ use crate::{
     config::{
         config::{Config, ConfigModule},
         delinks::Delinks,
+        memory_regions::MemoryRegion,
         module::ModuleKind,
+        overlay_groups::ExclusiveOverlayGroups,
+        section::SectionKind,
     },
     util::{
-        io::{create_dir_all, create_file_and_dirs, open_file},
+        io::{create_dir_all, create_file_and_dirs},
         path::PathExt,
     },
 };
@@ -36,13 +40,29 @@ pub struct Lcf {
     /// Path to object list file.
     #[arg(long, short = 'o')]
     pub objects_file: PathBuf,
+
+    /// Linker to generate the script for.
+    #[arg(long, short = 'f', value_enum, default_value_t = LcfFormat::Mwld)]
+    pub format: LcfFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum LcfFormat {
+    /// Linker command file for `mwldarm`.
+    Mwld,
+    /// Linker script for GNU `ld`.
+    Gnu,
 }
 
 impl Lcf {
     pub fn run(&self) -> Result<()> {
-        let config: Config = serde_yml::from_reader(open_file(&self.config_path)?)?;
+        let config = Config::from_file(&self.config_path)?;
         let config_dir = self.config_path.parent().unwrap();
 
+        if self.format == LcfFormat::Gnu {
+            return self.write_gnu_script(config_dir, &config);
+        }
+
         let rom = Rom::load(
             config_dir.join(&config.rom_config),
             RomLoadOptions { key: None, compress: false, encrypt: false, load_files: false },
@@ -51,7 +71,8 @@ impl Lcf {
         let build_path = config_dir.normalize_join(&config.build_path)?;
         let delinks_path = config_dir.normalize_join(&config.delinks_path)?;
 
-        let overlay_groups = OverlayGroups::analyze(rom.arm9().end_address()?, rom.arm9_overlays())?;
+        let exclusive_groups = ExclusiveOverlayGroups::from_groups(&config.overlay_groups)?;
+        let overlay_groups = OverlayGroups::analyze(rom.arm9().end_address()?, rom.arm9_overlays(), &exclusive_groups)?;
 
         let lcf_file = create_file_and_dirs(&self.lcf_file)?;
         let mut lcf = BufWriter::new(lcf_file);
@@ -125,20 +146,23 @@ impl Lcf {
         let arm9_bin = config_dir.normalize_join(&config.main_module.object)?;
         create_dir_all(arm9_bin.parent().unwrap())?; // Empty directory, but mwld doesn't create it by itself
         let arm9_bin = arm9_bin.strip_prefix_ext(build_path)?; // mwld expects memory files to be relative to the linked ELF binary
-        writeln!(lcf, "    ARM9 : ORIGIN = {:#x} > {}", rom.arm9().base_address(), arm9_bin.display())?;
+        match MemoryRegion::find(&config.memory_regions, "ARM9") {
+            Some(region) => writeln!(lcf, "    {} > {}", region.memory_entry(), arm9_bin.display())?,
+            None => writeln!(lcf, "    ARM9 : ORIGIN = {:#x} > {}", rom.arm9().base_address(), arm9_bin.display())?,
+        }
         for autoload in rom.arm9().autoloads()?.iter() {
             let memory_name = match autoload.kind() {
                 AutoloadKind::Itcm => "ITCM",
                 AutoloadKind::Dtcm => "DTCM",
                 AutoloadKind::Unknown(_) => bail!("Unknown autoload kind"),
             };
-            let config = config.autoloads.iter().find(|a| a.kind == autoload.kind()).unwrap();
-            writeln!(
-                lcf,
-                "    {memory_name} : ORIGIN = {:#x} > {}",
-                autoload.base_address(),
-                config_dir.normalize_join(&config.module.object)?.strip_prefix_ext(build_path)?.display()
-            )?;
+            let autoload_config = config.autoloads.iter().find(|a| a.kind == autoload.kind()).unwrap();
+            let object = config_dir.normalize_join(&autoload_config.module.object)?;
+            let object = object.strip_prefix_ext(build_path)?;
+            match MemoryRegion::find(&config.memory_regions, memory_name) {
+                Some(region) => writeln!(lcf, "    {} > {}", region.memory_entry(), object.display())?,
+                None => writeln!(lcf, "    {memory_name} : ORIGIN = {:#x} > {}", autoload.base_address(), object.display())?,
+            }
         }
         for group in overlay_groups.iter() {
             for &overlay_id in &group.overlays {
@@ -146,28 +170,38 @@ impl Lcf {
 
                 let memory_name = format!("OV{:03}", overlay.id());
 
-                write!(lcf, "    {memory_name} : ORIGIN = AFTER(")?;
-
-                if group.after.is_empty() {
-                    write!(lcf, "ARM9")?;
+                if let Some(region) = MemoryRegion::find(&config.memory_regions, &memory_name) {
+                    write!(lcf, "    {}", region.memory_entry())?;
+                } else if let Some(origin) = group.origin {
+                    write!(lcf, "    {memory_name} : ORIGIN = {origin:#x}")?;
                 } else {
-                    for (i, id) in group.after.iter().enumerate() {
-                        if i > 0 {
-                            write!(lcf, ",")?;
+                    write!(lcf, "    {memory_name} : ORIGIN = AFTER(")?;
+
+                    if group.after.is_empty() {
+                        write!(lcf, "ARM9")?;
+                    } else {
+                        for (i, id) in group.after.iter().enumerate() {
+                            if i > 0 {
+                                write!(lcf, ",")?;
+                            }
+                            let memory_name = format!("OV{:03}", id);
+                            write!(lcf, "{memory_name}")?;
                         }
-                        let memory_name = format!("OV{:03}", id);
-                        write!(lcf, "{memory_name}")?;
                     }
+                    write!(lcf, ")")?;
                 }
 
-                let config = config.overlays.iter().find(|o| o.id == overlay_id).unwrap();
+                let overlay_config = config.overlays.iter().find(|o| o.id == overlay_id).unwrap();
                 writeln!(
                     lcf,
-                    ") > {}",
-                    config_dir.normalize_join(&config.module.object)?.strip_prefix_ext(build_path)?.display()
+                    " > {}",
+                    config_dir.normalize_join(&overlay_config.module.object)?.strip_prefix_ext(build_path)?.display()
                 )?;
             }
         }
+        for region in Self::extra_memory_regions(config)? {
+            writeln!(lcf, "    {}", region.memory_entry())?;
+        }
         writeln!(lcf, "}}\n")?;
         Ok(())
     }
@@ -183,13 +217,7 @@ impl Lcf {
         build_path: &Path,
         delinks_path: &Path,
     ) -> Result<()> {
-        let (module_name, memory_name): (Cow<str>, Cow<str>) = match module_kind {
-            ModuleKind::Arm9 => (".arm9".into(), "ARM9".into()),
-            ModuleKind::Overlay(id) => (format!(".ov{:03}", id).into(), format!("OV{:03}", id).into()),
-            ModuleKind::Autoload(AutoloadKind::Itcm) => (".itcm".into(), "ITCM".into()),
-            ModuleKind::Autoload(AutoloadKind::Dtcm) => (".dtcm".into(), "DTCM".into()),
-            ModuleKind::Autoload(_) => bail!("Unknown autoload kind"),
-        };
+        let (module_name, memory_name) = Self::module_names(module_kind)?;
 
         writeln!(lcf, "    {module_name} : {{")?;
         let delinks = Delinks::from_file(config_dir.join(&module.delinks), module_kind)?;
@@ -218,4 +246,170 @@ impl Lcf {
 
         Ok(())
     }
+
+    /// Returns the memory regions in the config which don't replace the region of a module.
+    fn extra_memory_regions(config: &Config) -> Result<Vec<&MemoryRegion>> {
+        let mut module_memory_names = vec![];
+        for (module_kind, _) in config.modules() {
+            let (_, memory_name) = Self::module_names(module_kind)?;
+            module_memory_names.push(memory_name);
+        }
+        Ok(config
+            .memory_regions
+            .iter()
+            .filter(|region| !module_memory_names.iter().any(|name| name.as_ref() == region.name.as_str()))
+            .collect())
+    }
+
+    fn module_names(module_kind: ModuleKind) -> Result<(Cow<'static, str>, Cow<'static, str>)> {
+        Ok(match module_kind {
+            ModuleKind::Arm9 => (".arm9".into(), "ARM9".into()),
+            ModuleKind::Overlay(id) => (format!(".ov{:03}", id).into(), format!("OV{:03}", id).into()),
+            ModuleKind::Autoload(AutoloadKind::Itcm) => (".itcm".into(), "ITCM".into()),
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => (".dtcm".into(), "DTCM".into()),
+            ModuleKind::Autoload(_) => bail!("Unknown autoload kind"),
+        })
+    }
+
+    /// Writes a GNU `ld` script with the same layout and boundary symbols as the LCF. Module addresses are taken from
+    /// the delinks files, so the ROM is not needed. Overlays which start at the same address are placed in one `OVERLAY`
+    /// statement, and .bss sections are placed in NOLOAD sections after each module.
+    fn write_gnu_script(&self, config_dir: &Path, config: &Config) -> Result<()> {
+        let build_path = config_dir.normalize_join(&config.build_path)?;
+        let delinks_path = config_dir.normalize_join(&config.delinks_path)?;
+
+        let mut modules = vec![];
+        for (module_kind, module) in config.modules() {
+            let delinks = Delinks::from_file(config_dir.join(&module.delinks), module_kind)?;
+            modules.push((module_kind, delinks));
+        }
+
+        let lcf_file = create_file_and_dirs(&self.lcf_file)?;
+        let mut lcf = BufWriter::new(lcf_file);
+
+        let objects_file = create_file_and_dirs(&self.objects_file)?;
+        let mut objects = BufWriter::new(objects_file);
+
+        writeln!(lcf, "OUTPUT_ARCH(arm)\n")?;
+
+        writeln!(lcf, "MEMORY {{")?;
+        for (module_kind, delinks) in &modules {
+            let (_, memory_name) = Self::module_names(*module_kind)?;
+            if let Some(region) = MemoryRegion::find(&config.memory_regions, &memory_name) {
+                writeln!(lcf, "    {}", region.memory_entry())?;
+                continue;
+            }
+            let (Some(start), Some(end)) = (delinks.sections.base_address(), delinks.sections.end_address()) else {
+                bail!("No sections in {module_kind}");
+            };
+            writeln!(lcf, "    {memory_name} : ORIGIN = {start:#x}, LENGTH = {:#x}", end - start)?;
+        }
+        for region in Self::extra_memory_regions(config)? {
+            writeln!(lcf, "    {}", region.memory_entry())?;
+        }
+        writeln!(lcf, "}}\n")?;
+
+        let mut overlays: BTreeMap<u32, Vec<_>> = BTreeMap::new();
+        writeln!(lcf, "SECTIONS {{")?;
+        for (module_kind, delinks) in &modules {
+            if let ModuleKind::Overlay(_) = module_kind {
+                let start = delinks.sections.base_address().unwrap();
+                overlays.entry(start).or_default().push((*module_kind, delinks));
+                continue;
+            }
+            let (module_name, memory_name) = Self::module_names(*module_kind)?;
+            writeln!(lcf, "    {module_name} : {{")?;
+            Self::write_gnu_module_contents(&mut lcf, &memory_name, delinks, false, "        ")?;
+            writeln!(lcf, "    }} > {memory_name}\n")?;
+            Self::write_gnu_bss_section(&mut lcf, &module_name, &memory_name, delinks, None)?;
+        }
+        for (start, overlays) in &overlays {
+            // The overlays share their start address, so the group is placed in the region of the largest overlay
+            let (largest_kind, _) = overlays.iter().max_by_key(|(_, delinks)| delinks.sections.end_address()).unwrap();
+            let (_, group_memory_name) = Self::module_names(*largest_kind)?;
+
+            writeln!(lcf, "    OVERLAY {start:#x} : {{")?;
+            for (module_kind, delinks) in overlays {
+                let (module_name, memory_name) = Self::module_names(*module_kind)?;
+                writeln!(lcf, "        {module_name} {{")?;
+                Self::write_gnu_module_contents(&mut lcf, &memory_name, delinks, false, "            ")?;
+                writeln!(lcf, "        }}")?;
+            }
+            writeln!(lcf, "    }} > {group_memory_name}\n")?;
+
+            // Overlay members can't be NOLOAD, so each .bss follows its overlay in a section of its own
+            for (module_kind, delinks) in overlays {
+                let (module_name, memory_name) = Self::module_names(*module_kind)?;
+                let address = format!("ADDR({module_name}) + SIZEOF({module_name})");
+                Self::write_gnu_bss_section(&mut lcf, &module_name, &memory_name, delinks, Some(&address))?;
+            }
+        }
+        writeln!(lcf, "}}")?;
+
+        for (_, delinks) in &modules {
+            for file in &delinks.files {
+                let (file_path, _) = file.split_file_ext();
+                let base_path = if file.complete { &build_path } else { &delinks_path };
+                let file_path = base_path.join(file_path);
+                writeln!(objects, "{}.o", file_path.display())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a NOLOAD output section with the .bss sections of a module, if it has any. It's placed after the module's
+    /// other sections, or at `address` if given.
+    fn write_gnu_bss_section(
+        lcf: &mut BufWriter<File>,
+        module_name: &str,
+        memory_name: &str,
+        delinks: &Delinks,
+        address: Option<&str>,
+    ) -> Result<()> {
+        if !delinks.sections.iter().any(|section| section.kind() == SectionKind::Bss) {
+            return Ok(());
+        }
+        match address {
+            Some(address) => writeln!(lcf, "    {module_name}.bss ({address}) (NOLOAD) : {{")?,
+            None => writeln!(lcf, "    {module_name}.bss (NOLOAD) : {{")?,
+        }
+        Self::write_gnu_module_contents(lcf, memory_name, delinks, true, "        ")?;
+        writeln!(lcf, "    }} > {memory_name}\n")?;
+        Ok(())
+    }
+
+    /// Writes the .bss sections of a module if `bss` is true, otherwise all of its other sections.
+    fn write_gnu_module_contents(
+        lcf: &mut BufWriter<File>,
+        memory_name: &str,
+        delinks: &Delinks,
+        bss: bool,
+        indent: &str,
+    ) -> Result<()> {
+        for section in delinks.sections.sorted_by_address() {
+            if (section.kind() == SectionKind::Bss) != bss {
+                continue;
+            }
+            writeln!(lcf, "{indent}. = ALIGN({});", section.alignment())?;
+            let section_boundary_name = section.boundary_name();
+            writeln!(lcf, "{indent}{memory_name}_{section_boundary_name}_START = .;")?;
+            // Equivalent to KEEP_SECTION in the LCF
+            let keep = matches!(section.name(), ".init" | ".ctor");
+            for file in &delinks.files {
+                if file.sections.by_name(section.name()).is_none() {
+                    continue;
+                }
+                let (file_path, _) = file.split_file_ext();
+                let (_, file_name) = file_path.rsplit_once('/').unwrap_or(("", file_path));
+                if keep {
+                    writeln!(lcf, "{indent}KEEP(*/{file_name}.o({}))", section.name())?;
+                } else {
+                    writeln!(lcf, "{indent}*/{file_name}.o({})", section.name())?;
+                }
+            }
+            writeln!(lcf, "{indent}{memory_name}_{section_boundary_name}_END = .;")?;
+        }
+        Ok(())
+    }
 }
@@ -1,15 +1,27 @@
+mod apply;
 mod check;
+mod config;
 mod delink;
+mod diff;
 mod dis;
+mod dump;
+mod export;
+mod fix;
 mod import;
 mod init;
 mod lcf;
 mod objdiff;
 mod rom;
 
+pub use apply::*;
 pub use check::*;
+pub use config::*;
 pub use delink::*;
+pub use diff::*;
 pub use dis::*;
+pub use dump::*;
+pub use export::*;
+pub use fix::*;
 pub use import::*;
 pub use init::*;
 pub use lcf::*;
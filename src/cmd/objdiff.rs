@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Args;
 use globset::Glob;
 use objdiff_core::config::ProjectObject;
@@ -8,13 +8,10 @@ use objdiff_core::config::ProjectObject;
 use crate::{
     config::{
         config::{Config, ConfigModule},
-        delinks::Delinks,
+        delinks::{DelinkFile, Delinks},
         module::ModuleKind,
     },
-    util::{
-        io::{create_dir_all, open_file},
-        path::PathExt,
-    },
+    util::{io::create_dir_all, path::PathExt},
 };
 
 const MIN_OBJDIFF_VERSION: &str = "2.3.2";
@@ -24,40 +21,44 @@ const MIN_OBJDIFF_VERSION: &str = "2.3.2";
 pub struct Objdiff {
     /// Path to config.yaml.
     #[arg(long, short = 'c')]
-    config_path: PathBuf,
+    pub config_path: PathBuf,
 
     /// Path to directory to generate objdiff.json.
     #[arg(long, short = 'o')]
-    output_path: Option<PathBuf>,
+    pub output_path: Option<PathBuf>,
 
     /// Includes decomp.me scratches.
     #[arg(long, short = 's')]
-    scratch: bool,
+    pub scratch: bool,
 
     /// See https://decomp.me/api/compiler with compilers for the `nds_arm9` platform.
     #[arg(long, short = 'C')]
-    compiler: Option<String>,
+    pub compiler: Option<String>,
 
-    /// Flags to pass to the compiler in decomp.me.
+    /// Flags to pass to the compiler in decomp.me, unless a unit has its own `flags` in delinks.txt.
     #[arg(long, short = 'f', allow_hyphen_values = true)]
-    c_flags: Option<String>,
+    pub c_flags: Option<String>,
 
     /// Preset ID to use in decomp.me.
     #[arg(long, short = 'p')]
-    preset_id: Option<u32>,
+    pub preset_id: Option<u32>,
 
     /// Custom build command.
     #[arg(long, short = 'm')]
-    custom_make: Option<String>,
+    pub custom_make: Option<String>,
 
     /// Arguments to custom build command.
     #[arg(long, short = 'M', allow_hyphen_values = true)]
-    custom_args: Vec<String>,
+    pub custom_args: Vec<String>,
+
+    /// Only includes this translation unit from delinks.txt, such as `src/main.c`.
+    #[arg(long, short = 'u')]
+    pub unit: Option<String>,
 }
 
 impl Objdiff {
     pub fn run(&self) -> Result<()> {
-        let config: Config = serde_yml::from_reader(open_file(&self.config_path)?)?;
+        let config = Config::from_file(&self.config_path)?;
         let config_path = self.config_path.parent().unwrap();
 
         let output_path = self.output_path.clone().unwrap_or(PathBuf::from("."));
@@ -84,6 +85,12 @@ impl Objdiff {
             )?);
         }
 
+        if let Some(unit) = &self.unit {
+            if units.is_empty() {
+                bail!("Unit '{unit}' not found in any delinks.txt");
+            }
+        }
+
         let target_dir = config_path.join(config.build_path).normalize_diff_paths(&abs_output_path)?;
         let base_dir = config_path.join(config.delinks_path).normalize_diff_paths(&abs_output_path)?;
 
@@ -130,6 +137,7 @@ impl Objdiff {
         delinks
             .files
             .iter()
+            .filter(|file| self.includes_unit(file))
             .map(|file| {
                 let (file_path, extension) = file.split_file_ext();
 
@@ -163,7 +171,7 @@ impl Objdiff {
                     Some(objdiff_core::config::ScratchConfig {
                         platform: Some("nds_arm9".to_string()),
                         compiler: self.compiler.clone(),
-                        c_flags: self.c_flags.clone(),
+                        c_flags: file.flags.clone().or_else(|| self.c_flags.clone()),
                         ctx_path: Some(ctx_path),
                         build_ctx: Some(true),
                         preset_id: self.preset_id,
@@ -197,4 +205,12 @@ impl Objdiff {
             })
             .collect::<Result<Vec<_>>>()
     }
+
+    /// Returns whether `--unit` selects the file, with or without its file extension.
+    fn includes_unit(&self, file: &DelinkFile) -> bool {
+        match &self.unit {
+            Some(unit) => file.name == *unit || file.split_file_ext().0 == unit,
+            None => true,
+        }
+    }
 }
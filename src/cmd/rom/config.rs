@@ -3,7 +3,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::Args;
 use ds_rom::rom::{raw::AutoloadKind, OverlayConfig, Rom, RomConfig, RomLoadOptions};
 use object::{Object, ObjectSection, ObjectSymbol};
@@ -11,21 +11,20 @@ use path_slash::PathExt;
 use pathdiff::diff_paths;
 
 use crate::{
-    config::{
-        config::Config,
-        delinks::Delinks,
-        module::ModuleKind,
-        section::{Section, Sections},
-    },
-    util::io::{create_file, open_file, read_file},
+    config::{config::Config, delinks::Delinks, module::ModuleKind, section::Sections},
+    util::io::{create_file, read_file},
 };
 
 /// Creates a configuration to build a ROM from linked binaries.
 #[derive(Args, Clone)]
 pub struct ConfigRom {
     /// Path to linked ELF file
-    #[arg(long, short = 'e')]
-    pub elf: PathBuf,
+    #[arg(long, short = 'e', required_unless_present = "from_project")]
+    pub elf: Option<PathBuf>,
+
+    /// Takes the module layouts from the sections in delinks.txt instead of a linked ELF file.
+    #[arg(long, conflicts_with = "elf")]
+    pub from_project: bool,
 
     /// Path to config YAML
     #[arg(long, short = 'c')]
@@ -34,7 +33,7 @@ pub struct ConfigRom {
 
 impl ConfigRom {
     pub fn run(&self) -> Result<()> {
-        let config: Config = serde_yml::from_reader(open_file(&self.config)?)?;
+        let config = Config::from_file(&self.config)?;
         let config_path = self.config.parent().unwrap();
 
         let old_rom_paths_path = config_path.join(&config.rom_config);
@@ -49,12 +48,13 @@ impl ConfigRom {
 
         self.update_relative_paths(&mut rom_paths, old_rom_paths_dir, new_rom_paths_dir);
 
-        let file = read_file(&self.elf)?;
-        let object = object::File::parse(&*file)?;
+        let file = self.elf.as_ref().map(read_file).transpose()?;
+        let object = file.as_deref().map(object::File::parse).transpose()?;
+        let object = object.as_ref();
 
-        self.config_arm9(&object, &config, &rom, &mut rom_paths, new_rom_paths_dir)?;
-        self.config_autoloads(&object, &config, &rom, &mut rom_paths, new_rom_paths_dir)?;
-        self.config_overlays(&object, &config, &rom, &mut rom_paths, new_rom_paths_dir)?;
+        self.config_arm9(object, &config, &rom, &mut rom_paths, new_rom_paths_dir)?;
+        self.config_autoloads(object, &config, &rom, &mut rom_paths, new_rom_paths_dir)?;
+        self.config_overlays(object, &config, &rom, &mut rom_paths, new_rom_paths_dir)?;
 
         serde_yml::to_writer(create_file(new_rom_paths_dir.join("rom_config.yaml"))?, &rom_paths)?;
 
@@ -99,7 +99,7 @@ impl ConfigRom {
 
     fn config_overlays(
         &self,
-        object: &object::File<'_>,
+        object: Option<&object::File<'_>>,
         config: &Config,
         rom: &Rom<'_>,
         rom_paths: &mut RomConfig,
@@ -119,25 +119,24 @@ impl ConfigRom {
             let module_name = format!("OV{:03}", overlay.id);
             let file_name = format!("arm9_ov{:03}.bin", overlay.id);
 
-            let ctor_start = object
-                .symbol_by_name(&format!("{module_name}_CTOR_START"))
-                .with_context(|| format!("No CTOR_START in overlay {}", overlay.id))?;
-            let ctor_end = object
-                .symbol_by_name(&format!("{module_name}_CTOR_END"))
-                .with_context(|| format!("No CTOR_END in overlay {}", overlay.id))?;
-
+            let layout = ModuleLayout::new(&delinks.sections, &module_name, object)?;
             let mut info = rom_overlay.info().clone();
-            info.base_address = self.section_ranges(&delinks.sections, &module_name, object, |_| true)?.unwrap().start;
-            info.code_size = self
-                .section_ranges(&delinks.sections, &module_name, object, |s| s.kind().is_initialized())?
-                .map(|range| range.len())
-                .unwrap_or(0) as u32;
-            info.bss_size = self
-                .section_ranges(&delinks.sections, &module_name, object, |s| !s.kind().is_initialized())?
-                .map(|range| range.len())
-                .unwrap_or(0) as u32;
-            info.ctor_start = ctor_start.address() as u32;
-            info.ctor_end = ctor_end.address() as u32;
+            info.base_address = layout.base_address();
+            info.code_size = layout.code_size();
+            info.bss_size = layout.bss_size();
+            if let Some(object) = object {
+                let ctor_start = object
+                    .symbol_by_name(&format!("{module_name}_CTOR_START"))
+                    .with_context(|| format!("No CTOR_START in overlay {}", overlay.id))?;
+                let ctor_end = object
+                    .symbol_by_name(&format!("{module_name}_CTOR_END"))
+                    .with_context(|| format!("No CTOR_END in overlay {}", overlay.id))?;
+                info.ctor_start = ctor_start.address() as u32;
+                info.ctor_end = ctor_end.address() as u32;
+            } else if let Some(ctor) = &layout.ctor {
+                info.ctor_start = ctor.start;
+                info.ctor_end = ctor.end;
+            }
             info.compressed = rom_overlay.originally_compressed();
             overlay_configs.push(OverlayConfig { info, file_name });
         }
@@ -152,7 +151,7 @@ impl ConfigRom {
 
     fn config_autoloads(
         &self,
-        object: &object::File<'_>,
+        object: Option<&object::File<'_>>,
         config: &Config,
         rom: &Rom<'_>,
         rom_paths: &mut RomConfig,
@@ -175,15 +174,10 @@ impl ConfigRom {
                 AutoloadKind::Unknown(_) => panic!("Unknown autoload kind"),
             };
 
+            let layout = ModuleLayout::new(&delinks.sections, module_name, object)?;
             let mut autoload_info = *rom_autoload.info();
-            autoload_info.code_size = self
-                .section_ranges(&delinks.sections, module_name, object, |s| s.kind().is_initialized())?
-                .map(|range| range.len() as u32)
-                .unwrap_or(0);
-            autoload_info.bss_size = self
-                .section_ranges(&delinks.sections, module_name, object, |s| !s.kind().is_initialized())?
-                .map(|range| range.len() as u32)
-                .unwrap_or(0);
+            autoload_info.code_size = layout.code_size();
+            autoload_info.bss_size = layout.bss_size();
 
             if let Some(text_section) = delinks.sections.by_name(".text") {
                 autoload_info.code_size = autoload_info.code_size.next_multiple_of(text_section.alignment());
@@ -211,7 +205,7 @@ impl ConfigRom {
 
     fn config_arm9(
         &self,
-        object: &object::File<'_>,
+        object: Option<&object::File<'_>>,
         config: &Config,
         rom: &Rom<'_>,
         rom_paths: &mut RomConfig,
@@ -219,19 +213,23 @@ impl ConfigRom {
     ) -> Result<()> {
         let config_path = self.config.parent().unwrap();
 
-        let arm9_section = object.section_by_name("ARM9").context("ARM9 section not found")?;
-        let build_info_symbol = object.symbol_by_name("BuildInfo").context("BuildInfo symbol not found")?;
-        let autoload_callback_symbol = object.symbol_by_name("AutoloadCallback").context("BuildInfo symbol not found")?;
         let delinks = Delinks::from_file(config_path.join(&config.main_module.delinks), ModuleKind::Arm9)?;
-        let bss_range = self.section_ranges(&delinks.sections, "ARM9", object, |s| !s.kind().is_initialized())?.unwrap();
+        let layout = ModuleLayout::new(&delinks.sections, "ARM9", object)?;
 
         let mut arm9_build_config = rom.arm9_build_config()?;
-        arm9_build_config.offsets.base_address = arm9_section.address() as u32;
-        arm9_build_config.offsets.entry_function = object.entry() as u32;
-        arm9_build_config.offsets.build_info = (build_info_symbol.address() - arm9_section.address()) as u32;
-        arm9_build_config.offsets.autoload_callback = autoload_callback_symbol.address() as u32;
-        arm9_build_config.build_info.bss_start = bss_range.start;
-        arm9_build_config.build_info.bss_end = bss_range.end;
+        if let Some(object) = object {
+            let arm9_section = object.section_by_name("ARM9").context("ARM9 section not found")?;
+            let build_info_symbol = object.symbol_by_name("BuildInfo").context("BuildInfo symbol not found")?;
+            let autoload_callback_symbol = object.symbol_by_name("AutoloadCallback").context("BuildInfo symbol not found")?;
+            arm9_build_config.offsets.base_address = arm9_section.address() as u32;
+            arm9_build_config.offsets.entry_function = object.entry() as u32;
+            arm9_build_config.offsets.build_info = (build_info_symbol.address() - arm9_section.address()) as u32;
+            arm9_build_config.offsets.autoload_callback = autoload_callback_symbol.address() as u32;
+        } else {
+            arm9_build_config.offsets.base_address = layout.base_address();
+        }
+        arm9_build_config.build_info.bss_start = layout.bss.start;
+        arm9_build_config.build_info.bss_end = layout.bss.end;
         arm9_build_config.compressed = rom.arm9().originally_compressed();
         arm9_build_config.encrypted = rom.arm9().originally_encrypted();
 
@@ -245,26 +243,61 @@ impl ConfigRom {
         Ok(())
     }
 
-    fn section_ranges<F>(
-        &self,
-        sections: &Sections,
-        module_name: &str,
-        object: &object::File<'_>,
-        predicate: F,
-    ) -> Result<Option<Range<u32>>>
-    where
-        F: FnMut(&&Section) -> bool,
-    {
-        Ok(sections
-            .iter()
-            .filter(predicate)
-            .map(|s| s.range_from_object(module_name, object))
-            .collect::<Result<Vec<_>>>()?
-            .into_iter()
-            .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end)))
-    }
-
     fn make_path<P: AsRef<Path>, B: AsRef<Path>>(path: P, base: B) -> PathBuf {
         PathBuf::from(diff_paths(path, &base).unwrap().to_slash_lossy().as_ref())
     }
 }
+
+/// Address ranges of a module, taken from its sections in `delinks.txt` or from a linked ELF file.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ModuleLayout {
+    /// Code and data sections
+    pub code: Range<u32>,
+    /// .bss sections, or an empty range at the end of `code` if there are none
+    pub bss: Range<u32>,
+    pub ctor: Option<Range<u32>>,
+}
+
+impl ModuleLayout {
+    /// Computes the layout from the section boundaries in the linked ELF file if there is one, otherwise from the
+    /// sections themselves.
+    pub fn new(sections: &Sections, module_name: &str, object: Option<&object::File<'_>>) -> Result<Self> {
+        let mut code: Option<Range<u32>> = None;
+        let mut bss: Option<Range<u32>> = None;
+        let mut ctor = None;
+        for section in sections.iter() {
+            let range = match object {
+                Some(object) => section.range_from_object(module_name, object)?,
+                None => section.start_address()..section.end_address(),
+            };
+            if section.name() == ".ctor" {
+                ctor = Some(range.clone());
+            }
+            let merged = if section.kind().is_initialized() { &mut code } else { &mut bss };
+            *merged = Some(match merged.take() {
+                Some(merged) => merged.start.min(range.start)..merged.end.max(range.end),
+                None => range,
+            });
+        }
+
+        let code = match (code, &bss) {
+            (Some(code), _) => code,
+            (None, Some(bss)) => bss.start..bss.start,
+            (None, None) => bail!("{module_name} has no sections"),
+        };
+        let bss = bss.unwrap_or(code.end..code.end);
+        Ok(Self { code, bss, ctor })
+    }
+
+    pub fn base_address(&self) -> u32 {
+        self.code.start.min(self.bss.start)
+    }
+
+    pub fn code_size(&self) -> u32 {
+        self.code.len() as u32
+    }
+
+    pub fn bss_size(&self) -> u32 {
+        self.bss.len() as u32
+    }
+}
@@ -1,27 +1,95 @@
-use std::path::PathBuf;
+use std::{fs, path::PathBuf};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 
 use clap::Args;
 use ds_rom::{
     crypto::blowfish::BlowfishKey,
-    rom::{raw, Rom, RomSaveError},
+    rom::{raw, raw::AutoloadKind, Rom, RomSaveError},
 };
+use serde::Serialize;
+
+use crate::util::io::create_file;
 
 /// Extracts a ROM to a given path.
 #[derive(Args, Clone)]
 pub struct Extract {
     /// Nintendo DS game ROM.
     #[arg(long, short = 'r')]
-    rom: PathBuf,
+    pub rom: PathBuf,
 
     /// Nintendo DS ARM7 BIOS file.
     #[arg(long, short = '7')]
-    arm7_bios: Option<PathBuf>,
+    pub arm7_bios: Option<PathBuf>,
 
     /// Output path.
     #[arg(long, short = 'o')]
-    output_path: PathBuf,
+    pub output_path: PathBuf,
+
+    /// Only extract the ARM9 main module.
+    #[arg(long, group = "module")]
+    pub arm9: bool,
+
+    /// Only extract the ARM7 module.
+    #[arg(long, group = "module")]
+    pub arm7: bool,
+
+    /// Only extract the ARM9 overlay with this ID.
+    #[arg(long, group = "module")]
+    pub overlay: Option<u16>,
+
+    /// Only extract this autoload: `itcm`, `dtcm` or its index in the autoload list.
+    #[arg(long, group = "module")]
+    pub autoload: Option<String>,
+
+    /// Also write the ARM9 overlay table to this path as JSON.
+    #[arg(long)]
+    pub overlay_table_json: Option<PathBuf>,
+}
+
+/// Metadata written next to a single extracted module.
+#[derive(Serialize)]
+pub struct ExtractedModuleInfo {
+    pub base_address: u32,
+    pub code_size: u32,
+    pub bss_size: u32,
+    pub signed: bool,
+}
+
+/// An entry in the ARM9 overlay table.
+#[derive(Serialize)]
+pub struct OverlayTableEntry {
+    pub id: u16,
+    pub base_address: u32,
+    pub code_size: u32,
+    pub bss_size: u32,
+    pub ctor_start: u32,
+    pub ctor_end: u32,
+    pub file_id: u32,
+    pub compressed: bool,
+    pub signed: bool,
+}
+
+impl OverlayTableEntry {
+    pub fn table(rom: &Rom) -> Vec<Self> {
+        rom.arm9_overlays()
+            .iter()
+            .map(|overlay| {
+                let info = overlay.info();
+                Self {
+                    id: overlay.id(),
+                    base_address: info.base_address,
+                    code_size: info.code_size,
+                    bss_size: info.bss_size,
+                    ctor_start: info.ctor_start,
+                    ctor_end: info.ctor_end,
+                    file_id: info.file_id,
+                    compressed: overlay.originally_compressed(),
+                    signed: overlay.is_signed(),
+                }
+            })
+            .collect()
+    }
 }
 
 impl Extract {
@@ -31,6 +99,16 @@ impl Extract {
             if let Some(arm7_bios) = &self.arm7_bios { Some(BlowfishKey::from_arm7_bios_path(arm7_bios)?) } else { None };
         let rom = Rom::extract(&raw_rom)?;
 
+        if let Some(overlay_table_json) = &self.overlay_table_json {
+            let table = OverlayTableEntry::table(&rom);
+            serde_json::to_writer_pretty(create_file(overlay_table_json)?, &table)?;
+            log::info!("Wrote {} overlay table entries to {}", table.len(), overlay_table_json.display());
+        }
+
+        if self.arm9 || self.arm7 || self.overlay.is_some() || self.autoload.is_some() {
+            return self.extract_module(&rom, key.as_ref());
+        }
+
         match rom.save(&self.output_path, key.as_ref()) {
             Err(RomSaveError::BlowfishKeyNeeded) => {
                 log::error!("The ROM is encrypted, please provide ARM7 BIOS");
@@ -39,4 +117,89 @@ impl Extract {
             result => Ok(result?),
         }
     }
+
+    fn extract_module(&self, rom: &Rom, key: Option<&BlowfishKey>) -> Result<()> {
+        let (name, code, info) = if self.arm7 {
+            let arm7 = rom.arm7();
+            let code = arm7.full_data().to_vec();
+            let info = ExtractedModuleInfo {
+                base_address: arm7.base_address(),
+                code_size: code.len() as u32,
+                bss_size: 0,
+                signed: false,
+            };
+            ("arm7".to_string(), code, info)
+        } else if let Some(id) = self.overlay {
+            let mut overlay = rom
+                .arm9_overlays()
+                .iter()
+                .find(|overlay| overlay.id() == id)
+                .with_context(|| format!("Overlay {id} not found in ROM"))?
+                .clone();
+            overlay.decompress();
+            let code = overlay.code().to_vec();
+            let info = ExtractedModuleInfo {
+                base_address: overlay.base_address(),
+                code_size: code.len() as u32,
+                bss_size: overlay.bss_size(),
+                signed: overlay.is_signed(),
+            };
+            (format!("arm9_ov{id:03}"), code, info)
+        } else {
+            let mut arm9 = rom.arm9().clone();
+            if arm9.is_encrypted() {
+                let Some(key) = key else {
+                    log::error!("The ROM is encrypted, please provide ARM7 BIOS");
+                    bail!("The ROM is encrypted, please provide ARM7 BIOS");
+                };
+                arm9.decrypt(key, rom.header().original.gamecode.to_le_u32())?;
+            }
+            arm9.decompress()?;
+
+            if let Some(autoload) = &self.autoload {
+                let autoloads = arm9.autoloads()?;
+                let autoload_module = match autoload.as_str() {
+                    "itcm" => autoloads.iter().find(|a| matches!(a.kind(), AutoloadKind::Itcm)),
+                    "dtcm" => autoloads.iter().find(|a| matches!(a.kind(), AutoloadKind::Dtcm)),
+                    index => {
+                        let index: usize = index
+                            .parse()
+                            .with_context(|| format!("Invalid autoload '{index}', expected 'itcm', 'dtcm' or an index"))?;
+                        autoloads.get(index)
+                    }
+                }
+                .with_context(|| format!("Autoload '{autoload}' not found in ROM"))?;
+                let code = autoload_module.code().to_vec();
+                let info = ExtractedModuleInfo {
+                    base_address: autoload_module.base_address(),
+                    code_size: code.len() as u32,
+                    bss_size: autoload_module.bss_size(),
+                    signed: false,
+                };
+                let name = match autoload_module.kind() {
+                    AutoloadKind::Itcm => "itcm".to_string(),
+                    AutoloadKind::Dtcm => "dtcm".to_string(),
+                    AutoloadKind::Unknown(_) => format!("autoload_{autoload}"),
+                };
+                (name, code, info)
+            } else {
+                let code = arm9.code()?.to_vec();
+                let info = ExtractedModuleInfo {
+                    base_address: arm9.base_address(),
+                    code_size: code.len() as u32,
+                    bss_size: arm9.bss()?.len() as u32,
+                    signed: false,
+                };
+                ("arm9".to_string(), code, info)
+            }
+        };
+
+        fs::create_dir_all(&self.output_path)?;
+        let bin_path = self.output_path.join(format!("{name}.bin"));
+        fs::write(&bin_path, &code).with_context(|| format!("Failed to write {}", bin_path.display()))?;
+        serde_yml::to_writer(create_file(self.output_path.join(format!("{name}.yaml")))?, &info)?;
+
+        log::info!("Extracted {name} ({:#x} bytes at {:#010x}) to {}", info.code_size, info.base_address, bin_path.display());
+        Ok(())
+    }
 }
@@ -4,7 +4,7 @@ mod extract;
 
 use build::*;
 pub use config::*;
-use extract::*;
+pub use extract::*;
 
 use anyhow::Result;
 use clap::{Args, Subcommand};
@@ -1,18 +1,158 @@
-use std::path::PathBuf;
+use std::{
+    iter,
+    path::{Path, PathBuf},
+};
 
+use anyhow::{bail, Context, Result};
 use ds_rom::rom::raw::AutoloadKind;
 use serde::{Deserialize, Serialize};
+use unarm::Endian;
+
+use crate::{analysis::strings::StringEncoding, util::io::open_file};
+
+use super::{
+    data_regions::DataRegion, instruction_modes::InstructionModeOverride, memory_regions::MemoryRegion, module::ModuleKind,
+    name_prefixes::NamePrefixes, no_epilogue::NoEpilogueFallback, overlay_groups::ExclusiveOverlayGroup,
+    small_data::SmallDataSection,
+};
+
+/// Latest version of the config format. Older configs can be upgraded with `dsd config migrate`.
+pub const CONFIG_VERSION: u32 = 2;
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
+    /// Version of the config format, missing in version 1
+    #[serde(default = "Config::unversioned")]
+    pub version: u32,
     pub rom_config: PathBuf,
     pub build_path: PathBuf,
     pub delinks_path: PathBuf,
+    /// Path to function overrides file which was used when analyzing the ROM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_overrides: Option<PathBuf>,
+    /// Path to struct layouts file, which data symbols of kind `struct:<name>` refer to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub struct_layouts: Option<PathBuf>,
+    /// Address ranges which were treated as data when analyzing the ROM
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_regions: Vec<DataRegion>,
+    /// Address ranges of handwritten code which were parsed without the callee-saved register check when analyzing the ROM
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handwritten_code: Vec<DataRegion>,
+    /// Instruction modes of functions which were forced when analyzing the ROM
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instruction_modes: Vec<InstructionModeOverride>,
+    /// Modules where functions without a recognized return ended at the next symbol when analyzing the ROM
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_epilogue_fallbacks: Vec<NoEpilogueFallback>,
+    /// Overlays which share an address region and are never loaded at the same time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overlay_groups: Vec<ExclusiveOverlayGroup>,
+    /// `.sdata` and `.sbss` sections which were split from `.data` and `.bss` when analyzing the ROM
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub small_data_sections: Vec<SmallDataSection>,
+    /// Linker memory regions which replace or add to the default regions in the generated linker script
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory_regions: Vec<MemoryRegion>,
+    /// Encoding of strings which `dis` decodes into comments, unless overridden by `--encoding`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub string_encoding: Option<StringEncoding>,
+    /// Name prefixes of symbols which haven't been named by the user
+    #[serde(default, skip_serializing_if = "NamePrefixes::is_default")]
+    pub name_prefixes: NamePrefixes,
+    /// Byte order of the code and data, little-endian unless specified
+    #[serde(default, skip_serializing_if = "ConfigEndian::is_little")]
+    pub endian: ConfigEndian,
+    /// Whether the first 0x800 bytes of the ARM9 main module were analyzed as a secure area, false for homebrew ROMs
+    #[serde(default = "Config::default_has_secure_area", skip_serializing_if = "Config::is_default_has_secure_area")]
+    pub has_secure_area: bool,
     pub main_module: ConfigModule,
     pub autoloads: Vec<ConfigAutoload>,
     pub overlays: Vec<ConfigOverlay>,
 }
 
+impl Config {
+    fn unversioned() -> u32 {
+        1
+    }
+
+    fn default_has_secure_area() -> bool {
+        true
+    }
+
+    fn is_default_has_secure_area(has_secure_area: &bool) -> bool {
+        *has_secure_area
+    }
+
+    /// Loads `config.yaml` and checks that it can be used, which every subcommand should do before using the config.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let config = Self::from_file_unchecked(path)?;
+        if config.version > CONFIG_VERSION {
+            bail!(
+                "Config {} has version {}, but this dsd only supports up to version {CONFIG_VERSION}, please update dsd",
+                path.display(),
+                config.version
+            );
+        }
+        if config.version < CONFIG_VERSION {
+            log::warn!(
+                "Config {} has version {}, run `dsd config migrate` to upgrade it to version {CONFIG_VERSION}",
+                path.display(),
+                config.version
+            );
+        }
+        config.name_prefixes.validate().with_context(|| format!("Invalid name prefixes in {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Loads `config.yaml` without checking it, for `dsd config validate` to report every problem on its own.
+    pub fn from_file_unchecked<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        serde_yml::from_reader(open_file(path)?).with_context(|| format!("Failed to parse config {}", path.display()))
+    }
+
+    /// Iterates over the main module, autoloads and overlays, in that order.
+    pub fn modules(&self) -> impl Iterator<Item = (ModuleKind, &ConfigModule)> {
+        iter::once((ModuleKind::Arm9, &self.main_module))
+            .chain(self.autoloads.iter().map(|autoload| (ModuleKind::Autoload(autoload.kind), &autoload.module)))
+            .chain(self.overlays.iter().map(|overlay| (ModuleKind::Overlay(overlay.id), &overlay.module)))
+    }
+
+    /// Returns the base address of an overlay, which is `rom_base_address` unless the overlay was analyzed at another
+    /// base address.
+    pub fn overlay_base_address(&self, id: u16, rom_base_address: u32) -> u32 {
+        self.overlays
+            .iter()
+            .find(|overlay| overlay.id == id)
+            .and_then(|overlay| overlay.base_address)
+            .unwrap_or(rom_base_address)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigEndian {
+    #[default]
+    Little,
+    Big,
+}
+
+impl ConfigEndian {
+    fn is_little(&self) -> bool {
+        *self == Self::Little
+    }
+}
+
+impl From<ConfigEndian> for Endian {
+    fn from(value: ConfigEndian) -> Self {
+        match value {
+            ConfigEndian::Little => Endian::Little,
+            ConfigEndian::Big => Endian::Big,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ConfigModule {
     /// Name of module
@@ -32,6 +172,9 @@ pub struct ConfigModule {
 #[derive(Serialize, Deserialize)]
 pub struct ConfigOverlay {
     pub id: u16,
+    /// Base address which the overlay was analyzed at instead of the one in the ROM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_address: Option<u32>,
     #[serde(flatten)]
     pub module: ConfigModule,
 }
@@ -0,0 +1,122 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use ds_rom::rom::raw::AutoloadKind;
+use serde::{Deserialize, Serialize};
+
+use crate::util::parse::parse_u32;
+
+use super::module::ModuleKind;
+
+/// Address ranges given by the user which function analysis must treat as data, such as encrypted code or compressed
+/// tables.
+pub struct DataRegions {
+    modules: BTreeMap<ModuleKind, BTreeMap<u32, DataRegion>>,
+}
+
+/// Parsed from and written as `<module>:<start>:<end>`, for example `overlay:5:0x020a0000:0x020a0400`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DataRegion {
+    pub module: ModuleKind,
+    pub start: u32,
+    /// Exclusive
+    pub end: u32,
+}
+
+impl DataRegions {
+    pub fn new() -> Self {
+        Self { modules: BTreeMap::new() }
+    }
+
+    pub fn from_regions<'a, I: IntoIterator<Item = &'a DataRegion>>(regions: I) -> Result<Self> {
+        let mut data_regions = Self::new();
+        for region in regions {
+            data_regions.add(*region)?;
+        }
+        Ok(data_regions)
+    }
+
+    /// Adds a region, failing if it overlaps another region in the same module.
+    pub fn add(&mut self, region: DataRegion) -> Result<()> {
+        let module = self.modules.entry(region.module).or_default();
+
+        if let Some((_, prev)) = module.range(..=region.start).next_back() {
+            if prev.end > region.start {
+                bail!("data region {region} overlaps with data region {prev}");
+            }
+        }
+        if let Some((_, next)) = module.range(region.start + 1..).next() {
+            if region.end > next.start {
+                bail!("data region {region} overlaps with data region {next}");
+            }
+        }
+
+        module.insert(region.start, region);
+        Ok(())
+    }
+
+    /// Returns the regions of a module, keyed by start address.
+    pub fn get(&self, module_kind: ModuleKind) -> Option<&BTreeMap<u32, DataRegion>> {
+        self.modules.get(&module_kind)
+    }
+}
+
+impl Default for DataRegions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataRegion {
+    pub fn contains(&self, address: u32) -> bool {
+        (self.start..self.end).contains(&address)
+    }
+}
+
+impl FromStr for DataRegion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.rsplitn(3, ':');
+        let (Some(end), Some(start), Some(module)) = (parts.next(), parts.next(), parts.next()) else {
+            bail!("data region '{s}' must be formatted as <module>:<start>:<end>");
+        };
+
+        let module = ModuleKind::from_str(module).with_context(|| format!("invalid module in data region '{s}'"))?;
+        let start = parse_u32(start).with_context(|| format!("failed to parse start address '{start}'"))?;
+        let end = parse_u32(end).with_context(|| format!("failed to parse end address '{end}'"))?;
+        if end <= start {
+            bail!("end address {end:#010x} of data region must be after start address {start:#010x}");
+        }
+
+        Ok(Self { module, start, end })
+    }
+}
+
+impl TryFrom<String> for DataRegion {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<DataRegion> for String {
+    fn from(value: DataRegion) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for DataRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.module {
+            ModuleKind::Arm9 => write!(f, "main")?,
+            ModuleKind::Autoload(AutoloadKind::Itcm) => write!(f, "itcm")?,
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => write!(f, "dtcm")?,
+            ModuleKind::Autoload(kind) => write!(f, "{kind}")?,
+            ModuleKind::Overlay(id) => write!(f, "overlay:{id}")?,
+        }
+        write!(f, ":{:#010x}:{:#010x}", self.start, self.end)
+    }
+}
@@ -29,6 +29,8 @@ pub struct DelinkFile {
     pub name: String,
     pub sections: Sections,
     pub complete: bool,
+    /// Compiler flags of this translation unit, which override the default flags
+    pub flags: Option<String>,
     gap: bool,
 }
 
@@ -104,6 +106,41 @@ impl Delinks {
         Ok(())
     }
 
+    /// Writes the sections and files back to a delinks file. Gap files are not written, and the file sections only have
+    /// their address range since their kind and alignment are inherited from the module's sections.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+
+        let file = create_file(path)?;
+        let mut writer = BufWriter::new(file);
+
+        for section in self.sections.sorted_by_address() {
+            writeln!(writer, "    {section}")?;
+        }
+        writeln!(writer)?;
+        for file in self.files.iter().filter(|file| !file.gap) {
+            writeln!(writer, "{}:", file.name)?;
+            if file.complete {
+                writeln!(writer, "    complete")?;
+            }
+            if let Some(flags) = &file.flags {
+                writeln!(writer, "    flags: {flags}")?;
+            }
+            for section in file.sections.sorted_by_address() {
+                writeln!(
+                    writer,
+                    "    {:11} start:{:#010x} end:{:#010x}",
+                    section.name(),
+                    section.start_address(),
+                    section.end_address()
+                )?;
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
     pub fn display(&self) -> DisplayDelinks {
         DisplayDelinks { sections: &self.sections, files: &self.files }
     }
@@ -280,7 +317,7 @@ impl<'a> Display for DisplayDelinks<'a> {
 
 impl DelinkFile {
     pub fn new(name: String, sections: Sections, complete: bool) -> Self {
-        Self { name, sections, complete, gap: false }
+        Self { name, sections, complete, flags: None, gap: false }
     }
 
     fn new_gap(module_kind: ModuleKind, id: usize) -> Result<Self> {
@@ -297,7 +334,7 @@ impl DelinkFile {
             },
         };
 
-        Ok(Self { name, sections: Sections::new(), complete: false, gap: true })
+        Ok(Self { name, sections: Sections::new(), complete: false, flags: None, gap: true })
     }
 
     pub fn parse(
@@ -313,6 +350,7 @@ impl DelinkFile {
             .to_string();
 
         let mut complete = false;
+        let mut flags = None;
         let mut sections = Sections::new();
         for line in lines.by_ref() {
             context.row += 1;
@@ -325,11 +363,18 @@ impl DelinkFile {
                 complete = true;
                 continue;
             }
+            if let Some(value) = line.strip_prefix("flags:") {
+                if flags.is_some() {
+                    bail!("{}: flags of '{}' are given more than once", context, name);
+                }
+                flags = Some(value.trim().to_string());
+                continue;
+            }
             let section = Section::parse_inherit(line, context, inherit_sections)?.unwrap();
             sections.add(section)?;
         }
 
-        Ok(DelinkFile { name, sections, complete, gap: false })
+        Ok(DelinkFile { name, sections, complete, flags, gap: false })
     }
 
     pub fn split_file_ext(&self) -> (&str, &str) {
@@ -0,0 +1,121 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::util::{io::open_file, parse::parse_u32};
+
+use super::{module::ModuleKind, symbol::InstructionMode, ParseContext};
+
+/// Function boundaries given by the user, which take precedence over function analysis.
+pub struct FunctionOverrides {
+    modules: BTreeMap<ModuleKind, BTreeMap<u32, FunctionOverride>>,
+}
+
+#[derive(Clone, Copy)]
+pub struct FunctionOverride {
+    pub address: u32,
+    pub mode: InstructionMode,
+    pub end_address: u32,
+}
+
+impl FunctionOverrides {
+    pub fn new() -> Self {
+        Self { modules: BTreeMap::new() }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut context = ParseContext { file_path: path.to_str().unwrap().to_string(), row: 0 };
+
+        let file = open_file(path)?;
+        let reader = BufReader::new(file);
+
+        let mut overrides = Self::new();
+        for line in reader.lines() {
+            context.row += 1;
+
+            let line = line?;
+            let comment_start = line.find("//").unwrap_or(line.len());
+            let line = &line[..comment_start];
+
+            let Some((module_kind, function_override)) = FunctionOverride::parse(line, &context)? else {
+                continue;
+            };
+            overrides.add(module_kind, function_override, &context)?;
+        }
+
+        Ok(overrides)
+    }
+
+    fn add(&mut self, module_kind: ModuleKind, function_override: FunctionOverride, context: &ParseContext) -> Result<()> {
+        let module = self.modules.entry(module_kind).or_default();
+        let address = function_override.address;
+
+        if module.contains_key(&address) {
+            bail!("{context}: function override at {address:#010x} in {module_kind} already exists");
+        }
+        if let Some((_, prev)) = module.range(..address).next_back() {
+            if prev.end_address > address {
+                bail!("{context}: function override at {address:#010x} overlaps with override at {:#010x}", prev.address);
+            }
+        }
+        if let Some((&next_address, _)) = module.range(address + 1..).next() {
+            if function_override.end_address > next_address {
+                bail!("{context}: function override at {address:#010x} overlaps with override at {next_address:#010x}");
+            }
+        }
+
+        module.insert(address, function_override);
+        Ok(())
+    }
+
+    /// Returns the overrides of a module, keyed by function address.
+    pub fn get(&self, module_kind: ModuleKind) -> Option<&BTreeMap<u32, FunctionOverride>> {
+        self.modules.get(&module_kind)
+    }
+}
+
+impl Default for FunctionOverrides {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FunctionOverride {
+    fn parse(line: &str, context: &ParseContext) -> Result<Option<(ModuleKind, Self)>> {
+        let mut words = line.split_whitespace();
+        let Some(address) = words.next() else { return Ok(None) };
+
+        let address = parse_u32(address).with_context(|| format!("{context}: failed to parse address '{address}'"))?;
+        let mode = words.next().with_context(|| format!("{context}: missing instruction mode"))?;
+        let mode = InstructionMode::parse(mode, context)?;
+        let end_address = words.next().with_context(|| format!("{context}: missing end address"))?;
+        let end_address =
+            parse_u32(end_address).with_context(|| format!("{context}: failed to parse end address '{end_address}'"))?;
+        let module_kind = match words.next() {
+            Some(module) => ModuleKind::from_str(module).with_context(|| format!("{context}: invalid module"))?,
+            None => ModuleKind::Arm9,
+        };
+        if let Some(word) = words.next() {
+            bail!("{context}: unexpected '{word}' after function override");
+        }
+
+        if end_address <= address {
+            bail!("{context}: end address {end_address:#010x} must be after function address {address:#010x}");
+        }
+        let alignment = match mode {
+            InstructionMode::Arm => 4,
+            InstructionMode::Thumb => 2,
+        };
+        if address % alignment != 0 {
+            bail!("{context}: {mode} function address {address:#010x} must be aligned by {alignment}");
+        }
+
+        Ok(Some((module_kind, Self { address, mode, end_address })))
+    }
+}
@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use ds_rom::rom::raw::AutoloadKind;
+use serde::{Deserialize, Serialize};
+
+use crate::util::parse::parse_u32;
+
+use super::{module::ModuleKind, symbol::InstructionMode};
+
+/// Instruction modes given by the user for functions whose mode function analysis would guess wrong.
+pub struct InstructionModes {
+    modules: BTreeMap<ModuleKind, BTreeMap<u32, InstructionMode>>,
+}
+
+/// Parsed from and written as `<module>:<address>:<mode>`, for example `overlay:5:0x020a0000:thumb`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InstructionModeOverride {
+    pub module: ModuleKind,
+    pub address: u32,
+    pub mode: InstructionMode,
+}
+
+impl InstructionModes {
+    pub fn new() -> Self {
+        Self { modules: BTreeMap::new() }
+    }
+
+    pub fn from_overrides<'a, I: IntoIterator<Item = &'a InstructionModeOverride>>(overrides: I) -> Result<Self> {
+        let mut instruction_modes = Self::new();
+        for mode_override in overrides {
+            instruction_modes.add(*mode_override)?;
+        }
+        Ok(instruction_modes)
+    }
+
+    /// Adds an override, failing if the address already has one.
+    pub fn add(&mut self, mode_override: InstructionModeOverride) -> Result<()> {
+        let module = self.modules.entry(mode_override.module).or_default();
+        if module.insert(mode_override.address, mode_override.mode).is_some() {
+            bail!("instruction mode of {:#010x} in {} is given more than once", mode_override.address, mode_override.module);
+        }
+        Ok(())
+    }
+
+    /// Returns the instruction modes of a module, keyed by function address.
+    pub fn get(&self, module_kind: ModuleKind) -> Option<&BTreeMap<u32, InstructionMode>> {
+        self.modules.get(&module_kind)
+    }
+}
+
+impl Default for InstructionModes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for InstructionModeOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.rsplitn(3, ':');
+        let (Some(mode), Some(address), Some(module)) = (parts.next(), parts.next(), parts.next()) else {
+            bail!("instruction mode '{s}' must be formatted as <module>:<address>:<arm|thumb>");
+        };
+
+        let module = ModuleKind::from_str(module).with_context(|| format!("invalid module in instruction mode '{s}'"))?;
+        let address = parse_u32(address).with_context(|| format!("failed to parse address '{address}'"))?;
+        let (mode, alignment) = match mode {
+            "arm" => (InstructionMode::Arm, 4),
+            "thumb" => (InstructionMode::Thumb, 2),
+            _ => bail!("expected instruction mode 'arm' or 'thumb' but got '{mode}'"),
+        };
+        if address % alignment != 0 {
+            bail!("{mode} function address {address:#010x} must be aligned by {alignment}");
+        }
+
+        Ok(Self { module, address, mode })
+    }
+}
+
+impl TryFrom<String> for InstructionModeOverride {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<InstructionModeOverride> for String {
+    fn from(value: InstructionModeOverride) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for InstructionModeOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.module {
+            ModuleKind::Arm9 => write!(f, "main")?,
+            ModuleKind::Autoload(AutoloadKind::Itcm) => write!(f, "itcm")?,
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => write!(f, "dtcm")?,
+            ModuleKind::Autoload(kind) => write!(f, "{kind}")?,
+            ModuleKind::Overlay(id) => write!(f, "overlay:{id}")?,
+        }
+        write!(f, ":{:#010x}:{}", self.address, self.mode)
+    }
+}
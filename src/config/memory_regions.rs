@@ -0,0 +1,100 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::util::parse::parse_u32;
+
+/// Linker memory region given by the user. A region named after a module's memory, such as `ARM9`, `ITCM` or `OV005`,
+/// replaces that module's default region in the linker script, and other regions are added as they are. Parsed from and
+/// written as `<name>:<origin>:<length>[:<attributes>]`, for example `ARM9:0x02000000:0x400000:rwx`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MemoryRegion {
+    pub name: String,
+    pub origin: u32,
+    pub length: u32,
+    /// Access attributes like `rwx`, written in parentheses after the region name
+    pub attributes: Option<String>,
+}
+
+impl MemoryRegion {
+    /// Exclusive end address.
+    pub fn end(&self) -> u32 {
+        self.origin + self.length
+    }
+
+    /// Fails if two regions have the same name.
+    pub fn check_unique(regions: &[MemoryRegion]) -> Result<()> {
+        let mut seen = BTreeSet::new();
+        for region in regions {
+            if !seen.insert(region.name.as_str()) {
+                bail!("memory region {} is given more than once", region.name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the region with the given name.
+    pub fn find<'a>(regions: &'a [MemoryRegion], name: &str) -> Option<&'a MemoryRegion> {
+        regions.iter().find(|region| region.name == name)
+    }
+
+    /// Returns the `MEMORY` entry of this region, from the name to the length.
+    pub fn memory_entry(&self) -> String {
+        let attributes = self.attributes.as_ref().map(|attributes| format!(" ({attributes})")).unwrap_or_default();
+        format!("{}{attributes} : ORIGIN = {:#x}, LENGTH = {:#x}", self.name, self.origin, self.length)
+    }
+}
+
+impl FromStr for MemoryRegion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.splitn(4, ':');
+        let (Some(name), Some(origin), Some(length)) = (parts.next(), parts.next(), parts.next()) else {
+            bail!("memory region '{s}' must be formatted as <name>:<origin>:<length>[:<attributes>]");
+        };
+        let attributes = parts.next();
+
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!("invalid memory region name '{name}', must only contain letters, digits and underscores");
+        }
+        let origin = parse_u32(origin).with_context(|| format!("failed to parse origin '{origin}'"))?;
+        let length = parse_u32(length).with_context(|| format!("failed to parse length '{length}'"))?;
+        if length == 0 || origin.checked_add(length).is_none() {
+            bail!("memory region {name} has invalid length {length:#x}");
+        }
+        if let Some(attributes) = attributes {
+            if attributes.is_empty() || !attributes.chars().all(|c| c.is_ascii_alphabetic() || c == '!') {
+                bail!("invalid attributes '{attributes}' of memory region {name}");
+            }
+        }
+
+        Ok(Self { name: name.to_string(), origin, length, attributes: attributes.map(|a| a.to_string()) })
+    }
+}
+
+impl TryFrom<String> for MemoryRegion {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<MemoryRegion> for String {
+    fn from(value: MemoryRegion) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for MemoryRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{:#010x}:{:#x}", self.name, self.origin, self.length)?;
+        if let Some(attributes) = &self.attributes {
+            write!(f, ":{attributes}")?;
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,24 @@
 use std::{fmt::Display, str::SplitWhitespace};
 
 pub mod config;
+pub mod data_regions;
 pub mod delinks;
+pub mod function_overrides;
+pub mod instruction_modes;
+pub mod memory_regions;
 pub mod module;
+pub mod name_prefixes;
+pub mod no_epilogue;
+pub mod overlay_base_addresses;
+pub mod overlay_groups;
 pub mod program;
 pub mod relocation;
 pub mod section;
+pub mod small_data;
+pub mod struct_layouts;
 pub mod symbol;
 
+#[derive(Clone)]
 pub struct ParseContext {
     file_path: String,
     row: usize,
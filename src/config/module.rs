@@ -1,10 +1,12 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     fmt::Display,
+    str::FromStr,
 };
 
 use anyhow::{bail, Context, Result};
 use ds_rom::rom::{raw::AutoloadKind, Arm9, Autoload, Overlay};
+use unarm::Endian;
 
 use crate::{
     analysis::{
@@ -15,14 +17,24 @@ use crate::{
             ParseFunctionResult,
         },
         main::MainFunction,
+        rtti,
     },
     config::section::SectionKind,
+    util::{bytes::FromSlice, parse::parse_u16},
 };
 
 use super::{
-    relocation::Relocations,
+    data_regions::DataRegions,
+    function_overrides::FunctionOverrides,
+    instruction_modes::InstructionModes,
+    name_prefixes::NamePrefixes,
+    no_epilogue::NoEpilogueFallbacks,
+    overlay_base_addresses::OverlayBaseAddresses,
+    overlay_groups::ExclusiveOverlayGroups,
+    relocation::{RelocCommentTemplate, Relocations},
     section::{Section, Sections},
-    symbol::{SymData, SymbolKind, SymbolMap, SymbolMaps},
+    small_data::SmallDataSection,
+    symbol::{OverlappingFunctionsError, SymData, SymbolKind, SymbolMap, SymbolMaps},
 };
 
 pub struct Module<'a> {
@@ -30,6 +42,8 @@ pub struct Module<'a> {
     kind: ModuleKind,
     relocations: Relocations,
     code: &'a [u8],
+    /// Byte order of the code and data
+    endian: Endian,
     base_address: u32,
     bss_size: u32,
     pub default_func_prefix: String,
@@ -38,50 +52,79 @@ pub struct Module<'a> {
 }
 
 impl<'a> Module<'a> {
-    pub fn new_arm9(
+    /// Creates a module of any kind with the given byte order. See [Module::new_arm9], [Module::new_autoload] and
+    /// [Module::new_overlay] for little-endian DS modules.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        kind: ModuleKind,
         name: String,
         symbol_map: &mut SymbolMap,
         relocations: Relocations,
         mut sections: Sections,
         code: &'a [u8],
-    ) -> Result<Module<'a>> {
+        name_prefixes: &NamePrefixes,
+        endian: Endian,
+    ) -> Result<Self> {
         let base_address = sections.base_address().context("no sections provided")?;
         let end_address = sections.end_address().context("no sections provided")?;
         let bss_size = sections.bss_size();
-        Self::import_functions(symbol_map, &mut sections, base_address, end_address, code)?;
+        Self::import_functions(symbol_map, &mut sections, base_address, end_address, code, endian)?;
         Ok(Self {
             name,
-            kind: ModuleKind::Arm9,
+            kind,
             relocations,
             code,
+            endian,
             base_address,
             bss_size,
-            default_func_prefix: "func_".to_string(),
-            default_data_prefix: "data_".to_string(),
+            default_func_prefix: name_prefixes.func_prefix(kind),
+            default_data_prefix: name_prefixes.data_prefix(kind),
             sections,
         })
     }
 
+    pub fn new_arm9(
+        name: String,
+        symbol_map: &mut SymbolMap,
+        relocations: Relocations,
+        sections: Sections,
+        code: &'a [u8],
+        name_prefixes: &NamePrefixes,
+    ) -> Result<Module<'a>> {
+        Self::new(ModuleKind::Arm9, name, symbol_map, relocations, sections, code, name_prefixes, Endian::Little)
+    }
+
     pub fn analyze_arm9(arm9: &'a Arm9, symbol_maps: &mut SymbolMaps, options: &AnalysisOptions) -> Result<Self> {
-        let ctor_range = CtorRange::find_in_arm9(arm9)?;
-        let main_func = MainFunction::find_in_arm9(arm9)?;
+        let instruction_modes = options.instruction_modes.get(ModuleKind::Arm9);
+        let ctor_range = CtorRange::find_in_arm9(arm9, options.endian, instruction_modes)?;
+        let main_func = MainFunction::find_in_arm9(arm9, options.endian, instruction_modes)?;
 
         let mut module = Self {
             name: "main".to_string(),
             kind: ModuleKind::Arm9,
             relocations: Relocations::new(),
             code: arm9.code()?,
+            endian: options.endian,
             base_address: arm9.base_address(),
             bss_size: arm9.bss()?.len() as u32,
-            default_func_prefix: "func_".to_string(),
-            default_data_prefix: "data_".to_string(),
+            default_func_prefix: options.name_prefixes.func_prefix(ModuleKind::Arm9),
+            default_data_prefix: options.name_prefixes.data_prefix(ModuleKind::Arm9),
             sections: Sections::new(),
         };
         let symbol_map = symbol_maps.get_mut(module.kind);
 
-        module.find_sections_arm9(symbol_map, ctor_range, main_func, arm9)?;
+        module.find_sections_arm9(symbol_map, ctor_range, main_func, arm9, options)?;
+        if options.find_pool_function_pointers {
+            module.find_pool_function_pointers(symbol_map, options)?;
+        }
         module.find_data_from_pools(symbol_map, options)?;
         module.find_data_from_sections(symbol_map, options)?;
+        if options.find_vtables {
+            module.find_vtables(symbol_map, options)?;
+        }
+        if options.find_rtti {
+            module.find_type_info(symbol_map, options)?;
+        }
 
         symbol_map.rename_by_address(arm9.entry_function(), "Entry")?;
         symbol_map.rename_by_address(main_func.address, "main")?;
@@ -93,45 +136,68 @@ impl<'a> Module<'a> {
         name: String,
         symbol_map: &mut SymbolMap,
         relocations: Relocations,
-        mut sections: Sections,
+        sections: Sections,
         id: u16,
         code: &'a [u8],
+        name_prefixes: &NamePrefixes,
     ) -> Result<Self> {
-        let base_address = sections.base_address().context("no sections provided")?;
-        let end_address = sections.end_address().context("no sections provided")?;
-        let bss_size = sections.bss_size();
-        Self::import_functions(symbol_map, &mut sections, base_address, end_address, code)?;
-        Ok(Self {
-            name,
-            kind: ModuleKind::Overlay(id),
-            relocations,
-            code,
-            base_address,
-            bss_size,
-            default_func_prefix: format!("func_ov{:03}_", id),
-            default_data_prefix: format!("data_ov{:03}_", id),
-            sections,
-        })
+        Self::new(ModuleKind::Overlay(id), name, symbol_map, relocations, sections, code, name_prefixes, Endian::Little)
     }
 
     pub fn analyze_overlay(overlay: &'a Overlay, symbol_maps: &mut SymbolMaps, options: &AnalysisOptions) -> Result<Self> {
+        Self::analyze_overlay_code(
+            overlay.id(),
+            overlay.code(),
+            overlay.base_address(),
+            overlay.bss_size(),
+            CtorRange { start: overlay.ctor_start(), end: overlay.ctor_end() },
+            symbol_maps,
+            options,
+        )
+    }
+
+    /// Analyzes the code of an overlay. `rom_base_address` and `ctor` are the addresses given by the ROM, which are moved
+    /// to the overlay's base address in [`AnalysisOptions::overlay_base_addresses`] if it has one.
+    pub fn analyze_overlay_code(
+        id: u16,
+        code: &'a [u8],
+        rom_base_address: u32,
+        bss_size: u32,
+        ctor: CtorRange,
+        symbol_maps: &mut SymbolMaps,
+        options: &AnalysisOptions,
+    ) -> Result<Self> {
+        let base_address = options.overlay_base_addresses.get(id, rom_base_address);
+        let ctor =
+            CtorRange { start: ctor.start - rom_base_address + base_address, end: ctor.end - rom_base_address + base_address };
+
         let mut module = Self {
-            name: format!("ov{:03}", overlay.id()),
-            kind: ModuleKind::Overlay(overlay.id()),
+            name: format!("ov{:03}", id),
+            kind: ModuleKind::Overlay(id),
             relocations: Relocations::new(),
-            code: overlay.code(),
-            base_address: overlay.base_address(),
-            bss_size: overlay.bss_size(),
-            default_func_prefix: format!("func_ov{:03}_", overlay.id()),
-            default_data_prefix: format!("data_ov{:03}_", overlay.id()),
+            code,
+            endian: options.endian,
+            base_address,
+            bss_size,
+            default_func_prefix: options.name_prefixes.func_prefix(ModuleKind::Overlay(id)),
+            default_data_prefix: options.name_prefixes.data_prefix(ModuleKind::Overlay(id)),
             sections: Sections::new(),
         };
         let symbol_map = symbol_maps.get_mut(module.kind);
 
-        log::debug!("Analyzing overlay {}", overlay.id());
-        module.find_sections_overlay(symbol_map, CtorRange { start: overlay.ctor_start(), end: overlay.ctor_end() })?;
+        log::debug!("Analyzing overlay {id} at {base_address:#010x}");
+        module.find_sections_overlay(symbol_map, ctor, options)?;
+        if options.find_pool_function_pointers {
+            module.find_pool_function_pointers(symbol_map, options)?;
+        }
         module.find_data_from_pools(symbol_map, options)?;
         module.find_data_from_sections(symbol_map, options)?;
+        if options.find_vtables {
+            module.find_vtables(symbol_map, options)?;
+        }
+        if options.find_rtti {
+            module.find_type_info(symbol_map, options)?;
+        }
 
         Ok(module)
     }
@@ -140,25 +206,12 @@ impl<'a> Module<'a> {
         name: String,
         symbol_map: &mut SymbolMap,
         relocations: Relocations,
-        mut sections: Sections,
+        sections: Sections,
         kind: AutoloadKind,
         code: &'a [u8],
+        name_prefixes: &NamePrefixes,
     ) -> Result<Self> {
-        let base_address = sections.base_address().context("no sections provided")?;
-        let end_address = sections.end_address().context("no sections provided")?;
-        let bss_size = sections.bss_size();
-        Self::import_functions(symbol_map, &mut sections, base_address, end_address, code)?;
-        Ok(Self {
-            name,
-            kind: ModuleKind::Autoload(kind),
-            relocations,
-            code,
-            base_address,
-            bss_size,
-            default_func_prefix: "func_".to_string(),
-            default_data_prefix: "data_".to_string(),
-            sections,
-        })
+        Self::new(ModuleKind::Autoload(kind), name, symbol_map, relocations, sections, code, name_prefixes, Endian::Little)
     }
 
     pub fn analyze_itcm(autoload: &'a Autoload, symbol_maps: &mut SymbolMaps, options: &AnalysisOptions) -> Result<Self> {
@@ -167,15 +220,16 @@ impl<'a> Module<'a> {
             kind: ModuleKind::Autoload(AutoloadKind::Itcm),
             relocations: Relocations::new(),
             code: autoload.code(),
+            endian: options.endian,
             base_address: autoload.base_address(),
             bss_size: autoload.bss_size(),
-            default_func_prefix: "func_".to_string(),
-            default_data_prefix: "data_".to_string(),
+            default_func_prefix: options.name_prefixes.func_prefix(ModuleKind::Autoload(AutoloadKind::Itcm)),
+            default_data_prefix: options.name_prefixes.data_prefix(ModuleKind::Autoload(AutoloadKind::Itcm)),
             sections: Sections::new(),
         };
         let symbol_map = symbol_maps.get_mut(module.kind);
 
-        module.find_sections_itcm(symbol_map)?;
+        module.find_sections_itcm(symbol_map, options)?;
         module.find_data_from_pools(symbol_map, options)?;
 
         Ok(module)
@@ -187,27 +241,74 @@ impl<'a> Module<'a> {
             kind: ModuleKind::Autoload(AutoloadKind::Dtcm),
             relocations: Relocations::new(),
             code: autoload.code(),
+            endian: options.endian,
             base_address: autoload.base_address(),
             bss_size: autoload.bss_size(),
-            default_func_prefix: "func_".to_string(),
-            default_data_prefix: "data_".to_string(),
+            default_func_prefix: options.name_prefixes.func_prefix(ModuleKind::Autoload(AutoloadKind::Dtcm)),
+            default_data_prefix: options.name_prefixes.data_prefix(ModuleKind::Autoload(AutoloadKind::Dtcm)),
             sections: Sections::new(),
         };
         let symbol_map = symbol_maps.get_mut(module.kind);
 
-        module.find_sections_dtcm()?;
+        module.find_sections_dtcm(options)?;
         module.find_data_from_sections(symbol_map, options)?;
 
         Ok(module)
     }
 
+    /// Analyzes a raw code blob which doesn't come from a ROM, such as hand-assembled code or a memory dump. Functions are
+    /// searched from `base_address` and the rest of the blob becomes a .rodata section.
+    pub fn analyze_code_blob(
+        code: &'a [u8],
+        base_address: u32,
+        kind: ModuleKind,
+        symbol_maps: &mut SymbolMaps,
+        options: &AnalysisOptions,
+    ) -> Result<Self> {
+        let mut module = Self {
+            name: "blob".to_string(),
+            kind,
+            relocations: Relocations::new(),
+            code,
+            endian: options.endian,
+            base_address,
+            bss_size: 0,
+            default_func_prefix: options.name_prefixes.func_prefix(kind),
+            default_data_prefix: options.name_prefixes.data_prefix(kind),
+            sections: Sections::new(),
+        };
+        let symbol_map = symbol_maps.get_mut(module.kind);
+
+        module.find_sections_blob(symbol_map, options)?;
+        if options.find_pool_function_pointers {
+            module.find_pool_function_pointers(symbol_map, options)?;
+        }
+        module.find_data_from_pools(symbol_map, options)?;
+        module.find_data_from_sections(symbol_map, options)?;
+        if options.find_vtables {
+            module.find_vtables(symbol_map, options)?;
+        }
+        if options.find_rtti {
+            module.find_type_info(symbol_map, options)?;
+        }
+
+        Ok(module)
+    }
+
     fn import_functions(
         symbol_map: &mut SymbolMap,
         sections: &mut Sections,
         base_address: u32,
         end_address: u32,
         code: &'a [u8],
+        endian: Endian,
     ) -> Result<()> {
+        if let Some(&(first, second)) = symbol_map.overlapping_functions().first() {
+            let error = OverlappingFunctionsError::new(first, second);
+            log::error!("{error}");
+            return Err(error.into());
+        }
+
         for (sym_function, symbol) in symbol_map.clone_functions() {
             if sym_function.unknown {
                 continue;
@@ -222,7 +323,7 @@ impl<'a> Module<'a> {
                 known_end_address: Some(symbol.addr + size),
                 module_start_address: base_address,
                 module_end_address: end_address,
-                parse_options: ParseFunctionOptions { thumb: sym_function.mode.into_thumb() },
+                parse_options: ParseFunctionOptions { thumb: sym_function.mode.into_thumb(), endian, ..Default::default() },
             })?;
             let function = match parse_result {
                 ParseFunctionResult::Found(function) => function,
@@ -238,6 +339,7 @@ impl<'a> Module<'a> {
         &mut self,
         symbol_map: &mut SymbolMap,
         search_options: FunctionSearchOptions,
+        options: &AnalysisOptions,
     ) -> Result<Option<FoundFunctions>> {
         let functions = Function::find_functions(FindFunctionsOptions {
             default_name_prefix: &self.default_func_prefix,
@@ -246,14 +348,27 @@ impl<'a> Module<'a> {
             symbol_map,
             module_start_address: self.base_address,
             module_end_address: self.end_address(),
-            search_options,
+            search_options: FunctionSearchOptions {
+                min_function_size: options.min_function_size,
+                // The main module and ITCM have handwritten SDK code which doesn't follow the calling convention
+                check_defs_uses: options.check_defs_uses
+                    && !matches!(self.kind, ModuleKind::Arm9 | ModuleKind::Autoload(AutoloadKind::Itcm)),
+                relocate_function_branches: options.relocate_function_branches,
+                ..search_options
+            },
+            function_overrides: options.function_overrides.get(self.kind),
+            data_regions: options.data_regions.get(self.kind),
+            handwritten_code: options.handwritten_code.get(self.kind),
+            instruction_modes: options.instruction_modes.get(self.kind),
+            no_epilogue_fallback: options.no_epilogue_fallbacks.get(self.kind),
+            endian: options.endian,
         })?;
 
         if functions.is_empty() {
             Ok(None)
         } else {
             let start = functions.first_key_value().unwrap().1.start_address();
-            let end = functions.last_key_value().unwrap().1.end_address();
+            let end = functions.last_key_value().unwrap().1.padded_end_address();
             Ok(Some(FoundFunctions { functions, start, end }))
         }
     }
@@ -269,7 +384,7 @@ impl<'a> Module<'a> {
         let mut init_functions = InitFunctions(BTreeSet::new());
 
         let mut prev_address = 0;
-        for address in ctor.chunks(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).take_while(|&addr| addr != 0) {
+        for address in ctor.chunks(4).map(|b| u32::from_slice(b, self.endian)).take_while(|&addr| addr != 0) {
             if address < prev_address {
                 // Not in order, abort
 
@@ -296,6 +411,7 @@ impl<'a> Module<'a> {
         ctor: &CtorRange,
         init_functions: InitFunctions,
         continuous: bool,
+        options: &AnalysisOptions,
     ) -> Result<Option<(u32, u32)>> {
         let functions_min = *init_functions.0.first().unwrap();
         let functions_max = *init_functions.0.last().unwrap();
@@ -308,6 +424,7 @@ impl<'a> Module<'a> {
                     function_addresses: Some(init_functions.0),
                     ..Default::default()
                 },
+                options,
             )?
             .with_context(|| {
                 format!(
@@ -348,21 +465,34 @@ impl<'a> Module<'a> {
         Ok(())
     }
 
-    fn add_data_section(&mut self, start: u32, end: u32) -> Result<()> {
+    fn add_data_section(&mut self, start: u32, end: u32, options: &AnalysisOptions) -> Result<()> {
         if start < end {
-            self.sections.add(Section::new(".data".to_string(), SectionKind::Data, start, end, 32)?)?;
+            let section = Section::new(".data".to_string(), SectionKind::Data, start, end, 32)?;
+            self.add_section_with_small_data(section, options)?;
         }
         Ok(())
     }
 
-    fn add_bss_section(&mut self, start: u32) -> Result<()> {
-        self.sections.add(Section::new(".bss".to_string(), SectionKind::Bss, start, start + self.bss_size, 32)?)?;
-        Ok(())
+    fn add_bss_section(&mut self, start: u32, options: &AnalysisOptions) -> Result<()> {
+        let section = Section::new(".bss".to_string(), SectionKind::Bss, start, start + self.bss_size, 32)?;
+        self.add_section_with_small_data(section, options)
+    }
+
+    /// Adds a section, splitting the user-provided `.sdata` or `.sbss` section of this module from it.
+    fn add_section_with_small_data(&mut self, section: Section, options: &AnalysisOptions) -> Result<()> {
+        let small_data = options
+            .small_data_sections
+            .iter()
+            .find(|small_data| small_data.module == self.kind && small_data.kind == section.kind());
+        match small_data {
+            Some(small_data) => self.sections.add_with_small_data(section, small_data),
+            None => self.sections.add(section),
+        }
     }
 
-    fn find_sections_overlay(&mut self, symbol_map: &mut SymbolMap, ctor: CtorRange) -> Result<()> {
+    fn find_sections_overlay(&mut self, symbol_map: &mut SymbolMap, ctor: CtorRange, options: &AnalysisOptions) -> Result<()> {
         let rodata_end = if let Some(init_functions) = self.add_ctor_section(&ctor)? {
-            if let Some((init_start, _)) = self.add_init_section(symbol_map, &ctor, init_functions, true)? {
+            if let Some((init_start, _)) = self.add_init_section(symbol_map, &ctor, init_functions, true, options)? {
                 init_start
             } else {
                 ctor.start
@@ -374,6 +504,7 @@ impl<'a> Module<'a> {
         let rodata_start = if let Some(functions_result) = self.find_functions(
             symbol_map,
             FunctionSearchOptions { end_address: Some(rodata_end), use_data_as_upper_bound: true, ..Default::default() },
+            options,
         )? {
             let end = functions_result.end;
             self.add_text_section(functions_result)?;
@@ -386,8 +517,8 @@ impl<'a> Module<'a> {
 
         let data_start = ctor.end.next_multiple_of(32);
         let data_end = self.base_address + self.code.len() as u32;
-        self.add_data_section(data_start, data_end)?;
-        self.add_bss_section(data_end)?;
+        self.add_data_section(data_start, data_end, options)?;
+        self.add_bss_section(data_end, options)?;
 
         Ok(())
     }
@@ -398,10 +529,11 @@ impl<'a> Module<'a> {
         ctor: CtorRange,
         main_func: MainFunction,
         arm9: &Arm9,
+        options: &AnalysisOptions,
     ) -> Result<()> {
         // .ctor and .init
         let (read_only_end, rodata_start) = if let Some(init_functions) = self.add_ctor_section(&ctor)? {
-            if let Some(init_range) = self.add_init_section(symbol_map, &ctor, init_functions, false)? {
+            if let Some(init_range) = self.add_init_section(symbol_map, &ctor, init_functions, false, options)? {
                 (init_range.0, Some(init_range.1))
             } else {
                 (ctor.start, None)
@@ -411,10 +543,6 @@ impl<'a> Module<'a> {
         };
         let has_init_section = read_only_end != ctor.start;
 
-        // Secure area functions (software interrupts)
-        let secure_area = &self.code[..0x800];
-        let mut functions = Function::find_secure_area_functions(secure_area, self.base_address, symbol_map);
-
         // Build info
         let build_info_offset = arm9.build_info_offset();
         let build_info_address = arm9.base_address() + build_info_offset;
@@ -430,27 +558,19 @@ impl<'a> Module<'a> {
             known_end_address: None,
             module_start_address: self.base_address,
             module_end_address: self.end_address(),
-            parse_options: Default::default(),
+            parse_options: ParseFunctionOptions {
+                instruction_modes: options.instruction_modes.get(self.kind),
+                ..Default::default()
+            },
         })? {
             ParseFunctionResult::Found(function) => function,
             ParseFunctionResult::IllegalIns { .. } => bail!("Illegal instruction in autoload callback"),
             ParseFunctionResult::NoEpilogue => bail!("No epilogue in autoload callback"),
             ParseFunctionResult::InvalidStart { .. } => bail!("Autoload callback has an invalid start instruction"),
         };
-        symbol_map.add_function(&autoload_function);
+        symbol_map.add_function(&autoload_function)?;
 
-        // Entry functions
-        let FoundFunctions { functions: entry_functions, .. } = self
-            .find_functions(
-                symbol_map,
-                FunctionSearchOptions {
-                    start_address: Some(self.base_address + 0x800),
-                    end_address: Some(build_info_address),
-                    ..Default::default()
-                },
-            )?
-            .context("Entry functions not found")?;
-        functions.extend(entry_functions);
+        let mut functions = self.find_entry_functions(symbol_map, build_info_address, options)?;
 
         // All other functions, starting from main
         let FoundFunctions { functions: text_functions, end: mut text_end, .. } = self
@@ -464,6 +584,7 @@ impl<'a> Module<'a> {
                     use_data_as_upper_bound: true,
                     ..Default::default()
                 },
+                options,
             )?
             .context("No functions in ARM9 main module")?;
         if text_end != read_only_end && has_init_section {
@@ -483,14 +604,46 @@ impl<'a> Module<'a> {
         // .data and .bss
         let data_start = ctor.end.next_multiple_of(32);
         let data_end = self.base_address + self.code.len() as u32;
-        self.add_data_section(data_start, data_end)?;
+        self.add_data_section(data_start, data_end, options)?;
         let bss_start = data_end.next_multiple_of(32);
-        self.add_bss_section(bss_start)?;
+        self.add_bss_section(bss_start, options)?;
 
         Ok(())
     }
 
-    fn find_sections_itcm(&mut self, symbol_map: &mut SymbolMap) -> Result<()> {
+    /// Finds the secure area functions (software interrupts) and the entry functions which follow the secure area, up to
+    /// `end_address`. Without a secure area, such as in homebrew, its bytes are searched like any other code.
+    pub fn find_entry_functions(
+        &mut self,
+        symbol_map: &mut SymbolMap,
+        end_address: u32,
+        options: &AnalysisOptions,
+    ) -> Result<BTreeMap<u32, Function>> {
+        let (mut functions, start_address) = if options.has_secure_area {
+            let secure_area = &self.code[..SECURE_AREA_SIZE as usize];
+            let functions =
+                Function::find_secure_area_functions(secure_area, self.base_address, symbol_map, options.arm9_bios_calls);
+            (functions, self.base_address + SECURE_AREA_SIZE)
+        } else {
+            (BTreeMap::new(), self.base_address)
+        };
+
+        let FoundFunctions { functions: entry_functions, .. } = self
+            .find_functions(
+                symbol_map,
+                FunctionSearchOptions {
+                    start_address: Some(start_address),
+                    end_address: Some(end_address),
+                    ..Default::default()
+                },
+                options,
+            )?
+            .context("Entry functions not found")?;
+        functions.extend(entry_functions);
+        Ok(functions)
+    }
+
+    fn find_sections_itcm(&mut self, symbol_map: &mut SymbolMap, options: &AnalysisOptions) -> Result<()> {
         let text_functions = self
             .find_functions(
                 symbol_map,
@@ -499,24 +652,44 @@ impl<'a> Module<'a> {
                     keep_searching_for_valid_function_start: true,
                     ..Default::default()
                 },
+                options,
             )?
             .context("No functions in ITCM")?;
         let text_end = text_functions.end;
         self.add_text_section(text_functions)?;
 
         let bss_start = text_end.next_multiple_of(32);
-        self.add_bss_section(bss_start)?;
+        self.add_bss_section(bss_start, options)?;
 
         Ok(())
     }
 
-    fn find_sections_dtcm(&mut self) -> Result<()> {
+    fn find_sections_dtcm(&mut self, options: &AnalysisOptions) -> Result<()> {
         let data_start = self.base_address;
         let data_end = data_start + self.code.len() as u32;
-        self.add_data_section(data_start, data_end)?;
+        self.add_data_section(data_start, data_end, options)?;
 
         let bss_start = data_end.next_multiple_of(32);
-        self.add_bss_section(bss_start)?;
+        self.add_bss_section(bss_start, options)?;
+
+        Ok(())
+    }
+
+    fn find_sections_blob(&mut self, symbol_map: &mut SymbolMap, options: &AnalysisOptions) -> Result<()> {
+        let rodata_start = if let Some(FoundFunctions { functions, start, end }) = self.find_functions(
+            symbol_map,
+            FunctionSearchOptions { use_data_as_upper_bound: true, ..Default::default() },
+            options,
+        )? {
+            // The blob can start at any word, so don't require the usual .text alignment
+            self.sections.add(Section::with_functions(".text".to_string(), SectionKind::Code, start, end, 4, functions)?)?;
+            end
+        } else {
+            self.base_address
+        };
+
+        let rodata_end = self.base_address + self.code.len() as u32;
+        self.add_rodata_section(rodata_start, rodata_end)?;
 
         Ok(())
     }
@@ -569,7 +742,7 @@ impl<'a> Module<'a> {
                         .peekable();
                     let mut gaps = vec![];
                     while let Some(symbol) = symbols.next() {
-                        if symbol.addr >= 0x2000000 && symbol.addr < 0x2000800 {
+                        if options.has_secure_area && symbol.addr >= 0x2000000 && symbol.addr < 0x2000000 + SECURE_AREA_SIZE {
                             // Secure area gaps are just random bytes
                             continue;
                         }
@@ -606,6 +779,229 @@ impl<'a> Module<'a> {
         Ok(())
     }
 
+    /// Finds vtables in data sections, which are runs of at least [`MIN_VTABLE_ENTRIES`] function pointers, optionally
+    /// preceded by a header word. Entries are relocated, and functions which are only reachable through a vtable are
+    /// added to the module.
+    pub fn find_vtables(&mut self, symbol_map: &mut SymbolMap, options: &AnalysisOptions) -> Result<()> {
+        let mut section_words = vec![];
+        for section in self.sections.iter().filter(|section| section.kind() == SectionKind::Data) {
+            let code = section.code(self.code, self.base_address)?.unwrap();
+            section_words.push(
+                section.iter_words(code, None, options.endian).map(|word| (word.address, word.value)).collect::<Vec<_>>(),
+            );
+        }
+
+        for words in section_words {
+            let mut entries = vec![];
+            for (address, pointer) in words {
+                match self.find_vtable_entry(symbol_map, pointer, options)? {
+                    Some(target) => entries.push(VtableEntry { address, pointer, target }),
+                    None => self.add_vtable(symbol_map, std::mem::take(&mut entries), options)?,
+                }
+            }
+            self.add_vtable(symbol_map, entries, options)?;
+        }
+        Ok(())
+    }
+
+    /// Finds pool constants which point to the start of a function that isn't known yet, and adds those functions to the
+    /// module. Constants in the pools of the new functions are followed too. The pool constants themselves are relocated
+    /// later by [`Self::find_data_from_pools`].
+    pub fn find_pool_function_pointers(&mut self, symbol_map: &mut SymbolMap, options: &AnalysisOptions) -> Result<()> {
+        let mut pointers = self
+            .sections
+            .functions()
+            .flat_map(|function| function.iter_pool_constants(self.code, self.base_address, options.endian))
+            .map(|constant| constant.value)
+            .collect::<Vec<_>>();
+
+        while let Some(pointer) = pointers.pop() {
+            let Some(VtableTarget::New(function)) = self.find_vtable_entry(symbol_map, pointer, options)? else { continue };
+            log::debug!("Found function {} through pool constant {pointer:#010x}", function.name());
+            pointers.extend(
+                function.iter_pool_constants(self.code, self.base_address, options.endian).map(|constant| constant.value),
+            );
+            symbol_map.add_function(&function)?;
+            function.add_local_symbols_to_map(symbol_map)?;
+            self.sections.add_function(function);
+        }
+        Ok(())
+    }
+
+    /// Returns the function which `pointer` leads to, if it points to the start of a function with the same instruction
+    /// mode. If no function is there yet, a new function is parsed.
+    fn find_vtable_entry(
+        &self,
+        symbol_map: &SymbolMap,
+        pointer: u32,
+        options: &AnalysisOptions,
+    ) -> Result<Option<VtableTarget>> {
+        let Some((_, section)) = self.sections.get_by_contained_address(pointer) else { return Ok(None) };
+        if section.kind() != SectionKind::Code {
+            return Ok(None);
+        }
+        let thumb = (pointer & 1) != 0;
+        let address = pointer & !1;
+        if !thumb && (address & 3) != 0 {
+            return Ok(None);
+        }
+
+        if let Some((function, _)) = symbol_map.get_function(address)? {
+            return Ok((function.mode.into_thumb() == Some(thumb)).then_some(VtableTarget::Known));
+        }
+        if symbol_map.get_function_containing(address).is_some() {
+            return Ok(None);
+        }
+
+        let parse = |known_end_address: Option<u32>| {
+            Function::parse_function(FunctionParseOptions {
+                name: format!("{}{:08x}", self.default_func_prefix, address),
+                start_address: address,
+                base_address: self.base_address,
+                module_code: self.code,
+                known_end_address,
+                module_start_address: self.base_address,
+                module_end_address: self.end_address(),
+                parse_options: ParseFunctionOptions { thumb: Some(thumb), endian: options.endian, ..Default::default() },
+            })
+        };
+        let ParseFunctionResult::Found(mut function) = parse(None)? else { return Ok(None) };
+        let next_function = symbol_map
+            .iter_by_address(address + 1..section.end_address())
+            .find(|symbol| matches!(symbol.kind, SymbolKind::Function(_)));
+        if let Some(next_function) = next_function {
+            if function.end_address() > next_function.addr {
+                let ParseFunctionResult::Found(cut_function) = parse(Some(next_function.addr))? else { return Ok(None) };
+                function = cut_function;
+            }
+        }
+        Ok(Some(VtableTarget::New(function)))
+    }
+
+    fn add_vtable(&mut self, symbol_map: &mut SymbolMap, entries: Vec<VtableEntry>, options: &AnalysisOptions) -> Result<()> {
+        // Require a known function so that runs of code addresses in random data are less likely to become vtables
+        if entries.len() < MIN_VTABLE_ENTRIES || !entries.iter().any(|entry| matches!(entry.target, VtableTarget::Known)) {
+            return Ok(());
+        }
+
+        // The vtable may start with a non-pointer header word, such as a pointer to type info, which is referenced
+        // instead of the first entry
+        let first_entry = entries[0].address;
+        let header = first_entry - 4;
+        let has_header = symbol_map.by_address(first_entry)?.is_none()
+            && self.sections.get_by_contained_address(header).is_some_and(|(_, section)| section.kind() == SectionKind::Data)
+            && symbol_map.by_address(header)?.is_some();
+        let address = if has_header { header } else { first_entry };
+        let name = match self.kind {
+            ModuleKind::Overlay(id) => format!("vtable_ov{id:03}_{address:08x}"),
+            ModuleKind::Arm9 | ModuleKind::Autoload(_) => format!("vtable_{address:08x}"),
+        };
+        log::debug!("Found vtable {name} with {} entries", entries.len());
+
+        let num_words = entries.len() as u32 + has_header as u32;
+        for entry in entries {
+            if let VtableTarget::New(function) = entry.target {
+                if symbol_map.get_function_containing(function.first_instruction_address()).is_none() {
+                    log::debug!("Found function {} through vtable {name}", function.name());
+                    symbol_map.add_function(&function)?;
+                    function.add_local_symbols_to_map(symbol_map)?;
+                    data::find_local_data_from_pools(
+                        &function,
+                        FindLocalDataOptions {
+                            sections: &self.sections,
+                            module_kind: self.kind,
+                            symbol_map,
+                            relocations: &mut self.relocations,
+                            name_prefix: &self.default_data_prefix,
+                            code: self.code,
+                            base_address: self.base_address,
+                            address_range: None,
+                        },
+                        options,
+                    )?;
+                    self.sections.add_function(function);
+                }
+            }
+            if self.relocations.get(entry.address).is_none() {
+                let relocation = self.relocations.add_load(entry.address, entry.pointer, 0, self.kind.try_into()?)?;
+                relocation.source = options.reloc_source(|| format!("entry in {name}"));
+            }
+        }
+
+        self.name_data_symbol(symbol_map, address, name, SymData::Word { count: Some(num_words) })
+    }
+
+    /// Finds mwcc type info blocks in data sections, which are a pointer to the NUL-terminated type name followed by a
+    /// pointer to the base class list, or 0 if there are no base classes. Only blocks which already have a data symbol are
+    /// considered, so that other pointers to strings are less likely to become type info.
+    pub fn find_type_info(&mut self, symbol_map: &mut SymbolMap, options: &AnalysisOptions) -> Result<()> {
+        let mut blocks = vec![];
+        for section in self.sections.iter().filter(|section| section.kind() == SectionKind::Data) {
+            let code = section.code(self.code, self.base_address)?.unwrap();
+            let words = section.iter_words(code, None, options.endian).collect::<Vec<_>>();
+            blocks.extend(words.windows(2).map(|pair| (pair[0].address, pair[0].value, pair[1].value)));
+        }
+
+        for (address, name_pointer, bases_pointer) in blocks {
+            if !symbol_map.by_address(address)?.is_some_and(|(_, symbol)| matches!(symbol.kind, SymbolKind::Data(_))) {
+                continue;
+            }
+            if bases_pointer != 0 && self.data_section_bytes(bases_pointer).is_none() {
+                continue;
+            }
+            let Some(type_name) = self.data_section_bytes(name_pointer).and_then(rtti::read_type_name) else { continue };
+            let Some(demangled) = rtti::demangle_type_name(type_name) else { continue };
+            let name_len = type_name.len() as u32;
+
+            let suffix = match self.kind {
+                ModuleKind::Overlay(id) => format!("ov{id:03}_{demangled}"),
+                ModuleKind::Arm9 | ModuleKind::Autoload(_) => demangled,
+            };
+            let mut name = format!("typeinfo_{suffix}");
+            let mut string_name = format!("typename_{suffix}");
+            if symbol_map.by_name(&name)?.is_some() || symbol_map.by_name(&string_name)?.is_some() {
+                name = format!("{name}_{address:08x}");
+                string_name = format!("{string_name}_{address:08x}");
+            }
+            log::debug!("Found type info {name} for type '{type_name}'");
+
+            for (from, pointer) in [(address, name_pointer), (address + 4, bases_pointer)] {
+                if pointer != 0 && self.relocations.get(from).is_none() {
+                    let relocation = self.relocations.add_load(from, pointer, 0, self.kind.try_into()?)?;
+                    relocation.source = options.reloc_source(|| format!("field of {name}"));
+                }
+            }
+            self.name_data_symbol(symbol_map, name_pointer, string_name, SymData::Byte { count: Some(name_len + 1) })?;
+            self.name_data_symbol(symbol_map, address, name, SymData::Word { count: Some(2) })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes from `address` to the end of its data section, if `address` is in a data section.
+    fn data_section_bytes(&self, address: u32) -> Option<&'a [u8]> {
+        let (_, section) = self.sections.get_by_contained_address(address)?;
+        if section.kind() != SectionKind::Data {
+            return None;
+        }
+        let start = (address - self.base_address) as usize;
+        let end = (section.end_address() - self.base_address) as usize;
+        self.code.get(start..end)
+    }
+
+    /// Names the data symbol at `address`, or adds one if there is none. Symbols named by the user are kept.
+    fn name_data_symbol(&self, symbol_map: &mut SymbolMap, address: u32, name: String, data: SymData) -> Result<()> {
+        // Rename the symbol if it was generated by data analysis, otherwise keep the name
+        let default_name = format!("{}{:08x}", self.default_data_prefix, address);
+        match symbol_map.by_address(address)? {
+            Some((_, symbol)) if symbol.name == default_name => symbol_map.rename_by_address(address, &name)?,
+            Some(_) => {}
+            None => {
+                symbol_map.add_data(Some(name), address, data)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn relocations(&self) -> &Relocations {
         &self.relocations
     }
@@ -622,6 +1018,10 @@ impl<'a> Module<'a> {
         &mut self.sections
     }
 
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
     pub fn code(&self) -> &[u8] {
         self.code
     }
@@ -676,6 +1076,58 @@ impl ModuleKind {
             ModuleKind::Overlay(id) => 4 + id as usize,
         }
     }
+
+    /// Returns whether this is the ITCM or DTCM, which stay loaded at a fixed address range for the whole runtime.
+    pub fn is_tcm(self) -> bool {
+        matches!(self, ModuleKind::Autoload(AutoloadKind::Itcm | AutoloadKind::Dtcm))
+    }
+
+    /// Name prefix of functions which haven't been named by the user.
+    pub fn default_func_prefix(self) -> String {
+        NamePrefixes::default().func_prefix(self)
+    }
+
+    /// Name prefix of data/bss which haven't been named by the user.
+    pub fn default_data_prefix(self) -> String {
+        NamePrefixes::default().data_prefix(self)
+    }
+
+    /// Parses the name of a module's section in a linked ELF file, such as `ARM9`, `ITCM` or `ov012`. Returns `None` if the
+    /// section doesn't belong to a module.
+    pub fn from_elf_section_name(name: &str) -> Result<Option<Self>> {
+        if name == "ARM9" {
+            Ok(Some(ModuleKind::Arm9))
+        } else if name == "ITCM" {
+            Ok(Some(ModuleKind::Autoload(AutoloadKind::Itcm)))
+        } else if name == "DTCM" {
+            Ok(Some(ModuleKind::Autoload(AutoloadKind::Dtcm)))
+        } else if let Some(overlay_number) = name.strip_prefix("ov") {
+            let overlay_id = overlay_number.parse()?;
+            Ok(Some(ModuleKind::Overlay(overlay_id)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl FromStr for ModuleKind {
+    type Err = anyhow::Error;
+
+    /// Parses `main`, `itcm`, `dtcm` or `overlay:N`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "main" | "arm9" => Ok(ModuleKind::Arm9),
+            "itcm" => Ok(ModuleKind::Autoload(AutoloadKind::Itcm)),
+            "dtcm" => Ok(ModuleKind::Autoload(AutoloadKind::Dtcm)),
+            _ => {
+                let Some(id) = s.strip_prefix("overlay:") else {
+                    bail!("unknown module '{s}', must be one of: main, itcm, dtcm, overlay:N");
+                };
+                let id = parse_u16(id).with_context(|| format!("failed to parse overlay ID '{id}'"))?;
+                Ok(ModuleKind::Overlay(id))
+            }
+        }
+    }
 }
 
 impl Display for ModuleKind {
@@ -688,6 +1140,24 @@ impl Display for ModuleKind {
     }
 }
 
+/// Size of the secure area at the start of the ARM9 main module in retail ROMs.
+pub const SECURE_AREA_SIZE: u32 = 0x800;
+
+/// Minimum number of function pointers in a row to be considered a vtable.
+pub const MIN_VTABLE_ENTRIES: usize = 2;
+
+struct VtableEntry {
+    address: u32,
+    pointer: u32,
+    target: VtableTarget,
+}
+
+enum VtableTarget {
+    Known,
+    /// Function which is only known through a vtable or pool constant so far
+    New(Function),
+}
+
 /// Sorted list of .init function addresses
 struct InitFunctions(BTreeSet<u32>);
 
@@ -695,6 +1165,80 @@ pub struct AnalysisOptions {
     /// Generates function symbols when a local function call doesn't lead to a known function. This can happen if the
     /// destination function is encrypted or otherwise wasn't found during function analysis.
     pub allow_unknown_function_calls: bool,
-    /// If true, every relocation in relocs.txt will have a comment explaining where/why it was generated.
-    pub provide_reloc_source: bool,
+    /// If set, every relocation in relocs.txt will have a comment explaining where/why it was generated, formatted by this
+    /// template.
+    pub reloc_comment: Option<RelocCommentTemplate>,
+    /// User-provided function boundaries which take precedence over function analysis.
+    pub function_overrides: FunctionOverrides,
+    /// User-provided address ranges which are never parsed as code.
+    pub data_regions: DataRegions,
+    /// User-provided address ranges of handwritten code, which may read callee-saved registers without saving them.
+    pub handwritten_code: DataRegions,
+    /// Ends functions which read a callee-saved register before saving or writing it, outside the main module, ITCM and
+    /// [`Self::handwritten_code`]. See [`FunctionSearchOptions::check_defs_uses`].
+    pub check_defs_uses: bool,
+    /// Records every function branch as a call and relocates conditional calls. Off by default, since mwldarm drops the
+    /// condition code of relocated calls, so only enable it if the linker keeps it.
+    pub relocate_function_branches: bool,
+    /// User-provided instruction modes of functions, which take precedence over guessing the mode.
+    pub instruction_modes: InstructionModes,
+    /// Modules where functions without a recognized return end at the next symbol, instead of ending function analysis.
+    pub no_epilogue_fallbacks: NoEpilogueFallbacks,
+    /// User-provided groups of overlays which are never loaded at the same time.
+    pub overlay_groups: ExclusiveOverlayGroups,
+    /// User-provided base addresses of overlays, which take precedence over the base addresses in the ROM.
+    pub overlay_base_addresses: OverlayBaseAddresses,
+    /// User-provided `.sdata` and `.sbss` sections, which are split from `.data` and `.bss`.
+    pub small_data_sections: Vec<SmallDataSection>,
+    /// Byte order of the code being analyzed.
+    pub endian: Endian,
+    /// Name prefixes of generated function and data symbols.
+    pub name_prefixes: NamePrefixes,
+    /// Finds vtables in data sections and adds the functions they point to.
+    pub find_vtables: bool,
+    /// Finds and names mwcc type info blocks in data sections.
+    pub find_rtti: bool,
+    /// Adds functions which are only reachable through a function pointer in a literal pool.
+    pub find_pool_function_pointers: bool,
+    /// Minimum size of discovered functions, see [`FunctionSearchOptions::min_function_size`].
+    pub min_function_size: u32,
+    /// Whether the ARM9 main module starts with a secure area, which retail ROMs do but homebrew may not.
+    pub has_secure_area: bool,
+    /// Prefixes the BIOS call functions in the secure area with `SVC_`, like the SDK names them.
+    pub arm9_bios_calls: bool,
+}
+
+impl Default for AnalysisOptions {
+    /// Options of `dsd init` without any flags or user-provided files, for a retail ROM.
+    fn default() -> Self {
+        Self {
+            allow_unknown_function_calls: false,
+            reloc_comment: None,
+            function_overrides: FunctionOverrides::new(),
+            data_regions: DataRegions::new(),
+            handwritten_code: DataRegions::new(),
+            check_defs_uses: false,
+            relocate_function_branches: false,
+            instruction_modes: InstructionModes::new(),
+            no_epilogue_fallbacks: NoEpilogueFallbacks::new(),
+            overlay_groups: ExclusiveOverlayGroups::new(),
+            overlay_base_addresses: OverlayBaseAddresses::new(),
+            small_data_sections: vec![],
+            endian: Endian::Little,
+            name_prefixes: NamePrefixes::default(),
+            find_vtables: false,
+            find_rtti: false,
+            find_pool_function_pointers: false,
+            min_function_size: 0,
+            has_secure_area: true,
+            arm9_bios_calls: false,
+        }
+    }
+}
+
+impl AnalysisOptions {
+    /// Returns the relocation comment for the explanation from `source`, or `None` if relocations aren't annotated.
+    pub fn reloc_source<F: FnOnce() -> String>(&self, source: F) -> Option<String> {
+        self.reloc_comment.as_ref().map(|template| template.format(&source()))
+    }
 }
@@ -0,0 +1,124 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use super::module::ModuleKind;
+
+const OVERLAY_PLACEHOLDER: &str = "{overlay}";
+/// Default name prefix of labels, see [`NamePrefixes::label_prefix`].
+pub const DEFAULT_LABEL_PREFIX: &str = "_";
+
+/// Templates for the name prefixes of symbols which haven't been named by the user. In overlay templates, `{overlay}` is
+/// replaced by the zero-padded overlay ID.
+#[derive(Args, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct NamePrefixes {
+    /// Name prefix of functions in the main module and autoloads.
+    #[arg(long, default_value = "func_")]
+    pub func_prefix: String,
+
+    /// Name prefix of data/bss in the main module and autoloads.
+    #[arg(long, default_value = "data_")]
+    pub data_prefix: String,
+
+    /// Name prefix of functions in overlays, `{overlay}` is replaced by the overlay ID.
+    #[arg(long, default_value = "func_ov{overlay}_")]
+    pub overlay_func_prefix: String,
+
+    /// Name prefix of data/bss in overlays, `{overlay}` is replaced by the overlay ID.
+    #[arg(long, default_value = "data_ov{overlay}_")]
+    pub overlay_data_prefix: String,
+
+    /// Name prefix of labels, pool constants, jump tables and unnamed data, which is followed by the address. May start
+    /// with a dot, like `.L_`.
+    #[arg(long, default_value = "_")]
+    pub label_prefix: String,
+}
+
+impl Default for NamePrefixes {
+    fn default() -> Self {
+        Self {
+            func_prefix: "func_".to_string(),
+            data_prefix: "data_".to_string(),
+            overlay_func_prefix: "func_ov{overlay}_".to_string(),
+            overlay_data_prefix: "data_ov{overlay}_".to_string(),
+            label_prefix: DEFAULT_LABEL_PREFIX.to_string(),
+        }
+    }
+}
+
+impl NamePrefixes {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Name prefix of functions in the given module.
+    pub fn func_prefix(&self, module_kind: ModuleKind) -> String {
+        match module_kind {
+            ModuleKind::Overlay(id) => Self::expand(&self.overlay_func_prefix, id),
+            ModuleKind::Arm9 | ModuleKind::Autoload(_) => self.func_prefix.clone(),
+        }
+    }
+
+    /// Name prefix of data/bss in the given module.
+    pub fn data_prefix(&self, module_kind: ModuleKind) -> String {
+        match module_kind {
+            ModuleKind::Overlay(id) => Self::expand(&self.overlay_data_prefix, id),
+            ModuleKind::Arm9 | ModuleKind::Autoload(_) => self.data_prefix.clone(),
+        }
+    }
+
+    /// Returns whether `name` starts with the function or data prefix of the given module, like `func_02001234`.
+    pub fn is_default_name(&self, name: &str, module_kind: ModuleKind) -> bool {
+        name.starts_with(&self.func_prefix(module_kind)) || name.starts_with(&self.data_prefix(module_kind))
+    }
+
+    fn expand(template: &str, overlay_id: u16) -> String {
+        template.replace(OVERLAY_PLACEHOLDER, &format!("{overlay_id:03}"))
+    }
+
+    /// Checks that the templates generate legal identifiers, and that the generated names can't collide across symbol
+    /// kinds or overlays.
+    pub fn validate(&self) -> Result<()> {
+        for (option, template, is_overlay) in [
+            ("func_prefix", &self.func_prefix, false),
+            ("data_prefix", &self.data_prefix, false),
+            ("overlay_func_prefix", &self.overlay_func_prefix, true),
+            ("overlay_data_prefix", &self.overlay_data_prefix, true),
+            ("label_prefix", &self.label_prefix, false),
+        ] {
+            // Labels are local, so they may use the assembler's local label prefix
+            let allow_dot = option == "label_prefix";
+            let has_placeholder = template.contains(OVERLAY_PLACEHOLDER);
+            if is_overlay && !has_placeholder {
+                bail!("Name prefix {option} '{template}' must contain {OVERLAY_PLACEHOLDER} to keep overlay symbols unique");
+            }
+            if !is_overlay && has_placeholder {
+                bail!("Name prefix {option} '{template}' can't contain {OVERLAY_PLACEHOLDER}, it's not used in overlays");
+            }
+
+            let expanded = template.replace(OVERLAY_PLACEHOLDER, "000");
+            let Some(first) = expanded.chars().next() else {
+                bail!("Name prefix {option} can't be empty");
+            };
+            if !(first.is_ascii_alphabetic() || first == '_' || (allow_dot && first == '.')) {
+                bail!("Name prefix {option} '{template}' must start with a letter or underscore");
+            }
+            if let Some(c) = expanded.chars().find(|&c| !(c.is_ascii_alphanumeric() || c == '_' || (allow_dot && c == '.'))) {
+                bail!("Name prefix {option} '{template}' contains illegal character '{c}'");
+            }
+        }
+
+        if self.func_prefix == self.data_prefix {
+            bail!("Name prefixes func_prefix and data_prefix must be different");
+        }
+        if self.overlay_func_prefix == self.overlay_data_prefix {
+            bail!("Name prefixes overlay_func_prefix and overlay_data_prefix must be different");
+        }
+        if self.label_prefix == self.func_prefix || self.label_prefix == self.data_prefix {
+            bail!("Name prefix label_prefix must be different from func_prefix and data_prefix");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use ds_rom::rom::raw::AutoloadKind;
+use serde::{Deserialize, Serialize};
+
+use crate::util::parse::parse_u32;
+
+use super::module::ModuleKind;
+
+/// Modules where a function without a recognized return ends at the next symbol, instead of ending function analysis.
+/// Meant for handwritten functions like infinite dispatch loops.
+pub struct NoEpilogueFallbacks {
+    modules: BTreeMap<ModuleKind, u32>,
+}
+
+/// Parsed from and written as `<module>:<max distance>`, for example `overlay:5:0x100`. The function only ends at the next
+/// symbol if it's at most `max_distance` bytes after the function start.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NoEpilogueFallback {
+    pub module: ModuleKind,
+    pub max_distance: u32,
+}
+
+impl NoEpilogueFallbacks {
+    pub fn new() -> Self {
+        Self { modules: BTreeMap::new() }
+    }
+
+    pub fn from_fallbacks<'a, I: IntoIterator<Item = &'a NoEpilogueFallback>>(fallbacks: I) -> Result<Self> {
+        let mut no_epilogue_fallbacks = Self::new();
+        for fallback in fallbacks {
+            no_epilogue_fallbacks.add(*fallback)?;
+        }
+        Ok(no_epilogue_fallbacks)
+    }
+
+    /// Adds a fallback, failing if the module already has one.
+    pub fn add(&mut self, fallback: NoEpilogueFallback) -> Result<()> {
+        if self.modules.insert(fallback.module, fallback.max_distance).is_some() {
+            bail!("no-epilogue fallback of {} is given more than once", fallback.module);
+        }
+        Ok(())
+    }
+
+    /// Returns the max distance from a function start to the next symbol in a module, if the module has a fallback.
+    pub fn get(&self, module_kind: ModuleKind) -> Option<u32> {
+        self.modules.get(&module_kind).copied()
+    }
+}
+
+impl Default for NoEpilogueFallbacks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for NoEpilogueFallback {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((module, max_distance)) = s.rsplit_once(':') else {
+            bail!("no-epilogue fallback '{s}' must be formatted as <module>:<max distance>");
+        };
+
+        let module = ModuleKind::from_str(module).with_context(|| format!("invalid module in no-epilogue fallback '{s}'"))?;
+        let max_distance =
+            parse_u32(max_distance).with_context(|| format!("failed to parse max distance '{max_distance}'"))?;
+
+        Ok(Self { module, max_distance })
+    }
+}
+
+impl TryFrom<String> for NoEpilogueFallback {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<NoEpilogueFallback> for String {
+    fn from(value: NoEpilogueFallback) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for NoEpilogueFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.module {
+            ModuleKind::Arm9 => write!(f, "main")?,
+            ModuleKind::Autoload(AutoloadKind::Itcm) => write!(f, "itcm")?,
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => write!(f, "dtcm")?,
+            ModuleKind::Autoload(kind) => write!(f, "{kind}")?,
+            ModuleKind::Overlay(id) => write!(f, "overlay:{id}")?,
+        }
+        write!(f, ":{:#x}", self.max_distance)
+    }
+}
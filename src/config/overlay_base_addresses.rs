@@ -0,0 +1,96 @@
+use std::{collections::BTreeMap, fmt::Display, ops::Range, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+
+use crate::util::parse::{parse_u16, parse_u32};
+
+use super::module::ModuleKind;
+
+/// Base addresses given by the user for overlays which are loaded elsewhere than what the ROM says, such as overlays
+/// relocated by a patch.
+pub struct OverlayBaseAddresses {
+    addresses: BTreeMap<u16, u32>,
+}
+
+/// Parsed from `<overlay ID>=<address>`, for example `5=0x02200000`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OverlayBaseAddress {
+    pub id: u16,
+    pub address: u32,
+}
+
+impl OverlayBaseAddresses {
+    pub fn new() -> Self {
+        Self { addresses: BTreeMap::new() }
+    }
+
+    pub fn from_overrides<'a, I: IntoIterator<Item = &'a OverlayBaseAddress>>(overrides: I) -> Result<Self> {
+        let mut base_addresses = Self::new();
+        for base_address in overrides {
+            base_addresses.add(*base_address)?;
+        }
+        Ok(base_addresses)
+    }
+
+    /// Adds a base address, failing if the overlay already has one.
+    pub fn add(&mut self, base_address: OverlayBaseAddress) -> Result<()> {
+        if self.addresses.insert(base_address.id, base_address.address).is_some() {
+            bail!("base address of overlay {} is given more than once", base_address.id);
+        }
+        Ok(())
+    }
+
+    /// Returns the base address of an overlay, which is `rom_base_address` unless it was overridden.
+    pub fn get(&self, id: u16, rom_base_address: u32) -> u32 {
+        self.addresses.get(&id).copied().unwrap_or(rom_base_address)
+    }
+
+    /// Checks that every overridden overlay, which occupies `size` bytes from its base address, doesn't overlap with the
+    /// main module or an autoload. Overlays may overlap with each other as they aren't loaded at the same time.
+    pub fn check_overlaps<F>(&self, overlay_size: F, modules: &[(ModuleKind, Range<u32>)]) -> Result<()>
+    where
+        F: Fn(u16) -> Option<u32>,
+    {
+        for (&id, &base_address) in &self.addresses {
+            let size = overlay_size(id).with_context(|| format!("overlay {id} has a base address but doesn't exist"))?;
+            let range = base_address..base_address + size;
+            for (module_kind, module_range) in modules.iter().filter(|(kind, _)| !matches!(kind, ModuleKind::Overlay(_))) {
+                if range.start < module_range.end && module_range.start < range.end {
+                    bail!(
+                        "overlay {id} at {:#010x}..{:#010x} overlaps with {module_kind} at {:#010x}..{:#010x}",
+                        range.start,
+                        range.end,
+                        module_range.start,
+                        module_range.end
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for OverlayBaseAddresses {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for OverlayBaseAddress {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((id, address)) = s.split_once('=') else {
+            bail!("overlay base address '{s}' must be formatted as <overlay ID>=<address>");
+        };
+        let id = parse_u16(id.trim()).with_context(|| format!("failed to parse overlay ID '{id}'"))?;
+        let address = parse_u32(address.trim()).with_context(|| format!("failed to parse base address '{address}'"))?;
+        Ok(Self { id, address })
+    }
+}
+
+impl Display for OverlayBaseAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={:#010x}", self.id, self.address)
+    }
+}
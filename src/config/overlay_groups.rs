@@ -0,0 +1,130 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::util::parse::parse_u16;
+
+use super::module::ModuleKind;
+
+/// Groups of overlays given by the user which share an address region and are never loaded at the same time.
+pub struct ExclusiveOverlayGroups {
+    groups: Vec<ExclusiveOverlayGroup>,
+    /// Maps overlay ID to index in `groups`
+    group_indices: BTreeMap<u16, usize>,
+}
+
+/// Parsed from and written as comma-separated overlay IDs, for example `1,2,5`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "Vec<u16>", into = "Vec<u16>")]
+pub struct ExclusiveOverlayGroup {
+    /// Sorted, at least two overlays
+    overlays: Vec<u16>,
+}
+
+impl ExclusiveOverlayGroups {
+    pub fn new() -> Self {
+        Self { groups: vec![], group_indices: BTreeMap::new() }
+    }
+
+    pub fn from_groups<'a, I: IntoIterator<Item = &'a ExclusiveOverlayGroup>>(groups: I) -> Result<Self> {
+        let mut overlay_groups = Self::new();
+        for group in groups {
+            overlay_groups.add(group.clone())?;
+        }
+        Ok(overlay_groups)
+    }
+
+    /// Adds a group, failing if one of its overlays is already in another group.
+    pub fn add(&mut self, group: ExclusiveOverlayGroup) -> Result<()> {
+        if let Some(&id) = group.overlays.iter().find(|id| self.group_indices.contains_key(id)) {
+            let other = &self.groups[self.group_indices[&id]];
+            bail!("overlay {id} of overlay group {group} is already in overlay group {other}");
+        }
+
+        let index = self.groups.len();
+        self.group_indices.extend(group.overlays.iter().map(|&id| (id, index)));
+        self.groups.push(group);
+        Ok(())
+    }
+
+    /// Returns true if the two modules are different overlays in the same group, meaning that one can never refer to the
+    /// other.
+    pub fn are_exclusive(&self, a: ModuleKind, b: ModuleKind) -> bool {
+        let (ModuleKind::Overlay(a), ModuleKind::Overlay(b)) = (a, b) else { return false };
+        if a == b {
+            return false;
+        }
+        match (self.group_indices.get(&a), self.group_indices.get(&b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ExclusiveOverlayGroup> {
+        self.groups.iter()
+    }
+}
+
+impl Default for ExclusiveOverlayGroups {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExclusiveOverlayGroup {
+    pub fn new(mut overlays: Vec<u16>) -> Result<Self> {
+        overlays.sort_unstable();
+        if overlays.len() < 2 {
+            bail!("overlay group must have two or more overlays, but got {overlays:?}");
+        }
+        if let Some(pair) = overlays.windows(2).find(|pair| pair[0] == pair[1]) {
+            bail!("overlay {} is listed more than once in overlay group {overlays:?}", pair[0]);
+        }
+        Ok(Self { overlays })
+    }
+
+    pub fn overlays(&self) -> &[u16] {
+        &self.overlays
+    }
+}
+
+impl FromStr for ExclusiveOverlayGroup {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let overlays = s
+            .split(',')
+            .map(|id| {
+                parse_u16(id.trim()).with_context(|| format!("failed to parse overlay ID '{id}' in overlay group '{s}'"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(overlays)
+    }
+}
+
+impl TryFrom<Vec<u16>> for ExclusiveOverlayGroup {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Vec<u16>) -> Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl From<ExclusiveOverlayGroup> for Vec<u16> {
+    fn from(value: ExclusiveOverlayGroup) -> Self {
+        value.overlays
+    }
+}
+
+impl Display for ExclusiveOverlayGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, id) in self.overlays.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{id}")?;
+        }
+        Ok(())
+    }
+}
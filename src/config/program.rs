@@ -2,10 +2,7 @@ use std::ops::Range;
 
 use anyhow::{bail, Result};
 
-use crate::{
-    analysis::data::{self, AnalyzeExternalReferencesOptions, RelocationResult, SymbolCandidate},
-    function,
-};
+use crate::analysis::data::{self, AnalyzeExternalReferencesOptions, RelocationResult, SymbolCandidate};
 
 use super::{
     module::{AnalysisOptions, Module},
@@ -45,10 +42,7 @@ impl<'a> Program<'a> {
 
             let module_relocations = self.modules[module_index].relocations_mut();
             for reloc in relocations {
-                let reloc = module_relocations.add(reloc)?;
-                if options.provide_reloc_source {
-                    reloc.source = Some(function!().to_string());
-                }
+                module_relocations.add(reloc)?;
             }
 
             for symbol in external_symbols {
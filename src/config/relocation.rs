@@ -1,15 +1,16 @@
 use std::{
-    collections::{btree_map, BTreeMap},
+    collections::{btree_map, BTreeMap, BTreeSet},
     fmt::Display,
     io::{BufRead, BufReader, BufWriter, Write},
     iter,
     ops::Range,
     path::Path,
+    str::FromStr,
 };
 
 use anyhow::{bail, Context, Result};
 use ds_rom::rom::raw::AutoloadKind;
-use object::elf::{R_ARM_ABS32, R_ARM_PC24, R_ARM_THM_PC22, R_ARM_XPC25};
+use object::elf::{R_ARM_ABS32, R_ARM_ALU_PC_G0, R_ARM_PC24, R_ARM_THM_PC22, R_ARM_THM_PC8, R_ARM_XPC25};
 
 use crate::util::{
     io::{create_file, open_file},
@@ -17,8 +18,10 @@ use crate::util::{
 };
 
 use super::{
+    config::Config,
     iter_attributes,
     module::{Module, ModuleKind},
+    symbol::{InstructionMode, SymbolKind, SymbolMaps},
     ParseContext,
 };
 
@@ -31,8 +34,23 @@ impl Relocations {
         Self { relocations: BTreeMap::new() }
     }
 
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let path = path.as_ref();
+    /// Loads relocations from a `relocs.txt` file. Relocations with a symbol name as destination are resolved using
+    /// `symbol_maps`.
+    pub fn from_file<P: AsRef<Path>>(path: P, symbol_maps: &SymbolMaps) -> Result<Self> {
+        Self::load_file(path.as_ref(), symbol_maps, None)
+    }
+
+    /// Loads relocations like [`Self::from_file`], but doesn't stop at lines which fail to parse. Their errors are pushed
+    /// to `errors` instead.
+    pub fn from_file_lenient<P: AsRef<Path>>(
+        path: P,
+        symbol_maps: &SymbolMaps,
+        errors: &mut Vec<anyhow::Error>,
+    ) -> Result<Self> {
+        Self::load_file(path.as_ref(), symbol_maps, Some(errors))
+    }
+
+    fn load_file(path: &Path, symbol_maps: &SymbolMaps, mut errors: Option<&mut Vec<anyhow::Error>>) -> Result<Self> {
         let mut context = ParseContext { file_path: path.to_str().unwrap().to_string(), row: 0 };
 
         let file = open_file(path)?;
@@ -43,12 +61,23 @@ impl Relocations {
             context.row += 1;
 
             let line = line?;
-            let comment_start = line.find("//").unwrap_or(line.len());
-            let line = &line[..comment_start];
+            let (line, source) = match line.find("//") {
+                Some(comment_start) => (&line[..comment_start], line[comment_start..].strip_prefix(SOURCE_COMMENT_MARKER)),
+                None => (line.as_str(), None),
+            };
 
-            let Some(relocation) = Relocation::parse(line, &context)? else {
-                continue;
+            let mut relocation = match (Relocation::parse(line, &context, symbol_maps), errors.as_deref_mut()) {
+                (Ok(Some(relocation)), _) => relocation,
+                (Ok(None), _) => continue,
+                (Err(error), Some(errors)) => {
+                    errors.push(error);
+                    continue;
+                }
+                (Err(error), None) => return Err(error),
             };
+            // Keep the source comment so it survives rewriting the file, but it has no effect on the relocation. Other
+            // comments are ignored.
+            relocation.source = source.map(str::trim).filter(|source| !source.is_empty()).map(|source| source.to_string());
             relocations.insert(relocation.from, relocation);
         }
 
@@ -109,6 +138,17 @@ impl Relocations {
         self.add(Relocation::new_load(from, to, addend, module))
     }
 
+    pub fn add_adr(
+        &mut self,
+        from: u32,
+        to: u32,
+        addend: i32,
+        module: RelocationModule,
+        thumb: bool,
+    ) -> Result<&mut Relocation> {
+        self.add(Relocation::new_adr(from, to, addend, module, thumb))
+    }
+
     pub fn get(&self, from: u32) -> Option<&Relocation> {
         self.relocations.get(&from)
     }
@@ -122,22 +162,137 @@ impl Relocations {
     }
 }
 
-#[derive(PartialEq, Eq)]
+/// Where every relocation in a config comes from, grouped by destination. Used to tell which symbols are referenced
+/// from outside of their own file.
+pub struct RelocationSources {
+    sources: BTreeMap<(ModuleKind, u32), Vec<(ModuleKind, u32)>>,
+    /// Addresses which are referenced by something other than relocations, like the ROM header or overlay table
+    roots: BTreeSet<(ModuleKind, u32)>,
+    /// Destinations of relocations to an unknown module, which may be in any module
+    unknown_module_targets: BTreeSet<u32>,
+}
+
+impl RelocationSources {
+    pub fn new() -> Self {
+        Self { sources: BTreeMap::new(), roots: BTreeSet::new(), unknown_module_targets: BTreeSet::new() }
+    }
+
+    pub fn from_config<P: AsRef<Path>>(config_path: P, config: &Config, symbol_maps: &SymbolMaps) -> Result<Self> {
+        let config_path = config_path.as_ref();
+
+        let mut sources = Self::new();
+        for (module_kind, module) in config.modules() {
+            let relocations = Relocations::from_file(config_path.join(&module.relocations), symbol_maps)?;
+            sources.add_module(module_kind, &relocations);
+        }
+        Ok(sources)
+    }
+
+    pub fn add_module(&mut self, module_kind: ModuleKind, relocations: &Relocations) {
+        for relocation in relocations.iter() {
+            let Some(first_module) = relocation.module.first_module() else {
+                self.unknown_module_targets.insert(relocation.to);
+                continue;
+            };
+            let to_modules = iter::once(first_module).chain(relocation.module.other_modules().into_iter().flatten());
+            for to_module in to_modules {
+                self.sources.entry((to_module, relocation.to)).or_default().push((module_kind, relocation.from));
+            }
+        }
+    }
+
+    /// Marks `address` in `module_kind` as referenced from outside of any module, so that it's never considered
+    /// unreferenced.
+    pub fn add_root(&mut self, module_kind: ModuleKind, address: u32) {
+        self.roots.insert((module_kind, address));
+    }
+
+    /// Returns true if `address` in `module_kind` may be referenced from outside of `ranges` in `module_kind`. This is the
+    /// case for roots, destinations of relocations to an unknown module, and destinations of relocations from outside of
+    /// `ranges`.
+    pub fn is_referenced_outside(&self, module_kind: ModuleKind, address: u32, ranges: &[Range<u32>]) -> bool {
+        if self.roots.contains(&(module_kind, address)) || self.unknown_module_targets.contains(&address) {
+            return true;
+        }
+        let Some(sources) = self.sources.get(&(module_kind, address)) else { return false };
+        sources
+            .iter()
+            .any(|&(from_module, from)| from_module != module_kind || !ranges.iter().any(|range| range.contains(&from)))
+    }
+}
+
+const SOURCE_PLACEHOLDER: &str = "{source}";
+/// Starts the comment which explains where a relocation was generated, see [`Relocation::source`].
+const SOURCE_COMMENT_MARKER: &str = "// source:";
+
+/// Template of the comments which explain where a relocation was generated, where `{source}` is replaced by the
+/// explanation, like `call from func_02001234`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RelocCommentTemplate(String);
+
+impl Default for RelocCommentTemplate {
+    fn default() -> Self {
+        Self(SOURCE_PLACEHOLDER.to_string())
+    }
+}
+
+impl FromStr for RelocCommentTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if !s.contains(SOURCE_PLACEHOLDER) {
+            bail!("relocation comment template '{s}' must contain {SOURCE_PLACEHOLDER}");
+        }
+        if s.contains(['\n', '\r']) {
+            bail!("relocation comment template can't contain line breaks");
+        }
+        if s.trim() != s {
+            bail!("relocation comment template '{s}' can't start or end with whitespace");
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl RelocCommentTemplate {
+    pub fn format(&self, source: &str) -> String {
+        self.0.replace(SOURCE_PLACEHOLDER, source)
+    }
+}
+
 pub struct Relocation {
     from: u32,
     to: u32,
     addend: i32,
     kind: RelocationKind,
     module: RelocationModule,
+    /// Destination symbol name, if the relocation was specified by name rather than address
+    to_name: Option<String>,
+    /// Comment explaining where/why the relocation was generated, written after the relocation in relocs.txt as a
+    /// `// source:` comment
     pub source: Option<String>,
 }
 
+impl PartialEq for Relocation {
+    fn eq(&self, other: &Self) -> bool {
+        // The source comment is not part of the relocation itself
+        self.from == other.from
+            && self.to == other.to
+            && self.addend == other.addend
+            && self.kind == other.kind
+            && self.module == other.module
+            && self.to_name == other.to_name
+    }
+}
+
+impl Eq for Relocation {}
+
 impl Relocation {
-    fn parse(line: &str, context: &ParseContext) -> Result<Option<Self>> {
+    fn parse(line: &str, context: &ParseContext, symbol_maps: &SymbolMaps) -> Result<Option<Self>> {
         let words = line.split_whitespace();
 
         let mut from = None;
         let mut to = None;
+        let mut to_name = None;
         let mut addend = 0;
         let mut kind = None;
         let mut module = None;
@@ -149,9 +304,14 @@ impl Relocation {
                     )
                 }
                 "to" => {
-                    to = Some(
-                        parse_u32(value).with_context(|| format!("{context}: failed to parse \"to\" address '{value}'"))?,
-                    )
+                    if value.starts_with(|c: char| c.is_ascii_digit()) {
+                        to = Some(
+                            parse_u32(value)
+                                .with_context(|| format!("{context}: failed to parse \"to\" address '{value}'"))?,
+                        )
+                    } else {
+                        to_name = Some(value.to_string())
+                    }
                 }
                 "add" => {
                     addend =
@@ -164,11 +324,56 @@ impl Relocation {
         }
 
         let from = from.with_context(|| format!("{}: missing 'from' attribute", context))?;
-        let to = to.with_context(|| format!("{}: missing 'to' attribute", context))?;
         let kind = kind.with_context(|| format!("{}: missing 'kind' attribute", context))?;
         let module = module.with_context(|| format!("{}: missing 'module' attribute", context))?;
+        let to = match &to_name {
+            Some(name) => Self::resolve_name(name, kind, &module, context, symbol_maps)?,
+            None => to.with_context(|| format!("{}: missing 'to' attribute", context))?,
+        };
+
+        Ok(Some(Self { from, to, addend, kind, module, to_name, source: None }))
+    }
 
-        Ok(Some(Self { from, to, addend, kind, module, source: None }))
+    /// Resolves a destination symbol name in every destination module. Ambiguous relocations must point to the same
+    /// address in all of their modules, since only one of them will be chosen when delinking.
+    fn resolve_name(
+        name: &str,
+        kind: RelocationKind,
+        module: &RelocationModule,
+        context: &ParseContext,
+        symbol_maps: &SymbolMaps,
+    ) -> Result<u32> {
+        let Some(first_module) = module.first_module() else {
+            bail!("{context}: relocation to symbol '{name}' must have a destination module other than 'none'");
+        };
+
+        let mut resolved: Option<(ModuleKind, u32)> = None;
+        for module_kind in iter::once(first_module).chain(module.other_modules().into_iter().flatten()) {
+            let symbol_map =
+                symbol_maps.get(module_kind).with_context(|| format!("{context}: no symbol map found for {module_kind}"))?;
+            let (_, symbol) = symbol_map
+                .by_name(name)
+                .with_context(|| format!("{context}: failed to resolve relocation to symbol '{name}'"))?
+                .with_context(|| {
+                    format!("{context}: relocation to symbol '{name}' but no such symbol exists in {module_kind}")
+                })?;
+
+            // Pointers to Thumb functions have the Thumb bit set
+            let thumb_bit = match symbol.kind {
+                SymbolKind::Function(function) if kind == RelocationKind::Load && function.mode == InstructionMode::Thumb => 1,
+                _ => 0,
+            };
+            let address = symbol.addr | thumb_bit;
+
+            match resolved {
+                None => resolved = Some((module_kind, address)),
+                Some((first_kind, first_address)) if first_address != address => bail!(
+                    "{context}: symbol '{name}' is at {first_address:#010x} in {first_kind} but at {address:#010x} in {module_kind}"
+                ),
+                Some(_) => {}
+            }
+        }
+        Ok(resolved.unwrap().1)
     }
 
     pub fn new_call(from: u32, to: u32, module: RelocationModule, from_thumb: bool, to_thumb: bool) -> Self {
@@ -183,16 +388,27 @@ impl Relocation {
                 (false, false) => RelocationKind::ArmCall,
             },
             module,
+            to_name: None,
             source: None,
         }
     }
 
     pub fn new_branch(from: u32, to: u32, module: RelocationModule) -> Self {
-        Self { from, to, addend: 0, kind: RelocationKind::ArmBranch, module, source: None }
+        Self { from, to, addend: 0, kind: RelocationKind::ArmBranch, module, to_name: None, source: None }
     }
 
     pub fn new_load(from: u32, to: u32, addend: i32, module: RelocationModule) -> Self {
-        Self { from, to, addend, kind: RelocationKind::Load, module, source: None }
+        Self { from, to, addend, kind: RelocationKind::Load, module, to_name: None, source: None }
+    }
+
+    pub fn new_adr(from: u32, to: u32, addend: i32, module: RelocationModule, thumb: bool) -> Self {
+        let kind = if thumb { RelocationKind::ThumbAdr } else { RelocationKind::ArmAdr };
+        Self { from, to, addend, kind, module, to_name: None, source: None }
+    }
+
+    pub fn with_source(mut self, source: Option<String>) -> Self {
+        self.source = source;
+        self
     }
 
     pub fn from_address(&self) -> u32 {
@@ -218,9 +434,14 @@ impl Relocation {
 
 impl Display for Relocation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "from:{:#010x} kind:{} to:{:#010x} module:{}", self.from, self.kind, self.to, self.module)?;
+        write!(f, "from:{:#010x} kind:{} ", self.from, self.kind)?;
+        match &self.to_name {
+            Some(name) => write!(f, "to:{name}")?,
+            None => write!(f, "to:{:#010x}", self.to)?,
+        }
+        write!(f, " module:{}", self.module)?;
         if let Some(source) = &self.source {
-            write!(f, " // {source}")?;
+            write!(f, " {SOURCE_COMMENT_MARKER} {source}")?;
         }
         Ok(())
     }
@@ -234,6 +455,8 @@ pub enum RelocationKind {
     ThumbCallArm,
     ArmBranch,
     Load,
+    ArmAdr,
+    ThumbAdr,
 }
 
 impl RelocationKind {
@@ -245,8 +468,10 @@ impl RelocationKind {
             "thumb_call_arm" => Ok(Self::ThumbCallArm),
             "arm_branch" => Ok(Self::ArmBranch),
             "load" => Ok(Self::Load),
+            "arm_adr" => Ok(Self::ArmAdr),
+            "thumb_adr" => Ok(Self::ThumbAdr),
             _ => bail!(
-                "{}: unknown relocation kind '{}', must be one of: arm_call, thumb_call, arm_call_thumb, thumb_call_arm, arm_branch, load",
+                "{}: unknown relocation kind '{}', must be one of: arm_call, thumb_call, arm_call_thumb, thumb_call_arm, arm_branch, load, arm_adr, thumb_adr",
                 context,
                 text
             ),
@@ -261,6 +486,8 @@ impl RelocationKind {
             Self::ThumbCallArm => object::SymbolKind::Text,
             Self::ArmBranch => object::SymbolKind::Text,
             Self::Load => object::SymbolKind::Data,
+            Self::ArmAdr => object::SymbolKind::Data,
+            Self::ThumbAdr => object::SymbolKind::Data,
         }
     }
 
@@ -274,6 +501,8 @@ impl RelocationKind {
             Self::ThumbCallArm => R_ARM_THM_PC22,
             Self::ArmBranch => R_ARM_PC24,
             Self::Load => R_ARM_ABS32,
+            Self::ArmAdr => R_ARM_ALU_PC_G0,
+            Self::ThumbAdr => R_ARM_THM_PC8,
         }
     }
 
@@ -285,6 +514,8 @@ impl RelocationKind {
             Self::ThumbCallArm => -4,
             Self::ArmBranch => -8,
             Self::Load => 0,
+            Self::ArmAdr => -8,
+            Self::ThumbAdr => -4,
         }
     }
 }
@@ -298,6 +529,8 @@ impl Display for RelocationKind {
             Self::ThumbCallArm => write!(f, "thumb_call_arm"),
             Self::ArmBranch => write!(f, "arm_branch"),
             Self::Load => write!(f, "load"),
+            Self::ArmAdr => write!(f, "arm_adr"),
+            Self::ThumbAdr => write!(f, "thumb_adr"),
         }
     }
 }
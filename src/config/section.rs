@@ -6,6 +6,7 @@ use std::{
 
 use anyhow::{bail, Context, Result};
 use object::{Object, ObjectSymbol};
+use unarm::Endian;
 
 use crate::{
     analysis::functions::Function,
@@ -16,6 +17,7 @@ use super::{
     iter_attributes,
     module::Module,
     relocation::{Relocation, RelocationKind},
+    small_data::SmallDataSection,
     ParseContext,
 };
 
@@ -135,6 +137,20 @@ impl Section {
         )?))
     }
 
+    /// Moves the start and end of this section, keeping its kind and alignment.
+    pub fn set_range(&mut self, start_address: u32, end_address: u32) -> Result<()> {
+        let name = &self.name;
+        if end_address < start_address {
+            bail!("Section {name} must not end ({end_address:#010x}) before it starts ({start_address:#010x})");
+        }
+        if (start_address & (self.alignment - 1)) != 0 {
+            bail!("Section {name} starts at a misaligned address {start_address:#010x}; the alignment is {}", self.alignment);
+        }
+        self.start_address = start_address;
+        self.end_address = end_address;
+        Ok(())
+    }
+
     pub fn code_from_module<'a>(&'a self, module: &'a Module) -> Result<Option<&'a [u8]>> {
         self.code(module.code(), module.base_address())
     }
@@ -188,6 +204,22 @@ impl Section {
                     // R_ARM_ABS32
                     &[0x00, 0x00, 0x00, 0x00]
                 }
+                RelocationKind::ArmAdr => {
+                    // R_ARM_ALU_PC_G0, keeps the condition and destination register
+                    let ins = u32::from_slice(&code[offset..], module.endian());
+                    let ins = (ins & 0xf000f000) | 0x028f0000; // add rN, pc, #0
+                    let bytes = match module.endian() {
+                        Endian::Little => ins.to_le_bytes(),
+                        Endian::Big => ins.to_be_bytes(),
+                    };
+                    code[offset..offset + 4].copy_from_slice(&bytes);
+                    continue;
+                }
+                RelocationKind::ThumbAdr => {
+                    // R_ARM_THM_PC8, keeps the destination register
+                    code[offset] = 0x00; // add rN, pc, #0
+                    continue;
+                }
             };
             code[offset..offset + 4].copy_from_slice(ins);
         }
@@ -205,7 +237,12 @@ impl Section {
 
     /// Iterates over every 32-bit word in the specified `range`, which defaults to the entire section if it is `None`. Note
     /// that `code` must be the full raw content of this section.
-    pub fn iter_words<'a>(&'a self, code: &'a [u8], range: Option<Range<u32>>) -> impl Iterator<Item = Word> + 'a {
+    pub fn iter_words<'a>(
+        &'a self,
+        code: &'a [u8],
+        range: Option<Range<u32>>,
+        endian: Endian,
+    ) -> impl Iterator<Item = Word> + 'a {
         let range = range.unwrap_or(self.address_range());
         let start = range.start.next_multiple_of(4);
         let end = range.end & !3;
@@ -213,7 +250,7 @@ impl Section {
         (start..end).step_by(4).map(move |address| {
             let offset = address - self.start_address;
             let bytes = &code[offset as usize..];
-            Word { address, value: u32::from_le_slice(bytes) }
+            Word { address, value: u32::from_slice(bytes, endian) }
         })
     }
 
@@ -279,7 +316,7 @@ impl Display for Section {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum SectionKind {
     Code,
     Data,
@@ -303,6 +340,23 @@ impl SectionKind {
             SectionKind::Bss => false,
         }
     }
+
+    /// Returns the name of the small data section of this kind, which is addressed relative to a base register.
+    pub fn small_section_name(self) -> Option<&'static str> {
+        match self {
+            SectionKind::Code => None,
+            SectionKind::Data => Some(".sdata"),
+            SectionKind::Bss => Some(".sbss"),
+        }
+    }
+
+    pub fn from_small_section_name(name: &str) -> Option<Self> {
+        match name {
+            ".sdata" => Some(SectionKind::Data),
+            ".sbss" => Some(SectionKind::Bss),
+            _ => None,
+        }
+    }
 }
 
 impl Display for SectionKind {
@@ -317,6 +371,9 @@ impl Display for SectionKind {
 
 type SectionIndex = usize;
 
+/// Alignment of small data sections, and of the rest of a section which follows one
+const SMALL_DATA_ALIGNMENT: u32 = 4;
+
 pub struct Sections {
     sections: Vec<Section>,
     sections_by_name: HashMap<String, SectionIndex>,
@@ -343,6 +400,35 @@ impl Sections {
         Ok(())
     }
 
+    /// Adds a section with a small data section split from it. The small data section must be at the start or end of
+    /// the section, since the rest of the section can't be split into two sections of the same name.
+    pub fn add_with_small_data(&mut self, section: Section, small_data: &SmallDataSection) -> Result<()> {
+        if small_data.kind != section.kind {
+            bail!("{} section can't be split from {} section '{}'", small_data.name(), section.kind, section.name);
+        }
+        if small_data.start < section.start_address || small_data.end > section.end_address {
+            bail!(
+                "{small_data} is outside of section '{}' ({:#010x}..{:#010x})",
+                section.name,
+                section.start_address,
+                section.end_address
+            );
+        }
+        if small_data.start > section.start_address && small_data.end < section.end_address {
+            bail!("{small_data} must be at the start or end of section '{}'", section.name);
+        }
+
+        let Section { name, kind, start_address, end_address, alignment, .. } = section;
+        if start_address < small_data.start {
+            self.add(Section::new(name.clone(), kind, start_address, small_data.start, alignment)?)?;
+        }
+        self.add(Section::new(small_data.name().to_string(), kind, small_data.start, small_data.end, SMALL_DATA_ALIGNMENT)?)?;
+        if small_data.end < end_address {
+            self.add(Section::new(name, kind, small_data.end, end_address, SMALL_DATA_ALIGNMENT)?)?;
+        }
+        Ok(())
+    }
+
     pub fn get(&self, index: usize) -> &Section {
         &self.sections[index]
     }
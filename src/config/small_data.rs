@@ -0,0 +1,91 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use ds_rom::rom::raw::AutoloadKind;
+use serde::{Deserialize, Serialize};
+
+use crate::util::parse::parse_u32;
+
+use super::{module::ModuleKind, section::SectionKind};
+
+/// Small data section (`.sdata` or `.sbss`) given by the user, which is split from the `.data` or `.bss` section of its
+/// module. Parsed from and written as `<module>:<section>:<start>:<end>`, for example
+/// `main:.sdata:0x020c0000:0x020c0100`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SmallDataSection {
+    pub module: ModuleKind,
+    /// [`SectionKind::Data`] for `.sdata` and [`SectionKind::Bss`] for `.sbss`
+    pub kind: SectionKind,
+    pub start: u32,
+    /// Exclusive
+    pub end: u32,
+}
+
+impl SmallDataSection {
+    pub fn name(&self) -> &'static str {
+        self.kind.small_section_name().unwrap()
+    }
+
+    /// Fails if a module has more than one `.sdata` or `.sbss` section.
+    pub fn check_unique(sections: &[SmallDataSection]) -> Result<()> {
+        let mut seen = BTreeSet::new();
+        for section in sections {
+            if !seen.insert((section.module, section.name())) {
+                bail!("{} has more than one {} section", section.module, section.name());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SmallDataSection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.rsplitn(4, ':');
+        let (Some(end), Some(start), Some(name), Some(module)) = (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("small data section '{s}' must be formatted as <module>:<section>:<start>:<end>");
+        };
+
+        let module = ModuleKind::from_str(module).with_context(|| format!("invalid module in small data section '{s}'"))?;
+        let Some(kind) = SectionKind::from_small_section_name(name) else {
+            bail!("unknown small data section '{name}', must be one of: .sdata, .sbss");
+        };
+        let start = parse_u32(start).with_context(|| format!("failed to parse start address '{start}'"))?;
+        let end = parse_u32(end).with_context(|| format!("failed to parse end address '{end}'"))?;
+        if end <= start {
+            bail!("end address {end:#010x} of {name} section must be after start address {start:#010x}");
+        }
+
+        Ok(Self { module, kind, start, end })
+    }
+}
+
+impl TryFrom<String> for SmallDataSection {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<SmallDataSection> for String {
+    fn from(value: SmallDataSection) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for SmallDataSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.module {
+            ModuleKind::Arm9 => write!(f, "main")?,
+            ModuleKind::Autoload(AutoloadKind::Itcm) => write!(f, "itcm")?,
+            ModuleKind::Autoload(AutoloadKind::Dtcm) => write!(f, "dtcm")?,
+            ModuleKind::Autoload(kind) => write!(f, "{kind}")?,
+            ModuleKind::Overlay(id) => write!(f, "overlay:{id}")?,
+        }
+        write!(f, ":{}:{:#010x}:{:#010x}", self.name(), self.start, self.end)
+    }
+}
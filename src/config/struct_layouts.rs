@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::util::{io::open_file, parse::parse_u32};
+
+use super::{iter_attributes, symbol::SymData, ParseContext};
+
+/// Named struct layouts which data symbols can refer to with `kind:data(struct:<name>)`.
+pub struct StructLayouts {
+    layouts: BTreeMap<String, StructLayout>,
+}
+
+pub struct StructLayout {
+    pub name: String,
+    /// Fields sorted by offset
+    fields: Vec<StructField>,
+}
+
+pub struct StructField {
+    pub name: String,
+    pub offset: u32,
+    /// Scalar or array type of known size
+    pub data: SymData,
+}
+
+impl StructLayouts {
+    pub fn new() -> Self {
+        Self { layouts: BTreeMap::new() }
+    }
+
+    /// Loads a struct layouts file, where each line is one field: `<struct> field:<name> offset:<offset> kind:<type>`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut context = ParseContext { file_path: path.to_str().unwrap().to_string(), row: 0 };
+
+        let file = open_file(path)?;
+        let reader = BufReader::new(file);
+
+        let mut layouts = Self::new();
+        for line in reader.lines() {
+            context.row += 1;
+
+            let line = line?;
+            let comment_start = line.find("//").unwrap_or(line.len());
+            let line = &line[..comment_start];
+
+            let Some((struct_name, field)) = StructField::parse(line, &context)? else { continue };
+            layouts.get_or_add(struct_name).add_field(field).with_context(|| format!("{context}: invalid field"))?;
+        }
+
+        Ok(layouts)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&StructLayout> {
+        self.layouts.get(name)
+    }
+
+    pub fn get_or_add(&mut self, name: &str) -> &mut StructLayout {
+        self.layouts.entry(name.to_string()).or_insert_with(|| StructLayout { name: name.to_string(), fields: vec![] })
+    }
+}
+
+impl StructLayout {
+    /// Adds a field, failing if it overlaps another field or has the same name.
+    pub fn add_field(&mut self, field: StructField) -> Result<()> {
+        if field.data.size().is_none() || matches!(field.data, SymData::Struct { .. }) {
+            bail!("field '{}' of struct '{}' must be a byte, short or word array of known size", field.name, self.name);
+        }
+        if self.fields.iter().any(|other| other.name == field.name) {
+            bail!("struct '{}' already has a field named '{}'", self.name, field.name);
+        }
+
+        let index = self.fields.partition_point(|other| other.offset < field.offset);
+        if let Some(prev) = index.checked_sub(1).map(|i| &self.fields[i]) {
+            if prev.end() > field.offset {
+                bail!("field '{}' of struct '{}' overlaps with field '{}'", field.name, self.name, prev.name);
+            }
+        }
+        if let Some(next) = self.fields.get(index) {
+            if field.end() > next.offset {
+                bail!("field '{}' of struct '{}' overlaps with field '{}'", field.name, self.name, next.name);
+            }
+        }
+
+        self.fields.insert(index, field);
+        Ok(())
+    }
+
+    pub fn fields(&self) -> &[StructField] {
+        &self.fields
+    }
+
+    /// Returns the end offset of the last field.
+    pub fn size(&self) -> u32 {
+        self.fields.last().map(|field| field.end()).unwrap_or(0)
+    }
+}
+
+impl StructField {
+    fn parse<'a>(line: &'a str, context: &ParseContext) -> Result<Option<(&'a str, Self)>> {
+        let mut words = line.split_whitespace();
+        let Some(struct_name) = words.next() else { return Ok(None) };
+
+        let mut name = None;
+        let mut offset = None;
+        let mut data = None;
+        for (key, value) in iter_attributes(words) {
+            match key {
+                "field" => name = Some(value.to_string()),
+                "offset" => {
+                    offset = Some(parse_u32(value).with_context(|| format!("{context}: failed to parse offset '{value}'"))?)
+                }
+                "kind" => data = Some(SymData::parse(value, context)?),
+                _ => bail!("{context}: expected struct field attribute 'field', 'offset' or 'kind' but got '{key}'"),
+            }
+        }
+
+        let name = name.with_context(|| format!("{context}: missing 'field' attribute"))?;
+        let offset = offset.with_context(|| format!("{context}: missing 'offset' attribute"))?;
+        let data = data.with_context(|| format!("{context}: missing 'kind' attribute"))?;
+
+        Ok(Some((struct_name, Self { name, offset, data })))
+    }
+
+    pub fn size(&self) -> u32 {
+        self.data.size().unwrap_or(0)
+    }
+
+    fn end(&self) -> u32 {
+        self.offset + self.size()
+    }
+}
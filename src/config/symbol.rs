@@ -1,43 +1,82 @@
 use anyhow::{bail, ensure, Context, Result};
+use clap::ValueEnum;
+use snafu::Snafu;
 use std::{
     collections::{btree_map, hash_map, BTreeMap, HashMap},
     fmt::Display,
     io::{self, BufRead, BufReader, BufWriter, Write},
+    mem,
     ops::Range,
     path::Path,
     slice,
 };
-use unarm::LookupSymbol;
+use unarm::{Endian, LookupSymbol};
 
 use crate::{
-    analysis::{functions::Function, jump_table::JumpTable},
+    analysis::{
+        functions::Function,
+        jump_table::JumpTable,
+        strings::{decode_string, StringEncoding},
+    },
     util::{
+        address::AddressFormat,
         bytes::FromSlice,
         io::{create_file, open_file},
         parse::parse_u32,
     },
 };
 
-use super::{config::Config, iter_attributes, module::ModuleKind, relocation::Relocations, ParseContext};
+use super::{
+    config::Config,
+    iter_attributes,
+    module::ModuleKind,
+    name_prefixes::{NamePrefixes, DEFAULT_LABEL_PREFIX},
+    relocation::Relocations,
+    struct_layouts::StructLayouts,
+    ParseContext,
+};
+
+/// Shorter strings are likely to be other data which happens to be printable
+const MIN_STRING_COMMENT_CHARS: usize = 2;
 
 pub struct SymbolMaps {
     symbol_maps: Vec<SymbolMap>,
+    struct_layouts: StructLayouts,
+    label_prefix: String,
 }
 
 impl SymbolMaps {
     pub fn new() -> Self {
-        Self { symbol_maps: vec![] }
+        Self { symbol_maps: vec![], struct_layouts: StructLayouts::new(), label_prefix: DEFAULT_LABEL_PREFIX.to_string() }
+    }
+
+    /// Sets the name prefix of generated labels in every symbol map, including ones which are created later.
+    pub fn set_label_prefix(&mut self, label_prefix: &str) {
+        self.label_prefix = label_prefix.to_string();
+        for symbol_map in &mut self.symbol_maps {
+            symbol_map.set_label_prefix(label_prefix);
+        }
     }
 
     pub fn get(&self, module: ModuleKind) -> Option<&SymbolMap> {
         self.symbol_maps.get(module.index())
     }
 
+    /// Iterates over the symbol maps of all modules, in the order of their module indices.
+    pub fn iter(&self) -> impl Iterator<Item = &SymbolMap> {
+        self.symbol_maps.iter()
+    }
+
     pub fn get_mut(&mut self, module: ModuleKind) -> &mut SymbolMap {
         let index = module.index();
         if index >= self.symbol_maps.len() {
             assert!(index < 1000, "sanity check");
-            self.symbol_maps.resize_with(index + 1, SymbolMap::new);
+            let label_prefix = &self.label_prefix;
+            self.symbol_maps.resize_with(index + 1, || {
+                let mut symbol_map = SymbolMap::new();
+                symbol_map.set_label_prefix(label_prefix);
+                symbol_map
+            });
         }
         &mut self.symbol_maps[index]
     }
@@ -46,6 +85,10 @@ impl SymbolMaps {
         let config_path = config_path.as_ref();
 
         let mut symbol_maps = SymbolMaps::new();
+        symbol_maps.set_label_prefix(&config.name_prefixes.label_prefix);
+        if let Some(struct_layouts) = &config.struct_layouts {
+            symbol_maps.struct_layouts = StructLayouts::from_file(config_path.join(struct_layouts))?;
+        }
         symbol_maps.get_mut(ModuleKind::Arm9).load(config_path.join(&config.main_module.symbols))?;
         for autoload in &config.autoloads {
             symbol_maps.get_mut(ModuleKind::Autoload(autoload.kind)).load(config_path.join(&autoload.module.symbols))?;
@@ -53,10 +96,33 @@ impl SymbolMaps {
         for overlay in &config.overlays {
             symbol_maps.get_mut(ModuleKind::Overlay(overlay.id)).load(config_path.join(&overlay.module.symbols))?;
         }
+        symbol_maps.resolve_struct_sizes();
 
         Ok(symbol_maps)
     }
 
+    /// Sets the size of every struct symbol to the size of its layout in [`Self::struct_layouts`], so that the symbol
+    /// spans the whole struct. Symbols with an unknown layout are left without a size.
+    pub fn resolve_struct_sizes(&mut self) {
+        let struct_layouts = &self.struct_layouts;
+        for symbol_map in &mut self.symbol_maps {
+            for symbol in &mut symbol_map.symbols {
+                if let SymbolKind::Data(SymData::Struct { layout, size }) = &mut symbol.kind {
+                    *size = struct_layouts.get(layout).map(|layout| layout.size());
+                }
+            }
+        }
+    }
+
+    /// Layouts of data symbols of kind `struct:<name>`.
+    pub fn struct_layouts(&self) -> &StructLayouts {
+        &self.struct_layouts
+    }
+
+    pub fn struct_layouts_mut(&mut self) -> &mut StructLayouts {
+        &mut self.struct_layouts
+    }
+
     pub fn to_files<P: AsRef<Path>>(&self, config: &Config, config_path: P) -> Result<()> {
         let config_path = config_path.as_ref();
         self.get(ModuleKind::Arm9)
@@ -75,15 +141,73 @@ impl SymbolMaps {
 
         Ok(())
     }
+
+    /// Merges the symbols of `other` into these symbol maps, for each of the given modules. Returns the renamed symbols.
+    /// See [`SymbolMap::merge`].
+    pub fn merge<I: IntoIterator<Item = ModuleKind>>(
+        &mut self,
+        other: &SymbolMaps,
+        module_kinds: I,
+        policy: MergePolicy,
+        name_prefixes: &NamePrefixes,
+    ) -> Result<Vec<SymbolRename>> {
+        let mut renames = vec![];
+        for module_kind in module_kinds {
+            let Some(other_map) = other.get(module_kind) else { continue };
+            renames.extend(self.get_mut(module_kind).merge(other_map, policy, module_kind, name_prefixes)?);
+        }
+        Ok(renames)
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+/// How to resolve two symbols with different names at the same address when merging symbol maps.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum MergePolicy {
+    /// Keeps the name of the symbol map being merged into.
+    PreferSelf,
+    /// Takes the name of the symbol map being merged from.
+    PreferOther,
+    /// Takes the name which isn't a default name like `func_02001234`, or keeps the current name if both or neither are.
+    PreferNamed,
+}
+
+/// A symbol which was renamed by importing or merging symbols.
+pub struct SymbolRename {
+    pub module_kind: ModuleKind,
+    pub address: u32,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SymbolIndex(usize);
 
+/// Where each symbol of a symbols file was defined, used to report duplicates while loading.
+#[derive(Default)]
+struct LoadedSymbols {
+    by_address: HashMap<u32, (ParseContext, SymbolIndex)>,
+    by_name: HashMap<String, (ParseContext, SymbolIndex)>,
+}
+
+/// `//` comments of a symbol in a symbols file.
+#[derive(Clone, Default)]
+struct SymbolComments {
+    /// Comment lines right above the symbol.
+    lines: Vec<String>,
+    /// Comment at the end of the symbol's line, including the whitespace before it.
+    trailing: Option<String>,
+}
+
+#[derive(Clone)]
 pub struct SymbolMap {
     symbols: Vec<Symbol>,
     symbols_by_address: BTreeMap<u32, Vec<SymbolIndex>>,
     symbols_by_name: HashMap<String, Vec<SymbolIndex>>,
+    label_prefix: String,
+    /// Comments loaded from the symbols file, which are written back by [`Self::to_file`].
+    comments: HashMap<SymbolIndex, SymbolComments>,
+    /// Comment lines after the last symbol of the symbols file.
+    end_comments: Vec<String>,
 }
 
 impl SymbolMap {
@@ -100,7 +224,19 @@ impl SymbolMap {
             symbols_by_name.entry(symbol.name.clone()).or_default().push(SymbolIndex(index));
         }
 
-        Self { symbols, symbols_by_address, symbols_by_name }
+        Self {
+            symbols,
+            symbols_by_address,
+            symbols_by_name,
+            label_prefix: DEFAULT_LABEL_PREFIX.to_string(),
+            comments: HashMap::new(),
+            end_comments: vec![],
+        }
+    }
+
+    /// Sets the name prefix of labels, pool constants, jump tables and unnamed data which are added to this map.
+    pub fn set_label_prefix(&mut self, label_prefix: &str) {
+        self.label_prefix = label_prefix.to_string();
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -110,23 +246,110 @@ impl SymbolMap {
     }
 
     pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        let path = path.as_ref();
+        self.load_file(path.as_ref(), None)?;
+        Ok(())
+    }
+
+    /// Loads symbols like [`Self::load`], but doesn't stop at lines which fail to parse. Their errors are pushed to
+    /// `errors` instead. Returns every loaded symbol along with the line it was defined on.
+    pub fn load_lenient<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        errors: &mut Vec<anyhow::Error>,
+    ) -> Result<Vec<(ParseContext, Symbol)>> {
+        self.load_file(path.as_ref(), Some(errors))
+    }
+
+    /// Parser shared by [`Self::load`] and [`Self::load_lenient`]. If `errors` is given, errors of a line are pushed to it
+    /// and the loaded symbols are returned, otherwise the first error is returned.
+    fn load_file(&mut self, path: &Path, mut errors: Option<&mut Vec<anyhow::Error>>) -> Result<Vec<(ParseContext, Symbol)>> {
         let mut context = ParseContext { file_path: path.to_str().unwrap().to_string(), row: 0 };
 
         let file = open_file(path)?;
         let reader = BufReader::new(file);
 
+        let mut loaded = LoadedSymbols::default();
+        let mut symbols = vec![];
+        let mut comment_lines = vec![];
         for line in reader.lines() {
             context.row += 1;
 
             let line = line?;
             let comment_start = line.find("//").unwrap_or(line.len());
-            let line = &line[..comment_start];
+            let (symbol_line, comment) = line.split_at(comment_start);
 
-            let Some(symbol) = Symbol::parse(line, &context)? else { continue };
-            self.add(symbol);
+            let index = match Symbol::parse(symbol_line, &context) {
+                Ok(None) => {
+                    if !comment.is_empty() {
+                        comment_lines.push(line);
+                    }
+                    continue;
+                }
+                Ok(Some(symbol)) => self.add_loaded(symbol, &context, &mut loaded),
+                Err(error) => Err(error),
+            };
+            let index = match (index, errors.as_deref_mut()) {
+                (Ok(Some(index)), _) => index,
+                (Ok(None), _) => continue,
+                (Err(error), Some(errors)) => {
+                    errors.push(error);
+                    continue;
+                }
+                (Err(error), None) => return Err(error),
+            };
+            if errors.is_some() {
+                symbols.push((context.clone(), self.symbols[index.0].clone()));
+            }
+
+            let trailing = (!comment.is_empty()).then(|| line[symbol_line.trim_end().len()..].to_string());
+            if !comment_lines.is_empty() || trailing.is_some() {
+                self.comments.insert(index, SymbolComments { lines: std::mem::take(&mut comment_lines), trailing });
+            }
         }
-        Ok(())
+        self.end_comments.extend(comment_lines);
+        Ok(symbols)
+    }
+
+    /// Adds a symbol read from a symbols file, failing if its address or name was already defined in the same file.
+    /// Duplicate lines of the same name, kind and address are merged instead. Returns the index of the symbol if it was
+    /// added.
+    fn add_loaded(
+        &mut self,
+        symbol: Symbol,
+        context: &ParseContext,
+        loaded: &mut LoadedSymbols,
+    ) -> Result<Option<SymbolIndex>> {
+        if let Some((other_context, index)) = loaded.by_address.get(&symbol.addr) {
+            let other = &mut self.symbols[index.0];
+            if other.name != symbol.name || other.kind != symbol.kind {
+                bail!(
+                    "{context}: symbol '{}' at {:#010x} has the same address as '{}' defined at {other_context}",
+                    symbol.name,
+                    symbol.addr,
+                    other.name
+                );
+            }
+            log::debug!("{context}: merging duplicate of symbol '{}' defined at {other_context}", symbol.name);
+            other.ambiguous &= symbol.ambiguous;
+            other.local |= symbol.local;
+            return Ok(None);
+        }
+        if let Some((other_context, index)) = loaded.by_name.get(&symbol.name) {
+            bail!(
+                "{context}: symbol '{}' at {:#010x} has the same name as the symbol at {:#010x} defined at {other_context}",
+                symbol.name,
+                symbol.addr,
+                self.symbols[index.0].addr
+            );
+        }
+
+        self.check_function_overlap(&symbol)
+            .with_context(|| format!("{context}: please fix the size of the function in symbols.txt"))?;
+
+        let (index, symbol) = self.add(symbol);
+        loaded.by_address.insert(symbol.addr, (context.clone(), index));
+        loaded.by_name.insert(symbol.name.clone(), (context.clone(), index));
+        Ok(Some(index))
     }
 
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
@@ -138,11 +361,22 @@ impl SymbolMap {
         for indices in self.symbols_by_address.values() {
             for &index in indices {
                 let symbol = &self.symbols[index.0];
-                if symbol.should_write() {
+                if !symbol.should_write() {
+                    continue;
+                }
+                let Some(comments) = self.comments.get(&index) else {
                     writeln!(writer, "{symbol}")?;
+                    continue;
+                };
+                for line in &comments.lines {
+                    writeln!(writer, "{line}")?;
                 }
+                writeln!(writer, "{symbol}{}", comments.trailing.as_deref().unwrap_or(""))?;
             }
         }
+        for line in &self.end_comments {
+            writeln!(writer, "{line}")?;
+        }
 
         Ok(())
     }
@@ -178,10 +412,17 @@ impl SymbolMap {
         Ok(Some((index, symbol)))
     }
 
+    /// Iterates over all symbols in ascending address order.
+    pub fn iter(&self) -> SymbolIterator {
+        SymbolIterator { symbols_by_address: self.symbols_by_address.range(..), indices: [].iter(), symbols: &self.symbols }
+    }
+
     pub fn iter_by_address(&self, range: Range<u32>) -> SymbolIterator {
         SymbolIterator { symbols_by_address: self.symbols_by_address.range(range), indices: [].iter(), symbols: &self.symbols }
     }
 
+    /// Adds a symbol without any checks. Prefer [`Self::add_function`] for functions, which fails if the function overlaps
+    /// with another.
     pub fn add(&mut self, symbol: Symbol) -> (SymbolIndex, &Symbol) {
         let index = SymbolIndex(self.symbols.len());
         self.symbols_by_address.entry(symbol.addr).or_default().push(index);
@@ -199,8 +440,43 @@ impl SymbolMap {
         }
     }
 
-    pub fn add_function(&mut self, function: &Function) -> (SymbolIndex, &Symbol) {
-        self.add(Symbol::from_function(function))
+    pub fn add_function(&mut self, function: &Function) -> Result<(SymbolIndex, &Symbol)> {
+        let symbol = Symbol::from_function(function);
+        if let Err(error) = self.check_function_overlap(&symbol) {
+            log::error!("{error}");
+            return Err(error.into());
+        }
+        Ok(self.add(symbol))
+    }
+
+    /// Fails if `symbol` is a function of known size which would overlap with a function already in this map.
+    pub fn check_function_overlap(&self, symbol: &Symbol) -> Result<(), OverlappingFunctionsError> {
+        let SymbolKind::Function(function) = symbol.kind else { return Ok(()) };
+        if function.unknown {
+            return Ok(());
+        }
+        let known_function = |symbol: &Symbol| match symbol.kind {
+            SymbolKind::Function(function) if !function.unknown => Some(function),
+            _ => None,
+        };
+
+        let previous = self
+            .symbols_by_address
+            .range(..symbol.addr)
+            .rev()
+            .flat_map(|(_, indices)| indices.iter().map(|index| &self.symbols[index.0]))
+            .find_map(|previous| Some((known_function(previous)?, previous)));
+        if let Some((previous_function, previous)) = previous {
+            if previous.addr + previous_function.size > symbol.addr {
+                return Err(OverlappingFunctionsError::new(previous, symbol));
+            }
+        }
+
+        let end = symbol.addr + function.size.max(1);
+        if let Some(next) = self.iter_by_address(symbol.addr..end).find(|next| known_function(next).is_some()) {
+            return Err(OverlappingFunctionsError::new(symbol, next));
+        }
+        Ok(())
     }
 
     pub fn add_unknown_function(&mut self, name: String, addr: u32, thumb: bool) -> (SymbolIndex, &Symbol) {
@@ -239,22 +515,35 @@ impl SymbolMap {
         }
     }
 
+    /// Returns every pair of functions where the first function's size makes it extend past the start of the second one.
+    pub fn overlapping_functions(&self) -> Vec<(&Symbol, &Symbol)> {
+        let functions = self.functions().filter(|(function, _)| !function.unknown).collect::<Vec<_>>();
+        let mut overlaps = vec![];
+        for (i, &(function, symbol)) in functions.iter().enumerate() {
+            let end = symbol.addr + function.size;
+            for &(_, next_symbol) in functions[i + 1..].iter().take_while(|(_, next_symbol)| next_symbol.addr < end) {
+                overlaps.push((symbol, next_symbol));
+            }
+        }
+        overlaps
+    }
+
     pub fn clone_functions(&self) -> Vec<(SymFunction, Symbol)> {
         self.functions().map(|(function, symbol)| (function, symbol.clone())).collect()
     }
 
-    fn label_name(addr: u32) -> String {
-        format!("_{:08x}", addr)
+    fn label_name(&self, addr: u32) -> String {
+        format!("{}{:08x}", self.label_prefix, addr)
     }
 
     pub fn add_label(&mut self, addr: u32, thumb: bool) -> Result<(SymbolIndex, &Symbol)> {
-        let name = Self::label_name(addr);
+        let name = self.label_name(addr);
         self.add_if_new_address(Symbol::new_label(name, addr, thumb))
     }
 
     /// See [SymLabel::external].
     pub fn add_external_label(&mut self, addr: u32, thumb: bool) -> Result<(SymbolIndex, &Symbol)> {
-        let name = Self::label_name(addr);
+        let name = self.label_name(addr);
         self.add_if_new_address(Symbol::new_external_label(name, addr, thumb))
     }
 
@@ -263,7 +552,7 @@ impl SymbolMap {
     }
 
     pub fn add_pool_constant(&mut self, addr: u32) -> Result<(SymbolIndex, &Symbol)> {
-        let name = Self::label_name(addr);
+        let name = self.label_name(addr);
         self.add_if_new_address(Symbol::new_pool_constant(name, addr))
     }
 
@@ -272,8 +561,8 @@ impl SymbolMap {
     }
 
     pub fn add_jump_table(&mut self, table: &JumpTable) -> Result<(SymbolIndex, &Symbol)> {
-        let name = Self::label_name(table.address);
-        self.add_if_new_address(Symbol::new_jump_table(name, table.address, table.size, table.code))
+        let name = self.label_name(table.address);
+        self.add_if_new_address(Symbol::new_jump_table(name, table))
     }
 
     pub fn get_jump_table(&self, addr: u32) -> Result<Option<(SymJumpTable, &Symbol)>> {
@@ -295,42 +584,105 @@ impl SymbolMap {
     }
 
     pub fn add_data(&mut self, name: Option<String>, addr: u32, data: SymData) -> Result<(SymbolIndex, &Symbol)> {
-        let name = name.unwrap_or_else(|| Self::label_name(addr));
+        let name = name.unwrap_or_else(|| self.label_name(addr));
         self.make_unambiguous(addr)?;
         self.add_if_new_address(Symbol::new_data(name, addr, data, false))
     }
 
     pub fn add_ambiguous_data(&mut self, name: Option<String>, addr: u32, data: SymData) -> Result<(SymbolIndex, &Symbol)> {
-        let name = name.unwrap_or_else(|| Self::label_name(addr));
+        let name = name.unwrap_or_else(|| self.label_name(addr));
         self.add_if_new_address(Symbol::new_data(name, addr, data, true))
     }
 
-    pub fn get_data(&self, addr: u32) -> Result<Option<(SymData, &Symbol)>> {
-        Ok(self.by_address(addr)?.and_then(|(_, s)| match s.kind {
+    pub fn get_data(&self, addr: u32) -> Result<Option<(&SymData, &Symbol)>> {
+        Ok(self.by_address(addr)?.and_then(|(_, s)| match &s.kind {
             SymbolKind::Data(data) => Some((data, s)),
             _ => None,
         }))
     }
 
     pub fn add_bss(&mut self, name: Option<String>, addr: u32, data: SymBss) -> Result<(SymbolIndex, &Symbol)> {
-        let name = name.unwrap_or_else(|| Self::label_name(addr));
+        let name = name.unwrap_or_else(|| self.label_name(addr));
         self.make_unambiguous(addr)?;
         self.add_if_new_address(Symbol::new_bss(name, addr, data, false))
     }
 
     pub fn add_ambiguous_bss(&mut self, name: Option<String>, addr: u32, data: SymBss) -> Result<(SymbolIndex, &Symbol)> {
-        let name = name.unwrap_or_else(|| Self::label_name(addr));
+        let name = name.unwrap_or_else(|| self.label_name(addr));
         self.add_if_new_address(Symbol::new_bss(name, addr, data, true))
     }
 
+    /// Returns the bss symbol at `addr`, or the closest bss symbol before `addr` if its size is known and covers `addr`.
+    pub fn get_bss_containing(&self, addr: u32) -> Option<(SymBss, &Symbol)> {
+        let (&symbol_addr, indices) = self.symbols_by_address.range(0..=addr).next_back()?;
+        indices.iter().map(|index| &self.symbols[index.0]).find_map(|symbol| {
+            let SymbolKind::Bss(bss) = symbol.kind else { return None };
+            let contains = symbol_addr == addr || bss.size.is_some_and(|size| addr < symbol_addr + size);
+            contains.then_some((bss, symbol))
+        })
+    }
+
+    /// Removes the symbol at `index` and returns it. The symbol keeps its slot so that other [`SymbolIndex`]es stay
+    /// valid, but it can't be looked up or iterated over anymore.
+    pub fn remove(&mut self, index: SymbolIndex) -> Result<Symbol> {
+        let symbol = self.symbols.get(index.0).with_context(|| format!("Symbol index {} is out of bounds", index.0))?.clone();
+
+        let symbol_indices = self.symbols_by_address.get_mut(&symbol.addr);
+        let Some((symbol_indices, pos)) =
+            symbol_indices.and_then(|indices| indices.iter().position(|&i| i == index).map(|pos| (indices, pos)))
+        else {
+            bail!("Symbol '{}' at {:#010x} was already removed", symbol.name, symbol.addr);
+        };
+        symbol_indices.remove(pos);
+        if symbol_indices.is_empty() {
+            self.symbols_by_address.remove(&symbol.addr);
+        }
+
+        self.remove_name_index(&symbol.name, index)?;
+
+        Ok(symbol)
+    }
+
+    /// Removes every symbol at `address` and returns them in the order they were added.
+    pub fn remove_by_address(&mut self, address: u32) -> Result<Vec<Symbol>> {
+        let Some(symbol_indices) = self.symbols_by_address.remove(&address) else {
+            return Ok(vec![]);
+        };
+
+        let mut symbols = Vec::with_capacity(symbol_indices.len());
+        for index in symbol_indices {
+            let symbol = self.symbols[index.0].clone();
+            self.remove_name_index(&symbol.name, index)?;
+            symbols.push(symbol);
+        }
+        Ok(symbols)
+    }
+
+    fn remove_name_index(&mut self, name: &str, index: SymbolIndex) -> Result<()> {
+        let symbol_indices =
+            self.symbols_by_name.get_mut(name).with_context(|| format!("No symbol name entry found for '{name}'"))?;
+        let pos = symbol_indices
+            .iter()
+            .position(|&i| i == index)
+            .with_context(|| format!("No symbol index found for '{name}' in its name entry"))?;
+        symbol_indices.remove(pos);
+        if symbol_indices.is_empty() {
+            self.symbols_by_name.remove(name);
+        }
+        Ok(())
+    }
+
     pub fn rename_by_address(&mut self, address: u32, new_name: &str) -> Result<()> {
         let symbol_indices = self
             .symbols_by_address
             .get(&address)
             .with_context(|| format!("No symbol at {address:#x} to rename to '{new_name}'"))?;
         ensure!(symbol_indices.len() == 1, "There must be exactly one symbol at {address:#x} to rename to '{new_name}'");
+        self.rename_by_index(symbol_indices[0], new_name)
+    }
 
-        let symbol_index = symbol_indices[0];
+    /// Renames the symbol at `symbol_index`, for addresses which have more than one symbol.
+    pub fn rename_by_index(&mut self, symbol_index: SymbolIndex, new_name: &str) -> Result<()> {
         let name = &self.symbols[symbol_index.0].name;
 
         match self.symbols_by_name.entry(name.clone()) {
@@ -362,6 +714,75 @@ impl SymbolMap {
 
         Ok(())
     }
+
+    /// Changes the kind of the only symbol at `address`, such as to correct its size.
+    pub fn set_kind_by_address(&mut self, address: u32, kind: SymbolKind) -> Result<()> {
+        let symbol_indices = self
+            .symbols_by_address
+            .get(&address)
+            .with_context(|| format!("No symbol at {address:#x} to change the kind of"))?;
+        ensure!(symbol_indices.len() == 1, "There must be exactly one symbol at {address:#x} to change its kind");
+        self.symbols[symbol_indices[0].0].kind = kind;
+        Ok(())
+    }
+
+    /// Adds the symbols of `other` which are missing from this map, and returns the renamed symbols. Symbols at the same
+    /// address with different names are resolved by `policy`, and other differences like the symbol kind are kept from
+    /// this map. If an address has several symbols, the one of the same kind is renamed.
+    pub fn merge(
+        &mut self,
+        other: &SymbolMap,
+        policy: MergePolicy,
+        module_kind: ModuleKind,
+        name_prefixes: &NamePrefixes,
+    ) -> Result<Vec<SymbolRename>> {
+        let mut renames = vec![];
+        for symbol in other.iter() {
+            let candidates = self.for_address(symbol.addr).map(|symbols| symbols.collect::<Vec<_>>()).unwrap_or_default();
+            if candidates.iter().any(|(_, current)| current.name == symbol.name) {
+                continue;
+            }
+            let current = candidates
+                .iter()
+                .find(|(_, current)| mem::discriminant(&current.kind) == mem::discriminant(&symbol.kind))
+                .or(candidates.first())
+                .map(|(index, current)| (*index, current.name.clone()));
+            let Some((index, current_name)) = current else {
+                self.ensure_name_is_free(&symbol.name, symbol.addr)?;
+                self.check_function_overlap(symbol)?;
+                self.add(symbol.clone());
+                continue;
+            };
+
+            let take_other = match policy {
+                MergePolicy::PreferSelf => false,
+                MergePolicy::PreferOther => true,
+                MergePolicy::PreferNamed => {
+                    name_prefixes.is_default_name(&current_name, module_kind)
+                        && !name_prefixes.is_default_name(&symbol.name, module_kind)
+                }
+            };
+            if take_other {
+                log::debug!("Renaming '{}' to '{}' at {:#010x}", current_name, symbol.name, symbol.addr);
+                self.ensure_name_is_free(&symbol.name, symbol.addr)?;
+                self.rename_by_index(index, &symbol.name)?;
+                renames.push(SymbolRename {
+                    module_kind,
+                    address: symbol.addr,
+                    old_name: current_name,
+                    new_name: symbol.name.clone(),
+                });
+            }
+        }
+        Ok(renames)
+    }
+
+    fn ensure_name_is_free(&self, name: &str, address: u32) -> Result<()> {
+        if let Some((_, existing)) = self.by_name(name)? {
+            bail!("Can't merge symbol '{name}' at {address:#010x}, the name is already used at {:#010x}", existing.addr);
+        }
+        Ok(())
+    }
 }
 
 impl LookupSymbol for SymbolMap {
@@ -430,6 +851,8 @@ pub struct Symbol {
     pub addr: u32,
     /// If true, this symbol is involved in an ambiguous external reference to one of many overlays
     pub ambiguous: bool,
+    /// If true, this symbol always has local scope when delinked, even if other files refer to it
+    pub local: bool,
 }
 
 impl Symbol {
@@ -440,6 +863,7 @@ impl Symbol {
         let mut kind = None;
         let mut addr = None;
         let mut ambiguous = false;
+        let mut local = false;
         for (key, value) in iter_attributes(words) {
             match key {
                 "kind" => kind = Some(SymbolKind::parse(value, context)?),
@@ -447,6 +871,7 @@ impl Symbol {
                     addr = Some(parse_u32(value).with_context(|| format!("{context}: failed to parse address '{value}'"))?)
                 }
                 "ambiguous" => ambiguous = true,
+                "local" => local = true,
                 _ => bail!("{context}: expected symbol attribute 'kind' or 'addr' but got '{key}'"),
             }
         }
@@ -455,7 +880,7 @@ impl Symbol {
         let kind = kind.with_context(|| format!("{context}: missing 'kind' attribute"))?;
         let addr = addr.with_context(|| format!("{context}: missing 'addr' attribute"))?;
 
-        Ok(Some(Symbol { name, kind, addr, ambiguous }))
+        Ok(Some(Symbol { name, kind, addr, ambiguous, local }))
     }
 
     fn should_write(&self) -> bool {
@@ -472,6 +897,17 @@ impl Symbol {
             }),
             addr: function.first_instruction_address() & !1,
             ambiguous: false,
+            local: false,
+        }
+    }
+
+    pub fn new_function(name: String, addr: u32, mode: InstructionMode, size: u32) -> Self {
+        Self {
+            name,
+            kind: SymbolKind::Function(SymFunction { mode, size, unknown: false }),
+            addr,
+            ambiguous: false,
+            local: false,
         }
     }
 
@@ -481,6 +917,7 @@ impl Symbol {
             kind: SymbolKind::Function(SymFunction { mode: InstructionMode::from_thumb(thumb), size: 0, unknown: true }),
             addr,
             ambiguous: false,
+            local: false,
         }
     }
 
@@ -490,6 +927,7 @@ impl Symbol {
             kind: SymbolKind::Label(SymLabel { external: false, mode: InstructionMode::from_thumb(thumb) }),
             addr,
             ambiguous: false,
+            local: false,
         }
     }
 
@@ -499,23 +937,35 @@ impl Symbol {
             kind: SymbolKind::Label(SymLabel { external: true, mode: InstructionMode::from_thumb(thumb) }),
             addr,
             ambiguous: false,
+            local: false,
         }
     }
 
     pub fn new_pool_constant(name: String, addr: u32) -> Self {
-        Self { name, kind: SymbolKind::PoolConstant, addr, ambiguous: false }
+        Self { name, kind: SymbolKind::PoolConstant, addr, ambiguous: false, local: false }
     }
 
-    pub fn new_jump_table(name: String, addr: u32, size: u32, code: bool) -> Self {
-        Self { name, kind: SymbolKind::JumpTable(SymJumpTable { size, code }), addr, ambiguous: false }
+    pub fn new_jump_table(name: String, table: &JumpTable) -> Self {
+        Self {
+            name,
+            kind: SymbolKind::JumpTable(SymJumpTable {
+                size: table.size,
+                code: table.code,
+                entry_size: table.entry_size,
+                signed: table.signed,
+            }),
+            addr: table.address,
+            ambiguous: false,
+            local: false,
+        }
     }
 
     pub fn new_data(name: String, addr: u32, data: SymData, ambiguous: bool) -> Symbol {
-        Self { name, kind: SymbolKind::Data(data), addr, ambiguous }
+        Self { name, kind: SymbolKind::Data(data), addr, ambiguous, local: false }
     }
 
     pub fn new_bss(name: String, addr: u32, data: SymBss, ambiguous: bool) -> Symbol {
-        Self { name, kind: SymbolKind::Bss(data), addr, ambiguous }
+        Self { name, kind: SymbolKind::Bss(data), addr, ambiguous, local: false }
     }
 
     pub fn size(&self, max_address: u32) -> u32 {
@@ -540,6 +990,25 @@ impl Symbol {
             SymbolKind::Bss(_) => None,
         }
     }
+
+    /// Writes a GNU assembler `.type` directive for functions, data and bss. Other symbol kinds are ignored.
+    pub fn write_type_directive<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        match self.kind {
+            SymbolKind::Function(_) => writeln!(w, "    .type {}, %function", self.name),
+            SymbolKind::Data(_) | SymbolKind::Bss(_) => writeln!(w, "    .type {}, %object", self.name),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes a GNU assembler `.size` directive for functions, data and bss, to be placed after the symbol's contents.
+    /// Data and bss of unknown size will be sized up to `max_address`.
+    pub fn write_size_directive<W: io::Write>(&self, w: &mut W, max_address: u32) -> io::Result<()> {
+        match self.kind {
+            SymbolKind::Function(_) => writeln!(w, "    .size {0}, .-{0}", self.name),
+            SymbolKind::Data(_) | SymbolKind::Bss(_) => writeln!(w, "    .size {}, {:#x}", self.name, self.size(max_address)),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Display for Symbol {
@@ -548,11 +1017,14 @@ impl Display for Symbol {
         if self.ambiguous {
             write!(f, " ambiguous")?;
         }
+        if self.local {
+            write!(f, " local")?;
+        }
         Ok(())
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub enum SymbolKind {
     Function(SymFunction),
     Label(SymLabel),
@@ -713,14 +1185,14 @@ impl Display for SymLabel {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum InstructionMode {
     Arm,
     Thumb,
 }
 
 impl InstructionMode {
-    fn parse(text: &str, context: &ParseContext) -> Result<Self> {
+    pub fn parse(text: &str, context: &ParseContext) -> Result<Self> {
         match text {
             "arm" => Ok(Self::Arm),
             "thumb" => Ok(Self::Thumb),
@@ -753,24 +1225,77 @@ impl Display for InstructionMode {
     }
 }
 
+#[derive(Debug, Snafu)]
+#[snafu(display(
+    "Function {first} ({first_start:#010x}..{first_end:#010x}) overlaps with function {second} at {second_start:#010x}"
+))]
+pub struct OverlappingFunctionsError {
+    first: String,
+    first_start: u32,
+    first_end: u32,
+    second: String,
+    second_start: u32,
+}
+
+impl OverlappingFunctionsError {
+    pub fn new(first: &Symbol, second: &Symbol) -> Self {
+        Self {
+            first: first.name.clone(),
+            first_start: first.addr,
+            first_end: first.addr + first.size(u32::MAX),
+            second: second.name.clone(),
+            second_start: second.addr,
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct SymJumpTable {
     pub size: u32,
     pub code: bool,
+    /// Size of each entry in bytes.
+    pub entry_size: u32,
+    /// If true, the entries of a data table are signed offsets.
+    pub signed: bool,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub enum SymData {
     Any,
-    Byte { count: Option<u32> },
-    Short { count: Option<u32> },
-    Word { count: Option<u32> },
+    Byte {
+        count: Option<u32>,
+    },
+    Short {
+        count: Option<u32>,
+    },
+    Word {
+        count: Option<u32>,
+    },
+    /// Words which are always pointers, so the delinker relocates them even if analysis found no relocation.
+    Pointer {
+        count: Option<u32>,
+    },
+    /// Struct with a layout from [`StructLayouts`], see [`SymbolMaps::struct_layouts`].
+    Struct {
+        layout: String,
+        /// Size of the layout, or `None` if it isn't loaded. Set by [`SymbolMaps::resolve_struct_sizes`].
+        size: Option<u32>,
+    },
 }
 
 impl SymData {
-    fn parse(kind: &str, context: &ParseContext) -> Result<Self> {
+    pub(crate) fn parse(kind: &str, context: &ParseContext) -> Result<Self> {
         if kind.is_empty() {
-            bail!("{context}: expected data kind 'any', 'byte', 'short' or 'word' but got nothing");
+            bail!(
+                "{context}: expected data kind 'any', 'byte', 'short', 'word', 'pointer' or 'struct:<name>' but got nothing"
+            );
+        }
+
+        if let Some(layout) = kind.strip_prefix("struct:") {
+            if layout.is_empty() {
+                bail!("{context}: expected struct layout name after 'struct:'");
+            }
+            return Ok(Self::Struct { layout: layout.to_string(), size: None });
         }
 
         let (kind, rest) = kind.split_once('[').unwrap_or((kind, ""));
@@ -795,30 +1320,41 @@ impl SymData {
             "short" => Ok(Self::Short { count }),
             "byte" => Ok(Self::Byte { count }),
             "word" => Ok(Self::Word { count }),
-            kind => bail!("{context}: expected data kind 'any', 'byte', 'short' or 'word' but got '{kind}'"),
+            "pointer" => Ok(Self::Pointer { count }),
+            kind => bail!(
+                "{context}: expected data kind 'any', 'byte', 'short', 'word', 'pointer' or 'struct:<name>' but got '{kind}'"
+            ),
         }
     }
 
-    pub fn count(self) -> Option<u32> {
-        match self {
+    /// Returns the number of elements of an array. Is `None` for `any`, structs and arrays of unknown length.
+    pub fn count(&self) -> Option<u32> {
+        match *self {
             Self::Any => None,
             Self::Byte { count } => count,
             Self::Short { count } => count,
             Self::Word { count } => count,
+            Self::Pointer { count } => count,
+            Self::Struct { .. } => None,
         }
     }
 
-    pub fn element_size(self) -> u32 {
+    pub fn element_size(&self) -> u32 {
         match self {
             Self::Any => 1,
             Self::Byte { .. } => 1,
             Self::Short { .. } => 2,
             Self::Word { .. } => 4,
+            Self::Pointer { .. } => 4,
+            Self::Struct { .. } => 1,
         }
     }
 
     pub fn size(&self) -> Option<u32> {
-        self.count().map(|count| self.element_size() * count)
+        match *self {
+            Self::Struct { size, .. } => size,
+            _ => self.count().map(|count| self.element_size() * count),
+        }
     }
 
     pub fn write_assembly<W: io::Write>(
@@ -835,6 +1371,65 @@ impl SymData {
             }
         }
 
+        if let Self::Struct { layout, .. } = self {
+            return Self::write_struct(w, layout, symbol, bytes, symbols);
+        }
+        if let (Self::Any | Self::Byte { .. }, Some(encoding)) = (self, symbols.string_encoding) {
+            Self::write_string_comment(w, bytes, encoding)?;
+        }
+        self.write_elements(w, symbol.addr, bytes, symbols)
+    }
+
+    /// Writes the string at the start of `bytes` as a comment, so the text is readable while the bytes are still written
+    /// verbatim. Nothing is written if the bytes aren't a valid string in the given encoding.
+    fn write_string_comment<W: io::Write>(w: &mut W, bytes: &[u8], encoding: StringEncoding) -> Result<()> {
+        let Some((_, text)) = decode_string(bytes, encoding) else { return Ok(()) };
+        if text.chars().count() >= MIN_STRING_COMMENT_CHARS {
+            writeln!(w, "    ; {text:?}")?;
+        }
+        Ok(())
+    }
+
+    /// Writes each field of a struct, preceded by a comment with the field name. Padding and bytes past the last field
+    /// are written as bytes.
+    fn write_struct<W: io::Write>(
+        w: &mut W,
+        layout: &str,
+        symbol: &Symbol,
+        bytes: &[u8],
+        symbols: &SymbolLookup,
+    ) -> Result<()> {
+        let Some(layout) = symbols.symbol_maps.struct_layouts().get(layout) else {
+            log::error!("Struct layout '{layout}' of symbol '{}' not found", symbol.name);
+            bail!("Struct layout '{layout}' of symbol '{}' not found", symbol.name);
+        };
+        if bytes.len() < layout.size() as usize {
+            log::error!("Not enough bytes to write struct '{}' of symbol '{}'", layout.name, symbol.name);
+            bail!("Not enough bytes to write struct '{}' of symbol '{}'", layout.name, symbol.name);
+        }
+
+        let padding = SymData::Byte { count: None };
+        let mut offset = 0;
+        for field in layout.fields() {
+            let field_offset = field.offset as usize;
+            if offset < field_offset {
+                padding.write_elements(w, symbol.addr + offset as u32, &bytes[offset..field_offset], symbols)?;
+            }
+
+            let field_end = field_offset + field.size() as usize;
+            writeln!(w, "    ; {}", field.name)?;
+            field.data.write_elements(w, symbol.addr + field.offset, &bytes[field_offset..field_end], symbols)?;
+            offset = field_end;
+        }
+        if offset < bytes.len() {
+            padding.write_elements(w, symbol.addr + offset as u32, &bytes[offset..], symbols)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `bytes` as data directives of this type, or as symbols where there are pointers.
+    fn write_elements<W: io::Write>(&self, w: &mut W, address: u32, bytes: &[u8], symbols: &SymbolLookup) -> Result<()> {
         let mut offset = 0;
         while offset < bytes.len() {
             let mut data_directive = false;
@@ -847,11 +1442,11 @@ impl SymData {
                 }
                 let bytes = &bytes[offset..];
 
-                let address = symbol.addr + offset as u32;
+                let address = address + offset as u32;
 
                 // Try write symbol
                 if bytes.len() >= 4 && (address & 3) == 0 {
-                    let pointer = u32::from_le_slice(bytes);
+                    let pointer = u32::from_slice(bytes, symbols.endian);
 
                     if symbols.write_symbol(w, address, pointer, &mut data_directive, "    ")? {
                         column += 4;
@@ -862,18 +1457,22 @@ impl SymData {
                 // If no symbol, write data literals
                 if !data_directive {
                     match self {
-                        SymData::Any => write!(w, "    .byte 0x{:02x}", bytes[0])?,
-                        SymData::Byte { .. } => write!(w, "    .byte 0x{:02x}", bytes[0])?,
+                        SymData::Any | SymData::Byte { .. } | SymData::Struct { .. } => {
+                            write!(w, "    .byte 0x{:02x}", bytes[0])?
+                        }
                         SymData::Short { .. } => write!(w, "    .short {:#x}", bytes[0])?,
-                        SymData::Word { .. } => write!(w, "    .word {:#x}", u32::from_le_slice(bytes))?,
+                        SymData::Word { .. } | SymData::Pointer { .. } => {
+                            write!(w, "    .word {:#x}", u32::from_slice(bytes, symbols.endian))?
+                        }
                     }
                     data_directive = true;
                 } else {
                     match self {
-                        SymData::Any => write!(w, ", 0x{:02x}", bytes[0])?,
-                        SymData::Byte { .. } => write!(w, ", 0x{:02x}", bytes[0])?,
-                        SymData::Short { .. } => write!(w, ", {:#x}", u16::from_le_slice(bytes))?,
-                        SymData::Word { .. } => write!(w, ", {:#x}", u32::from_le_slice(bytes))?,
+                        SymData::Any | SymData::Byte { .. } | SymData::Struct { .. } => write!(w, ", 0x{:02x}", bytes[0])?,
+                        SymData::Short { .. } => write!(w, ", {:#x}", u16::from_slice(bytes, symbols.endian))?,
+                        SymData::Word { .. } | SymData::Pointer { .. } => {
+                            write!(w, ", {:#x}", u32::from_slice(bytes, symbols.endian))?
+                        }
                     }
                 }
                 column += self.element_size() as usize;
@@ -896,12 +1495,16 @@ impl Display for SymData {
             Self::Byte { count: Some(1) } => write!(f, "byte"),
             Self::Short { count: Some(1) } => write!(f, "short"),
             Self::Word { count: Some(1) } => write!(f, "word"),
+            Self::Pointer { count: Some(1) } => write!(f, "pointer"),
             Self::Byte { count: Some(count) } => write!(f, "byte[{count}]"),
             Self::Short { count: Some(count) } => write!(f, "short[{count}]"),
             Self::Word { count: Some(count) } => write!(f, "word[{count}]"),
+            Self::Pointer { count: Some(count) } => write!(f, "pointer[{count}]"),
             Self::Byte { count: None } => write!(f, "byte[]"),
             Self::Short { count: None } => write!(f, "short[]"),
             Self::Word { count: None } => write!(f, "word[]"),
+            Self::Pointer { count: None } => write!(f, "pointer[]"),
+            Self::Struct { layout, .. } => write!(f, "struct:{layout}"),
         }
     }
 }
@@ -945,6 +1548,12 @@ pub struct SymbolLookup<'a> {
     /// All symbol maps, including external modules
     pub symbol_maps: &'a SymbolMaps,
     pub relocations: &'a Relocations,
+    /// Byte order of the module's code and data
+    pub endian: Endian,
+    /// Encoding of strings to decode into comments next to byte data, or `None` to not decode strings
+    pub string_encoding: Option<StringEncoding>,
+    /// Format of addresses in comments
+    pub address_format: AddressFormat,
 }
 
 impl<'a> SymbolLookup<'a> {
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use ds_decomp::cmd::{CheckArgs, Delink, Disassemble, ImportArgs, Init, Lcf, Objdiff, RomArgs};
+use ds_decomp::cmd::{
+    ApplyArgs, CheckArgs, ConfigArgs, Delink, DiffArgs, Disassemble, DumpArgs, ExportArgs, FixArgs, ImportArgs, Init, Lcf,
+    Objdiff, RomArgs,
+};
 use log::LevelFilter;
 
 /// Command-line toolkit for decompiling DS games.
@@ -25,7 +28,13 @@ enum Command {
     Lcf(Lcf),
     Import(ImportArgs),
     Check(CheckArgs),
+    Config(ConfigArgs),
     Objdiff(Objdiff),
+    Dump(DumpArgs),
+    Export(ExportArgs),
+    Fix(FixArgs),
+    Diff(DiffArgs),
+    Apply(ApplyArgs),
 }
 
 impl Command {
@@ -38,7 +47,13 @@ impl Command {
             Command::Lcf(lcf) => lcf.run(),
             Command::Import(import) => import.run(),
             Command::Check(check) => check.run(),
+            Command::Config(config) => config.run(),
             Command::Objdiff(objdiff) => objdiff.run(),
+            Command::Dump(dump) => dump.run(),
+            Command::Export(export) => export.run(),
+            Command::Fix(fix) => fix.run(),
+            Command::Diff(diff) => diff.run(),
+            Command::Apply(apply) => apply.run(),
         }
     }
 }
@@ -0,0 +1,26 @@
+use clap::ValueEnum;
+
+/// How addresses are written in comments of disassembled code. Symbol and label names are not affected.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum AddressFormat {
+    /// Lowercase hex with leading zeros, like `0x0200abcd`.
+    #[default]
+    Padded,
+    /// Uppercase hex with leading zeros, like `0x0200ABCD`.
+    PaddedUpper,
+    /// Lowercase hex without leading zeros, like `0x200abcd`.
+    Unpadded,
+    /// Uppercase hex without leading zeros, like `0x200ABCD`.
+    UnpaddedUpper,
+}
+
+impl AddressFormat {
+    pub fn format(self, address: u32) -> String {
+        match self {
+            Self::Padded => format!("{address:#010x}"),
+            Self::PaddedUpper => format!("0x{address:08X}"),
+            Self::Unpadded => format!("{address:#x}"),
+            Self::UnpaddedUpper => format!("0x{address:X}"),
+        }
+    }
+}
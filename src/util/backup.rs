@@ -0,0 +1,61 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+
+use super::io::{create_dir_all, write_file};
+
+/// Name of the directory which backups are written to, next to the backed up file.
+pub const BACKUP_DIR: &str = ".dsd_backups";
+
+struct StagedBackup {
+    path: PathBuf,
+    original: Vec<u8>,
+    staged_path: PathBuf,
+}
+
+/// Runs `write`, which may overwrite the files at `paths`. The previous contents of every file which `write` changed are
+/// kept in [`BACKUP_DIR`] next to the file, as `<file name>.<timestamp>.bak`. Returns the paths of the backups.
+pub fn write_with_backups<I, P, F>(paths: I, write: F) -> Result<Vec<PathBuf>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+    F: FnOnce() -> Result<()>,
+{
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+
+    // Stage the backups before writing, so they exist even if writing fails halfway
+    let mut staged = vec![];
+    for path in paths {
+        let path = path.as_ref();
+        let Ok(original) = fs::read(path) else { continue };
+        let backup_dir = path.parent().unwrap().join(BACKUP_DIR);
+        create_dir_all(&backup_dir)?;
+        let file_name = path.file_name().context("Backed up path has no file name")?.to_string_lossy();
+        let staged_path = backup_dir.join(format!("{file_name}.{timestamp}.tmp"));
+        write_file(&staged_path, &original)?;
+        staged.push(StagedBackup { path: path.to_path_buf(), original, staged_path });
+    }
+
+    let result = write();
+
+    let mut backups = vec![];
+    for StagedBackup { path, original, staged_path } in staged {
+        let changed = fs::read(&path).map_or(true, |contents| contents != original);
+        if !changed {
+            fs::remove_file(&staged_path)?;
+            continue;
+        }
+        // Renaming is atomic, so a backup is never seen half written
+        let backup_path = staged_path.with_extension("bak");
+        fs::rename(&staged_path, &backup_path)?;
+        log::info!("Backed up {} to {}", path.display(), backup_path.display());
+        backups.push(backup_path);
+    }
+
+    result?;
+    Ok(backups)
+}
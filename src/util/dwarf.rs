@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+
+use anyhow::Result;
+use gimli::{AttributeValue, EndianSlice, Reader, RunTimeEndian, Unit, UnitOffset};
+use object::{Object, ObjectSection};
+
+/// Max number of typedefs and qualifiers to follow when resolving the size of a type.
+const MAX_TYPE_DEPTH: usize = 16;
+
+/// Function or variable described by DWARF debug info.
+pub struct DwarfSymbol {
+    pub name: String,
+    pub address: u32,
+    pub kind: DwarfSymbolKind,
+}
+
+pub enum DwarfSymbolKind {
+    Function {
+        size: u32,
+    },
+    /// The type is unknown if it couldn't be resolved to a size
+    Variable {
+        ty: Option<DwarfType>,
+    },
+}
+
+/// Size of a variable's type, split into elements if it's an array.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DwarfType {
+    pub element_size: u32,
+    pub count: u32,
+}
+
+/// Reads functions and variables with an address from the DWARF debug info of an object file. Returns `None` if the
+/// object has no DWARF debug info.
+pub fn read_dwarf_symbols(object: &object::File) -> Result<Option<Vec<DwarfSymbol>>> {
+    if object.section_by_name(".debug_info").is_none() {
+        return Ok(None);
+    }
+
+    let endian = if object.is_little_endian() { RunTimeEndian::Little } else { RunTimeEndian::Big };
+    let load_section = |id: gimli::SectionId| -> Result<Cow<[u8]>> {
+        match object.section_by_name(id.name()) {
+            Some(section) => Ok(section.uncompressed_data()?),
+            None => Ok(Cow::Borrowed(&[])),
+        }
+    };
+    let dwarf_sections = gimli::Dwarf::load(load_section)?;
+    let borrow_section: &dyn for<'a> Fn(&'a Cow<[u8]>) -> EndianSlice<'a, RunTimeEndian> =
+        &|section| EndianSlice::new(section, endian);
+    let dwarf = dwarf_sections.borrow(borrow_section);
+
+    let mut symbols = vec![];
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            let (address, kind) = match entry.tag() {
+                gimli::DW_TAG_subprogram => {
+                    let (Some(low_pc), Some(high_pc)) =
+                        (entry.attr_value(gimli::DW_AT_low_pc)?, entry.attr_value(gimli::DW_AT_high_pc)?)
+                    else {
+                        continue;
+                    };
+                    let Some(address) = dwarf.attr_address(&unit, low_pc)? else { continue };
+                    let size = match high_pc {
+                        AttributeValue::Addr(end) => end.saturating_sub(address),
+                        value => match value.udata_value() {
+                            Some(size) => size,
+                            None => continue,
+                        },
+                    };
+                    (address, DwarfSymbolKind::Function { size: size as u32 })
+                }
+                gimli::DW_TAG_variable => {
+                    let Some(AttributeValue::Exprloc(expression)) = entry.attr_value(gimli::DW_AT_location)? else {
+                        continue;
+                    };
+                    // Only static variables have a fixed address
+                    let mut operations = expression.operations(unit.encoding());
+                    let Some(gimli::Operation::Address { address }) = operations.next()? else { continue };
+                    if operations.next()?.is_some() {
+                        continue;
+                    }
+                    let ty = match entry.attr_value(gimli::DW_AT_type)? {
+                        Some(AttributeValue::UnitRef(offset)) => resolve_type(&unit, offset, 0)?,
+                        _ => None,
+                    };
+                    (address, DwarfSymbolKind::Variable { ty })
+                }
+                _ => continue,
+            };
+
+            // Prefer the mangled name, which is what symbols.txt contains
+            let name = match entry.attr_value(gimli::DW_AT_linkage_name)? {
+                Some(name) => Some(name),
+                None => match entry.attr_value(gimli::DW_AT_MIPS_linkage_name)? {
+                    Some(name) => Some(name),
+                    None => entry.attr_value(gimli::DW_AT_name)?,
+                },
+            };
+            let Some(name) = name else { continue };
+            let name = Reader::to_string_lossy(&dwarf.attr_string(&unit, name)?)?.into_owned();
+            symbols.push(DwarfSymbol { name, address: address as u32, kind });
+        }
+    }
+
+    Ok(Some(symbols))
+}
+
+/// Follows typedefs and qualifiers until a type with a known size is found.
+fn resolve_type<R: Reader>(unit: &Unit<R>, offset: UnitOffset<R::Offset>, depth: usize) -> Result<Option<DwarfType>> {
+    if depth >= MAX_TYPE_DEPTH {
+        return Ok(None);
+    }
+    let entry = unit.entry(offset)?;
+    let byte_size = entry.attr_value(gimli::DW_AT_byte_size)?.and_then(|size| size.udata_value());
+    let inner_type = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(offset)) => Some(offset),
+        _ => None,
+    };
+
+    match entry.tag() {
+        gimli::DW_TAG_typedef | gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type => match inner_type {
+            Some(inner_type) => resolve_type(unit, inner_type, depth + 1),
+            None => Ok(None),
+        },
+        gimli::DW_TAG_pointer_type | gimli::DW_TAG_reference_type => {
+            Ok(Some(DwarfType { element_size: byte_size.unwrap_or(4) as u32, count: 1 }))
+        }
+        gimli::DW_TAG_array_type => {
+            let Some(element) = inner_type else { return Ok(None) };
+            let Some(element) = resolve_type(unit, element, depth + 1)? else { return Ok(None) };
+
+            let mut count = element.count;
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_subrange_type {
+                    continue;
+                }
+                let length = match child.attr_value(gimli::DW_AT_count)?.and_then(|count| count.udata_value()) {
+                    Some(length) => length,
+                    None => match child.attr_value(gimli::DW_AT_upper_bound)?.and_then(|bound| bound.udata_value()) {
+                        Some(upper_bound) => upper_bound + 1,
+                        None => return Ok(None),
+                    },
+                };
+                count *= length as u32;
+            }
+            Ok(Some(DwarfType { element_size: element.element_size, count }))
+        }
+        _ => Ok(byte_size.map(|size| DwarfType { element_size: size as u32, count: 1 })),
+    }
+}
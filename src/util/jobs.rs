@@ -0,0 +1,29 @@
+use anyhow::Result;
+use rayon::prelude::*;
+
+/// Calls `f` on every item, on up to `num_jobs` threads. Runs on the current thread if `num_jobs` is 1 or less.
+pub fn try_for_each<T, F>(num_jobs: usize, items: &[T], f: F) -> Result<()>
+where
+    T: Sync,
+    F: Fn(&T) -> Result<()> + Sync + Send,
+{
+    if num_jobs <= 1 {
+        return items.iter().try_for_each(f);
+    }
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(num_jobs).build()?;
+    pool.install(|| items.par_iter().try_for_each(f))
+}
+
+/// Calls `f` on every item like [`try_for_each`], and returns the results in the same order as the items.
+pub fn try_map<T, U, F>(num_jobs: usize, items: &[T], f: F) -> Result<Vec<U>>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> Result<U> + Sync + Send,
+{
+    if num_jobs <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(num_jobs).build()?;
+    pool.install(|| items.par_iter().map(f).collect())
+}
@@ -0,0 +1,49 @@
+use std::borrow::Cow;
+
+/// Symbol names which the compiler generates for local objects. The numbers in them count the objects which came before,
+/// so they change between builds whenever unrelated code is added or removed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompilerLocalName {
+    /// `@1304`, an anonymous object such as a string literal or a switch table.
+    Anonymous,
+    /// `sCounter$1304`, a static variable declared inside a function.
+    FunctionStatic,
+    /// `$1304` or `$tmp0`, a compiler temporary. ARM mapping symbols like `$a`, `$t` and `$d` are not temporaries.
+    Temporary,
+}
+
+impl CompilerLocalName {
+    /// Returns which kind of compiler-generated local `name` is, or `None` if it was named by the user.
+    pub fn classify(name: &str) -> Option<Self> {
+        if let Some(number) = name.strip_prefix('@') {
+            return is_number(number).then_some(Self::Anonymous);
+        }
+        if let Some(rest) = name.strip_prefix('$') {
+            let (mapping, _) = rest.split_once('.').unwrap_or((rest, ""));
+            return (!rest.is_empty() && !matches!(mapping, "a" | "t" | "d")).then_some(Self::Temporary);
+        }
+        let (base, number) = name.rsplit_once('$')?;
+        (!base.is_empty() && is_number(number)).then_some(Self::FunctionStatic)
+    }
+
+    /// Removes the number from a compiler-generated local name, such as `@1304` to `@` or `sCounter$1304` to `sCounter$`.
+    /// Other names are returned as they are.
+    pub fn normalize(name: &str) -> Cow<'_, str> {
+        match Self::classify(name) {
+            Some(Self::Anonymous) => "@".into(),
+            Some(Self::FunctionStatic) => name[..=name.rfind('$').unwrap()].into(),
+            Some(Self::Temporary) => "$".into(),
+            None => name.into(),
+        }
+    }
+
+    /// Returns whether two names may refer to the same object in different builds, which is when they're equal after
+    /// [`Self::normalize`]. Callers should also compare the address, kind and size of the symbols.
+    pub fn names_match(a: &str, b: &str) -> bool {
+        a == b || Self::normalize(a) == Self::normalize(b)
+    }
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|byte| byte.is_ascii_digit())
+}
@@ -1,5 +1,11 @@
+pub mod address;
+pub mod backup;
 pub mod bytes;
-pub mod debug;
+pub mod dwarf;
 pub mod io;
+pub mod jobs;
+pub mod local_name;
 pub mod parse;
 pub mod path;
+pub mod progress;
+pub mod watch;
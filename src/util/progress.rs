@@ -0,0 +1,61 @@
+use std::io::{self, IsTerminal, Stderr, Write};
+
+use anyhow::Result;
+use log::LevelFilter;
+
+const BAR_WIDTH: usize = 30;
+
+/// Reports how many out of a number of modules have been analyzed, such as `12/40 modules`.
+pub struct Progress<W: Write> {
+    writer: Option<W>,
+    bar: bool,
+    done: usize,
+    total: usize,
+}
+
+impl Progress<Stderr> {
+    /// Writes the progress to stderr as a bar. Writes nothing if stderr is not a terminal, or if debug logs are enabled
+    /// since the bar would be interleaved with them.
+    pub fn stderr(total: usize) -> Self {
+        let enabled = io::stderr().is_terminal() && log::max_level() < LevelFilter::Debug;
+        Self { writer: enabled.then(io::stderr), bar: true, done: 0, total }
+    }
+}
+
+impl<W: Write> Progress<W> {
+    /// Writes the progress to `writer`. With `bar`, the same line is overwritten with a bar on every update, otherwise one
+    /// line is written per update.
+    pub fn new(writer: W, total: usize, bar: bool) -> Self {
+        Self { writer: Some(writer), bar, done: 0, total }
+    }
+
+    /// Counts one more module as analyzed and writes the new progress.
+    pub fn advance(&mut self) -> Result<()> {
+        self.done = (self.done + 1).min(self.total);
+        let Some(writer) = &mut self.writer else { return Ok(()) };
+
+        if self.bar {
+            let filled = (BAR_WIDTH * self.done).checked_div(self.total).unwrap_or(BAR_WIDTH);
+            let percent = (100 * self.done).checked_div(self.total).unwrap_or(100);
+            write!(
+                writer,
+                "\r[{}{}] {percent:>3}% {}/{} modules",
+                "#".repeat(filled),
+                " ".repeat(BAR_WIDTH - filled),
+                self.done,
+                self.total
+            )?;
+            if self.done == self.total {
+                writeln!(writer)?;
+            }
+        } else {
+            writeln!(writer, "{}/{} modules", self.done, self.total)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_writer(self) -> Option<W> {
+        self.writer
+    }
+}
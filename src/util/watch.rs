@@ -0,0 +1,109 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Result;
+
+/// How often watched files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long files must stay unchanged before a change is reported, so that a build writing several files only causes
+/// one re-check.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+/// Watches files for changes by polling their modification time and size. Files which don't exist yet are watched for
+/// being created.
+pub struct FileWatcher {
+    files: BTreeMap<PathBuf, Option<FileStamp>>,
+}
+
+impl FileWatcher {
+    pub fn new<I: IntoIterator<Item = PathBuf>>(paths: I) -> Self {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = Self::stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+        Self { files }
+    }
+
+    fn stamp(path: &Path) -> Option<FileStamp> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileStamp { modified: metadata.modified().ok()?, len: metadata.len() })
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns whether any file was changed, created or removed since the watcher was created or last polled.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        for (path, stamp) in &mut self.files {
+            let new_stamp = Self::stamp(path);
+            if new_stamp != *stamp {
+                *stamp = new_stamp;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Blocks until a file changes and then stays unchanged for [`DEBOUNCE`]. Returns false if nothing changed within
+    /// `timeout`.
+    pub fn wait_for_change(&mut self, timeout: Option<Duration>) -> bool {
+        let start = Instant::now();
+        loop {
+            if self.poll() {
+                break;
+            }
+            if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        loop {
+            thread::sleep(DEBOUNCE);
+            if !self.poll() {
+                return true;
+            }
+        }
+    }
+}
+
+/// Runs `check` now and again every time one of the files returned by `paths` changes, and logs whether it passed.
+/// `paths` is called before every check so that changes to the config update the watched files. Errors are logged
+/// instead of stopping the loop, since the files may be mid-rebuild.
+pub fn watch<P, C>(mut paths: P, mut check: C) -> !
+where
+    P: FnMut() -> Result<Vec<PathBuf>>,
+    C: FnMut() -> Result<bool>,
+{
+    let mut watched = vec![];
+    loop {
+        match paths() {
+            Ok(paths) => watched = paths,
+            Err(error) => log::error!("{error:#}"),
+        }
+        let mut watcher = FileWatcher::new(watched.iter().cloned());
+
+        match check() {
+            Ok(true) => log::info!("PASS"),
+            Ok(false) => log::error!("FAIL"),
+            Err(error) => log::error!("FAIL: {error:#}"),
+        }
+        log::info!("Watching {} file(s) for changes...", watcher.len());
+        watcher.wait_for_change(None);
+    }
+}
@@ -0,0 +1,9 @@
+    .include "macros/function.inc"
+
+    .text
+    .global func_0200abc0
+    arm_func_start func_0200abc0
+func_0200abc0: ; 0x200ABC0
+    bx lr
+    arm_func_end func_0200abc0
+
@@ -0,0 +1,40 @@
+    .include "macros/function.inc"
+
+    .text
+    .arm
+    .type func_02000000, %function
+    .global func_02000000
+    arm_func_start func_02000000
+func_02000000: ; 0x02000000
+    bx lr
+    arm_func_end func_02000000
+
+    .size func_02000000, .-func_02000000
+    .thumb
+    .type func_02000004, %function
+    .global func_02000004
+    thumb_func_start func_02000004
+    .thumb_func
+func_02000004: ; 0x02000004
+    bx lr
+    thumb_func_end func_02000004
+
+    .size func_02000004, .-func_02000004
+    .type func_02000006, %function
+    .global func_02000006
+    thumb_func_start func_02000006
+    .thumb_func
+func_02000006: ; 0x02000006
+    bx lr
+    thumb_func_end func_02000006
+
+    .size func_02000006, .-func_02000006
+    .arm
+    .type func_02000008, %function
+    .global func_02000008
+    arm_func_start func_02000008
+func_02000008: ; 0x02000008
+    bx lr
+    arm_func_end func_02000008
+
+    .size func_02000008, .-func_02000008
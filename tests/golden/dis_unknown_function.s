@@ -0,0 +1,19 @@
+    .include "macros/function.inc"
+
+    .text
+    .arm
+    .type func_02000000, %function
+    .global func_02000000
+    arm_func_start func_02000000
+func_02000000: ; 0x02000000
+    bx lr
+    arm_func_end func_02000000
+
+    .size func_02000000, .-func_02000000
+    .type func_02000004, %function
+    .global func_02000004
+    arm_func_start func_02000004
+func_02000004: ; 0x02000004
+    .byte 0x00, 0x00, 0xa0, 0xe3, 0x1e, 0xff, 0x2f, 0xe1
+
+    .size func_02000004, .-func_02000004
@@ -0,0 +1,15 @@
+    .include "macros/function.inc"
+
+    .text
+    .global func_02000000
+    arm_func_start func_02000000
+func_02000000: ; 0x02000000
+    bx lr
+    arm_func_end func_02000000
+
+    .global func_02000004
+    arm_func_start func_02000004
+func_02000004: ; 0x02000004
+    bx lr
+    arm_func_end func_02000004
+
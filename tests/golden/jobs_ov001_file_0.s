@@ -0,0 +1,10 @@
+    .include "macros/function.inc"
+
+    .text
+    .global func_ov001_02100000
+    arm_func_start func_ov001_02100000
+func_ov001_02100000: ; 0x02100000
+    bl func_02000000
+    bx lr
+    arm_func_end func_ov001_02100000
+
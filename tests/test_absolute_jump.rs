@@ -0,0 +1,79 @@
+use anyhow::Result;
+use ds_decomp::analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
+
+fn parse(code: &[u8], base_address: u32, thumb: bool) -> Result<Function> {
+    let result = Function::parse_function(FunctionParseOptions {
+        name: format!("func_{base_address:08x}"),
+        start_address: base_address,
+        base_address,
+        module_code: code,
+        known_end_address: None,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: Some(thumb), ..Default::default() },
+    })?;
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function at {base_address:#010x} was not found: {result:?}");
+    };
+    Ok(function)
+}
+
+#[test]
+fn test_arm_pool_jump() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1
+        0x04, 0xf0, 0x1f, 0xe5, // ldr pc, [pc, #-0x4]
+        0x0c, 0x00, 0x00, 0x02, // .word 0x0200000c
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    ];
+
+    let function = parse(&code, base_address, false)?;
+    assert_eq!(function.end_address(), base_address + 0xc, "pool constant must be part of the function");
+    assert!(function.pool_constants().contains(&(base_address + 0x8)));
+
+    let call = function.function_calls().get(&(base_address + 0x4)).expect("absolute jump should be a tail call");
+    assert_eq!(call.address, base_address + 0xc);
+    assert!(!call.thumb);
+
+    Ok(())
+}
+
+#[test]
+fn test_thumb_pool_jump() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x00, 0x4b,             // ldr r3, [pc, #0x0]
+        0x18, 0x47,             // bx r3
+        0x01, 0x01, 0x00, 0x02, // .word 0x02000101
+    ];
+
+    let function = parse(&code, base_address, true)?;
+    assert_eq!(function.end_address(), base_address + 0x8);
+
+    let call = function.function_calls().get(&(base_address + 0x2)).expect("absolute jump should be a tail call");
+    assert_eq!(call.address, base_address + 0x100);
+    assert!(call.thumb, "BX to an odd address switches to Thumb");
+
+    Ok(())
+}
+
+#[test]
+fn test_arm_immediate_jump() -> Result<()> {
+    let base_address = 0x00000000;
+    #[rustfmt::skip]
+    let code = [
+        0x40, 0xff, 0xa0, 0xe3, // mov pc, #0x100
+    ];
+
+    let function = parse(&code, base_address, false)?;
+    assert_eq!(function.end_address(), base_address + 0x4);
+
+    let call = function.function_calls().get(&base_address).expect("absolute jump should be a tail call");
+    assert_eq!(call.address, 0x100);
+    assert!(!call.thumb);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x4] = [
+    0x1e, 0xff, 0x2f, 0xe1, // func_0200abc0: bx lr
+];
+
+fn disassemble(address_format: AddressFormat) -> Result<String> {
+    let mut symbol_maps = SymbolMaps::new();
+    symbol_maps.get_mut(ModuleKind::Arm9).add(Symbol::new_function(
+        "func_0200abc0".to_string(),
+        0x0200abc0,
+        InstructionMode::Arm,
+        4,
+    ));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x0200abc0, 0x0200abc4, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &CODE,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x0200abc0, 0x0200abc4, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let disassemble = Disassemble {
+        config_path: PathBuf::new(),
+        asm_path: PathBuf::new(),
+        ual: false,
+        module: None,
+        start: None,
+        end: None,
+        jobs: 1,
+        incbin_threshold: None,
+        encoding: None,
+        single_file: false,
+        address_format,
+    };
+    let mut output = vec![];
+    disassemble.disassemble(&module, &delink_file, &mut output, &symbol_maps, None)?;
+    Ok(String::from_utf8(output)?)
+}
+
+#[test]
+fn test_address_formats() -> Result<()> {
+    assert_eq!(disassemble(AddressFormat::Padded)?, include_str!("golden/dis_address_padded.s"));
+    // Only the comment changes, the symbol name keeps its format
+    assert_eq!(disassemble(AddressFormat::UnpaddedUpper)?, include_str!("golden/dis_address_unpadded_upper.s"));
+
+    Ok(())
+}
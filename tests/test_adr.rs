@@ -0,0 +1,73 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult},
+    config::{
+        module::{AnalysisOptions, Module, ModuleKind},
+        symbol::SymbolMaps,
+    },
+};
+
+#[test]
+fn test_adr_data_load() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x08, 0x40, 0x2d, 0xe9, // push {r3, lr}
+        0x00, 0x00, 0x8f, 0xe2, // add r0, pc, #0
+        0x08, 0x80, 0xbd, 0xe8, // pop {r3, pc}
+        b'h', b'i', 0x00, 0x00, // "hi"
+    ];
+
+    let result = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: base_address,
+        base_address,
+        module_code: &code,
+        known_end_address: None,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        parse_options: ParseFunctionOptions::default(),
+    })?;
+
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function was not found: {result:?}");
+    };
+    assert_eq!(function.end_address(), base_address + 0xc);
+    assert_eq!(function.data_loads().get(&(base_address + 4)), Some(&(base_address + 0xc)));
+
+    Ok(())
+}
+
+#[rustfmt::skip]
+const BLOB: [u8; 0x1c] = [
+    0x0c, 0x00, 0x8f, 0xe2, // func_02000000: add r0, pc, #0xc
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x00, 0x00, 0x9f, 0xe5, // func_02000008: ldr r0, [pc, #0x0]
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x14, 0x00, 0x00, 0x02, // .word data_02000014
+    b'H', b'e', b'l', b'l', b'o', 0x00, 0x00, 0x00, // data_02000014: .asciz "Hello"
+];
+
+#[test]
+fn test_adr_relocation() -> Result<()> {
+    let options = AnalysisOptions { has_secure_area: false, ..Default::default() };
+    let mut symbol_maps = SymbolMaps::new();
+    let module = Module::analyze_code_blob(&BLOB, 0x02000000, ModuleKind::Arm9, &mut symbol_maps, &options)?;
+    let symbol_map = symbol_maps.get(ModuleKind::Arm9).unwrap();
+
+    let (_, symbol) = symbol_map.get_data(0x02000014)?.expect("string was not found");
+    assert_eq!(symbol.name, "data_02000014");
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("adr_relocs.txt");
+    module.relocations().to_file(&path)?;
+    let relocs = fs::read_to_string(&path)?;
+    let expected = "\
+from:0x02000000 kind:arm_adr to:0x02000014 module:main
+from:0x02000010 kind:load to:0x02000014 module:main
+";
+    assert_eq!(relocs, expected);
+
+    Ok(())
+}
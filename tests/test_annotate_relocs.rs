@@ -0,0 +1,101 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::{
+        data::{self, FindLocalDataOptions},
+        functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult},
+    },
+    config::{
+        module::{AnalysisOptions, ModuleKind},
+        relocation::{RelocCommentTemplate, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{SymbolMap, SymbolMaps},
+    },
+};
+
+#[test]
+fn test_pool_relocation_source() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x00, 0x00, 0x9f, 0xe5, // ldr r0, [pc, #0x0]
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+        0x0c, 0x00, 0x00, 0x02, // .word 0x0200000c
+        0x00, 0x00, 0x00, 0x00, // .data
+    ];
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, base_address, base_address + 0xc, 4)?)?;
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, base_address + 0xc, base_address + 0x10, 4)?)?;
+
+    let ParseFunctionResult::Found(function) = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: base_address,
+        base_address,
+        module_code: &code,
+        known_end_address: None,
+        module_start_address: base_address,
+        module_end_address: base_address + 0xc,
+        parse_options: ParseFunctionOptions { thumb: Some(false), ..Default::default() },
+    })?
+    else {
+        panic!("function was not found");
+    };
+
+    let analysis_options = AnalysisOptions { reloc_comment: Some(RelocCommentTemplate::default()), ..Default::default() };
+    let mut symbol_map = SymbolMap::new();
+    let mut relocations = Relocations::new();
+    data::find_local_data_from_pools(
+        &function,
+        FindLocalDataOptions {
+            sections: &sections,
+            module_kind: ModuleKind::Arm9,
+            symbol_map: &mut symbol_map,
+            relocations: &mut relocations,
+            name_prefix: "data_",
+            code: &code,
+            base_address,
+            address_range: None,
+        },
+        &analysis_options,
+    )?;
+
+    let relocation = relocations.get(base_address + 0x8).expect("pool constant should be relocated");
+    assert_eq!(relocation.source.as_deref(), Some("pointer in pool of func_02000000"));
+
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("annotate_relocs");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("relocs.txt");
+    relocations.to_file(&path)?;
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        "from:0x02000008 kind:load to:0x0200000c module:main // source: pointer in pool of func_02000000\n"
+    );
+
+    // The comment is ignored when loading, apart from being kept for the next write
+    let reloaded = Relocations::from_file(&path, &SymbolMaps::new())?;
+    let reloaded_relocation = reloaded.get(base_address + 0x8).unwrap();
+    assert!(reloaded_relocation == relocation);
+    assert_eq!(reloaded_relocation.to_address(), base_address + 0xc);
+    assert_eq!(reloaded_relocation.source, relocation.source);
+
+    // Comments without the source marker are not relocation sources
+    fs::write(&path, "from:0x02000008 kind:load to:0x0200000c module:main // TODO: check this\n")?;
+    let reloaded = Relocations::from_file(&path, &SymbolMaps::new())?;
+    assert_eq!(reloaded.get(base_address + 0x8).unwrap().source, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_reloc_comment_template() -> Result<()> {
+    let template: RelocCommentTemplate = "dsd: {source}".parse()?;
+    assert_eq!(template.format("call from func_02000000"), "dsd: call from func_02000000");
+    assert_eq!(RelocCommentTemplate::default().format("call from func_02000000"), "call from func_02000000");
+
+    assert!("dsd".parse::<RelocCommentTemplate>().is_err(), "template without placeholder");
+    assert!("{source}\nmore".parse::<RelocCommentTemplate>().is_err(), "template with line break");
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use ds_decomp::cmd::{ApplyRenames, RenameFailureReason};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+const SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+";
+
+const RENAMES: &str = "\
+# Renames by name and by address
+func_02000000 MyFunction
+0x02000104 gMissing
+";
+
+fn setup(name: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("symbols.txt"), SYMBOLS)?;
+    fs::write(path.join("renames.txt"), RENAMES)?;
+    Ok(path)
+}
+
+fn apply_renames(path: &Path, strict: bool) -> ApplyRenames {
+    ApplyRenames {
+        config_path: path.join("config.yaml"),
+        file: path.join("renames.txt"),
+        module: None,
+        strict,
+        dry: false,
+        no_backup: true,
+    }
+}
+
+#[test]
+fn test_apply_renames_strict() -> Result<()> {
+    let path = setup("apply_renames_strict")?;
+
+    let result = apply_renames(&path, true).apply()?;
+    assert_eq!(result.renames.len(), 1);
+    assert_eq!(result.failures.len(), 1);
+    assert_eq!(result.failures[0].line, 3);
+    assert_eq!(result.failures[0].reason, RenameFailureReason::NotFound);
+    assert_eq!(fs::read_to_string(path.join("symbols.txt"))?, SYMBOLS, "strict mode must not write after a failure");
+
+    Ok(())
+}
+
+#[test]
+fn test_apply_renames_partial() -> Result<()> {
+    let path = setup("apply_renames_partial")?;
+
+    let result = apply_renames(&path, false).apply()?;
+    assert_eq!(result.renames.len(), 1);
+    assert_eq!(result.failures.len(), 1);
+    assert!(fs::read_to_string(path.join("symbols.txt"))?.starts_with("MyFunction kind:function(arm,size=0x8)"));
+
+    Ok(())
+}
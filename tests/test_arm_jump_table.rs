@@ -0,0 +1,63 @@
+use anyhow::Result;
+use ds_decomp::analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0x38] = [
+    0x02, 0x00, 0x50, 0xe3, // cmp r0, #0x2
+    0x00, 0xf1, 0x8f, 0x90, // addls pc, pc, r0, lsl #0x2
+    0x08, 0x00, 0x00, 0xea, // b default
+    0x01, 0x00, 0x00, 0xea, // b case 0
+    0x02, 0x00, 0x00, 0xea, // b case 1
+    0x03, 0x00, 0x00, 0xea, // b case 2
+    // case 0
+    0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // case 1
+    0x02, 0x00, 0xa0, 0xe3, // mov r0, #0x2
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // case 2
+    0x03, 0x00, 0xa0, 0xe3, // mov r0, #0x3
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // default
+    0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+#[test]
+fn test_bounded_arm_switch() -> Result<()> {
+    let result = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: BASE_ADDRESS,
+        base_address: BASE_ADDRESS,
+        module_code: &CODE,
+        known_end_address: None,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + CODE.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: Some(false), ..Default::default() },
+    })?;
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function was not found: {result:?}");
+    };
+
+    assert_eq!(function.end_address(), BASE_ADDRESS + CODE.len() as u32);
+
+    // The cmp immediate is the highest case, so the table has one more entry than that
+    let jump_tables = function.jump_tables().collect::<Vec<_>>();
+    assert_eq!(jump_tables.len(), 1);
+    assert_eq!(jump_tables[0].address, BASE_ADDRESS + 0xc);
+    assert_eq!(jump_tables[0].size, 3 * 4);
+    assert_eq!(jump_tables[0].entry_size, 4);
+    assert!(jump_tables[0].code);
+
+    for case_address in [0x18, 0x20, 0x28, 0x30] {
+        assert!(
+            function.labels().any(|&label| label == BASE_ADDRESS + case_address),
+            "no label for case at {case_address:#x}"
+        );
+    }
+    assert!(function.external_jump_targets().next().is_none(), "cases within the function are not external");
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::data::{self, FindLocalDataOptions},
+    config::{
+        module::{AnalysisOptions, ModuleKind},
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{SymBss, Symbol, SymbolKind, SymbolMap},
+    },
+};
+
+#[test]
+fn test_data_pointers_into_bss() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x20, 0x00, 0x00, 0x02, // .word 0x02000020, start of unknown bss symbol
+        0x18, 0x00, 0x00, 0x02, // .word 0x02000018, field of gStruct
+    ];
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, base_address, base_address + 0x8, 4)?)?;
+    sections.add(Section::new(".bss".to_string(), SectionKind::Bss, base_address + 0x10, base_address + 0x40, 16)?)?;
+
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_bss("gStruct".to_string(), base_address + 0x10, SymBss { size: Some(0x10) }, false));
+
+    let analysis_options = AnalysisOptions { ..Default::default() };
+    let mut relocations = Relocations::new();
+    data::find_local_data_from_section(
+        sections.by_name(".data").unwrap(),
+        FindLocalDataOptions {
+            sections: &sections,
+            module_kind: ModuleKind::Arm9,
+            symbol_map: &mut symbol_map,
+            relocations: &mut relocations,
+            name_prefix: "data_",
+            code: &code,
+            base_address,
+            address_range: None,
+        },
+        &analysis_options,
+    )?;
+
+    let (_, symbol) = symbol_map.by_address(base_address + 0x20)?.expect("bss symbol should be added");
+    assert_eq!(symbol.name, "data_02000020");
+    assert!(symbol.kind == SymbolKind::Bss(SymBss { size: None }));
+    let relocation = relocations.get(base_address).expect("pointer into bss should be relocated");
+    assert_eq!(relocation.to_address(), base_address + 0x20);
+    assert_eq!(relocation.addend(), 0);
+
+    assert!(symbol_map.by_address(base_address + 0x18)?.is_none(), "gStruct must not be split");
+    let relocation = relocations.get(base_address + 0x4).expect("pointer into gStruct should be relocated");
+    assert_eq!(relocation.to_address(), base_address + 0x10);
+    assert_eq!(relocation.addend(), 0x8);
+
+    Ok(())
+}
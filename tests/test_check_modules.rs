@@ -0,0 +1,81 @@
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{AlignmentFix, CheckModules, ModuleDifference},
+    config::{
+        module::ModuleKind,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMap},
+    },
+};
+
+#[test]
+fn test_first_difference() -> Result<()> {
+    #[rustfmt::skip]
+    let base_code = [
+        0x1e, 0xff, 0x2f, 0xe1, // func_02000000: bx lr
+        0x01, 0x00, 0xa0, 0xe3, // func_02000004: mov r0, #0x1
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    ];
+    let mut code = base_code;
+    code[8] = 0x1d;
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let symbol_map = SymbolMap::from_symbols(vec![
+        Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 0x4),
+        Symbol::new_function("func_02000004".to_string(), 0x02000004, InstructionMode::Arm, 0x8),
+    ]);
+
+    let difference = CheckModules::find_difference(&base_code, &code, &sections, &symbol_map);
+    assert_eq!(
+        difference,
+        Some(ModuleDifference {
+            offset: 0x8,
+            address: 0x02000008,
+            section: Some(".text".to_string()),
+            symbol: Some(("func_02000004".to_string(), 0x4)),
+        })
+    );
+    assert_eq!(difference.unwrap().to_string(), "first difference at offset 0x8 (0x02000008) in .text at func_02000004+0x4");
+
+    assert_eq!(CheckModules::find_difference(&base_code, &base_code, &sections, &symbol_map), None);
+
+    // A truncated module differs where it ends
+    let difference = CheckModules::find_difference(&base_code, &base_code[..6], &sections, &symbol_map).unwrap();
+    assert_eq!(difference.offset, 0x6);
+
+    Ok(())
+}
+
+#[test]
+fn test_alignment_fix() -> Result<()> {
+    let text = [0x1e, 0xff, 0x2f, 0xe1, 0x01, 0x00, 0xa0, 0xe3];
+    let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+    // The base overlay aligns .data to 32 bytes, but the built one only to 8
+    let base_code = [&text[..], &[0; 0x18], &data].concat();
+    let code = [text, data].concat();
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02200000, 0x02200008, 4)?)?;
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02200020, 0x02200028, 32)?)?;
+    let symbol_map = SymbolMap::from_symbols(vec![Symbol::new_function(
+        "func_02200000".to_string(),
+        0x02200000,
+        InstructionMode::Arm,
+        0x8,
+    )]);
+
+    let difference = CheckModules::find_difference(&base_code, &code, &sections, &symbol_map).unwrap();
+    let fix = CheckModules::find_alignment_fix(&base_code, &code, &sections, ModuleKind::Overlay(5), &difference);
+    assert_eq!(fix, Some(AlignmentFix { module: ModuleKind::Overlay(5), section: ".data".to_string(), alignment: 32 }));
+    assert_eq!(fix.unwrap().to_string(), "overlay 5 is misaligned, add `. = ALIGN(32);` before .data in the LCF");
+
+    // Not confident if the section doesn't match after skipping the padding
+    let mut code = code;
+    code[0xc] = 0;
+    let difference = CheckModules::find_difference(&base_code, &code, &sections, &symbol_map).unwrap();
+    assert_eq!(CheckModules::find_alignment_fix(&base_code, &code, &sections, ModuleKind::Overlay(5), &difference), None);
+
+    Ok(())
+}
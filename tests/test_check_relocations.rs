@@ -0,0 +1,75 @@
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{EncodingChecker, EncodingMismatch},
+    config::{
+        relocation::{Relocation, RelocationModule},
+        symbol::{InstructionMode, Symbol, SymbolMap},
+    },
+};
+use unarm::Endian;
+
+#[test]
+fn test_relocation_encodings() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x00, 0x00, 0x00, 0xeb, // bl Callee
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+        0x1e, 0xff, 0x2f, 0xe1, // Callee: bx lr
+    ];
+    let symbol_map = SymbolMap::from_symbols(vec![
+        Symbol::new_function("Caller".to_string(), base_address, InstructionMode::Arm, 0x8),
+        Symbol::new_function("Callee".to_string(), base_address + 0x8, InstructionMode::Arm, 0x4),
+    ]);
+
+    let mut checker = EncodingChecker::new(&code, base_address, &symbol_map, Endian::Little);
+    let mut check = |relocation: Relocation| checker.check(&relocation);
+
+    let arm_call = Relocation::new_call(base_address, base_address + 0x8, RelocationModule::Main, false, false);
+    assert_eq!(check(arm_call)?, None);
+
+    // Thumb BL relocation on an ARM instruction
+    let thumb_call = Relocation::new_call(base_address, base_address + 0x8, RelocationModule::Main, true, true);
+    assert_eq!(check(thumb_call)?, Some(EncodingMismatch::WrongMode { function: "Caller".to_string() }));
+
+    let branch = Relocation::new_branch(base_address, base_address + 0x8, RelocationModule::Main);
+    let Some(EncodingMismatch::WrongInstruction { mnemonic }) = check(branch)? else {
+        panic!("branch relocation on a BL instruction should fail");
+    };
+    assert!(mnemonic.starts_with("bl"), "{mnemonic}");
+
+    let load = Relocation::new_load(base_address + 0x4, base_address + 0x8, 0, RelocationModule::Main);
+    assert_eq!(check(load)?, Some(EncodingMismatch::NotPoolConstant { function: "Caller".to_string() }));
+
+    let misaligned = Relocation::new_call(base_address + 0x2, base_address + 0x8, RelocationModule::Main, false, false);
+    assert_eq!(check(misaligned)?, Some(EncodingMismatch::Misaligned { alignment: 4 }));
+
+    Ok(())
+}
+
+#[test]
+fn test_big_endian_relocation_encodings() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0xeb, 0x00, 0x00, 0x02, // bl Callee
+        0xe5, 0x9f, 0x00, 0x00, // ldr r0, [pc, #0x0]
+        0xe1, 0x2f, 0xff, 0x1e, // bx lr
+        0x02, 0x00, 0x00, 0x10, // .word Callee
+        0xe1, 0x2f, 0xff, 0x1e, // Callee: bx lr
+    ];
+    let symbol_map = SymbolMap::from_symbols(vec![
+        Symbol::new_function("Caller".to_string(), base_address, InstructionMode::Arm, 0x10),
+        Symbol::new_function("Callee".to_string(), base_address + 0x10, InstructionMode::Arm, 0x4),
+    ]);
+
+    let mut checker = EncodingChecker::new(&code, base_address, &symbol_map, Endian::Big);
+
+    let arm_call = Relocation::new_call(base_address, base_address + 0x10, RelocationModule::Main, false, false);
+    assert_eq!(checker.check(&arm_call)?, None);
+
+    let load = Relocation::new_load(base_address + 0xc, base_address + 0x10, 0, RelocationModule::Main);
+    assert_eq!(checker.check(&load)?, None);
+
+    Ok(())
+}
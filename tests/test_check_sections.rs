@@ -0,0 +1,87 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{CheckSections, SectionViolation},
+    config::module::ModuleKind,
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: ov000_delinks.txt
+  symbols: ov000_symbols.txt
+  relocations: ov000_relocs.txt
+- id: 1
+  name: ov001
+  object: build/ov001.bin
+  hash: '0000000000000000'
+  delinks: ov001_delinks.txt
+  symbols: ov001_symbols.txt
+  relocations: ov001_relocs.txt
+";
+
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000104 kind:code align:32
+    .data       start:0x02000120 end:0x02000140 kind:data align:32
+    .bss        start:0x02000180 end:0x02000200 kind:bss align:32
+";
+
+// Parses, but mwldarm would place the overlay at 0x02100020
+const OV000_DELINKS: &str = "    .text       start:0x02100010 end:0x02100100 kind:code align:4
+";
+
+const OV001_DELINKS: &str = "    .text       start:0x02100000 end:0x02100110 kind:code align:32
+    .data       start:0x02100110 end:0x02100200 kind:data align:32
+";
+
+#[test]
+fn test_check_sections() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("check_sections");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), MAIN_DELINKS)?;
+    fs::write(path.join("ov000_delinks.txt"), OV000_DELINKS)?;
+    fs::write(path.join("ov001_delinks.txt"), OV001_DELINKS)?;
+
+    let check_sections = |allow_gaps| CheckSections { config_path: path.join("config.yaml"), allow_gaps, fail: false };
+
+    let violations = check_sections(false).check()?;
+    assert_eq!(violations.len(), 3, "{violations:?}");
+    assert_eq!(
+        violations[0],
+        (
+            ModuleKind::Arm9,
+            SectionViolation::Gap {
+                before: ".data".to_string(),
+                after: ".bss".to_string(),
+                start: 0x02000140,
+                end: 0x02000180
+            }
+        )
+    );
+    assert_eq!(violations[1], (ModuleKind::Overlay(0), SectionViolation::MisalignedOverlay { address: 0x02100010 }));
+    let (module_kind, SectionViolation::Invalid { message }) = &violations[2] else {
+        panic!("misaligned .data was not reported: {:?}", violations[2]);
+    };
+    assert_eq!(*module_kind, ModuleKind::Overlay(1));
+    assert!(message.contains("misaligned address 0x02100110"), "{message}");
+
+    let violations = check_sections(true).check()?;
+    assert_eq!(violations.len(), 2, "gaps must not be reported: {violations:?}");
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{CheckSymbols, ElfSymbols, SymbolCheckSummary},
+    config::{
+        module::ModuleKind,
+        symbol::{InstructionMode, SymBss, SymData, Symbol, SymbolKind, SymbolMap},
+    },
+};
+use object::{
+    write::{Object, SymbolSection},
+    Architecture, BinaryFormat, Endianness, SymbolFlags, SymbolScope,
+};
+
+fn add_elf_symbol(object: &mut Object, section: SymbolSection, name: &str, address: u32, size: u32) {
+    object.add_symbol(object::write::Symbol {
+        name: name.as_bytes().to_vec(),
+        value: address as u64,
+        size: size as u64,
+        kind: object::SymbolKind::Data,
+        scope: SymbolScope::Linkage,
+        weak: false,
+        section,
+        flags: SymbolFlags::None,
+    });
+}
+
+#[test]
+fn test_check_symbols_summary() -> Result<()> {
+    let symbol_map = SymbolMap::from_symbols(vec![
+        Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 0x10),
+        Symbol::new_data("data_02000100".to_string(), 0x02000100, SymData::Word { count: Some(1) }, false),
+        Symbol::new_data("data_02000200".to_string(), 0x02000200, SymData::Word { count: Some(1) }, false),
+        Symbol::new_data("data_02000300".to_string(), 0x02000300, SymData::Any, false),
+        Symbol::new_bss("bss_02000400".to_string(), 0x02000400, SymBss { size: Some(0x20) }, false),
+    ]);
+
+    let mut object = Object::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Little);
+    let section_id = object.add_section(vec![], b"ARM9".to_vec(), object::SectionKind::Data);
+    object.append_section_data(section_id, &[0; 0x500], 4);
+    let section = SymbolSection::Section(section_id);
+    add_elf_symbol(&mut object, section, "func_02000000", 0x02000000, 0x10);
+    add_elf_symbol(&mut object, section, "data_02000100", 0x02000104, 4);
+    add_elf_symbol(&mut object, section, "data_02000200", 0x02000200, 8);
+    add_elf_symbol(&mut object, section, "bss_02000400", 0x02000400, 0x20);
+    let elf = object.write()?;
+    let elf_symbols = ElfSymbols::from_object(&object::File::parse(&*elf)?)?;
+
+    let check_symbols = CheckSymbols {
+        config_path: PathBuf::new(),
+        elf_paths: vec![],
+        elf_dir: None,
+        fail: false,
+        summary: true,
+        limit: None,
+        watch: false,
+        normalize_locals: false,
+    };
+    let mut num_printed = 0;
+    let summary = check_symbols.check_module(ModuleKind::Arm9, &symbol_map, &elf_symbols, &mut num_printed);
+
+    assert_eq!(summary, SymbolCheckSummary { checked: 5, matched: 2, missing: 1, address_mismatched: 1, size_mismatched: 1 });
+    assert!(!summary.passed());
+    assert_eq!(num_printed, 0);
+
+    Ok(())
+}
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+/// Writes an ELF file with one section named after a module, containing the given symbols.
+fn write_elf(path: PathBuf, section_name: &str, symbols: &[(&str, u32, u32)]) -> Result<PathBuf> {
+    let mut object = Object::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Little);
+    let section_id = object.add_section(vec![], section_name.as_bytes().to_vec(), object::SectionKind::Data);
+    object.append_section_data(section_id, &[0; 0x100], 4);
+    for &(name, address, size) in symbols {
+        add_elf_symbol(&mut object, SymbolSection::Section(section_id), name, address, size);
+    }
+    fs::write(&path, object.write()?)?;
+    Ok(path)
+}
+
+#[test]
+fn test_check_symbols_multiple_elfs() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("check_symbols_multiple_elfs");
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(path.join("overlays/ov000"))?;
+    fs::create_dir_all(path.join("build/elf"))?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("symbols.txt"), "data_02000000 kind:data(word) addr:0x02000000\n")?;
+    fs::write(path.join("overlays/ov000/symbols.txt"), "data_ov000_02100000 kind:data(word) addr:0x02100000\n")?;
+
+    let main_elf = write_elf(path.join("build/elf/main.elf"), "ARM9", &[("data_02000000", 0x02000000, 4)])?;
+    let overlay_elf = write_elf(path.join("build/elf/ov000.elf"), "ov000", &[("data_ov000_02100000", 0x02100000, 4)])?;
+
+    let check_symbols = |elf_paths: Vec<PathBuf>, elf_dir: Option<PathBuf>| CheckSymbols {
+        config_path: path.join("config.yaml"),
+        elf_paths,
+        elf_dir,
+        fail: false,
+        summary: true,
+        limit: None,
+        watch: false,
+        normalize_locals: false,
+    };
+
+    assert!(check_symbols(vec![main_elf.clone(), overlay_elf.clone()], None).check()?);
+    assert!(!check_symbols(vec![main_elf.clone()], None).check()?, "overlay symbols must be missing without its ELF");
+
+    let from_dir = check_symbols(vec![], Some(path.join("build/elf")));
+    assert_eq!(from_dir.elf_files()?, [main_elf, overlay_elf]);
+    assert!(from_dir.check()?);
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+use anyhow::Result;
+use ds_decomp::{cmd::CheckModules, util::watch::FileWatcher};
+
+const MATCHING_CODE: [u8; 8] = [0x01, 0x00, 0xa0, 0xe3, 0x1e, 0xff, 0x2f, 0xe1];
+
+fn config(hash: u64) -> String {
+    format!(
+        "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '{hash:016x}'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+"
+    )
+}
+
+#[test]
+fn test_check_modules_recheck_on_change() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("check_watch");
+    fs::create_dir_all(path.join("build"))?;
+    fs::write(path.join("config.yaml"), config(fxhash::hash64(&MATCHING_CODE)))?;
+    fs::write(path.join("build/arm9.bin"), [0x1e, 0xff, 0x2f, 0xe1])?;
+
+    let check_modules = CheckModules {
+        config_path: path.join("config.yaml"),
+        fail: false,
+        color: false,
+        watch: true,
+        fix_alignment: false,
+    };
+    let mut watcher = FileWatcher::new(check_modules.watched_paths()?);
+    assert!(!check_modules.check()?);
+    assert!(!watcher.wait_for_change(Some(Duration::from_millis(100))), "nothing has changed yet");
+
+    // Simulate a rebuild which fixes the module
+    let object_path = path.join("build/arm9.bin");
+    let rebuild = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        fs::write(object_path, MATCHING_CODE)
+    });
+    assert!(watcher.wait_for_change(Some(Duration::from_secs(10))), "rebuilt module was not detected");
+    rebuild.join().unwrap()?;
+    assert!(check_modules.check()?);
+
+    Ok(())
+}
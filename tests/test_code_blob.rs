@@ -0,0 +1,42 @@
+use anyhow::Result;
+use ds_decomp::config::{
+    module::{AnalysisOptions, Module, ModuleKind},
+    symbol::SymbolMaps,
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x1c] = [
+    0x00, 0x00, 0x9f, 0xe5, // func_02000000: ldr r0, [pc, #0x0]
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x14, 0x00, 0x00, 0x02, // .word data_02000014
+    0x01, 0x00, 0xa0, 0xe3, // func_0200000c: mov r0, #0x1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    b'H', b'e', b'l', b'l', b'o', 0x00, 0x00, 0x00, // data_02000014: .asciz "Hello"
+];
+
+#[test]
+fn test_analyze_code_blob() -> Result<()> {
+    let options = AnalysisOptions { has_secure_area: false, ..Default::default() };
+    let mut symbol_maps = SymbolMaps::new();
+    let module = Module::analyze_code_blob(&CODE, 0x02000000, ModuleKind::Arm9, &mut symbol_maps, &options)?;
+    let symbol_map = symbol_maps.get(ModuleKind::Arm9).unwrap();
+
+    for (address, size) in [(0x02000000, 0xc), (0x0200000c, 0x8)] {
+        let (function, symbol) = symbol_map.get_function(address)?.expect("function was not found");
+        assert_eq!(symbol.name, format!("func_{address:08x}"));
+        assert_eq!(function.size, size);
+        assert!(module.get_function(address).is_some());
+    }
+
+    let (_, symbol) = symbol_map.get_data(0x02000014)?.expect("string was not found");
+    assert_eq!(symbol.name, "data_02000014");
+
+    let relocations = module.relocations().iter().map(|relocation| (relocation.from_address(), relocation.to_address()));
+    assert_eq!(relocations.collect::<Vec<_>>(), [(0x02000008, 0x02000014)]);
+
+    let sections = module.sections();
+    assert_eq!(sections.by_name(".text").map(|section| section.end_address()), Some(0x02000014));
+    assert_eq!(sections.by_name(".rodata").map(|section| section.end_address()), Some(0x0200001c));
+
+    Ok(())
+}
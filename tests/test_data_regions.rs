@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::{
+        data_regions::{DataRegion, DataRegions},
+        module::ModuleKind,
+        symbol::SymbolMap,
+    },
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0x18] = [
+    0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // Data which happens to look like a function
+    0x01, 0x00, 0xa0, 0xe3,
+    0x1e, 0xff, 0x2f, 0xe1,
+    0x02, 0x00, 0xa0, 0xe3, // mov r0, #0x2
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+fn find_function_addresses(data_regions: &DataRegions) -> Result<Vec<u32>> {
+    let mut symbol_map = SymbolMap::new();
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: &CODE,
+        symbol_map: &mut symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + CODE.len() as u32,
+        search_options: FunctionSearchOptions::default(),
+        function_overrides: None,
+        data_regions: data_regions.get(ModuleKind::Arm9),
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+    Ok(functions.keys().copied().collect())
+}
+
+#[test]
+fn test_data_region_is_not_code() -> Result<()> {
+    assert_eq!(find_function_addresses(&DataRegions::new())?, vec![BASE_ADDRESS, BASE_ADDRESS + 0x8, BASE_ADDRESS + 0x10]);
+
+    let region = DataRegion::from_str("main:0x02000008:0x02000010")?;
+    let data_regions = DataRegions::from_regions([&region])?;
+    assert_eq!(find_function_addresses(&data_regions)?, vec![BASE_ADDRESS, BASE_ADDRESS + 0x10]);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_data_region() -> Result<()> {
+    let region = DataRegion::from_str("overlay:5:0x020a0000:0x020a0400")?;
+    assert_eq!(region, DataRegion { module: ModuleKind::Overlay(5), start: 0x020a0000, end: 0x020a0400 });
+    assert_eq!(region.to_string(), "overlay:5:0x020a0000:0x020a0400");
+
+    assert!(DataRegion::from_str("main:0x02000010:0x02000010").is_err());
+    assert!(DataRegion::from_str("0x02000000:0x02000010").is_err());
+
+    let overlapping = DataRegion::from_str("overlay:5:0x020a0200:0x020a0600")?;
+    assert!(DataRegions::from_regions([&region, &overlapping]).is_err());
+
+    Ok(())
+}
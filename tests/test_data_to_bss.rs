@@ -0,0 +1,163 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{Delink, FixDataToBss},
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, RelocationSources, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{SymData, Symbol, SymbolMap, SymbolMaps},
+    },
+};
+use object::{Object, ObjectSection, ObjectSymbol};
+
+#[rustfmt::skip]
+static DATA: [u8; 0x20] = [
+    0x10, 0x01, 0x00, 0x02, // data_02000100: pointer to data_02000110
+    0x00, 0x00, 0x00, 0x00, // data_02000104: not at the end of .data
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, // gZeroed: named by the user
+    0x00, 0x00, 0x00, 0x00, // data_02000110: referenced
+    0x00, 0x00, 0x00, 0x00, // data_02000114
+    0x00, 0x00, 0x00, 0x00, // data_02000118
+    0x00, 0x00, 0x00, 0x00,
+];
+
+fn sections() -> Result<Sections> {
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000100, 0x02000120, 4)?)?;
+    sections.add(Section::new(".bss".to_string(), SectionKind::Bss, 0x02000120, 0x02000140, 4)?)?;
+    Ok(sections)
+}
+
+fn symbol_map() -> SymbolMap {
+    let word = |name: &str, addr: u32, count: u32| {
+        Symbol::new_data(name.to_string(), addr, SymData::Word { count: Some(count) }, false)
+    };
+    SymbolMap::from_symbols(vec![
+        word("data_02000100", 0x02000100, 1),
+        word("data_02000104", 0x02000104, 2),
+        word("gZeroed", 0x0200010c, 1),
+        word("data_02000110", 0x02000110, 1),
+        word("data_02000114", 0x02000114, 1),
+        word("data_02000118", 0x02000118, 2),
+    ])
+}
+
+fn relocations() -> Result<Relocations> {
+    let mut relocations = Relocations::new();
+    relocations.add_load(0x02000100, 0x02000110, 0, RelocationModule::Main)?;
+    Ok(relocations)
+}
+
+fn data_to_bss(symbol_map: &mut SymbolMap, sections: &mut Sections, files: &[DelinkFile], force: bool) -> Result<usize> {
+    let relocation_targets = BTreeSet::from([(ModuleKind::Arm9, 0x02000110)]);
+    FixDataToBss::data_to_bss(
+        symbol_map,
+        sections,
+        files,
+        &DATA,
+        0x02000100,
+        ModuleKind::Arm9,
+        &relocation_targets,
+        &relocations()?,
+        "data_",
+        force,
+    )
+}
+
+#[test]
+fn test_data_to_bss_keeps_referenced_symbols() -> Result<()> {
+    let mut symbol_map = symbol_map();
+    let mut sections = sections()?;
+    assert_eq!(data_to_bss(&mut symbol_map, &mut sections, &[], true)?, 2);
+
+    let symbols = symbol_map.iter().map(|symbol| symbol.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        symbols,
+        vec![
+            "data_02000100 kind:data(word) addr:0x02000100",
+            "data_02000104 kind:data(word) addr:0x02000104",
+            "gZeroed kind:data(word) addr:0x0200010c",
+            "data_02000110 kind:data(word) addr:0x02000110",
+            "data_02000114 kind:bss(size=0x4) addr:0x02000114",
+            "data_02000118 kind:bss(size=0x8) addr:0x02000118",
+        ]
+    );
+
+    // .bss starts at the first changed symbol
+    assert_eq!(sections.by_name(".data").unwrap().address_range(), 0x02000100..0x02000114);
+    assert_eq!(sections.by_name(".bss").unwrap().address_range(), 0x02000114..0x02000140);
+
+    Ok(())
+}
+
+#[test]
+fn test_data_to_bss_requires_force() -> Result<()> {
+    // Moving data to .bss shrinks the module, which then no longer matches the ROM
+    let mut symbol_map = symbol_map();
+    let mut sections = sections()?;
+    assert_eq!(data_to_bss(&mut symbol_map, &mut sections, &[], false)?, 0);
+    assert_eq!(sections.by_name(".bss").unwrap().address_range(), 0x02000120..0x02000140);
+    assert!(symbol_map.get_data(0x02000114)?.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_data_to_bss_keeps_file_data() -> Result<()> {
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000100, 0x02000120, 4)?)?;
+    let files = [DelinkFile::new("src/data.c".to_string(), file_sections, false)];
+
+    // The zeroed symbols are in .data of a file, which can't be changed without editing the file
+    let mut symbol_map = symbol_map();
+    let mut sections = sections()?;
+    assert_eq!(data_to_bss(&mut symbol_map, &mut sections, &files, true)?, 0);
+    assert_eq!(sections.by_name(".bss").unwrap().address_range(), 0x02000120..0x02000140);
+    assert!(symbol_map.get_data(0x02000114)?.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_data_to_bss_delink() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    *symbol_maps.get_mut(ModuleKind::Arm9) = symbol_map();
+    let mut sections = sections()?;
+    data_to_bss(symbol_maps.get_mut(ModuleKind::Arm9), &mut sections, &[], true)?;
+
+    let mut file_sections = Sections::new();
+    for section in sections.iter() {
+        file_sections.add(Section::inherit(section, section.start_address(), section.end_address())?)?;
+    }
+    let delink_file = DelinkFile::new("src/data.c".to_string(), file_sections, false);
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        relocations()?,
+        sections,
+        &DATA,
+        &NamePrefixes::default(),
+    )?;
+
+    let bytes = Delink::delink(&symbol_maps, &RelocationSources::new(), &module, &delink_file)?.write()?;
+    let object = object::File::parse(bytes.as_slice())?;
+
+    let section_name = |name: &str| -> Result<String> {
+        let symbol = object.symbols().find(|symbol| symbol.name() == Ok(name)).expect(name);
+        let section = object.section_by_index(symbol.section_index().expect(name))?;
+        Ok(section.name()?.to_string())
+    };
+    assert_eq!(section_name("data_02000110")?, ".data");
+    assert_eq!(section_name("data_02000114")?, ".bss");
+    assert_eq!(section_name("data_02000118")?, ".bss");
+
+    let data = object.section_by_name(".data").unwrap();
+    assert_eq!(data.size(), 0x14);
+
+    Ok(())
+}
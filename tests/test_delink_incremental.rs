@@ -0,0 +1,133 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{Delink, DelinkResult, DELINK_MANIFEST_FILE_NAME},
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, RelocationSources, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x10] = [
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000000: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000004: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000008: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_0200000c: bx lr
+];
+
+/// Marks an object file so that the test can tell whether it was rewritten
+const MARKER: &[u8] = b"not rewritten";
+
+/// Delinks the main module into two files, `file_0` at 0x02000000 and `second_file` at 0x02000008.
+fn delink(elf_path: &Path, second_file: &str, func_02000008: &str, relocations: Relocations) -> Result<DelinkResult> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_function("func_02000004".to_string(), 0x02000004, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_function(func_02000008.to_string(), 0x02000008, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_function("func_0200000c".to_string(), 0x0200000c, InstructionMode::Arm, 4));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000010, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        relocations,
+        sections,
+        &CODE,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut files = vec![];
+    for (name, start) in [("file_0", 0x02000000), (second_file, 0x02000008)] {
+        let mut sections = Sections::new();
+        sections.add(Section::new(".text".to_string(), SectionKind::Code, start, start + 0x8, 4)?)?;
+        files.push(DelinkFile::new(format!("src/{name}.c"), sections, false));
+    }
+
+    let delink = Delink { config_path: PathBuf::new(), jobs: 1, incremental: true };
+    delink.delink_modules(&[(module, files)], elf_path, &symbol_maps, &RelocationSources::new())
+}
+
+#[test]
+fn test_delink_incremental() -> Result<()> {
+    let elf_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("delink_incremental");
+    if elf_path.exists() {
+        fs::remove_dir_all(&elf_path)?;
+    }
+    let file_0 = elf_path.join("src/file_0.o");
+    let file_1 = elf_path.join("src/file_1.o");
+
+    let result = delink(&elf_path, "file_1", "func_02000008", Relocations::new())?;
+    assert_eq!(result.num_files, 2);
+    assert_eq!(result.num_skipped, 0);
+    assert!(elf_path.join(DELINK_MANIFEST_FILE_NAME).exists());
+
+    // Unchanged inputs
+    fs::write(&file_0, MARKER)?;
+    fs::write(&file_1, MARKER)?;
+    let result = delink(&elf_path, "file_1", "func_02000008", Relocations::new())?;
+    assert_eq!(result.num_skipped, 2);
+    assert_eq!(fs::read(&file_0)?, MARKER);
+    assert_eq!(fs::read(&file_1)?, MARKER);
+
+    // Renamed symbol in file_1
+    let result = delink(&elf_path, "file_1", "MyFunction", Relocations::new())?;
+    assert_eq!(result.num_skipped, 1);
+    assert_eq!(fs::read(&file_0)?, MARKER);
+    assert_ne!(fs::read(&file_1)?, MARKER);
+
+    // Renamed file_1
+    let result = delink(&elf_path, "file_2", "MyFunction", Relocations::new())?;
+    assert_eq!(result.num_skipped, 1);
+    assert_eq!(fs::read(&file_0)?, MARKER);
+    assert!(!file_1.exists(), "stale object was not removed");
+    assert!(elf_path.join("src/file_2.o").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_delink_incremental_referenced_symbol() -> Result<()> {
+    let elf_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("delink_incremental_referenced_symbol");
+    if elf_path.exists() {
+        fs::remove_dir_all(&elf_path)?;
+    }
+    let file_0 = elf_path.join("src/file_0.o");
+    let file_1 = elf_path.join("src/file_1.o");
+
+    // file_0 refers to func_02000008 in file_1
+    let relocations = || -> Result<Relocations> {
+        let mut relocations = Relocations::new();
+        relocations.add_load(0x02000004, 0x02000008, 0, RelocationModule::Main)?;
+        Ok(relocations)
+    };
+    delink(&elf_path, "file_1", "func_02000008", relocations()?)?;
+    fs::write(&file_0, MARKER)?;
+    fs::write(&file_1, MARKER)?;
+
+    // Renaming the referenced symbol changes the inputs of both files, even though file_0's own symbols didn't change
+    let result = delink(&elf_path, "file_1", "MyFunction", relocations()?)?;
+    assert_eq!(result.num_skipped, 0);
+    assert_ne!(fs::read(&file_0)?, MARKER);
+    assert_ne!(fs::read(&file_1)?, MARKER);
+
+    // Removing the relocation changes file_0's inputs only
+    fs::write(&file_0, MARKER)?;
+    fs::write(&file_1, MARKER)?;
+    let result = delink(&elf_path, "file_1", "MyFunction", Relocations::new())?;
+    assert_eq!(result.num_skipped, 1);
+    assert_ne!(fs::read(&file_0)?, MARKER);
+    assert_eq!(fs::read(&file_1)?, MARKER);
+
+    Ok(())
+}
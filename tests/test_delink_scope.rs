@@ -0,0 +1,175 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Delink,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, RelocationSources, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+};
+use object::{Object, ObjectSymbol};
+
+const CODE: [u8; 12] = [
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000000: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000004: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000008: bx lr
+];
+
+#[test]
+fn test_unreferenced_function_is_local() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_function("func_02000004".to_string(), 0x02000004, InstructionMode::Arm, 4));
+    symbol_map
+        .add(Symbol { local: true, ..Symbol::new_function("func_02000008".to_string(), 0x02000008, InstructionMode::Arm, 4) });
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &CODE,
+        &NamePrefixes::default(),
+    )?;
+
+    // An overlay calls func_02000000 and func_02000008
+    let mut overlay_relocations = Relocations::new();
+    overlay_relocations.add_call(0x02100000, 0x02000000, RelocationModule::Main, false, false)?;
+    overlay_relocations.add_call(0x02100004, 0x02000008, RelocationModule::Main, false, false)?;
+    let mut relocation_sources = RelocationSources::new();
+    relocation_sources.add_module(ModuleKind::Overlay(0), &overlay_relocations);
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let bytes = Delink::delink(&symbol_maps, &relocation_sources, &module, &delink_file)?.write()?;
+    let object = object::File::parse(bytes.as_slice())?;
+
+    let symbol = |name: &str| object.symbols().find(|s| s.name() == Ok(name)).expect(name);
+    assert!(symbol("func_02000000").is_global(), "externally referenced function should be global");
+    assert!(symbol("func_02000004").is_local(), "unreferenced function should be local");
+    assert!(symbol("func_02000008").is_local(), "function marked local should stay local");
+
+    Ok(())
+}
+
+/// Delinks [`CODE`] with the given symbols as one file, and returns the object.
+fn delink_main(symbols: &[Symbol], relocation_sources: &RelocationSources) -> Result<Vec<u8>> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    for symbol in symbols {
+        symbol_map.add(symbol.clone());
+    }
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &CODE,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    Ok(Delink::delink(&symbol_maps, relocation_sources, &module, &delink_file)?.write()?)
+}
+
+#[test]
+fn test_roots_and_unknown_module_targets_are_global() -> Result<()> {
+    let symbols = [
+        Symbol::new_function("Entry".to_string(), 0x02000000, InstructionMode::Arm, 4),
+        Symbol::new_function("func_02000004".to_string(), 0x02000004, InstructionMode::Arm, 4),
+        Symbol::new_function("func_02000008".to_string(), 0x02000008, InstructionMode::Arm, 4),
+    ];
+
+    // The entry function is only referenced by the ROM header, and func_02000008 by a relocation to an unknown module
+    let mut relocation_sources = RelocationSources::new();
+    relocation_sources.add_root(ModuleKind::Arm9, 0x02000000);
+    let mut overlay_relocations = Relocations::new();
+    overlay_relocations.add_call(0x02100000, 0x02000008, RelocationModule::None, false, false)?;
+    relocation_sources.add_module(ModuleKind::Overlay(0), &overlay_relocations);
+
+    let bytes = delink_main(&symbols, &relocation_sources)?;
+    let object = object::File::parse(bytes.as_slice())?;
+
+    let symbol = |name: &str| object.symbols().find(|s| s.name() == Ok(name)).expect(name);
+    assert!(symbol("Entry").is_global(), "root should be global");
+    assert!(symbol("func_02000004").is_local(), "unreferenced function should be local");
+    assert!(symbol("func_02000008").is_global(), "target of relocation to unknown module should be global");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the mwldarm.exe linker in tests/assets/mwccarm, which test_roundtrip downloads"]
+fn test_link_with_entry_root() -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let linker_path = cwd.join("tests/assets/mwccarm/dsi/1.6sp2/mwldarm.exe");
+    assert!(linker_path.exists(), "linker not found at {}", linker_path.display());
+
+    let symbols = [
+        Symbol::new_function("Entry".to_string(), 0x02000000, InstructionMode::Arm, 4),
+        Symbol::new_function("func_02000004".to_string(), 0x02000004, InstructionMode::Arm, 4),
+    ];
+    let mut relocation_sources = RelocationSources::new();
+    relocation_sources.add_root(ModuleKind::Arm9, 0x02000000);
+    let bytes = delink_main(&symbols, &relocation_sources)?;
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("delink_scope_link");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("main.o"), bytes)?;
+    fs::write(
+        path.join("linker_script.lcf"),
+        "MEMORY {
+    ARM9 : ORIGIN = 0x2000000 > arm9.bin
+}
+
+SECTIONS {
+    .arm9 : {
+        main.o (.text)
+    } > ARM9
+}
+",
+    )?;
+
+    let mut command;
+    #[cfg(target_os = "windows")]
+    {
+        command = Command::new(&linker_path);
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        command = Command::new("wine");
+        command.arg(&linker_path);
+    }
+    let output = command
+        .current_dir(&path)
+        .args(["-proc", "arm946e"])
+        .arg("-nostdlib")
+        .arg("-interworking")
+        .arg("-nodead")
+        .args(["-m", "Entry"])
+        .arg("main.o")
+        .arg("linker_script.lcf")
+        .arg("-o")
+        .arg("arm9.o")
+        .output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    Ok(())
+}
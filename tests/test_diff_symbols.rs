@@ -0,0 +1,96 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::{DiffSymbols, SymbolChange, SymbolDifference};
+
+fn write_symbols(name: &str, symbols: &str) -> Result<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("diff_symbols");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(name);
+    fs::write(&path, symbols)?;
+    Ok(path)
+}
+
+#[test]
+fn test_diff_symbols() -> Result<()> {
+    let left = write_symbols(
+        "left.txt",
+        "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+",
+    )?;
+    let right = write_symbols(
+        "right.txt",
+        "MyFunction kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+gCounter kind:bss(size=0x4) addr:0x02000200
+",
+    )?;
+
+    let diff_symbols = DiffSymbols { left, right, module: None, json: false, normalize_locals: false };
+    let differences = diff_symbols.diff()?;
+    assert_eq!(
+        differences,
+        vec![
+            SymbolDifference {
+                module: None,
+                address: 0x02000000,
+                change: SymbolChange::Renamed { old_name: "func_02000000".to_string(), new_name: "MyFunction".to_string() },
+            },
+            SymbolDifference {
+                module: None,
+                address: 0x02000200,
+                change: SymbolChange::Added { name: "gCounter".to_string(), kind: "bss(size=0x4)".to_string() },
+            },
+        ]
+    );
+
+    let json = serde_json::to_value(&differences)?;
+    assert_eq!(json[0]["change"], "renamed");
+    assert_eq!(json[1]["change"], "added");
+    assert!(json[0].get("module").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_symbols_normalize_locals() -> Result<()> {
+    let left = write_symbols(
+        "left_locals.txt",
+        "@1304 kind:data(byte[8]) addr:0x02000100
+@1305 kind:data(byte[4]) addr:0x02000108
+",
+    )?;
+    let right = write_symbols(
+        "right_locals.txt",
+        "@1306 kind:data(byte[8]) addr:0x02000100
+@1307 kind:data(byte[8]) addr:0x02000108
+",
+    )?;
+
+    let diff_symbols = DiffSymbols { left, right, module: None, json: false, normalize_locals: true };
+    let differences = diff_symbols.diff()?;
+
+    // Only the local with a different size is reported
+    assert_eq!(
+        differences,
+        vec![
+            SymbolDifference {
+                module: None,
+                address: 0x02000108,
+                change: SymbolChange::Renamed { old_name: "@1305".to_string(), new_name: "@1307".to_string() },
+            },
+            SymbolDifference {
+                module: None,
+                address: 0x02000108,
+                change: SymbolChange::Retyped {
+                    name: "@1307".to_string(),
+                    old_kind: "data(byte[4])".to_string(),
+                    new_kind: "data(byte[8])".to_string(),
+                },
+            },
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+
+fn disassemble_range(start: u32, end: u32) -> Result<String> {
+    #[rustfmt::skip]
+    let code = [
+        0x1e, 0xff, 0x2f, 0xe1, // func_02000000: bx lr
+        0x1e, 0xff, 0x2f, 0xe1, // func_02000004: bx lr
+        0x1e, 0xff, 0x2f, 0xe1, // func_02000008: bx lr
+    ];
+
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_function("func_02000004".to_string(), 0x02000004, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_function("func_02000008".to_string(), 0x02000008, InstructionMode::Arm, 4));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &code,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let disassemble = Disassemble {
+        config_path: PathBuf::new(),
+        asm_path: PathBuf::new(),
+        ual: false,
+        module: Some(ModuleKind::Arm9),
+        start: Some(start),
+        end: Some(end),
+        jobs: 1,
+        incbin_threshold: None,
+        encoding: None,
+        single_file: false,
+        address_format: AddressFormat::default(),
+    };
+    let mut output = vec![];
+    disassemble.disassemble(&module, &delink_file, &mut output, &symbol_maps, None)?;
+    Ok(String::from_utf8(output)?)
+}
+
+#[test]
+fn test_dis_range() -> Result<()> {
+    let assembly = disassemble_range(0x02000004, 0x02000008)?;
+    assert!(!assembly.contains("func_02000000"), "{assembly}");
+    assert!(assembly.contains("arm_func_start func_02000004"), "{assembly}");
+    assert!(!assembly.contains("func_02000008"), "{assembly}");
+    assert!(!assembly.contains(".byte"), "skipped functions must not be dumped as bytes:\n{assembly}");
+    Ok(())
+}
+
+#[test]
+fn test_dis_range_partial_functions() -> Result<()> {
+    let assembly = disassemble_range(0x02000006, 0x0200000a)?;
+    assert!(!assembly.contains("func_02000000"), "{assembly}");
+    assert!(assembly.contains("arm_func_start func_02000004"), "{assembly}");
+    assert!(assembly.contains("arm_func_end func_02000004"), "{assembly}");
+    assert!(assembly.contains("arm_func_start func_02000008"), "{assembly}");
+    assert!(assembly.contains("arm_func_end func_02000008"), "{assembly}");
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, SymData, Symbol, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x18] = [
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000000: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000004: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000008: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_0200000c: bx lr
+    0x00, 0x00, 0x00, 0x02, // data_02000010: .word func_02000000
+    0x01, 0x00, 0x00, 0x00,
+];
+
+fn disassembler(asm_path: PathBuf, single_file: bool) -> Disassemble {
+    Disassemble {
+        config_path: PathBuf::new(),
+        asm_path,
+        ual: false,
+        module: None,
+        start: None,
+        end: None,
+        jobs: 1,
+        incbin_threshold: None,
+        encoding: None,
+        single_file,
+        address_format: AddressFormat::default(),
+    }
+}
+
+/// Returns the lines of an assembly file, without the include and section directives which differ between the modes.
+fn body_lines(assembly: &str) -> Vec<&str> {
+    assembly
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("    .include") && !line.starts_with("    .text"))
+        .filter(|line| !line.starts_with("    .section"))
+        .collect()
+}
+
+#[test]
+fn test_dis_single_file() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    for addr in (0x02000000..0x02000010).step_by(4) {
+        symbol_map.add(Symbol::new_function(format!("func_{addr:08x}"), addr, InstructionMode::Arm, 4));
+    }
+    symbol_map.add(Symbol::new_data("data_02000010".to_string(), 0x02000010, SymData::Word { count: Some(2) }, false));
+
+    let mut relocations = Relocations::new();
+    relocations.add_load(0x02000010, 0x02000000, 0, RelocationModule::Main)?;
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000010, 4)?)?;
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000010, 0x02000018, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        relocations,
+        sections,
+        &CODE,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_0 = Sections::new();
+    file_0.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000008, 4)?)?;
+    let mut file_1 = Sections::new();
+    file_1.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000008, 0x02000010, 4)?)?;
+    file_1.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000010, 0x02000018, 4)?)?;
+    let files = vec![
+        DelinkFile::new("src/file_0.c".to_string(), file_0, true),
+        DelinkFile::new("src/file_1.c".to_string(), file_1, true),
+    ];
+    let modules = vec![(module, files)];
+
+    let asm_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("dis_single_file");
+    if asm_path.exists() {
+        fs::remove_dir_all(&asm_path)?;
+    }
+    disassembler(asm_path.join("multi"), false).disassemble_modules(&modules, &symbol_maps, None)?;
+    disassembler(asm_path.join("single"), true).disassemble_modules(&modules, &symbol_maps, None)?;
+
+    let file_0 = fs::read_to_string(asm_path.join("multi/main/src/file_0.s"))?;
+    let file_1 = fs::read_to_string(asm_path.join("multi/main/src/file_1.s"))?;
+    let single = fs::read_to_string(asm_path.join("single/main.s"))?;
+
+    // Each section is written once, in address order
+    assert_eq!(single.matches("    .text").count(), 1, "{single}");
+    assert_eq!(single.matches("    .section .data").count(), 1, "{single}");
+    assert!(single.find("    .text").unwrap() < single.find("    .section .data").unwrap(), "{single}");
+
+    let multi_lines = body_lines(&file_0).into_iter().chain(body_lines(&file_1)).collect::<Vec<_>>();
+    assert_eq!(body_lines(&single), multi_lines);
+    assert!(single.contains(".word func_02000000"), "{single}");
+
+    Ok(())
+}
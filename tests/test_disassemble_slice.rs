@@ -0,0 +1,71 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::disassemble::{disassemble_slice, DisassembleSliceOptions},
+    config::{
+        module::ModuleKind,
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        symbol::{InstructionMode, Symbol, SymbolMap, SymbolMaps},
+    },
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0x10] = [
+    0x00, 0x00, 0x9f, 0xe5, // ldr r0, [pc, #0x0]
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x00, 0x00, 0x00, 0x02, // .word 0x02000000
+    0xaa, 0xbb, 0xcc, 0xdd, // trailing bytes
+];
+
+fn options(module_kind: ModuleKind, name_prefixes: &NamePrefixes, ual: bool) -> DisassembleSliceOptions<'_> {
+    DisassembleSliceOptions {
+        module_kind,
+        base_address: BASE_ADDRESS,
+        thumb: false,
+        endian: Endian::Little,
+        name_prefixes,
+        ual,
+    }
+}
+
+#[test]
+fn test_disassemble_slice() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    *symbol_maps.get_mut(ModuleKind::Arm9) =
+        SymbolMap::from_symbols(vec![Symbol::new_function("GetSelf".to_string(), BASE_ADDRESS, InstructionMode::Arm, 0xc)]);
+
+    let name_prefixes = NamePrefixes::default();
+    let assembly =
+        disassemble_slice(&CODE, &symbol_maps, &Relocations::new(), &options(ModuleKind::Arm9, &name_prefixes, false))?;
+
+    assert!(assembly.starts_with("    .text\n"), "{assembly}");
+    assert!(assembly.contains("    arm_func_start GetSelf\nGetSelf: ; 0x02000000\n"), "{assembly}");
+    assert!(assembly.contains("    bx lr\n_02000008: .word GetSelf\n    arm_func_end GetSelf\n"), "{assembly}");
+    assert!(assembly.ends_with("    .byte 0xaa, 0xbb, 0xcc, 0xdd\n\n"), "{assembly}");
+
+    // The caller's symbol map is not modified by the analysis
+    assert!(symbol_maps.get(ModuleKind::Arm9).unwrap().get_pool_constant(BASE_ADDRESS + 8)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_disassemble_slice_ual() -> Result<()> {
+    // Functions without a symbol are named after the module's function prefix
+    let name_prefixes = NamePrefixes::default();
+    let assembly = disassemble_slice(
+        &CODE,
+        &SymbolMaps::new(),
+        &Relocations::new(),
+        &options(ModuleKind::Overlay(5), &name_prefixes, true),
+    )?;
+
+    assert!(assembly.contains("    .arm\n    .type func_ov005_02000000, %function\n"), "{assembly}");
+    assert!(assembly.contains("func_ov005_02000000: ; 0x02000000\n"), "{assembly}");
+    assert!(assembly.contains("    .size func_ov005_02000000, .-func_ov005_02000000\n"), "{assembly}");
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::DumpAmbigRelocs;
+use serde_json::json;
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: ov000_delinks.txt
+  symbols: ov000_symbols.txt
+  relocations: ov000_relocs.txt
+- id: 1
+  name: ov001
+  object: build/ov001.bin
+  hash: '0000000000000000'
+  delinks: ov001_delinks.txt
+  symbols: ov001_symbols.txt
+  relocations: ov001_relocs.txt
+";
+
+const RELOCS: &str = "from:0x02000000 kind:load to:0x02100000 module:overlays(0,1)
+from:0x02000004 kind:load to:0x02100000 module:overlays(0,1)
+from:0x02000008 kind:load to:0x02100000 module:overlay(0)
+";
+
+#[test]
+fn test_dump_ambig_relocs_json() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("dump_ambig_relocs");
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("symbols.txt"), "")?;
+    fs::write(path.join("relocs.txt"), RELOCS)?;
+    fs::write(path.join("ov000_symbols.txt"), "data_ov000_02100000 kind:data(word) addr:0x02100000\n")?;
+    fs::write(path.join("ov000_relocs.txt"), "")?;
+    fs::write(path.join("ov001_symbols.txt"), "data_ov001_02100000 kind:data(word) addr:0x02100000\n")?;
+    fs::write(path.join("ov001_relocs.txt"), "")?;
+
+    let dump = DumpAmbigRelocs { config_path: path.join("config.yaml"), module: None, limit: None, json: true };
+    let destinations = serde_json::to_value(dump.find_destinations()?)?;
+    assert_eq!(
+        destinations,
+        json!([{
+            "destination": 0x02100000,
+            "sources": [
+                { "module": "ARM9 main", "address": 0x02000000 },
+                { "module": "ARM9 main", "address": 0x02000004 },
+            ],
+            "candidates": [
+                { "module": "overlay 0", "symbol": "data_ov000_02100000" },
+                { "module": "overlay 1", "symbol": "data_ov001_02100000" },
+            ],
+        }])
+    );
+
+    let dump = DumpAmbigRelocs { config_path: path.join("config.yaml"), module: None, limit: Some(0), json: true };
+    assert!(dump.find_destinations()?.is_empty());
+
+    Ok(())
+}
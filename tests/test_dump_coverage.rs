@@ -0,0 +1,91 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::{DumpCoverage, ModuleCoverage, UncoveredRange};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+// The .rodata section is missing between .text and .data
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .data       start:0x02000120 end:0x02000200 kind:data align:32
+
+";
+const OVERLAY_DELINKS: &str = "    .text       start:0x02100000 end:0x02100100 kind:code align:32
+
+";
+
+// A function was missed between 0x02000080 and 0x020000c0
+const MAIN_SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x80) addr:0x02000000
+func_020000c0 kind:function(arm,size=0x40) addr:0x020000c0
+data_02000120 kind:data(any) addr:0x02000120
+";
+const OVERLAY_SYMBOLS: &str = "func_ov000_02100000 kind:function(thumb,size=0x100) addr:0x02100000
+";
+
+fn write_project(name: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(path.join("overlays/ov000"))?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), MAIN_DELINKS)?;
+    fs::write(path.join("symbols.txt"), MAIN_SYMBOLS)?;
+    fs::write(path.join("relocs.txt"), "")?;
+    fs::write(path.join("overlays/ov000/delinks.txt"), OVERLAY_DELINKS)?;
+    fs::write(path.join("overlays/ov000/symbols.txt"), OVERLAY_SYMBOLS)?;
+    fs::write(path.join("overlays/ov000/relocs.txt"), "")?;
+    Ok(path)
+}
+
+#[test]
+fn test_dump_coverage() -> Result<()> {
+    let path = write_project("dump_coverage")?;
+
+    let coverages = DumpCoverage { config_path: path.join("config.yaml"), module: None, json: false }.coverage()?;
+
+    assert_eq!(
+        coverages,
+        vec![
+            ModuleCoverage {
+                module: "ARM9 main".to_string(),
+                total: 0x200,
+                covered: 0x1a0,
+                uncovered: 0x60,
+                uncovered_ranges: vec![
+                    UncoveredRange { start: 0x02000080, end: 0x020000c0, section: Some(".text".to_string()) },
+                    UncoveredRange { start: 0x02000100, end: 0x02000120, section: None },
+                ],
+            },
+            ModuleCoverage {
+                module: "overlay 0".to_string(),
+                total: 0x100,
+                covered: 0x100,
+                uncovered: 0,
+                uncovered_ranges: vec![],
+            },
+        ]
+    );
+
+    Ok(())
+}
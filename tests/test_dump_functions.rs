@@ -0,0 +1,83 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{DumpFunctions, FunctionRow, FunctionSortOrder},
+    config::module::ModuleKind,
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+const MAIN_SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x80) addr:0x02000000
+MyFunction kind:function(thumb,size=0x20) addr:0x02000080
+data_02000120 kind:data(any) addr:0x02000120
+";
+const OVERLAY_SYMBOLS: &str = "func_ov000_02100000 kind:function(thumb,size=0x100) addr:0x02100000
+";
+
+fn write_project() -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("dump_functions");
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(path.join("overlays/ov000"))?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("symbols.txt"), MAIN_SYMBOLS)?;
+    fs::write(path.join("overlays/ov000/symbols.txt"), OVERLAY_SYMBOLS)?;
+    Ok(path)
+}
+
+fn row(module: &str, address: u32, name: &str, mode: &str, size: u32, auto_named: bool) -> FunctionRow {
+    FunctionRow { module: module.to_string(), address, name: name.to_string(), mode: mode.to_string(), size, auto_named }
+}
+
+#[test]
+fn test_dump_functions() -> Result<()> {
+    let config_path = write_project()?.join("config.yaml");
+
+    let dump = DumpFunctions { config_path: config_path.clone(), module: None, sort: FunctionSortOrder::Address, json: false };
+    assert_eq!(
+        dump.functions()?,
+        vec![
+            row("ARM9 main", 0x02000000, "func_02000000", "arm", 0x80, true),
+            row("ARM9 main", 0x02000080, "MyFunction", "thumb", 0x20, false),
+            row("overlay 0", 0x02100000, "func_ov000_02100000", "thumb", 0x100, true),
+        ]
+    );
+
+    let dump = DumpFunctions { config_path: config_path.clone(), module: None, sort: FunctionSortOrder::Size, json: false };
+    assert_eq!(
+        dump.functions()?,
+        vec![
+            row("overlay 0", 0x02100000, "func_ov000_02100000", "thumb", 0x100, true),
+            row("ARM9 main", 0x02000000, "func_02000000", "arm", 0x80, true),
+            row("ARM9 main", 0x02000080, "MyFunction", "thumb", 0x20, false),
+        ]
+    );
+
+    let dump =
+        DumpFunctions { config_path, module: Some(ModuleKind::Overlay(0)), sort: FunctionSortOrder::Address, json: false };
+    assert_eq!(dump.functions()?, vec![row("overlay 0", 0x02100000, "func_ov000_02100000", "thumb", 0x100, true)]);
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{DumpInlineTables, InlineTableRow},
+    config::{
+        module::Module,
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMap},
+    },
+};
+
+#[test]
+fn test_dump_inline_tables() -> Result<()> {
+    let mut code = vec![];
+    code.extend(0xe24f3c01u32.to_le_bytes()); // sub r3, pc, #0x100
+    code.extend(0xe7d30c20u32.to_le_bytes()); // ldrb r0, [r3, r0, lsr #0x18]
+    code.extend([0xff; 0x100]); // inline table
+    code.extend(0xe12fff1eu32.to_le_bytes()); // bx lr
+
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_function("LookupByte".to_string(), 0x02000000, InstructionMode::Arm, 0x10c));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200010c, 4)?)?;
+    let module =
+        Module::new_arm9("main".to_string(), &mut symbol_map, Relocations::new(), sections, &code, &NamePrefixes::default())?;
+
+    assert_eq!(
+        DumpInlineTables::inline_tables(&module),
+        [InlineTableRow {
+            module: "ARM9 main".to_string(),
+            address: 0x02000008,
+            size: 0x100,
+            function: "LookupByte".to_string()
+        }]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use ds_decomp::analysis::strings::{find_strings, FoundString, StringEncoding};
+
+#[rustfmt::skip]
+const RODATA: &[u8] = &[
+    0x01, 0x00, 0x00, 0x00,                         // .word 1
+    b'H', b'e', b'l', b'l', b'o', 0x00, 0x00, 0x00, // "Hello"
+    b'a', b'b', 0x00, 0x00,                         // "ab", too short
+    0xff, b'x', b'y', b'z', b'w', 0x01, 0x00, 0x00, // not null-terminated after printable bytes
+    0x83, 0x5a, 0x81, 0x5b, 0x83, 0x75, 0x00, 0x00, // "セーブ"
+    b'F', b'i', b'l', b'e', b' ', b'%', b'd', 0x00, // "File %d"
+];
+
+#[test]
+fn test_ascii_strings() {
+    let strings = find_strings(RODATA, 0x02100000, 4, StringEncoding::Ascii);
+    assert_eq!(
+        strings,
+        vec![
+            FoundString { address: 0x02100004, text: "Hello".to_string() },
+            FoundString { address: 0x02100020, text: "File %d".to_string() },
+        ]
+    );
+}
+
+#[test]
+fn test_shift_jis_strings() {
+    let strings = find_strings(RODATA, 0x02100000, 3, StringEncoding::ShiftJis);
+    assert_eq!(
+        strings,
+        vec![
+            FoundString { address: 0x02100004, text: "Hello".to_string() },
+            FoundString { address: 0x02100018, text: "セーブ".to_string() },
+            FoundString { address: 0x02100020, text: "File %d".to_string() },
+        ]
+    );
+}
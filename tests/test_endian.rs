@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult},
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, SymData, Symbol, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+use unarm::Endian;
+
+#[test]
+fn test_parse_big_endian_function() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0xe9, 0x2d, 0x40, 0x08, // push {r3, lr}
+        0xe8, 0xbd, 0x80, 0x08, // pop {r3, pc}
+    ];
+
+    let result = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: base_address,
+        base_address,
+        module_code: &code,
+        known_end_address: None,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: None, endian: Endian::Big, ..Default::default() },
+    })?;
+
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("big-endian function was not found: {result:?}");
+    };
+    assert!(!function.is_thumb());
+    assert_eq!(function.start_address(), base_address);
+    assert_eq!(function.end_address(), base_address + 8);
+
+    Ok(())
+}
+
+#[test]
+fn test_disassemble_big_endian_module() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        0xe1, 0x2f, 0xff, 0x1e, // func_02000000: bx lr
+        0x12, 0x34, 0x56, 0x78, // data_02000004: .word 0x12345678
+    ];
+
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_data("data_02000004".to_string(), 0x02000004, SymData::Word { count: Some(1) }, false));
+
+    let sections = || -> Result<Sections> {
+        let mut sections = Sections::new();
+        sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000004, 4)?)?;
+        sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000004, 0x02000008, 4)?)?;
+        Ok(sections)
+    };
+    let module = Module::new(
+        ModuleKind::Arm9,
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections()?,
+        &code,
+        &NamePrefixes::default(),
+        Endian::Big,
+    )?;
+    assert!(matches!(module.endian(), Endian::Big));
+
+    let delink_file = DelinkFile::new("src/main.c".to_string(), sections()?, false);
+    let disassemble = Disassemble {
+        config_path: PathBuf::new(),
+        asm_path: PathBuf::new(),
+        ual: false,
+        module: None,
+        start: None,
+        end: None,
+        jobs: 1,
+        incbin_threshold: None,
+        encoding: None,
+        single_file: false,
+        address_format: AddressFormat::default(),
+    };
+    let mut output = vec![];
+    disassemble.disassemble(&module, &delink_file, &mut output, &symbol_maps, None)?;
+    let output = String::from_utf8(output)?;
+    assert!(output.contains("    bx lr\n"), "{output}");
+    assert!(output.contains("    .word 0x12345678\n"), "{output}");
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use ds_decomp::analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+fn parse_arm(code: &[u8], start_address: u32) -> Result<Function> {
+    let result = Function::parse_function(FunctionParseOptions {
+        name: format!("func_{start_address:08x}"),
+        start_address,
+        base_address: BASE_ADDRESS,
+        module_code: code,
+        known_end_address: None,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + code.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: Some(false), ..Default::default() },
+    })?;
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function at {start_address:#010x} was not found: {result:?}");
+    };
+    Ok(function)
+}
+
+#[test]
+fn test_irq_handler_movs_pc_lr() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        // IRQ handler
+        0x0f, 0x50, 0x2d, 0xe9, // stmdb sp!, {r0-r3, r12, lr}
+        0x00, 0x00, 0x50, 0xe3, // cmp r0, #0x0
+        0x0e, 0xf0, 0xb0, 0x11, // movsne pc, lr
+        0x0f, 0x50, 0xbd, 0xe8, // ldmia sp!, {r0-r3, r12, lr}
+        0x0e, 0xf0, 0xb0, 0xe1, // movs pc, lr
+        // Restores from a saved link register
+        0x0e, 0xc0, 0xa0, 0xe1, // mov r12, lr
+        0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+        0x0c, 0xf0, 0xb0, 0xe1, // movs pc, r12
+        // Not code
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    let handler = parse_arm(&code, BASE_ADDRESS)?;
+    assert_eq!(handler.end_address(), BASE_ADDRESS + 0x14, "conditional movs must not end the handler");
+
+    let restore = parse_arm(&code, BASE_ADDRESS + 0x14)?;
+    assert_eq!(restore.end_address(), BASE_ADDRESS + 0x20);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::ExportCombinedSymbols;
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .data       start:0x02000100 end:0x02000200 kind:data align:32
+
+";
+const OVERLAY_DELINKS: &str = "    .text       start:0x02100000 end:0x02100100 kind:code align:32
+
+";
+
+const MAIN_SYMBOLS: &str = "InitGame kind:function(arm,size=0x8) addr:0x02000000
+gGameState kind:data(word) addr:0x02000100
+";
+const OVERLAY_SYMBOLS: &str = "Title_Update kind:function(thumb,size=0x4) addr:0x02100000
+";
+
+fn write_project(name: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(path.join("overlays/ov000"))?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), MAIN_DELINKS)?;
+    fs::write(path.join("symbols.txt"), MAIN_SYMBOLS)?;
+    fs::write(path.join("relocs.txt"), "")?;
+    fs::write(path.join("overlays/ov000/delinks.txt"), OVERLAY_DELINKS)?;
+    fs::write(path.join("overlays/ov000/symbols.txt"), OVERLAY_SYMBOLS)?;
+    fs::write(path.join("overlays/ov000/relocs.txt"), "")?;
+    Ok(path)
+}
+
+#[test]
+fn test_export_combined_symbols() -> Result<()> {
+    let path = write_project("export_combined_symbols")?;
+    let output_path = path.join("combined_symbols.txt");
+
+    ExportCombinedSymbols { config_path: path.join("config.yaml"), output_path: output_path.clone() }.run()?;
+
+    assert_eq!(
+        fs::read_to_string(&output_path)?,
+        "\
+InitGame kind:function(arm,size=0x8) addr:0x02000000 module:main
+gGameState kind:data(word) addr:0x02000100 module:main
+Title_Update kind:function(thumb,size=0x4) addr:0x02100000 module:overlay:0
+"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use std::{ffi::OsStr, fs};
+
+use anyhow::Result;
+use ds_decomp::cmd::Extract;
+use ds_rom::rom::{raw, Rom};
+
+#[test]
+fn test_extract_overlay() -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let roms_dir = cwd.join("tests/roms/");
+
+    for entry in roms_dir.read_dir()? {
+        let path = entry?.path();
+        if path.extension() != Some(OsStr::new("nds")) {
+            continue;
+        }
+
+        let raw_rom = raw::Rom::from_file(&path)?;
+        let rom = Rom::extract(&raw_rom)?;
+        let Some(overlay) = rom.arm9_overlays().first() else {
+            continue;
+        };
+
+        let base_name = path.with_extension("").file_name().unwrap().to_str().unwrap().to_string();
+        let output_path = roms_dir.join(base_name).join("single_overlay");
+        let extract = Extract {
+            rom: path.clone(),
+            arm7_bios: None,
+            output_path: output_path.clone(),
+            arm9: false,
+            arm7: false,
+            overlay: Some(overlay.id()),
+            autoload: None,
+            overlay_table_json: None,
+        };
+        extract.run()?;
+
+        let code = fs::read(output_path.join(format!("arm9_ov{:03}.bin", overlay.id())))?;
+        assert_eq!(code.len(), overlay.info().code_size as usize);
+        assert!(output_path.join(format!("arm9_ov{:03}.yaml", overlay.id())).is_file());
+    }
+
+    Ok(())
+}
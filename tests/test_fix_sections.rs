@@ -0,0 +1,77 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::FixSections,
+    config::{delinks::Delinks, module::ModuleKind},
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+const DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .data       start:0x02000100 end:0x02000200 kind:data align:32
+
+src/main.c:
+    complete
+    flags: -O4,p
+    .text       start:0x02000000 end:0x02000080
+
+src/data.c:
+    .data       start:0x02000100 end:0x02000200
+
+";
+
+// The code ends at 0x020000c0, so the rest of .text is data
+const SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x80) addr:0x02000000
+func_02000080 kind:function(arm,size=0x40) addr:0x02000080
+data_020000c0 kind:data(any) addr:0x020000c0
+data_02000100 kind:data(any) addr:0x02000100
+";
+
+// The gap file between src/main.c and the end of .text is not written
+const EXPECTED_DELINKS: &str = "    .text       start:0x02000000 end:0x020000c0 kind:code align:32
+    .data       start:0x020000c0 end:0x02000200 kind:data align:32
+
+src/main.c:
+    complete
+    flags: -O4,p
+    .text       start:0x02000000 end:0x02000080
+
+src/data.c:
+    .data       start:0x02000100 end:0x02000200
+
+";
+
+#[test]
+fn test_fix_sections_keeps_file_attributes() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("fix_sections");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), DELINKS)?;
+    fs::write(path.join("symbols.txt"), SYMBOLS)?;
+    fs::write(path.join("relocs.txt"), "")?;
+
+    FixSections { config_path: path.join("config.yaml"), dry: false, no_backup: true }.run()?;
+    assert_eq!(fs::read_to_string(path.join("delinks.txt"))?, EXPECTED_DELINKS);
+
+    // Other writers still show the whole sections of every file, including gaps
+    let delinks = Delinks::from_file(path.join("delinks.txt"), ModuleKind::Arm9)?;
+    let display = delinks.display().to_string();
+    assert!(display.contains("src/main.c:\n    .text       start:0x02000000 end:0x02000080 kind:code align:32\n"));
+    assert!(display.contains("    .text       start:0x02000080 end:0x020000c0 kind:code align:32\n"));
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult},
+    config::{
+        module::{AnalysisOptions, Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        program::Program,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+};
+
+#[rustfmt::skip]
+static CONDITIONAL_CODE: [u8; 0x10] = [
+    0x01, 0x00, 0x30, 0xe0, // eors r0, r0, r1
+    0x00, 0x00, 0x00, 0x4a, // bmi 0x0200000c
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+#[rustfmt::skip]
+static UNCONDITIONAL_CODE: [u8; 0xc] = [
+    0x0d, 0x10, 0xa0, 0xe1, // mov r1, sp
+    0xff, 0xff, 0xff, 0xea, // b 0x02000008
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+fn parse(code: &[u8], base_address: u32, relocate_function_branches: bool) -> Result<Function> {
+    let result = Function::parse_function(FunctionParseOptions {
+        name: format!("func_{base_address:08x}"),
+        start_address: base_address,
+        base_address,
+        module_code: code,
+        known_end_address: None,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: Some(false), relocate_function_branches, ..Default::default() },
+    })?;
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function at {base_address:#010x} was not found: {result:?}");
+    };
+    Ok(function)
+}
+
+#[test]
+fn test_conditional_function_branch() -> Result<()> {
+    let base_address = 0x02000000;
+    for relocate_function_branches in [false, true] {
+        let function = parse(&CONDITIONAL_CODE, base_address, relocate_function_branches)?;
+        assert_eq!(function.end_address(), base_address + 0xc);
+        assert!(function.labels().next().is_none(), "function branch must not be a label");
+
+        let call = function.function_calls().get(&(base_address + 0x4)).expect("conditional function branch should be a call");
+        assert_eq!(call.address, base_address + 0xc);
+        assert!(call.ins.is_conditional());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_unconditional_function_branch() -> Result<()> {
+    let base_address = 0x02000000;
+
+    let function = parse(&UNCONDITIONAL_CODE, base_address, false)?;
+    assert_eq!(function.end_address(), base_address + 0x8, "unconditional function branch ends the function");
+    assert!(function.function_calls().is_empty(), "unconditional function branch is only a call if enabled");
+
+    let function = parse(&UNCONDITIONAL_CODE, base_address, true)?;
+    assert_eq!(function.end_address(), base_address + 0x8, "unconditional function branch ends the function");
+    let call = function.function_calls().get(&(base_address + 0x4)).expect("unconditional function branch should be a call");
+    assert_eq!(call.address, base_address + 0x8);
+    assert!(!call.ins.is_conditional());
+
+    Ok(())
+}
+
+#[test]
+fn test_conditional_call_relocation() -> Result<()> {
+    for relocate_function_branches in [false, true] {
+        let mut symbol_maps = SymbolMaps::new();
+        let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+        for (addr, size) in [(0x02000000, 0xc), (0x0200000c, 0x4)] {
+            symbol_map.add(Symbol::new_function(format!("func_{addr:08x}"), addr, InstructionMode::Arm, size));
+        }
+
+        let mut sections = Sections::new();
+        sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000010, 4)?)?;
+        let main = Module::new_arm9(
+            "main".to_string(),
+            symbol_maps.get_mut(ModuleKind::Arm9),
+            Relocations::new(),
+            sections,
+            &CONDITIONAL_CODE,
+            &NamePrefixes::default(),
+        )?;
+
+        let mut program = Program::new(main, vec![], vec![], symbol_maps);
+        program.analyze_cross_references(&AnalysisOptions { relocate_function_branches, ..Default::default() })?;
+
+        // mwldarm drops the condition code of relocated calls, so they're only relocated if enabled
+        let relocation = program.main().relocations().get(0x02000004);
+        if relocate_function_branches {
+            let relocation = relocation.expect("conditional call was not relocated");
+            assert_eq!(relocation.to_address(), 0x0200000c);
+        } else {
+            assert!(relocation.is_none(), "conditional call must not be relocated by default");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::{
+        function_overrides::FunctionOverride,
+        symbol::{InstructionMode, SymbolMap},
+    },
+};
+use unarm::Endian;
+
+#[test]
+fn test_function_override_forces_thumb() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x08, 0xb5, // push {r3, lr}
+        0xff, 0xe7, // b 0x02000004, looks like an unconditional ARM instruction
+        0x08, 0xbd, // pop {r3, pc}
+        0x00, 0x00,
+    ];
+
+    let mut function_overrides = BTreeMap::new();
+    function_overrides.insert(
+        base_address,
+        FunctionOverride { address: base_address, mode: InstructionMode::Thumb, end_address: base_address + 6 },
+    );
+
+    let mut symbol_map = SymbolMap::new();
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address,
+        module_code: &code,
+        symbol_map: &mut symbol_map,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        search_options: FunctionSearchOptions { last_function_address: Some(base_address), ..Default::default() },
+        function_overrides: Some(&function_overrides),
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+
+    let function = functions.get(&base_address).expect("overridden function was not found");
+    assert!(function.is_thumb());
+    assert_eq!(function.start_address(), base_address);
+    assert_eq!(function.end_address(), base_address + 6);
+
+    Ok(())
+}
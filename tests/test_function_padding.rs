@@ -0,0 +1,59 @@
+use anyhow::Result;
+use ds_decomp::analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+fn parse_thumb_function(code: &[u8]) -> Result<Function> {
+    let result = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: BASE_ADDRESS,
+        base_address: BASE_ADDRESS,
+        module_code: code,
+        known_end_address: None,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + code.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: Some(true), ..Default::default() },
+    })?;
+
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function was not found: {result:?}");
+    };
+    Ok(function)
+}
+
+#[test]
+fn test_padding_before_pool() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        0x01, 0x48,             // ldr r0, [pc, #4]
+        0x00, 0x68,             // ldr r0, [r0]
+        0x70, 0x47,             // bx lr
+        0xc0, 0x46,             // nop
+        0x00, 0x01, 0x00, 0x02, // 0x02000100
+    ];
+
+    let function = parse_thumb_function(&code)?;
+    assert_eq!(function.padding(), Some(BASE_ADDRESS + 0x6..BASE_ADDRESS + 0x8));
+    assert_eq!(function.end_address(), BASE_ADDRESS + 0xc, "the pool must stay in the function");
+    assert_eq!(function.pool_constants().iter().copied().collect::<Vec<_>>(), vec![BASE_ADDRESS + 0x8]);
+
+    Ok(())
+}
+
+#[test]
+fn test_trailing_padding() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        0x00, 0x20, // movs r0, #0
+        0x01, 0x30, // adds r0, #1
+        0x70, 0x47, // bx lr
+        0xc0, 0x46, // nop
+        0x70, 0x47, // bx lr
+    ];
+
+    let function = parse_thumb_function(&code)?;
+    assert_eq!(function.end_address(), BASE_ADDRESS + 0x6, "padding must not be part of the function");
+    assert_eq!(function.padded_end_address(), BASE_ADDRESS + 0x8);
+
+    Ok(())
+}
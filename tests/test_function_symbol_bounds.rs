@@ -0,0 +1,95 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::symbol::{InstructionMode, Symbol, SymbolMap},
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0xc] = [
+    0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+    0x01, 0x10, 0xa0, 0xe3, // mov r1, #0x1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+#[rustfmt::skip]
+const CODE_AFTER_DATA: [u8; 0x18] = [
+    0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x00, 0x00, 0x20, 0xe0, // eor r0, r0, r0
+    0x00, 0x00, 0x00, 0x00, // .word 0x0
+    0x01, 0x20,             // movs r0, #0x1
+    0x70, 0x47,             // bx lr
+    0x00, 0x00, 0x00, 0x00, // .word 0x0
+];
+
+/// Finds functions in `code` and returns their start and end addresses.
+fn find_function_bounds(code: &[u8], symbol_map: &mut SymbolMap) -> Result<Vec<(u32, u32)>> {
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: code,
+        symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + code.len() as u32,
+        search_options: FunctionSearchOptions::default(),
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+    Ok(functions.values().map(|function| (function.start_address(), function.end_address())).collect())
+}
+
+#[test]
+fn test_function_symbol_is_boundary() -> Result<()> {
+    let bounds = find_function_bounds(&CODE, &mut SymbolMap::new())?;
+    assert_eq!(bounds, [(BASE_ADDRESS, BASE_ADDRESS + 0xc)]);
+
+    // A function symbol added by the user splits the function in two
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_function("SetFlag".to_string(), BASE_ADDRESS + 0x4, InstructionMode::Arm, 8));
+    let bounds = find_function_bounds(&CODE, &mut symbol_map)?;
+    assert_eq!(bounds, [(BASE_ADDRESS, BASE_ADDRESS + 0x4), (BASE_ADDRESS + 0x4, BASE_ADDRESS + 0xc)]);
+
+    let (_, symbol) = symbol_map.by_address(BASE_ADDRESS + 0x4)?.unwrap();
+    assert_eq!(symbol.name, "SetFlag");
+
+    Ok(())
+}
+
+#[test]
+fn test_function_symbol_is_start() -> Result<()> {
+    // The search ends at the data after the first function
+    let bounds = find_function_bounds(&CODE_AFTER_DATA, &mut SymbolMap::new())?;
+    assert_eq!(bounds, [(BASE_ADDRESS, BASE_ADDRESS + 0x8)]);
+
+    // A function symbol after the data is still analyzed, in the mode of the symbol
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_function("GetOne".to_string(), BASE_ADDRESS + 0x10, InstructionMode::Thumb, 4));
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: &CODE_AFTER_DATA,
+        symbol_map: &mut symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + CODE_AFTER_DATA.len() as u32,
+        search_options: FunctionSearchOptions::default(),
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+    let function = functions.get(&(BASE_ADDRESS + 0x10)).expect("function symbol should be analyzed");
+    assert_eq!(function.name(), "GetOne");
+    assert!(function.is_thumb());
+    assert_eq!(function.end_address(), BASE_ADDRESS + 0x14);
+
+    Ok(())
+}
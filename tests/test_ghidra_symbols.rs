@@ -0,0 +1,139 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{ExportGhidra, ImportSymbols},
+    config::{
+        module::ModuleKind,
+        symbol::{MergePolicy, SymbolMap},
+    },
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .data       start:0x02000100 end:0x02000200 kind:data align:32
+
+";
+const OVERLAY_DELINKS: &str = "    .text       start:0x02100000 end:0x02100100 kind:code align:32
+
+";
+
+const MAIN_SYMBOLS: &str = "InitGame kind:function(arm,size=0x8) addr:0x02000000
+func_02000008 kind:function(arm,size=0x8) addr:0x02000008
+gGameState kind:data(word) addr:0x02000100
+";
+const OVERLAY_SYMBOLS: &str = "Title_Update kind:function(thumb,size=0x4) addr:0x02100000
+";
+
+const DEFAULT_MAIN_SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+func_02000008 kind:function(arm,size=0x8) addr:0x02000008
+data_02000100 kind:data(word) addr:0x02000100
+";
+const DEFAULT_OVERLAY_SYMBOLS: &str = "func_ov000_02100000 kind:function(thumb,size=0x4) addr:0x02100000
+";
+
+const GHIDRA_CSV: &str = r#""Name","Location","Type","Namespace","Source","Reference Count","Offcut Ref Count"
+"InitGame","02000000","Function","Global","User Defined","2","0"
+"FUN_02000008","02000008","Function","Global","Default","1","0"
+"gGameState","02000100","Label","Global","User Defined","5","0"
+"Title_Update","ov000::02100000","Function","Global","Imported","0","0"
+"local_4","Stack[-0x4]","Local Var","InitGame","User Defined","1","0"
+"#;
+
+fn write_project(name: &str, main_symbols: &str, overlay_symbols: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(path.join("overlays/ov000"))?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), MAIN_DELINKS)?;
+    fs::write(path.join("symbols.txt"), main_symbols)?;
+    fs::write(path.join("relocs.txt"), "")?;
+    fs::write(path.join("overlays/ov000/delinks.txt"), OVERLAY_DELINKS)?;
+    fs::write(path.join("overlays/ov000/symbols.txt"), overlay_symbols)?;
+    fs::write(path.join("overlays/ov000/relocs.txt"), "")?;
+    Ok(path)
+}
+
+#[test]
+fn test_export_ghidra_script() -> Result<()> {
+    let path = write_project("ghidra_export", MAIN_SYMBOLS, OVERLAY_SYMBOLS)?;
+    let script_path = path.join("import_dsd.py");
+
+    ExportGhidra { config_path: path.join("config.yaml"), output_path: script_path.clone() }.run()?;
+
+    let script = fs::read_to_string(&script_path)?;
+    let symbols = "SYMBOLS = [
+    (\"\", 0x02000000, \"InitGame\", \"arm\"),
+    (\"\", 0x02000008, \"func_02000008\", \"arm\"),
+    (\"\", 0x02000100, \"gGameState\", \"data\"),
+    (\"ov000\", 0x02100000, \"Title_Update\", \"thumb\"),
+]
+";
+    assert!(script.contains(symbols), "unexpected symbols in:\n{script}");
+
+    Ok(())
+}
+
+#[test]
+fn test_import_ghidra_csv() -> Result<()> {
+    let path = write_project("ghidra_import", DEFAULT_MAIN_SYMBOLS, DEFAULT_OVERLAY_SYMBOLS)?;
+    let csv_path = path.join("symbols.csv");
+    fs::write(&csv_path, GHIDRA_CSV)?;
+
+    let renames = ImportSymbols {
+        config_path: path.join("config.yaml"),
+        elf_path: None,
+        sym_path: None,
+        dwarf_path: None,
+        ghidra_csv_path: Some(csv_path),
+        map_config_path: None,
+        merge_policy: MergePolicy::PreferNamed,
+        include_default_names: false,
+        dry: false,
+        no_backup: true,
+    }
+    .import()?;
+
+    let renames = renames
+        .iter()
+        .map(|rename| (rename.module_kind, rename.address, rename.old_name.as_str(), rename.new_name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        renames,
+        vec![
+            (ModuleKind::Arm9, 0x02000000, "func_02000000", "InitGame"),
+            (ModuleKind::Arm9, 0x02000100, "data_02000100", "gGameState"),
+            (ModuleKind::Overlay(0), 0x02100000, "func_ov000_02100000", "Title_Update"),
+        ]
+    );
+
+    let main_symbols = SymbolMap::from_file(path.join("symbols.txt"))?;
+    assert_eq!(main_symbols.by_address(0x02000008)?.unwrap().1.name, "func_02000008");
+    let overlay_symbols = SymbolMap::from_file(path.join("overlays/ov000/symbols.txt"))?;
+    assert_eq!(overlay_symbols.by_address(0x02100000)?.unwrap().1.name, "Title_Update");
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use ds_decomp::config::symbol::{InstructionMode, SymData, Symbol};
+
+fn write_directives(symbol: &Symbol, max_address: u32) -> Result<String> {
+    let mut output = vec![];
+    symbol.write_type_directive(&mut output)?;
+    symbol.write_size_directive(&mut output, max_address)?;
+    Ok(String::from_utf8(output)?)
+}
+
+#[test]
+fn test_function_directives() -> Result<()> {
+    let symbol = Symbol::new_function("func_02001000".to_string(), 0x02001000, InstructionMode::Thumb, 0x20);
+
+    let expected = "    .type func_02001000, %function\n    .size func_02001000, .-func_02001000\n";
+    assert_eq!(write_directives(&symbol, 0x02001020)?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_sized_data_directives() -> Result<()> {
+    let symbol = Symbol::new_data("data_02002000".to_string(), 0x02002000, SymData::Word { count: Some(3) }, false);
+
+    let expected = "    .type data_02002000, %object\n    .size data_02002000, 0xc\n";
+    assert_eq!(write_directives(&symbol, 0x02002100)?, expected);
+    Ok(())
+}
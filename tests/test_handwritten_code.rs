@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::{
+        data_regions::{DataRegion, DataRegions},
+        module::{AnalysisOptions, ModuleKind},
+        symbol::SymbolMap,
+    },
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02100000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0x18] = [
+    0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // Handwritten function which reads r4 without saving it
+    0x04, 0x00, 0xa0, 0xe1, // mov r0, r4
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x02, 0x00, 0xa0, 0xe3, // mov r0, #0x2
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+#[rustfmt::skip]
+const SAVING_CODE: [u8; 0x28] = [
+    // Compiled function which saves r4 with a single store
+    0x04, 0x40, 0x2d, 0xe5, // str r4, [sp, #-0x4]!
+    0x00, 0x40, 0xa0, 0xe1, // mov r4, r0
+    0x04, 0x00, 0xa0, 0xe1, // mov r0, r4
+    0x04, 0x40, 0x9d, 0xe4, // ldr r4, [sp], #0x4
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // Compiled function which saves r4 after allocating its stack frame
+    0x08, 0xd0, 0x4d, 0xe2, // sub sp, sp, #0x8
+    0x04, 0x40, 0x8d, 0xe5, // str r4, [sp, #0x4]
+    0x04, 0x40, 0x9d, 0xe5, // ldr r4, [sp, #0x4]
+    0x08, 0xd0, 0x8d, 0xe2, // add sp, sp, #0x8
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+fn find_function_addresses(handwritten_code: &DataRegions) -> Result<Vec<u32>> {
+    find_functions(&CODE, handwritten_code)
+}
+
+fn find_functions(code: &[u8], handwritten_code: &DataRegions) -> Result<Vec<u32>> {
+    let mut symbol_map = SymbolMap::new();
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: code,
+        symbol_map: &mut symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + code.len() as u32,
+        search_options: FunctionSearchOptions { check_defs_uses: true, ..Default::default() },
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: handwritten_code.get(ModuleKind::Overlay(0)),
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+    Ok(functions.keys().copied().collect())
+}
+
+#[test]
+fn test_handwritten_code_is_analyzed_fully() -> Result<()> {
+    // Reading r4 before saving it ends function analysis
+    assert_eq!(find_function_addresses(&DataRegions::new())?, vec![BASE_ADDRESS]);
+
+    let region = DataRegion::from_str("overlay:0:0x02100008:0x02100010")?;
+    let handwritten_code = DataRegions::from_regions([&region])?;
+    assert_eq!(find_function_addresses(&handwritten_code)?, vec![BASE_ADDRESS, BASE_ADDRESS + 0x8, BASE_ADDRESS + 0x10]);
+
+    Ok(())
+}
+
+#[test]
+fn test_single_register_save_is_not_a_read() -> Result<()> {
+    // Storing a callee-saved register on the stack saves it, so both functions are found
+    assert_eq!(find_functions(&SAVING_CODE, &DataRegions::new())?, vec![BASE_ADDRESS, BASE_ADDRESS + 0x14]);
+
+    Ok(())
+}
+
+#[test]
+fn test_defs_uses_check_is_opt_in() {
+    assert!(!AnalysisOptions::default().check_defs_uses);
+}
@@ -0,0 +1,321 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::ImportSymbols,
+    config::{module::ModuleKind, symbol::MergePolicy},
+    util::backup::BACKUP_DIR,
+};
+use object::{
+    write::{Object, SymbolSection},
+    Architecture, BinaryFormat, Endianness, SymbolFlags, SymbolScope,
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+const SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+";
+
+fn write_elf(path: &PathBuf) -> Result<()> {
+    let mut object = Object::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Little);
+    let section_id = object.add_section(vec![], b"ARM9".to_vec(), object::SectionKind::Text);
+    object.append_section_data(section_id, &[0; 0x104], 4);
+    for (name, address, kind) in
+        [("MyFunction", 0x02000000, object::SymbolKind::Text), ("data_02000100", 0x02000100, object::SymbolKind::Data)]
+    {
+        object.add_symbol(object::write::Symbol {
+            name: name.as_bytes().to_vec(),
+            value: address,
+            size: 4,
+            kind,
+            scope: SymbolScope::Linkage,
+            weak: false,
+            section: SymbolSection::Section(section_id),
+            flags: SymbolFlags::None,
+        });
+    }
+    fs::write(path, object.write()?)?;
+    Ok(())
+}
+
+#[test]
+fn test_import_symbols_dry_run() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("import_symbols");
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("symbols.txt"), SYMBOLS)?;
+    let elf_path = path.join("arm9.o");
+    write_elf(&elf_path)?;
+
+    let import = |dry| ImportSymbols {
+        config_path: path.join("config.yaml"),
+        elf_path: Some(elf_path.clone()),
+        sym_path: None,
+        dwarf_path: None,
+        ghidra_csv_path: None,
+        map_config_path: None,
+        merge_policy: MergePolicy::PreferNamed,
+        include_default_names: false,
+        dry,
+        no_backup: false,
+    };
+
+    let renames = import(true).import()?;
+    assert_eq!(fs::read_to_string(path.join("symbols.txt"))?, SYMBOLS, "dry run must not write files");
+    assert!(!path.join(BACKUP_DIR).exists(), "dry run must not make backups");
+    let renames = renames
+        .iter()
+        .map(|rename| (rename.module_kind, rename.address, rename.old_name.as_str(), rename.new_name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(renames, vec![(ModuleKind::Arm9, 0x02000000, "func_02000000", "MyFunction")]);
+
+    import(false).import()?;
+    assert!(fs::read_to_string(path.join("symbols.txt"))?.starts_with("MyFunction kind:function(arm,size=0x8)"));
+
+    let backups = fs::read_dir(path.join(BACKUP_DIR))?.map(|entry| Ok(entry?.path())).collect::<Result<Vec<_>>>()?;
+    assert_eq!(backups.len(), 1);
+    let backup_name = backups[0].file_name().unwrap().to_string_lossy().into_owned();
+    assert!(backup_name.starts_with("symbols.txt.") && backup_name.ends_with(".bak"), "unexpected backup {backup_name}");
+    assert_eq!(fs::read_to_string(&backups[0])?, SYMBOLS);
+
+    // Nothing changes the second time, so no backup is made
+    import(false).import()?;
+    assert_eq!(fs::read_dir(path.join(BACKUP_DIR))?.count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_import_symbols_with_name_prefixes() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("import_symbols_name_prefixes");
+    fs::create_dir_all(&path)?;
+    let config = format!("{CONFIG}name_prefixes:\n  func_prefix: fn_\n  data_prefix: var_\n");
+    fs::write(path.join("config.yaml"), &config)?;
+    fs::write(
+        path.join("symbols.txt"),
+        "fn_02000000 kind:function(arm,size=0x8) addr:0x02000000
+var_02000100 kind:data(word) addr:0x02000100
+",
+    )?;
+    let elf_path = path.join("arm9.o");
+    write_elf(&elf_path)?;
+
+    let import = || ImportSymbols {
+        config_path: path.join("config.yaml"),
+        elf_path: Some(elf_path.clone()),
+        sym_path: None,
+        dwarf_path: None,
+        ghidra_csv_path: None,
+        map_config_path: None,
+        merge_policy: MergePolicy::PreferNamed,
+        include_default_names: false,
+        dry: true,
+        no_backup: true,
+    };
+
+    // `data_02000100` is not a default name with these prefixes
+    let renames = import().import()?;
+    let renames = renames.iter().map(|rename| (rename.address, rename.new_name.as_str())).collect::<Vec<_>>();
+    assert_eq!(renames, vec![(0x02000000, "MyFunction"), (0x02000100, "data_02000100")]);
+
+    // Prefixes are validated when the config is loaded
+    fs::write(path.join("config.yaml"), format!("{CONFIG}name_prefixes:\n  func_prefix: 1fn_\n"))?;
+    let error = import().import().unwrap_err();
+    assert!(format!("{error:#}").contains("must start with a letter or underscore"), "{error:#}");
+
+    Ok(())
+}
+
+/// Writes an ELF with DWARF info for `MyFunction` (0x10 bytes) and `gSamples` (`u16[4]`). The symbol table says the
+/// function is 4 bytes.
+fn write_dwarf_elf(path: &PathBuf) -> Result<()> {
+    use gimli::write::{Address, AttributeValue, DwarfUnit, EndianVec, Expression, Sections};
+
+    let encoding = gimli::Encoding { format: gimli::Format::Dwarf32, version: 4, address_size: 4 };
+    let mut dwarf = DwarfUnit::new(encoding);
+    let root = dwarf.unit.root();
+
+    let function = dwarf.unit.add(root, gimli::DW_TAG_subprogram);
+    let entry = dwarf.unit.get_mut(function);
+    entry.set(gimli::DW_AT_name, AttributeValue::String(b"MyFunction".to_vec()));
+    entry.set(gimli::DW_AT_low_pc, AttributeValue::Address(Address::Constant(0x02000000)));
+    entry.set(gimli::DW_AT_high_pc, AttributeValue::Udata(0x10));
+
+    let base_type = dwarf.unit.add(root, gimli::DW_TAG_base_type);
+    let entry = dwarf.unit.get_mut(base_type);
+    entry.set(gimli::DW_AT_name, AttributeValue::String(b"u16".to_vec()));
+    entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(2));
+    let array_type = dwarf.unit.add(root, gimli::DW_TAG_array_type);
+    dwarf.unit.get_mut(array_type).set(gimli::DW_AT_type, AttributeValue::UnitRef(base_type));
+    let subrange = dwarf.unit.add(array_type, gimli::DW_TAG_subrange_type);
+    dwarf.unit.get_mut(subrange).set(gimli::DW_AT_count, AttributeValue::Udata(4));
+
+    let variable = dwarf.unit.add(root, gimli::DW_TAG_variable);
+    let mut location = Expression::new();
+    location.op_addr(Address::Constant(0x02000100));
+    let entry = dwarf.unit.get_mut(variable);
+    entry.set(gimli::DW_AT_name, AttributeValue::String(b"gSamples".to_vec()));
+    entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(array_type));
+    entry.set(gimli::DW_AT_location, AttributeValue::Exprloc(location));
+
+    let mut sections = Sections::new(EndianVec::new(gimli::LittleEndian));
+    dwarf.write(&mut sections)?;
+
+    let mut object = Object::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Little);
+    let section_id = object.add_section(vec![], b"ARM9".to_vec(), object::SectionKind::Text);
+    object.append_section_data(section_id, &[0; 0x108], 4);
+    object.add_symbol(object::write::Symbol {
+        name: b"MyFunction".to_vec(),
+        value: 0x02000000,
+        size: 4,
+        kind: object::SymbolKind::Text,
+        scope: SymbolScope::Linkage,
+        weak: false,
+        section: SymbolSection::Section(section_id),
+        flags: SymbolFlags::None,
+    });
+    sections.for_each(|id, data| -> Result<()> {
+        let debug_section = object.add_section(vec![], id.name().as_bytes().to_vec(), object::SectionKind::Debug);
+        object.append_section_data(debug_section, data.slice(), 1);
+        Ok(())
+    })?;
+
+    fs::write(path, object.write()?)?;
+    Ok(())
+}
+
+#[test]
+fn test_import_symbols_from_dwarf() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("import_symbols_dwarf");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(
+        path.join("symbols.txt"),
+        "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(any) addr:0x02000100
+",
+    )?;
+    let elf_path = path.join("arm9.elf");
+    write_dwarf_elf(&elf_path)?;
+
+    let renames = ImportSymbols {
+        config_path: path.join("config.yaml"),
+        elf_path: None,
+        sym_path: None,
+        dwarf_path: Some(elf_path),
+        ghidra_csv_path: None,
+        map_config_path: None,
+        merge_policy: MergePolicy::PreferNamed,
+        include_default_names: false,
+        dry: false,
+        no_backup: true,
+    }
+    .import()?;
+    assert_eq!(renames.len(), 2);
+
+    // The size comes from the DIE, not the symbol table
+    assert_eq!(
+        fs::read_to_string(path.join("symbols.txt"))?,
+        "MyFunction kind:function(arm,size=0x10) addr:0x02000000
+gSamples kind:data(short[4]) addr:0x02000100
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_import_symbols_without_dwarf() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("import_symbols_no_dwarf");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("symbols.txt"), SYMBOLS)?;
+    let elf_path = path.join("arm9.o");
+    write_elf(&elf_path)?;
+
+    // Falls back to the symbol table
+    let renames = ImportSymbols {
+        config_path: path.join("config.yaml"),
+        elf_path: None,
+        sym_path: None,
+        dwarf_path: Some(elf_path),
+        ghidra_csv_path: None,
+        map_config_path: None,
+        merge_policy: MergePolicy::PreferNamed,
+        include_default_names: false,
+        dry: true,
+        no_backup: true,
+    }
+    .import()?;
+    assert_eq!(renames.len(), 1);
+    assert_eq!(renames[0].new_name, "MyFunction");
+
+    Ok(())
+}
+
+#[test]
+fn test_import_symbols_from_map() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("import_symbols_map");
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    let self_path = path.join("self");
+    let other_path = path.join("other");
+    for (project_path, symbols) in [
+        (&self_path, SYMBOLS),
+        (
+            &other_path,
+            "MyFunction kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+gCounter kind:data(word) addr:0x02000104
+data_02000108 kind:data(word) addr:0x02000108
+",
+        ),
+    ] {
+        fs::create_dir_all(project_path)?;
+        fs::write(project_path.join("config.yaml"), CONFIG)?;
+        fs::write(project_path.join("symbols.txt"), symbols)?;
+    }
+
+    let renames = ImportSymbols {
+        config_path: self_path.join("config.yaml"),
+        elf_path: None,
+        sym_path: None,
+        dwarf_path: None,
+        ghidra_csv_path: None,
+        map_config_path: Some(other_path.join("config.yaml")),
+        merge_policy: MergePolicy::PreferNamed,
+        include_default_names: false,
+        dry: false,
+        no_backup: true,
+    }
+    .import()?;
+
+    let renames = renames.iter().map(|rename| (rename.address, rename.new_name.as_str())).collect::<Vec<_>>();
+    assert_eq!(renames, vec![(0x02000000, "MyFunction")]);
+
+    // Named symbols missing from this project are added, but default names are not
+    let symbols = fs::read_to_string(self_path.join("symbols.txt"))?;
+    assert!(symbols.starts_with("MyFunction kind:function(arm,size=0x8) addr:0x02000000\n"), "{symbols}");
+    assert!(symbols.contains("gCounter kind:data(word) addr:0x02000104\n"), "{symbols}");
+    assert!(!symbols.contains("data_02000108"), "{symbols}");
+
+    Ok(())
+}
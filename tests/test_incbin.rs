@@ -0,0 +1,87 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{SymbolMap, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+use path_slash::PathExt;
+
+#[test]
+fn test_incbin_threshold() -> Result<()> {
+    let asm_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("incbin");
+    fs::create_dir_all(&asm_path)?;
+
+    let mut code = (0..0x44).collect::<Vec<u8>>();
+    // Pointer to data_02000000 in data_02000020
+    code[0x20..0x24].copy_from_slice(&0x02000000u32.to_le_bytes());
+
+    let symbols_path = asm_path.join("symbols.txt");
+    fs::write(
+        &symbols_path,
+        "data_02000000 kind:data(any) addr:0x02000000
+data_02000020 kind:data(word[8]) addr:0x02000020
+data_02000040 kind:data(byte[4]) addr:0x02000040
+",
+    )?;
+    let mut symbol_maps = SymbolMaps::new();
+    *symbol_maps.get_mut(ModuleKind::Arm9) = SymbolMap::from_file(&symbols_path)?;
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000044, 4)?)?;
+    let mut relocations = Relocations::new();
+    relocations.add_load(0x02000020, 0x02000000, 0, RelocationModule::Main)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        relocations,
+        sections,
+        &code,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000044, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let disassemble = Disassemble {
+        config_path: PathBuf::new(),
+        asm_path: asm_path.clone(),
+        ual: false,
+        module: None,
+        start: None,
+        end: None,
+        jobs: 1,
+        incbin_threshold: Some(0x10),
+        encoding: None,
+        single_file: false,
+        address_format: AddressFormat::default(),
+    };
+    let mut output = vec![];
+    disassemble.disassemble(&module, &delink_file, &mut output, &symbol_maps, None)?;
+    let assembly = String::from_utf8(output)?;
+
+    // Large and without relocations
+    // Included with the path it was written to, not relative to the assembly path
+    let bin_path = asm_path.join("main/src/main/data_02000000.bin");
+    let incbin = format!("data_02000000:\n    .incbin \"{}\"\n", bin_path.to_slash_lossy());
+    assert!(assembly.contains(&incbin), "{assembly}");
+    assert_eq!(fs::read(&bin_path)?, &code[..0x20]);
+
+    // Large but has a relocation
+    assert!(assembly.contains("data_02000020:\n    .word data_02000000"), "{assembly}");
+    assert!(!asm_path.join("main/src/main/data_02000020.bin").exists());
+
+    // Below the threshold
+    assert!(assembly.contains("data_02000040:\n    .byte 0x40, 0x41, 0x42, 0x43\n"), "{assembly}");
+
+    Ok(())
+}
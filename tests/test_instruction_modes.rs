@@ -0,0 +1,107 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{
+        FindFunctionsOptions, Function, FunctionParseOptions, FunctionSearchOptions, ParseFunctionOptions, ParseFunctionResult,
+    },
+    config::{
+        instruction_modes::{InstructionModeOverride, InstructionModes},
+        module::ModuleKind,
+        symbol::{InstructionMode, SymbolMap},
+    },
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 8] = [
+    0x08, 0xb5, // push {r3, lr}
+    0xff, 0xe7, // b 0x02000004, looks like an unconditional ARM instruction
+    0x08, 0xbd, // pop {r3, pc}
+    0x00, 0x00,
+];
+
+fn find_thumb_function(instruction_modes: &InstructionModes) -> Result<Option<Function>> {
+    let mut symbol_map = SymbolMap::new();
+    let mut functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: &CODE,
+        symbol_map: &mut symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + CODE.len() as u32,
+        search_options: FunctionSearchOptions { last_function_address: Some(BASE_ADDRESS), ..Default::default() },
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: instruction_modes.get(ModuleKind::Arm9),
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+    Ok(functions.remove(&BASE_ADDRESS).filter(|function| function.is_thumb()))
+}
+
+#[test]
+fn test_instruction_mode_forces_thumb() -> Result<()> {
+    assert!(find_thumb_function(&InstructionModes::new())?.is_none(), "heuristic should guess ARM");
+
+    let mode_override = InstructionModeOverride::from_str("main:0x02000000:thumb")?;
+    let function = find_thumb_function(&InstructionModes::from_overrides([&mode_override])?)?
+        .expect("function was not analyzed as Thumb");
+    assert_eq!(function.start_address(), BASE_ADDRESS);
+    assert_eq!(function.end_address(), BASE_ADDRESS + 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_instruction_mode_forces_thumb_in_parse_function() -> Result<()> {
+    let parse = |instruction_modes: &InstructionModes| {
+        Function::parse_function(FunctionParseOptions {
+            name: "func_02000000".to_string(),
+            start_address: BASE_ADDRESS,
+            base_address: BASE_ADDRESS,
+            module_code: &CODE,
+            known_end_address: None,
+            module_start_address: BASE_ADDRESS,
+            module_end_address: BASE_ADDRESS + CODE.len() as u32,
+            parse_options: ParseFunctionOptions {
+                instruction_modes: instruction_modes.get(ModuleKind::Arm9),
+                ..Default::default()
+            },
+        })
+    };
+
+    let result = parse(&InstructionModes::new())?;
+    assert!(!matches!(result, ParseFunctionResult::Found(ref function) if function.is_thumb()), "heuristic should guess ARM");
+
+    let mode_override = InstructionModeOverride::from_str("main:0x02000000:thumb")?;
+    let ParseFunctionResult::Found(function) = parse(&InstructionModes::from_overrides([&mode_override])?)? else {
+        panic!("function was not found");
+    };
+    assert!(function.is_thumb());
+    assert_eq!(function.end_address(), BASE_ADDRESS + 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_instruction_mode() -> Result<()> {
+    let mode_override = InstructionModeOverride::from_str("overlay:5:0x020a0002:thumb")?;
+    assert_eq!(
+        mode_override,
+        InstructionModeOverride { module: ModuleKind::Overlay(5), address: 0x020a0002, mode: InstructionMode::Thumb }
+    );
+    assert_eq!(mode_override.to_string(), "overlay:5:0x020a0002:thumb");
+
+    assert!(InstructionModeOverride::from_str("main:0x02000002:arm").is_err(), "ARM must be 4-aligned");
+    assert!(InstructionModeOverride::from_str("main:0x02000000:thumb2").is_err());
+    assert!(InstructionModeOverride::from_str("0x02000000:arm").is_err());
+
+    let duplicate = InstructionModeOverride::from_str("overlay:5:0x020a0002:thumb")?;
+    assert!(InstructionModes::from_overrides([&mode_override, &duplicate]).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{Delink, Disassemble},
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, RelocationSources, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+use object::{
+    Object, ObjectSection, ObjectSymbol, RelocationFlags, RelocationTarget, SectionKind as ObjSectionKind, SymbolSection,
+};
+
+#[rustfmt::skip]
+const MAIN_CODE: [u8; 0x10] = [
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000000: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000004: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000008: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_0200000c: bx lr
+];
+
+#[rustfmt::skip]
+const OVERLAY_CODE: [u8; 0x10] = [
+    0xfe, 0xff, 0xfb, 0xeb, // func_ovXXX_02100000: bl func_02000000
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_ovXXX_02100008: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_ovXXX_0210000c: bx lr
+];
+
+const NUM_OVERLAYS: u16 = 3;
+
+/// Splits a module of 0x10 bytes into two delink files.
+fn delink_files(name: &str, base_address: u32) -> Result<Vec<DelinkFile>> {
+    let mut files = vec![];
+    for (index, offset) in [0x0, 0x8].into_iter().enumerate() {
+        let mut sections = Sections::new();
+        let start = base_address + offset;
+        sections.add(Section::new(".text".to_string(), SectionKind::Code, start, start + 0x8, 4)?)?;
+        files.push(DelinkFile::new(format!("src/{name}/file_{index}.c"), sections, false));
+    }
+    Ok(files)
+}
+
+fn load_modules(symbol_maps: &mut SymbolMaps) -> Result<Vec<(Module<'static>, Vec<DelinkFile>)>> {
+    let mut modules = vec![];
+
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    for offset in (0x0..0x10).step_by(4) {
+        let addr = 0x02000000 + offset;
+        symbol_map.add(Symbol::new_function(format!("func_{addr:08x}"), addr, InstructionMode::Arm, 4));
+    }
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000010, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &MAIN_CODE,
+        &NamePrefixes::default(),
+    )?;
+    modules.push((module, delink_files("main", 0x02000000)?));
+
+    for id in 0..NUM_OVERLAYS {
+        let module_kind = ModuleKind::Overlay(id);
+        let symbol_map = symbol_maps.get_mut(module_kind);
+        symbol_map.add(Symbol::new_function(format!("func_ov{id:03}_02100000"), 0x02100000, InstructionMode::Arm, 8));
+        symbol_map.add(Symbol::new_function(format!("func_ov{id:03}_02100008"), 0x02100008, InstructionMode::Arm, 4));
+        symbol_map.add(Symbol::new_function(format!("func_ov{id:03}_0210000c"), 0x0210000c, InstructionMode::Arm, 4));
+
+        let mut relocations = Relocations::new();
+        relocations.add_call(0x02100000, 0x02000000, RelocationModule::Main, false, false)?;
+
+        let mut sections = Sections::new();
+        sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02100000, 0x02100010, 4)?)?;
+        let name = format!("ov{id:03}");
+        let module = Module::new_overlay(
+            name.clone(),
+            symbol_maps.get_mut(module_kind),
+            relocations,
+            sections,
+            id,
+            &OVERLAY_CODE,
+            &NamePrefixes::default(),
+        )?;
+        modules.push((module, delink_files(&name, 0x02100000)?));
+    }
+
+    Ok(modules)
+}
+
+fn read_dir_recursive(root: &Path, dir: &Path, files: &mut BTreeMap<PathBuf, Vec<u8>>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            read_dir_recursive(root, &path, files)?;
+        } else {
+            files.insert(path.strip_prefix(root)?.to_path_buf(), fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
+fn output_dir(name: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("jobs").join(name);
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    Ok(path)
+}
+
+fn read_output(path: &Path) -> Result<BTreeMap<PathBuf, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    read_dir_recursive(path, path, &mut files)?;
+    Ok(files)
+}
+
+/// Describes the sections, symbols and relocations of an object file, to compare it against a golden file.
+fn describe_object(bytes: &[u8]) -> Result<String> {
+    let object = object::File::parse(bytes)?;
+    let mut description = String::new();
+
+    for section in object.sections() {
+        if !matches!(
+            section.kind(),
+            ObjSectionKind::Text | ObjSectionKind::Data | ObjSectionKind::ReadOnlyData | ObjSectionKind::UninitializedData
+        ) {
+            continue;
+        }
+        let data = section.data()?.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+        writeln!(description, "section {} {:#x} {data}", section.name()?, section.size())?;
+    }
+
+    let mut symbols = vec![];
+    for symbol in object.symbols() {
+        let section = match symbol.section() {
+            SymbolSection::Section(index) => object.section_by_index(index)?.name()?.to_string(),
+            _ => "*UND*".to_string(),
+        };
+        let binding = if symbol.is_weak() {
+            "weak"
+        } else if symbol.is_global() {
+            "global"
+        } else {
+            "local"
+        };
+        symbols.push(format!(
+            "symbol {section} {:#06x} {} size {:#x} {binding}",
+            symbol.address(),
+            symbol.name()?,
+            symbol.size()
+        ));
+    }
+    symbols.sort();
+    for symbol in symbols {
+        writeln!(description, "{symbol}")?;
+    }
+
+    for section in object.sections() {
+        for (offset, relocation) in section.relocations() {
+            let RelocationTarget::Symbol(index) = relocation.target() else { panic!("relocation to a non-symbol") };
+            let RelocationFlags::Elf { r_type } = relocation.flags() else { panic!("relocation is not ELF") };
+            writeln!(
+                description,
+                "reloc {} {offset:#06x} {} type {r_type} addend {}",
+                section.name()?,
+                object.symbol_by_index(index)?.name()?,
+                relocation.addend()
+            )?;
+        }
+    }
+
+    Ok(description)
+}
+
+#[test]
+fn test_dis_jobs() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    let modules = load_modules(&mut symbol_maps)?;
+
+    let mut outputs = vec![];
+    for jobs in [1, 4] {
+        let asm_path = output_dir(&format!("dis_{jobs}"))?;
+        let disassemble = Disassemble {
+            config_path: PathBuf::new(),
+            asm_path: asm_path.clone(),
+            ual: false,
+            module: None,
+            start: None,
+            end: None,
+            jobs,
+            incbin_threshold: None,
+            encoding: None,
+            single_file: false,
+            address_format: AddressFormat::default(),
+        };
+        disassemble.disassemble_modules(&modules, &symbol_maps, None)?;
+        outputs.push(read_output(&asm_path)?);
+    }
+
+    assert_eq!(outputs[0].len(), 2 * (1 + NUM_OVERLAYS as usize));
+    assert_eq!(outputs[0], outputs[1]);
+
+    // Both paths match the output of serial disassembly from before files were written in parallel
+    for output in &outputs {
+        let main_asm = String::from_utf8(output[Path::new("main/src/main/file_0.s")].clone())?;
+        assert_eq!(main_asm, include_str!("golden/jobs_main_file_0.s"));
+        let overlay_asm = String::from_utf8(output[Path::new("ov001/src/ov001/file_0.s")].clone())?;
+        assert_eq!(overlay_asm, include_str!("golden/jobs_ov001_file_0.s"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_delink_jobs() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    let modules = load_modules(&mut symbol_maps)?;
+
+    let mut relocation_sources = RelocationSources::new();
+    for (module, _) in &modules {
+        relocation_sources.add_module(module.kind(), module.relocations());
+    }
+
+    let mut outputs = vec![];
+    for jobs in [1, 4] {
+        let elf_path = output_dir(&format!("delink_{jobs}"))?;
+        let delink = Delink { config_path: PathBuf::new(), jobs, incremental: false };
+        let result = delink.delink_modules(&modules, &elf_path, &symbol_maps, &relocation_sources)?;
+        assert_eq!(result.num_files, 2 * (1 + NUM_OVERLAYS as usize));
+        assert_eq!(result.num_gaps, 0);
+        outputs.push(read_output(&elf_path)?);
+    }
+
+    // One object per file, plus the manifest
+    assert_eq!(outputs[0].len(), 2 * (1 + NUM_OVERLAYS as usize) + 1);
+    assert_eq!(outputs[0], outputs[1]);
+
+    // Both paths match the objects of serial delinking from before files were written in parallel
+    for output in &outputs {
+        let main_object = describe_object(&output[Path::new("src/main/file_0.o")])?;
+        assert_eq!(main_object, include_str!("golden/jobs_main_file_0.txt"));
+        let overlay_object = describe_object(&output[Path::new("src/ov001/file_0.o")])?;
+        assert_eq!(overlay_object, include_str!("golden/jobs_ov001_file_0.txt"));
+    }
+
+    Ok(())
+}
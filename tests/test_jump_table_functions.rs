@@ -0,0 +1,64 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::symbol::SymbolMap,
+};
+use unarm::Endian;
+
+#[test]
+fn test_switch_cases_as_functions() -> Result<()> {
+    let base_address = 0x02000000;
+    let switch_address = 0x02000010;
+    #[rustfmt::skip]
+    let code = [
+        // case 0, no prologue
+        0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+        // case 1, no prologue
+        0x02, 0x00, 0xa0, 0xe3, // mov r0, #0x2
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+        // switch
+        0x01, 0x00, 0x50, 0xe3, // cmp r0, #0x1
+        0x00, 0xf1, 0x8f, 0x90, // addls pc, pc, r0, lsl #0x2
+        0x01, 0x00, 0x00, 0xea, // b default
+        0xf7, 0xff, 0xff, 0xea, // b case 0
+        0xf8, 0xff, 0xff, 0xea, // b case 1
+        // default
+        0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    ];
+
+    let mut symbol_map = SymbolMap::new();
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address,
+        module_code: &code,
+        symbol_map: &mut symbol_map,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        search_options: FunctionSearchOptions { start_address: Some(switch_address), ..Default::default() },
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+
+    let switch = functions.get(&switch_address).expect("switch function was not found");
+    assert_eq!(switch.end_address(), base_address + code.len() as u32);
+    assert_eq!(switch.jump_tables().count(), 1);
+    assert_eq!(switch.external_jump_targets().copied().collect::<Vec<_>>(), vec![base_address, base_address + 8]);
+    assert!(switch.labels().all(|&label| label >= switch_address), "case functions must not be labels");
+    assert_eq!(switch.function_calls().get(&0x0200001c).map(|call| call.address), Some(base_address));
+    assert_eq!(switch.function_calls().get(&0x02000020).map(|call| call.address), Some(base_address + 8));
+
+    for case_address in [base_address, base_address + 8] {
+        let case = functions.get(&case_address).expect("case function was not found");
+        assert_eq!(case.end_address(), case_address + 8);
+        assert!(!case.is_thumb());
+        assert!(symbol_map.get_function(case_address)?.is_some());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::{Lcf, LcfFormat};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+- id: 1
+  name: ov001
+  object: build/ov001.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov001/delinks.txt
+  symbols: overlays/ov001/symbols.txt
+  relocations: overlays/ov001/relocs.txt
+- id: 2
+  name: ov002
+  object: build/ov002.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov002/delinks.txt
+  symbols: overlays/ov002/symbols.txt
+  relocations: overlays/ov002/relocs.txt
+";
+
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .init       start:0x02000100 end:0x02000120 kind:code align:4
+    .data       start:0x02000120 end:0x02000140 kind:data align:32
+    .bss        start:0x02000140 end:0x02000200 kind:bss align:32
+
+src/main.c:
+    complete
+    .text start:0x02000000 end:0x02000080
+    .init start:0x02000100 end:0x02000120
+    .data start:0x02000120 end:0x02000140
+
+src/util.c:
+    .text start:0x02000080 end:0x02000100
+    .bss start:0x02000140 end:0x02000200
+";
+
+const OV000_DELINKS: &str = "    .text       start:0x02100000 end:0x02100100 kind:code align:32
+    .data       start:0x02100100 end:0x02100200 kind:data align:32
+
+src/ov000/a.c:
+    .text start:0x02100000 end:0x02100100
+    .data start:0x02100100 end:0x02100200
+";
+
+const OV001_DELINKS: &str = "    .text       start:0x02100000 end:0x02100080 kind:code align:32
+    .bss        start:0x02100080 end:0x021000c0 kind:bss align:32
+
+src/ov001/b.c:
+    .text start:0x02100000 end:0x02100080
+    .bss start:0x02100080 end:0x021000c0
+";
+
+const OV002_DELINKS: &str = "    .text       start:0x02100200 end:0x02100240 kind:code align:32
+
+src/ov002/c.c:
+    .text start:0x02100200 end:0x02100240
+";
+
+#[test]
+fn test_lcf_gnu_golden() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("lcf_gnu");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), MAIN_DELINKS)?;
+    for (name, delinks) in [("ov000", OV000_DELINKS), ("ov001", OV001_DELINKS), ("ov002", OV002_DELINKS)] {
+        let overlay_path = path.join("overlays").join(name);
+        fs::create_dir_all(&overlay_path)?;
+        fs::write(overlay_path.join("delinks.txt"), delinks)?;
+    }
+
+    let lcf = Lcf {
+        config_path: path.join("config.yaml"),
+        lcf_file: path.join("build/linker_script.ld"),
+        objects_file: path.join("build/objects.txt"),
+        format: LcfFormat::Gnu,
+    };
+    lcf.run()?;
+
+    let script = fs::read_to_string(path.join("build/linker_script.ld"))?;
+    assert_eq!(script, include_str!("golden/lcf_gnu.ld"));
+
+    let objects = fs::read_to_string(path.join("build/objects.txt"))?;
+    let objects = objects.lines().map(|line| PathBuf::from(line).strip_prefix(&path).unwrap().to_owned()).collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        [
+            "build/src/main.o",
+            "build/delinks/src/util.o",
+            "build/delinks/src/ov000/a.o",
+            "build/delinks/src/ov001/b.o",
+            "build/delinks/src/ov002/c.o"
+        ]
+        .map(PathBuf::from)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{Lcf, LcfFormat},
+    config::memory_regions::MemoryRegion,
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+memory_regions:
+- ARM9:0x02000000:0x400000:rwx
+- WRAM:0x037f8000:0x18000
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+const DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+
+src/main.c:
+    .text start:0x02000000 end:0x02000100
+";
+
+#[test]
+fn test_lcf_custom_memory_regions() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("lcf_memory_regions");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), DELINKS)?;
+
+    let lcf = Lcf {
+        config_path: path.join("config.yaml"),
+        lcf_file: path.join("build/linker_script.ld"),
+        objects_file: path.join("build/objects.txt"),
+        format: LcfFormat::Gnu,
+    };
+    lcf.run()?;
+
+    let script = fs::read_to_string(path.join("build/linker_script.ld"))?;
+    let memory = "MEMORY {
+    ARM9 (rwx) : ORIGIN = 0x2000000, LENGTH = 0x400000
+    WRAM : ORIGIN = 0x37f8000, LENGTH = 0x18000
+}
+";
+    assert!(script.contains(memory), "unexpected MEMORY block in:\n{script}");
+    assert!(script.contains("    } > ARM9\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_memory_region_format() -> Result<()> {
+    let region: MemoryRegion = "ARM9:0x02000000:0x400000:rwx".parse()?;
+    assert_eq!(region.origin, 0x02000000);
+    assert_eq!(region.end(), 0x02400000);
+    assert_eq!(region.to_string(), "ARM9:0x02000000:0x400000:rwx");
+    assert!("ARM9:0x02000000:0".parse::<MemoryRegion>().is_err());
+    assert!(MemoryRegion::check_unique(&[region.clone(), region]).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use ds_decomp::analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
+use unarm::Endian;
+
+#[test]
+fn test_ldrd_pool_constant() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        0x08, 0x40, 0x2d, 0xe9, // push {r3, lr}
+        0xd4, 0x00, 0xcf, 0xe1, // ldrd r0, [pc, #4]
+        0x08, 0x20, 0x9f, 0xe5, // ldr r2, [pc, #8]
+        0x08, 0x80, 0xbd, 0xe8, // pop {r3, pc}
+        0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, // 0x1122334455667788
+        0xef, 0xbe, 0xad, 0xde, // 0xdeadbeef
+    ];
+
+    let result = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: base_address,
+        base_address,
+        module_code: &code,
+        known_end_address: None,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        parse_options: ParseFunctionOptions::default(),
+    })?;
+
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function was not found: {result:?}");
+    };
+    assert_eq!(function.end_address(), base_address + 0x1c);
+
+    let pool_constants = function
+        .iter_pool_constants(&code, base_address, Endian::Little)
+        .map(|constant| (constant.address - base_address, constant.value))
+        .collect::<Vec<_>>();
+    assert_eq!(pool_constants, vec![(0x10, 0x55667788), (0x14, 0x11223344), (0x18, 0xdeadbeef)]);
+
+    Ok(())
+}
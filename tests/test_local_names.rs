@@ -0,0 +1,34 @@
+use ds_decomp::util::local_name::CompilerLocalName;
+
+#[test]
+fn test_classify_compiler_local_names() {
+    assert_eq!(CompilerLocalName::classify("@1304"), Some(CompilerLocalName::Anonymous));
+    assert_eq!(CompilerLocalName::classify("sCounter$1304"), Some(CompilerLocalName::FunctionStatic));
+    assert_eq!(CompilerLocalName::classify("$1304"), Some(CompilerLocalName::Temporary));
+    assert_eq!(CompilerLocalName::classify("$tmp0"), Some(CompilerLocalName::Temporary));
+
+    // Named by the user, or not numbered
+    assert_eq!(CompilerLocalName::classify("func_02000000"), None);
+    assert_eq!(CompilerLocalName::classify("@etb_02000000"), None);
+    assert_eq!(CompilerLocalName::classify("@"), None);
+    assert_eq!(CompilerLocalName::classify("sCounter$"), None);
+    assert_eq!(CompilerLocalName::classify("$"), None);
+
+    // ARM mapping symbols
+    assert_eq!(CompilerLocalName::classify("$a"), None);
+    assert_eq!(CompilerLocalName::classify("$t"), None);
+    assert_eq!(CompilerLocalName::classify("$d.12"), None);
+}
+
+#[test]
+fn test_normalize_compiler_local_names() {
+    assert_eq!(CompilerLocalName::normalize("@1304"), "@");
+    assert_eq!(CompilerLocalName::normalize("sCounter$1304"), "sCounter$");
+    assert_eq!(CompilerLocalName::normalize("func_02000000"), "func_02000000");
+
+    assert!(CompilerLocalName::names_match("@1304", "@1306"));
+    assert!(CompilerLocalName::names_match("sCounter$1304", "sCounter$1310"));
+    assert!(!CompilerLocalName::names_match("sCounter$1304", "sTimer$1304"));
+    assert!(!CompilerLocalName::names_match("@1304", "$1304"));
+    assert!(!CompilerLocalName::names_match("func_02000000", "func_02000004"));
+}
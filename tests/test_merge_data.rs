@@ -0,0 +1,67 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::FixMergeData;
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+const DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .data       start:0x02000100 end:0x02000200 kind:data align:32
+
+";
+
+const SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+data_02000104 kind:data(word) addr:0x02000104
+data_02000108 kind:data(word) addr:0x02000108
+data_0200010c kind:data(word) addr:0x0200010c
+data_02000110 kind:data(word) addr:0x02000110
+data_02000114 kind:data(word) addr:0x02000114
+gRenamed kind:data(word) addr:0x02000118
+data_0200011c kind:data(word) addr:0x0200011c
+data_02000120 kind:data(short) addr:0x02000120
+data_02000122 kind:data(short) addr:0x02000122
+";
+
+const RELOCS: &str = "from:0x02000004 kind:load to:0x02000100 module:main
+from:0x02000008 kind:load to:0x0200010c module:main
+";
+
+const EXPECTED_SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word[3]) addr:0x02000100
+data_0200010c kind:data(word[3]) addr:0x0200010c
+gRenamed kind:data(word) addr:0x02000118
+data_0200011c kind:data(word) addr:0x0200011c
+data_02000120 kind:data(short[2]) addr:0x02000120
+";
+
+#[test]
+fn test_merge_data_keeps_referenced_symbols() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("merge_data");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), DELINKS)?;
+    fs::write(path.join("symbols.txt"), SYMBOLS)?;
+    fs::write(path.join("relocs.txt"), RELOCS)?;
+
+    FixMergeData { config_path: path.join("config.yaml"), dry: true, no_backup: true }.run()?;
+    assert_eq!(fs::read_to_string(path.join("symbols.txt"))?, SYMBOLS);
+
+    FixMergeData { config_path: path.join("config.yaml"), dry: false, no_backup: true }.run()?;
+    assert_eq!(fs::read_to_string(path.join("symbols.txt"))?, EXPECTED_SYMBOLS);
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::MigrateConfig,
+    config::{
+        config::{Config, ConfigEndian, CONFIG_VERSION},
+        name_prefixes::NamePrefixes,
+    },
+    util::backup::BACKUP_DIR,
+};
+
+const CONFIG_V1: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+fn write_config(name: &str, contents: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("migrate_config").join(name);
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(&path)?;
+    let config_path = path.join("config.yaml");
+    fs::write(&config_path, contents)?;
+    Ok(config_path)
+}
+
+fn backups(config_path: &Path) -> Result<Vec<PathBuf>> {
+    let backup_dir = config_path.parent().unwrap().join(BACKUP_DIR);
+    if !backup_dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut backups = vec![];
+    for entry in fs::read_dir(backup_dir)? {
+        backups.push(entry?.path());
+    }
+    Ok(backups)
+}
+
+#[test]
+fn test_migrate_v1_config() -> Result<()> {
+    let config_path = write_config("v1", CONFIG_V1)?;
+
+    let config: Config = serde_yml::from_str(CONFIG_V1)?;
+    assert_eq!(config.version, 1);
+
+    MigrateConfig { config_path: config_path.clone() }.run()?;
+
+    let migrated = fs::read_to_string(&config_path)?;
+    assert!(migrated.starts_with(&format!("version: {CONFIG_VERSION}\n")), "{migrated}");
+    let config: Config = serde_yml::from_str(&migrated)?;
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.main_module.name, "main");
+    assert!(config.data_regions.is_empty());
+
+    // The defaults which version 1 relied on are written out
+    assert!(migrated.contains("\nendian: little\n"), "{migrated}");
+    assert!(migrated.contains("\nname_prefixes:\n  func_prefix: func_\n"), "{migrated}");
+    assert_eq!(config.endian, ConfigEndian::Little);
+    assert_eq!(config.name_prefixes, NamePrefixes::default());
+
+    let backups = backups(&config_path)?;
+    assert_eq!(backups.len(), 1);
+    assert_eq!(fs::read_to_string(&backups[0])?, CONFIG_V1);
+    assert!(Config::from_file(&config_path).is_ok());
+
+    // Migrating a current config does nothing
+    MigrateConfig { config_path: config_path.clone() }.run()?;
+    assert_eq!(fs::read_to_string(&config_path)?, migrated);
+    assert_eq!(backups(&config_path)?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_migrate_future_config() -> Result<()> {
+    let future_config = format!("version: {}\n{CONFIG_V1}", CONFIG_VERSION + 1);
+    let config_path = write_config("future", &future_config)?;
+
+    assert!(MigrateConfig { config_path: config_path.clone() }.run().is_err());
+    assert_eq!(fs::read_to_string(&config_path)?, future_config);
+    assert!(backups(&config_path)?.is_empty());
+
+    // Other subcommands refuse to use it too
+    assert!(Config::from_file(&config_path).is_err());
+
+    Ok(())
+}
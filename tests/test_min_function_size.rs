@@ -0,0 +1,77 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::symbol::{InstructionMode, Symbol, SymbolMap},
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0x14] = [
+    0x00, 0x00, 0xa0, 0xe3, // func_02000000: mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // EmptyFunc: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // data which looks like bx lr
+    0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+const CODE_WITH_CALL: [u8; 0x1c] = [
+    0x00, 0x40, 0x2d, 0xe9, // func_02000000: stmdb sp!, {lr}
+    0x00, 0x00, 0x00, 0xeb, // bl func_0200000c
+    0x00, 0x80, 0xbd, 0xe8, // ldmia sp!, {pc}
+    0x1e, 0xff, 0x2f, 0xe1, // func_0200000c: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // data which looks like bx lr
+    0x00, 0x00, 0xa0, 0xe3, // func_02000014: mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+/// Finds functions in [`CODE`] with a known function at 0x02000008, and returns their start addresses.
+fn find_function_addresses(min_function_size: u32) -> Result<Vec<u32>> {
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_function("EmptyFunc".to_string(), BASE_ADDRESS + 0x8, InstructionMode::Arm, 4));
+    find_functions(&CODE, &mut symbol_map, min_function_size)
+}
+
+/// Finds functions in `code` and returns their start addresses.
+fn find_functions(code: &[u8], symbol_map: &mut SymbolMap, min_function_size: u32) -> Result<Vec<u32>> {
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: code,
+        symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + code.len() as u32,
+        search_options: FunctionSearchOptions { min_function_size, ..Default::default() },
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+    Ok(functions.keys().copied().collect())
+}
+
+#[test]
+fn test_min_function_size() -> Result<()> {
+    assert_eq!(find_function_addresses(0)?, [BASE_ADDRESS, BASE_ADDRESS + 0x8, BASE_ADDRESS + 0xc]);
+
+    // The bogus function is too small, but the known function of the same size is kept
+    assert_eq!(find_function_addresses(8)?, [BASE_ADDRESS, BASE_ADDRESS + 0x8]);
+
+    Ok(())
+}
+
+#[test]
+fn test_min_function_size_call_target() -> Result<()> {
+    let addresses = find_functions(&CODE_WITH_CALL, &mut SymbolMap::new(), 0)?;
+    assert_eq!(addresses, [BASE_ADDRESS, BASE_ADDRESS + 0xc, BASE_ADDRESS + 0x10, BASE_ADDRESS + 0x14]);
+
+    // The called function is kept despite its size, and the search continues past the bogus function
+    let addresses = find_functions(&CODE_WITH_CALL, &mut SymbolMap::new(), 8)?;
+    assert_eq!(addresses, [BASE_ADDRESS, BASE_ADDRESS + 0xc, BASE_ADDRESS + 0x14]);
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0xc] = [
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000000: bx lr
+    0x70, 0x47,             // func_02000004: bx lr
+    0x70, 0x47,             // func_02000006: bx lr
+    0x1e, 0xff, 0x2f, 0xe1, // func_02000008: bx lr
+];
+
+#[test]
+fn test_mixed_mode_directives() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 4));
+    symbol_map.add(Symbol::new_function("func_02000004".to_string(), 0x02000004, InstructionMode::Thumb, 2));
+    symbol_map.add(Symbol::new_function("func_02000006".to_string(), 0x02000006, InstructionMode::Thumb, 2));
+    symbol_map.add(Symbol::new_function("func_02000008".to_string(), 0x02000008, InstructionMode::Arm, 4));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &CODE,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let disassemble = Disassemble {
+        config_path: PathBuf::new(),
+        asm_path: PathBuf::new(),
+        ual: true,
+        module: None,
+        start: None,
+        end: None,
+        jobs: 1,
+        incbin_threshold: None,
+        encoding: None,
+        single_file: false,
+        address_format: AddressFormat::default(),
+    };
+    let mut output = vec![];
+    disassemble.disassemble(&module, &delink_file, &mut output, &symbol_maps, None)?;
+    assert_eq!(String::from_utf8(output)?, include_str!("golden/dis_mixed_modes.s"));
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::{
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{SymbolKind, SymbolMap},
+    },
+};
+use unarm::Endian;
+
+fn custom_prefixes() -> NamePrefixes {
+    NamePrefixes {
+        func_prefix: "fn_".to_string(),
+        data_prefix: "dat_".to_string(),
+        overlay_func_prefix: "FN_OV{overlay}_".to_string(),
+        overlay_data_prefix: "DAT_OV{overlay}_".to_string(),
+        label_prefix: "_".to_string(),
+    }
+}
+
+#[test]
+fn test_custom_name_prefixes() -> Result<()> {
+    let base_address = 0x02100000;
+    #[rustfmt::skip]
+    let code = [
+        0x00, 0x40, 0x2d, 0xe9, // push {lr}
+        0x00, 0x80, 0xbd, 0xe8, // pop {pc}
+    ];
+
+    let name_prefixes = custom_prefixes();
+    name_prefixes.validate()?;
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, base_address, base_address + 8, 4)?)?;
+    let mut symbol_map = SymbolMap::new();
+    let module =
+        Module::new_overlay("ov005".to_string(), &mut symbol_map, Relocations::new(), sections, 5, &code, &name_prefixes)?;
+    assert_eq!(module.default_func_prefix, "FN_OV005_");
+    assert_eq!(module.default_data_prefix, "DAT_OV005_");
+    assert_eq!(name_prefixes.func_prefix(ModuleKind::Arm9), "fn_");
+    assert_eq!(name_prefixes.data_prefix(ModuleKind::Arm9), "dat_");
+
+    Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: &module.default_func_prefix,
+        base_address,
+        module_code: &code,
+        symbol_map: &mut symbol_map,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        search_options: FunctionSearchOptions { last_function_address: Some(base_address), ..Default::default() },
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+
+    let (_, symbol) = symbol_map.get_function(base_address)?.expect("function was not found");
+    assert_eq!(symbol.name, "FN_OV005_02100000");
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_name_prefixes() {
+    let mut name_prefixes = custom_prefixes();
+    name_prefixes.overlay_func_prefix = "fn_ov_".to_string();
+    assert!(name_prefixes.validate().is_err(), "overlay prefix without placeholder");
+
+    let mut name_prefixes = custom_prefixes();
+    name_prefixes.data_prefix = name_prefixes.func_prefix.clone();
+    assert!(name_prefixes.validate().is_err(), "equal function and data prefixes");
+
+    let mut name_prefixes = custom_prefixes();
+    name_prefixes.func_prefix = "0fn_".to_string();
+    assert!(name_prefixes.validate().is_err(), "prefix starting with a digit");
+
+    let mut name_prefixes = custom_prefixes();
+    name_prefixes.func_prefix = "fn-".to_string();
+    assert!(name_prefixes.validate().is_err(), "prefix with illegal character");
+
+    assert!(NamePrefixes::default().validate().is_ok());
+}
+
+#[test]
+fn test_label_prefix() -> Result<()> {
+    let mut name_prefixes = custom_prefixes();
+    name_prefixes.label_prefix = ".L_".to_string();
+    name_prefixes.validate()?;
+
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.set_label_prefix(&name_prefixes.label_prefix);
+    symbol_map.add_label(0x02000004, false)?;
+    symbol_map.add_pool_constant(0x02000008)?;
+    assert_eq!(symbol_map.by_address(0x02000004)?.unwrap().1.name, ".L_02000004");
+    assert_eq!(symbol_map.by_address(0x02000008)?.unwrap().1.name, ".L_02000008");
+
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("label_prefix");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("symbols.txt");
+    symbol_map.to_file(&path)?;
+    let reloaded = SymbolMap::from_file(&path)?;
+    let (_, label) = reloaded.by_address(0x02000004)?.expect("label should be reloaded");
+    assert_eq!(label.name, ".L_02000004");
+    assert!(matches!(label.kind, SymbolKind::Label(_)));
+    let (_, pool_constant) = reloaded.by_address(0x02000008)?.expect("pool constant should be reloaded");
+    assert_eq!(pool_constant.name, ".L_02000008");
+    assert!(pool_constant.kind == SymbolKind::PoolConstant);
+
+    name_prefixes.label_prefix = ".L-".to_string();
+    assert!(name_prefixes.validate().is_err(), "label prefix with illegal character");
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::{
+        module::ModuleKind,
+        no_epilogue::{NoEpilogueFallback, NoEpilogueFallbacks},
+        symbol::{InstructionMode, Symbol, SymbolMap},
+    },
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0x1c] = [
+    // Dispatch loop, the branch makes every following return conditional
+    0x10, 0x40, 0x2d, 0xe9, // stmdb sp!, {r4, lr}
+    0x04, 0x00, 0x94, 0xe4, // ldr r0, [r4], #0x4
+    0x30, 0xff, 0x2f, 0xe1, // blx r0
+    0x01, 0x00, 0x00, 0x1a, // bne 0x02000018
+    // func_02000010
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // func_02000014
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // Not code
+    0x00, 0x00, 0x00, 0x00,
+];
+
+fn find_functions(no_epilogue_fallback: Option<u32>) -> Result<BTreeMap<u32, Function>> {
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_function("func_02000010".to_string(), BASE_ADDRESS + 0x10, InstructionMode::Arm, 4));
+
+    Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: &CODE,
+        symbol_map: &mut symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + CODE.len() as u32,
+        search_options: FunctionSearchOptions::default(),
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback,
+        endian: Endian::Little,
+    })
+}
+
+#[test]
+fn test_no_epilogue_fallback() -> Result<()> {
+    // Without the fallback, the dispatch loop ends the search
+    assert!(find_functions(None)?.is_empty());
+
+    let functions = find_functions(Some(0x10))?;
+    let dispatch = functions.get(&BASE_ADDRESS).expect("dispatch loop was not found");
+    assert_eq!(dispatch.end_address(), BASE_ADDRESS + 0x10, "must end at the next symbol");
+    assert_eq!(dispatch.labels().count(), 0, "branch past the end must not be a label");
+    assert_eq!(functions.get(&(BASE_ADDRESS + 0x10)).expect("next function was not found").end_address(), BASE_ADDRESS + 0x14);
+    assert_eq!(functions.get(&(BASE_ADDRESS + 0x14)).expect("last function was not found").end_address(), BASE_ADDRESS + 0x18);
+
+    // The next symbol is too far away
+    assert!(find_functions(Some(0xc))?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_no_epilogue_fallbacks_config() -> Result<()> {
+    let fallback: NoEpilogueFallback = "overlay:5:0x100".parse()?;
+    assert_eq!(fallback, NoEpilogueFallback { module: ModuleKind::Overlay(5), max_distance: 0x100 });
+    assert_eq!(fallback.to_string(), "overlay:5:0x100");
+
+    let fallbacks = NoEpilogueFallbacks::from_fallbacks(&[fallback])?;
+    assert_eq!(fallbacks.get(ModuleKind::Overlay(5)), Some(0x100));
+    assert_eq!(fallbacks.get(ModuleKind::Arm9), None);
+    assert!(NoEpilogueFallbacks::from_fallbacks(&[fallback, fallback]).is_err());
+
+    Ok(())
+}
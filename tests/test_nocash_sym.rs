@@ -0,0 +1,138 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::{ExportSym, ImportSymbols, SymFormat},
+    config::{
+        module::ModuleKind,
+        symbol::{InstructionMode, MergePolicy, SymFunction, SymbolKind, SymbolMap},
+    },
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .data       start:0x02000100 end:0x02000200 kind:data align:32
+
+";
+const OVERLAY_DELINKS: &str = "    .text       start:0x02100000 end:0x02100100 kind:code align:32
+
+";
+
+const MAIN_SYMBOLS: &str = "InitGame kind:function(arm,size=0x8) addr:0x02000000
+_02000004 kind:label(arm) addr:0x02000004
+gGameState kind:data(word) addr:0x02000100
+";
+const OVERLAY_SYMBOLS: &str = "Title_Update kind:function(thumb,size=0x4) addr:0x02100000
+";
+
+const DEFAULT_MAIN_SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+";
+const DEFAULT_OVERLAY_SYMBOLS: &str = "func_ov000_02100000 kind:function(thumb,size=0x4) addr:0x02100000
+";
+
+fn write_project(name: &str, main_symbols: &str, overlay_symbols: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if path.exists() {
+        fs::remove_dir_all(&path)?;
+    }
+    fs::create_dir_all(path.join("overlays/ov000"))?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), MAIN_DELINKS)?;
+    fs::write(path.join("symbols.txt"), main_symbols)?;
+    fs::write(path.join("relocs.txt"), "")?;
+    fs::write(path.join("overlays/ov000/delinks.txt"), OVERLAY_DELINKS)?;
+    fs::write(path.join("overlays/ov000/symbols.txt"), overlay_symbols)?;
+    fs::write(path.join("overlays/ov000/relocs.txt"), "")?;
+    Ok(path)
+}
+
+#[test]
+fn test_export_nocash_sym() -> Result<()> {
+    let path = write_project("nocash_sym_export", MAIN_SYMBOLS, OVERLAY_SYMBOLS)?;
+    let sym_path = path.join("game.sym");
+
+    ExportSym { config_path: path.join("config.yaml"), format: SymFormat::Nocash, output_path: sym_path.clone() }.run()?;
+
+    assert_eq!(
+        fs::read_to_string(&sym_path)?,
+        "02000000 .arm\n02000000 InitGame\n02000100 gGameState\n02100000 .thumb\n02100001 Title_Update\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_nocash_sym_round_trip() -> Result<()> {
+    let named_path = write_project("nocash_sym_named", MAIN_SYMBOLS, OVERLAY_SYMBOLS)?;
+    let sym_path = named_path.join("game.sym");
+    ExportSym { config_path: named_path.join("config.yaml"), format: SymFormat::Nocash, output_path: sym_path.clone() }
+        .run()?;
+
+    // Add a function which isn't in symbols.txt yet, along with a directive and a comment
+    let mut sym = fs::read_to_string(&sym_path)?;
+    sym.push_str("02000008 .arm\n02000008 DrawFrame ; added in no$gba\n02000010 .thumb\n02000010 DrawSprite\n");
+    fs::write(&sym_path, sym)?;
+
+    let path = write_project("nocash_sym_default", DEFAULT_MAIN_SYMBOLS, DEFAULT_OVERLAY_SYMBOLS)?;
+    let renames = ImportSymbols {
+        config_path: path.join("config.yaml"),
+        elf_path: None,
+        sym_path: Some(sym_path),
+        dwarf_path: None,
+        ghidra_csv_path: None,
+        map_config_path: None,
+        merge_policy: MergePolicy::PreferNamed,
+        include_default_names: false,
+        dry: false,
+        no_backup: true,
+    }
+    .import()?;
+
+    let renames = renames
+        .iter()
+        .map(|rename| (rename.module_kind, rename.address, rename.old_name.as_str(), rename.new_name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        renames,
+        vec![
+            (ModuleKind::Arm9, 0x02000000, "func_02000000", "InitGame"),
+            (ModuleKind::Arm9, 0x02000100, "data_02000100", "gGameState"),
+            (ModuleKind::Overlay(0), 0x02100000, "func_ov000_02100000", "Title_Update"),
+        ]
+    );
+
+    let main_symbols = SymbolMap::from_file(path.join("symbols.txt"))?;
+    let (_, added) = main_symbols.by_address(0x02000008)?.expect("function should be added");
+    assert_eq!(added.name, "DrawFrame");
+    assert!(matches!(added.kind, SymbolKind::Function(SymFunction { mode: InstructionMode::Arm, .. })));
+    let (_, added) = main_symbols.by_address(0x02000010)?.expect("function should be added");
+    assert_eq!(added.name, "DrawSprite");
+    assert!(matches!(added.kind, SymbolKind::Function(SymFunction { mode: InstructionMode::Thumb, .. })));
+
+    let overlay_symbols = SymbolMap::from_file(path.join("overlays/ov000/symbols.txt"))?;
+    assert_eq!(overlay_symbols.by_address(0x02100000)?.unwrap().1.name, "Title_Update");
+
+    Ok(())
+}
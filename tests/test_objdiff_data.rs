@@ -0,0 +1,85 @@
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Delink,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, RelocationSources, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, SymData, Symbol, SymbolKind, SymbolMaps},
+    },
+};
+use object::{Object, ObjectSection, ObjectSymbol};
+
+#[test]
+fn test_rodata_symbols_in_target_object() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        // .text
+        0x00, 0x00, 0x9f, 0xe5, // ldr r0, [pc, #0x0]
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+        0x0c, 0x00, 0x00, 0x02, // .word data_0200000c
+        // .rodata
+        0x01, 0x00, 0x00, 0x00, // data_0200000c
+        0x02, 0x00, 0x00, 0x00, // data_02000010
+    ];
+
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 0xc));
+    symbol_map.add_data(Some("data_0200000c".to_string()), 0x0200000c, SymData::Word { count: Some(1) })?;
+    symbol_map.add_data(Some("data_02000010".to_string()), 0x02000010, SymData::Word { count: Some(1) })?;
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    sections.add(Section::new(".rodata".to_string(), SectionKind::Data, 0x0200000c, 0x02000014, 4)?)?;
+
+    let mut relocations = Relocations::new();
+    relocations.add_load(0x02000008, 0x0200000c, 0, RelocationModule::Main)?;
+
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        relocations,
+        sections,
+        &code,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x0200000c, 4)?)?;
+    file_sections.add(Section::new(".rodata".to_string(), SectionKind::Data, 0x0200000c, 0x02000014, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let bytes = Delink::delink(&symbol_maps, &RelocationSources::new(), &module, &delink_file)?.write()?;
+    let object = object::File::parse(bytes.as_slice())?;
+
+    let rodata = object.section_by_name(".rodata").expect("missing .rodata section");
+    assert_eq!(rodata.kind(), object::SectionKind::ReadOnlyData);
+
+    let symbol = |name: &str| object.symbols().find(|s| s.name() == Ok(name)).expect(name);
+    for name in ["data_0200000c", "data_02000010"] {
+        let data = symbol(name);
+        assert_eq!(data.kind(), object::SymbolKind::Data);
+        assert_eq!(data.section_index(), Some(rodata.index()));
+        assert_eq!(data.size(), 4);
+    }
+
+    // Pool constants stay unsized labels for the linker, but are categorized as data
+    object
+        .symbols()
+        .find(|s| s.address() == 8 && s.kind() == object::SymbolKind::Data)
+        .expect("pool constant should be a data symbol");
+
+    let (_, relocation) = object
+        .section_by_name(".text")
+        .unwrap()
+        .relocations()
+        .find(|(offset, _)| *offset == 8)
+        .expect("missing relocation to .rodata");
+    let object::RelocationTarget::Symbol(target) = relocation.target() else { panic!("relocation has no symbol") };
+    assert_eq!(object.symbol_by_index(target)?.name()?, "data_0200000c");
+
+    Ok(())
+}
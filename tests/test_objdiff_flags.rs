@@ -0,0 +1,68 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Objdiff,
+    config::{delinks::Delinks, module::ModuleKind},
+};
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+const DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+
+src/main.c:
+    flags: -O4,p -inline noauto
+    .text start:0x02000000 end:0x02000080
+
+src/util.c:
+    .text start:0x02000080 end:0x02000100
+";
+
+#[test]
+fn test_objdiff_unit_flags() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("objdiff_flags");
+    fs::create_dir_all(&path)?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), DELINKS)?;
+
+    let delinks = Delinks::from_file(path.join("delinks.txt"), ModuleKind::Arm9)?;
+    assert_eq!(delinks.files[0].flags.as_deref(), Some("-O4,p -inline noauto"));
+    assert!(delinks.display().to_string().contains("src/main.c:\n    flags: -O4,p -inline noauto\n"));
+
+    let objdiff = Objdiff {
+        config_path: path.join("config.yaml"),
+        output_path: Some(path.clone()),
+        scratch: true,
+        compiler: Some("mwcc_30_131".to_string()),
+        c_flags: Some("-O2".to_string()),
+        preset_id: None,
+        custom_make: None,
+        custom_args: vec![],
+        unit: None,
+    };
+    objdiff.run()?;
+
+    let objdiff_json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path.join("objdiff.json"))?)?;
+    let units = objdiff_json["units"].as_array().expect("no units in objdiff.json");
+    let c_flags = |name: &str| {
+        let unit = units.iter().find(|unit| unit["name"] == name).expect("unit not found");
+        unit["scratch"]["c_flags"].as_str().map(|flags| flags.to_string())
+    };
+    assert_eq!(c_flags("src/main").as_deref(), Some("-O4,p -inline noauto"));
+    assert_eq!(c_flags("src/util").as_deref(), Some("-O2"));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::Objdiff;
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+
+src/main.c:
+    .text start:0x02000000 end:0x02000080
+
+src/util.c:
+    .text start:0x02000080 end:0x02000100
+";
+
+const OV000_DELINKS: &str = "    .text       start:0x02100000 end:0x02100100 kind:code align:32
+
+src/ov000/a.c:
+    .text start:0x02100000 end:0x02100100
+";
+
+fn generate(path: &PathBuf, unit: &str) -> Result<Vec<String>> {
+    let objdiff = Objdiff {
+        config_path: path.join("config.yaml"),
+        output_path: Some(path.clone()),
+        scratch: false,
+        compiler: None,
+        c_flags: None,
+        preset_id: None,
+        custom_make: None,
+        custom_args: vec![],
+        unit: Some(unit.to_string()),
+    };
+    objdiff.run()?;
+
+    let objdiff_json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path.join("objdiff.json"))?)?;
+    let units = objdiff_json["units"].as_array().expect("no units in objdiff.json");
+    Ok(units.iter().map(|unit| unit["name"].as_str().unwrap().to_string()).collect())
+}
+
+#[test]
+fn test_objdiff_single_unit() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("objdiff_unit");
+    fs::create_dir_all(path.join("overlays/ov000"))?;
+    fs::write(path.join("config.yaml"), CONFIG)?;
+    fs::write(path.join("delinks.txt"), MAIN_DELINKS)?;
+    fs::write(path.join("overlays/ov000/delinks.txt"), OV000_DELINKS)?;
+
+    assert_eq!(generate(&path, "src/util.c")?, vec!["src/util"]);
+    assert_eq!(generate(&path, "src/ov000/a")?, vec!["src/ov000/a"]);
+    assert!(generate(&path, "src/missing.c").is_err());
+
+    Ok(())
+}
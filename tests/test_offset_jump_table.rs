@@ -0,0 +1,247 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::{
+        disassemble::{disassemble_slice, DisassembleSliceOptions},
+        functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult},
+        jump_table::JumpTable,
+    },
+    config::{module::ModuleKind, name_prefixes::NamePrefixes, relocation::Relocations, symbol::SymbolMaps},
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const BYTE_CODE: [u8; 0x20] = [
+    0x02, 0x28, // cmp r0, #0x2
+    0x0b, 0xd8, // bhi default
+    0x78, 0x44, // add r0, pc
+    0x00, 0x79, // ldrb r0, [r0, #0x4]
+    0x40, 0x00, // lsl r0, r0, #0x1
+    0x87, 0x44, // add pc, r0
+    0x01, 0x03, // .byte case 0, case 1
+    0x05, 0x00, // .byte case 2, padding
+    // case 0
+    0x01, 0x20, // mov r0, #0x1
+    0x70, 0x47, // bx lr
+    // case 1
+    0x02, 0x20, // mov r0, #0x2
+    0x70, 0x47, // bx lr
+    // case 2
+    0x03, 0x20, // mov r0, #0x3
+    0x70, 0x47, // bx lr
+    // default
+    0x00, 0x20, // mov r0, #0x0
+    0x70, 0x47, // bx lr
+];
+
+fn parse_function(code: &[u8], thumb: bool) -> Result<Function> {
+    let result = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: BASE_ADDRESS,
+        base_address: BASE_ADDRESS,
+        module_code: code,
+        known_end_address: None,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + code.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: Some(thumb), ..Default::default() },
+    })?;
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function was not found: {result:?}");
+    };
+    assert_eq!(function.end_address(), BASE_ADDRESS + code.len() as u32);
+    Ok(function)
+}
+
+fn disassemble(code: &[u8], thumb: bool) -> Result<String> {
+    let options = DisassembleSliceOptions {
+        module_kind: ModuleKind::Arm9,
+        base_address: BASE_ADDRESS,
+        thumb,
+        endian: Endian::Little,
+        name_prefixes: &NamePrefixes::default(),
+        ual: false,
+    };
+    disassemble_slice(code, &SymbolMaps::new(), &Relocations::new(), &options)
+}
+
+/// Asserts that `function` has a single offset table at `address` with a label for each case.
+fn assert_offset_table(function: &Function, address: u32, size: u32, entry_size: u32, signed: bool, cases: &[u32]) {
+    let jump_tables = function.jump_tables().collect::<Vec<&JumpTable>>();
+    assert_eq!(jump_tables.len(), 1);
+    assert_eq!(jump_tables[0].address, BASE_ADDRESS + address);
+    assert_eq!(jump_tables[0].size, size);
+    assert_eq!(jump_tables[0].entry_size, entry_size);
+    assert_eq!(jump_tables[0].signed, signed);
+    assert!(!jump_tables[0].code);
+
+    for &case_address in cases {
+        assert!(
+            function.labels().any(|&label| label == BASE_ADDRESS + case_address),
+            "no label for case at {case_address:#x}"
+        );
+    }
+}
+
+#[test]
+fn test_byte_offset_jump_table() -> Result<()> {
+    let function = parse_function(&BYTE_CODE, true)?;
+    assert_offset_table(&function, 0xc, 3, 1, false, &[0x10, 0x14, 0x18]);
+    Ok(())
+}
+
+#[test]
+fn test_byte_offset_jump_table_assembly() -> Result<()> {
+    let assembly = disassemble(&BYTE_CODE, true)?;
+
+    assert!(assembly.contains("_0200000c: ; jump table\n"), "{assembly}");
+    assert!(assembly.contains("    .byte (_02000010 - _0200000c - 2) / 2 ; case 0\n"), "{assembly}");
+    assert!(assembly.contains("    .byte (_02000014 - _0200000c - 2) / 2 ; case 1\n"), "{assembly}");
+    assert!(assembly.contains("    .byte (_02000018 - _0200000c - 2) / 2 ; case 2\n    .byte 0x00\n"), "{assembly}");
+
+    Ok(())
+}
+
+// Case 2 comes before the switch, so its offset is negative
+#[rustfmt::skip]
+const SIGNED_BYTE_CODE: [u8; 0x20] = [
+    0x00, 0xe0, // b 0x04
+    // case 2
+    0x70, 0x47, // bx lr
+    0x02, 0x28, // cmp r0, #0x2
+    0x09, 0xd8, // bhi default
+    0x01, 0xa1, // add r1, pc, #0x4
+    0x08, 0x56, // ldrsb r0, [r1, r0]
+    0x40, 0x00, // lsl r0, r0, #0x1
+    0x87, 0x44, // add pc, r0
+    0x01, 0x03, // .byte case 0, case 1
+    0xf8, 0x00, // .byte case 2, padding
+    // case 0
+    0x01, 0x20, // mov r0, #0x1
+    0x70, 0x47, // bx lr
+    // case 1
+    0x02, 0x20, // mov r0, #0x2
+    0x70, 0x47, // bx lr
+    // default
+    0x00, 0x20, // mov r0, #0x0
+    0x70, 0x47, // bx lr
+];
+
+#[test]
+fn test_signed_byte_offset_jump_table() -> Result<()> {
+    let function = parse_function(&SIGNED_BYTE_CODE, true)?;
+    assert_offset_table(&function, 0x10, 3, 1, true, &[0x14, 0x18, 0x02]);
+
+    let assembly = disassemble(&SIGNED_BYTE_CODE, true)?;
+    assert!(assembly.contains("    .byte (_02000002 - _02000010 - 2) / 2 ; case 2\n    .byte 0x00\n"), "{assembly}");
+
+    Ok(())
+}
+
+#[rustfmt::skip]
+const SIGNED_HALFWORD_CODE: [u8; 0x22] = [
+    0x00, 0xe0, // b 0x04
+    // case 2
+    0x70, 0x47, // bx lr
+    0x02, 0x28, // cmp r0, #0x2
+    0x0a, 0xd8, // bhi default
+    0x00, 0x18, // add r0, r0, r0
+    0x01, 0xa1, // add r1, pc, #0x4
+    0x08, 0x5e, // ldrsh r0, [r1, r0]
+    0x87, 0x44, // add pc, r0
+    0x04, 0x00, // .short case 0
+    0x08, 0x00, // .short case 1
+    0xf0, 0xff, // .short case 2
+    // case 0
+    0x01, 0x20, // mov r0, #0x1
+    0x70, 0x47, // bx lr
+    // case 1
+    0x02, 0x20, // mov r0, #0x2
+    0x70, 0x47, // bx lr
+    // default
+    0x00, 0x20, // mov r0, #0x0
+    0x70, 0x47, // bx lr
+];
+
+#[test]
+fn test_signed_halfword_offset_jump_table() -> Result<()> {
+    let function = parse_function(&SIGNED_HALFWORD_CODE, true)?;
+    assert_offset_table(&function, 0x10, 6, 2, true, &[0x16, 0x1a, 0x02]);
+
+    let assembly = disassemble(&SIGNED_HALFWORD_CODE, true)?;
+    assert!(assembly.contains("    .short _02000016 - _02000010 - 2 ; case 0\n"), "{assembly}");
+    assert!(assembly.contains("    .short _02000002 - _02000010 - 2 ; case 2\n"), "{assembly}");
+
+    Ok(())
+}
+
+#[rustfmt::skip]
+const ARM_SIGNED_BYTE_CODE: [u8; 0x38] = [
+    0x00, 0x00, 0x00, 0xea, // b 0x08
+    // case 2
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x02, 0x00, 0x50, 0xe3, // cmp r0, #0x2
+    0x09, 0x00, 0x00, 0x8a, // bhi default
+    0x04, 0x10, 0x8f, 0xe2, // add r1, pc, #0x4
+    0xd0, 0x00, 0x91, 0xe1, // ldrsb r0, [r1, r0]
+    0x00, 0xf1, 0x8f, 0xe0, // add pc, pc, r0, lsl #0x2
+    0x00, 0x02, 0xf9, 0x00, // .byte case 0, case 1, case 2, padding
+    // case 0
+    0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // case 1
+    0x02, 0x00, 0xa0, 0xe3, // mov r0, #0x2
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // default
+    0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+#[test]
+fn test_arm_signed_byte_offset_jump_table() -> Result<()> {
+    let function = parse_function(&ARM_SIGNED_BYTE_CODE, false)?;
+    assert_offset_table(&function, 0x1c, 3, 1, true, &[0x20, 0x28, 0x04]);
+
+    let assembly = disassemble(&ARM_SIGNED_BYTE_CODE, false)?;
+    assert!(assembly.contains("_0200001c: ; jump table\n"), "{assembly}");
+    assert!(assembly.contains("    .byte (_02000020 - _0200001c - 4) / 4 ; case 0\n"), "{assembly}");
+    assert!(assembly.contains("    .byte (_02000004 - _0200001c - 4) / 4 ; case 2\n    .byte 0x00\n"), "{assembly}");
+
+    Ok(())
+}
+
+#[rustfmt::skip]
+const ARM_HALFWORD_CODE: [u8; 0x40] = [
+    0x02, 0x00, 0x50, 0xe3, // cmp r0, #0x2
+    0x0b, 0x00, 0x00, 0x8a, // bhi default
+    0x00, 0x00, 0x80, 0xe0, // add r0, r0, r0
+    0x04, 0x10, 0x8f, 0xe2, // add r1, pc, #0x4
+    0xb0, 0x00, 0x91, 0xe1, // ldrh r0, [r1, r0]
+    0x00, 0xf1, 0x8f, 0xe0, // add pc, pc, r0, lsl #0x2
+    0x01, 0x00, 0x03, 0x00, // .short case 0, case 1
+    0x05, 0x00, 0x00, 0x00, // .short case 2, padding
+    // case 0
+    0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // case 1
+    0x02, 0x00, 0xa0, 0xe3, // mov r0, #0x2
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // case 2
+    0x03, 0x00, 0xa0, 0xe3, // mov r0, #0x3
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // default
+    0x00, 0x00, 0xa0, 0xe3, // mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+#[test]
+fn test_arm_halfword_offset_jump_table() -> Result<()> {
+    let function = parse_function(&ARM_HALFWORD_CODE, false)?;
+    assert_offset_table(&function, 0x18, 6, 2, false, &[0x20, 0x28, 0x30]);
+
+    let assembly = disassemble(&ARM_HALFWORD_CODE, false)?;
+    assert!(assembly.contains("    .short (_02000020 - _02000018 - 4) / 4 ; case 0\n"), "{assembly}");
+    assert!(assembly.contains("    .short (_02000030 - _02000018 - 4) / 4 ; case 2\n    .short 0x0000\n"), "{assembly}");
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::ctor::CtorRange,
+    config::{
+        module::{AnalysisOptions, Module, ModuleKind},
+        overlay_base_addresses::{OverlayBaseAddress, OverlayBaseAddresses},
+        symbol::SymbolMaps,
+    },
+};
+
+const ROM_BASE_ADDRESS: u32 = 0x02100000;
+const PATCHED_BASE_ADDRESS: u32 = 0x02200000;
+
+/// Overlay 0, which was patched to be loaded at 0x02200000
+#[rustfmt::skip]
+const CODE: [u8; 0x20] = [
+    0x00, 0x00, 0x9f, 0xe5, // func_ov000_02200000: ldr r0, [pc, #0x0]
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x14, 0x00, 0x20, 0x02, // .word data_ov000_02200014
+    0x01, 0x00, 0xa0, 0xe3, // func_ov000_0220000c: mov r0, #0x1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    b'H', b'e', b'l', b'l', b'o', 0x00, 0x00, 0x00, // data_ov000_02200014: .asciz "Hello"
+    0x00, 0x00, 0x00, 0x00, // .ctor
+];
+
+fn options(overlay_base_addresses: OverlayBaseAddresses) -> AnalysisOptions {
+    AnalysisOptions { overlay_base_addresses, ..Default::default() }
+}
+
+fn analyze(options: &AnalysisOptions, symbol_maps: &mut SymbolMaps) -> Result<Module<'static>> {
+    let ctor = CtorRange { start: ROM_BASE_ADDRESS + 0x1c, end: ROM_BASE_ADDRESS + 0x20 };
+    Module::analyze_overlay_code(0, &CODE, ROM_BASE_ADDRESS, 0x20, ctor, symbol_maps, options)
+}
+
+#[test]
+fn test_overlay_base_address_override() -> Result<()> {
+    let module_kind = ModuleKind::Overlay(0);
+
+    let overrides = ["0=0x02200000".parse::<OverlayBaseAddress>()?];
+    let mut symbol_maps = SymbolMaps::new();
+    let module = analyze(&options(OverlayBaseAddresses::from_overrides(&overrides)?), &mut symbol_maps)?;
+    assert_eq!(module.base_address(), PATCHED_BASE_ADDRESS);
+    let symbol_map = symbol_maps.get(module_kind).unwrap();
+
+    for address in [PATCHED_BASE_ADDRESS, PATCHED_BASE_ADDRESS + 0xc] {
+        let (_, symbol) = symbol_map.get_function(address)?.expect("function was not found at the patched address");
+        assert_eq!(symbol.name, format!("func_ov000_{address:08x}"));
+    }
+    assert!(symbol_map.get_function(ROM_BASE_ADDRESS)?.is_none());
+
+    // The pointer only resolves to the string at the patched base address
+    let (_, symbol) = symbol_map.get_data(PATCHED_BASE_ADDRESS + 0x14)?.expect("string was not found");
+    assert_eq!(symbol.name, "data_ov000_02200014");
+    let relocations = module.relocations().iter().map(|relocation| (relocation.from_address(), relocation.to_address()));
+    assert_eq!(relocations.collect::<Vec<_>>(), [(PATCHED_BASE_ADDRESS + 0x8, PATCHED_BASE_ADDRESS + 0x14)]);
+
+    let sections = module.sections();
+    assert_eq!(sections.by_name(".ctor").map(|section| section.start_address()), Some(PATCHED_BASE_ADDRESS + 0x1c));
+
+    Ok(())
+}
+
+#[test]
+fn test_overlay_base_address_overlap() -> Result<()> {
+    let modules = [(ModuleKind::Arm9, 0x02000000..0x02180000)];
+    let overlay_size = |id: u16| (id == 0).then_some(0x1000);
+
+    let outside = OverlayBaseAddresses::from_overrides(&["0=0x02200000".parse()?])?;
+    outside.check_overlaps(overlay_size, &modules)?;
+
+    let inside = OverlayBaseAddresses::from_overrides(&["0=0x02170000".parse()?])?;
+    assert!(inside.check_overlaps(overlay_size, &modules).is_err());
+
+    let missing = OverlayBaseAddresses::from_overrides(&["1=0x02200000".parse()?])?;
+    assert!(missing.check_overlaps(overlay_size, &modules).is_err());
+
+    Ok(())
+}
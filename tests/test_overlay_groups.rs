@@ -0,0 +1,134 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::overlay_groups::{OverlayGroups, OverlayRange},
+    config::{
+        module::{AnalysisOptions, Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        overlay_groups::{ExclusiveOverlayGroup, ExclusiveOverlayGroups},
+        program::Program,
+        relocation::{RelocationModule, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::SymbolMaps,
+    },
+};
+
+static MAIN_CODE: [u8; 4] = [0; 4];
+/// Overlay 0 has a pointer to 0x02100200, which is inside both overlay 1 and overlay 2
+static OV000_CODE: [u8; 4] = 0x02100200u32.to_le_bytes();
+static OV001_CODE: [u8; 0x400] = [0; 0x400];
+static OV002_CODE: [u8; 0x300] = [0; 0x300];
+
+fn data_sections(start: u32, end: u32) -> Result<Sections> {
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, start, end, 4)?)?;
+    Ok(sections)
+}
+
+/// Overlays 0 and 1 share the base address 0x02100000, and overlay 2 overlaps the end of overlay 1.
+fn analyze_program(overlay_groups: ExclusiveOverlayGroups) -> Result<Program<'static>> {
+    let name_prefixes = NamePrefixes::default();
+    let mut symbol_maps = SymbolMaps::new();
+
+    let main = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        data_sections(0x02000000, 0x02000004)?,
+        &MAIN_CODE,
+        &name_prefixes,
+    )?;
+    let mut overlays = vec![];
+    for (id, code, base_address) in
+        [(0, &OV000_CODE[..], 0x02100000), (1, &OV001_CODE[..], 0x02100000), (2, &OV002_CODE[..], 0x02100100)]
+    {
+        overlays.push(Module::new_overlay(
+            format!("ov{id:03}"),
+            symbol_maps.get_mut(ModuleKind::Overlay(id)),
+            Relocations::new(),
+            data_sections(base_address, base_address + code.len() as u32)?,
+            id,
+            code,
+            &name_prefixes,
+        )?);
+    }
+
+    let mut program = Program::new(main, overlays, vec![], symbol_maps);
+    program.analyze_cross_references(&AnalysisOptions { overlay_groups, name_prefixes, ..Default::default() })?;
+    Ok(program)
+}
+
+#[test]
+fn test_overlay_group_resolves_relocation() -> Result<()> {
+    let program = analyze_program(ExclusiveOverlayGroups::new())?;
+    let relocation = program.overlays()[0].relocations().get(0x02100000).expect("pointer was not relocated");
+    assert!(matches!(relocation.module(), RelocationModule::Overlays { .. }), "{}", relocation.module());
+
+    let group = "0,1".parse::<ExclusiveOverlayGroup>()?;
+    let program = analyze_program(ExclusiveOverlayGroups::from_groups([&group])?)?;
+    let relocation = program.overlays()[0].relocations().get(0x02100000).expect("pointer was not relocated");
+    assert!(*relocation.module() == RelocationModule::Overlay { id: 2 }, "{}", relocation.module());
+
+    let symbol_maps = program.symbol_maps();
+    let (_, symbol) = symbol_maps.get(ModuleKind::Overlay(2)).unwrap().get_data(0x02100200)?.expect("no symbol in overlay 2");
+    assert!(!symbol.ambiguous);
+    assert!(symbol_maps.get(ModuleKind::Overlay(1)).unwrap().get_data(0x02100200)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_overlay_group() -> Result<()> {
+    let group = "3, 1,2".parse::<ExclusiveOverlayGroup>()?;
+    assert_eq!(group.overlays(), &[1, 2, 3]);
+    assert_eq!(group.to_string(), "1,2,3");
+
+    assert!("1".parse::<ExclusiveOverlayGroup>().is_err());
+    assert!("1,1".parse::<ExclusiveOverlayGroup>().is_err());
+
+    let overlapping = "3,4".parse::<ExclusiveOverlayGroup>()?;
+    assert!(ExclusiveOverlayGroups::from_groups([&group, &overlapping]).is_err());
+
+    let overlay_groups = ExclusiveOverlayGroups::from_groups([&group])?;
+    assert!(overlay_groups.are_exclusive(ModuleKind::Overlay(1), ModuleKind::Overlay(3)));
+    assert!(!overlay_groups.are_exclusive(ModuleKind::Overlay(1), ModuleKind::Overlay(1)));
+    assert!(!overlay_groups.are_exclusive(ModuleKind::Overlay(1), ModuleKind::Overlay(4)));
+    assert!(!overlay_groups.are_exclusive(ModuleKind::Overlay(1), ModuleKind::Arm9));
+    Ok(())
+}
+
+#[test]
+fn test_same_base_overlay_group_placement() -> Result<()> {
+    const MAIN_END: u32 = 0x02100000;
+    let overlays = [
+        OverlayRange { id: 0, base_address: MAIN_END, end_address: 0x02100400 },
+        // Overlays 1 and 2 are loaded at the same fixed address, which is not the end of any other module
+        OverlayRange { id: 1, base_address: 0x02200000, end_address: 0x02200400 },
+        OverlayRange { id: 2, base_address: 0x02200000, end_address: 0x02200300 },
+        OverlayRange { id: 3, base_address: 0x02200400, end_address: 0x02200500 },
+    ];
+
+    // Without a group, overlays 1 and 2 can't be placed after anything
+    assert!(OverlayGroups::analyze_ranges(MAIN_END, &overlays, &ExclusiveOverlayGroups::new()).is_err());
+
+    let group = "1,2".parse::<ExclusiveOverlayGroup>()?;
+    let overlay_groups = OverlayGroups::analyze_ranges(MAIN_END, &overlays, &ExclusiveOverlayGroups::from_groups([&group])?)?;
+    let groups = overlay_groups.iter().collect::<Vec<_>>();
+    assert_eq!(groups.len(), 3);
+
+    assert_eq!(groups[0].overlays, [0]);
+    assert_eq!(groups[0].origin, None);
+    assert!(groups[0].after.is_empty());
+
+    // The same-base group is placed at its base address in the LCF
+    assert_eq!(groups[1].overlays, [1, 2]);
+    assert_eq!(groups[1].origin, Some(0x02200000));
+    assert_eq!((groups[1].start_address, groups[1].end_address), (0x02200000, 0x02200400));
+
+    // Overlays after the group still follow the overlays they come after
+    assert_eq!(groups[2].overlays, [3]);
+    assert_eq!(groups[2].origin, None);
+    assert_eq!(groups[2].after, [1, 2]);
+    assert_eq!((groups[2].start_address, groups[2].end_address), (0x02200400, 0x02200500));
+
+    Ok(())
+}
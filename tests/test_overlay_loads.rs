@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use ds_decomp::analysis::{
+    functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult},
+    overlay_loads::{find_overlay_loads, OverlayLoad, OverlayLoadKind},
+};
+use unarm::Endian;
+
+#[test]
+fn test_overlay_load_with_literal_id() -> Result<()> {
+    let base_address = 0x02000000;
+    let loader_address = 0x02000100;
+    #[rustfmt::skip]
+    let mut code = vec![
+        0x08, 0x40, 0x2d, 0xe9, // push {r3, lr}
+        0x00, 0x00, 0xa0, 0xe3, // mov r0, #0
+        0x05, 0x10, 0xa0, 0xe3, // mov r1, #5
+        0x3b, 0x00, 0x00, 0xeb, // bl FS_LoadOverlay
+        0x08, 0x80, 0xbd, 0xe8, // pop {r3, pc}
+    ];
+    code.resize(0x104, 0);
+
+    let result = Function::parse_function(FunctionParseOptions {
+        name: "func_02000000".to_string(),
+        start_address: base_address,
+        base_address,
+        module_code: &code,
+        known_end_address: Some(base_address + 0x14),
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        parse_options: ParseFunctionOptions::default(),
+    })?;
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function was not found: {result:?}");
+    };
+
+    let loaders = BTreeMap::from([(loader_address, OverlayLoadKind::Load)]);
+    let loads = find_overlay_loads(&function, &code, base_address, Endian::Little, &loaders);
+    assert_eq!(loads, vec![OverlayLoad { address: base_address + 0xc, kind: OverlayLoadKind::Load, overlay_id: Some(5) }]);
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::{ffi::OsStr, fs};
+
+use anyhow::Result;
+use ds_decomp::cmd::Extract;
+use ds_rom::rom::{raw, Rom};
+
+#[test]
+fn test_overlay_table_json() -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let roms_dir = cwd.join("tests/roms/");
+
+    for entry in roms_dir.read_dir()? {
+        let path = entry?.path();
+        if path.extension() != Some(OsStr::new("nds")) {
+            continue;
+        }
+
+        let raw_rom = raw::Rom::from_file(&path)?;
+        let rom = Rom::extract(&raw_rom)?;
+
+        let base_name = path.with_extension("").file_name().unwrap().to_str().unwrap().to_string();
+        let output_path = roms_dir.join(base_name).join("overlay_table");
+        let json_path = output_path.join("overlays.json");
+        fs::create_dir_all(&output_path)?;
+        let extract = Extract {
+            rom: path.clone(),
+            arm7_bios: None,
+            output_path: output_path.clone(),
+            arm9: true,
+            arm7: false,
+            overlay: None,
+            autoload: None,
+            overlay_table_json: Some(json_path.clone()),
+        };
+        extract.run()?;
+
+        let table: serde_json::Value = serde_json::from_str(&fs::read_to_string(&json_path)?)?;
+        let entries = table.as_array().unwrap();
+        assert_eq!(entries.len(), rom.arm9_overlays().len());
+
+        for (entry, overlay) in entries.iter().zip(rom.arm9_overlays()).take(2) {
+            let info = overlay.info();
+            assert_eq!(entry["id"], overlay.id());
+            assert_eq!(entry["base_address"], info.base_address);
+            assert_eq!(entry["code_size"], info.code_size);
+            assert_eq!(entry["bss_size"], info.bss_size);
+            assert_eq!(entry["ctor_start"], info.ctor_start);
+            assert_eq!(entry["ctor_end"], info.ctor_end);
+            assert_eq!(entry["file_id"], info.file_id);
+            assert_eq!(entry["compressed"], overlay.originally_compressed());
+            assert_eq!(entry["signed"], overlay.is_signed());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Delink,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationSources, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, SymData, SymLabel, Symbol, SymbolKind, SymbolMaps},
+    },
+};
+use object::{Object, ObjectSection, ObjectSymbol, RelocationTarget};
+
+#[test]
+fn test_pointer_data_is_relocated() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        0x0c, 0x00, 0x00, 0x02, // gPointers: pointer to gValue
+        0x00, 0x00, 0x10, 0x02, // pointer to func_02100000 in overlay 0
+        0x00, 0x00, 0x30, 0x02, // gUnknown: pointer to nothing
+        0x2a, 0x00, 0x00, 0x00, // gValue
+    ];
+
+    let mut symbol_maps = SymbolMaps::new();
+    symbol_maps.get_mut(ModuleKind::Overlay(0)).add(Symbol::new_function(
+        "func_02100000".to_string(),
+        0x02100000,
+        InstructionMode::Arm,
+        4,
+    ));
+    // The first symbol at an address with several symbols is the target
+    symbol_maps.get_mut(ModuleKind::Overlay(0)).add(Symbol {
+        name: "_02100000".to_string(),
+        kind: SymbolKind::Label(SymLabel { external: true, mode: InstructionMode::Arm }),
+        addr: 0x02100000,
+        ambiguous: false,
+        local: false,
+    });
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_data("gPointers".to_string(), 0x02000000, SymData::Pointer { count: Some(2) }, false));
+    symbol_map.add(Symbol::new_data("gUnknown".to_string(), 0x02000008, SymData::Pointer { count: Some(1) }, false));
+    symbol_map.add(Symbol::new_data("gValue".to_string(), 0x0200000c, SymData::Word { count: Some(1) }, false));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000010, 4)?)?;
+
+    // No relocations were found by analysis
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &code,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000010, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let bytes = Delink::delink(&symbol_maps, &RelocationSources::new(), &module, &delink_file)?.write()?;
+    let object = object::File::parse(bytes.as_slice())?;
+
+    let section = object.section_by_name(".data").unwrap();
+    let relocations = section
+        .relocations()
+        .map(|(offset, relocation)| {
+            let RelocationTarget::Symbol(index) = relocation.target() else { panic!("relocation to a non-symbol") };
+            let symbol = object.symbol_by_index(index).unwrap();
+            (offset, symbol.name().unwrap().to_string(), symbol.is_undefined())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        relocations,
+        vec![
+            (0x0, "gValue".to_string(), false),
+            (0x4, "func_02100000".to_string(), true),
+            (0x8, "data_02300000".to_string(), true),
+        ]
+    );
+
+    // Undefined targets must be resolved by the linker, whether a symbol was found for them or not
+    for name in ["func_02100000", "data_02300000"] {
+        let symbol = object.symbols().find(|symbol| symbol.name() == Ok(name)).expect(name);
+        assert!(symbol.is_global(), "{name} should be global");
+        assert!(!symbol.is_weak(), "{name} should not be weak");
+    }
+
+    // Relocated words are cleared, the rest is kept
+    assert_eq!(section.data()?, &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x2a, 0, 0, 0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_thumb_function_pointer_is_local() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        0x00, 0x20, // func_02000000: movs r0, #0x0
+        0x70, 0x47, // bx lr
+        0x01, 0x00, 0x00, 0x02, // gCallback: pointer to func_02000000 with the Thumb bit set
+    ];
+
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Thumb, 4));
+    symbol_map.add(Symbol::new_data("gCallback".to_string(), 0x02000004, SymData::Pointer { count: Some(1) }, false));
+
+    let sections = || -> Result<Sections> {
+        let mut sections = Sections::new();
+        sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000004, 4)?)?;
+        sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000004, 0x02000008, 4)?)?;
+        Ok(sections)
+    };
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections()?,
+        &code,
+        &NamePrefixes::default(),
+    )?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), sections()?, false);
+
+    let bytes = Delink::delink(&symbol_maps, &RelocationSources::new(), &module, &delink_file)?.write()?;
+    let object = object::File::parse(bytes.as_slice())?;
+
+    // The pointer is relocated to the function defined in this file, not to an undefined duplicate of it
+    let (_, relocation) = object.section_by_name(".data").unwrap().relocations().next().expect("pointer was not relocated");
+    let RelocationTarget::Symbol(index) = relocation.target() else { panic!("relocation to a non-symbol") };
+    let symbol = object.symbol_by_index(index)?;
+    assert_eq!(symbol.name()?, "func_02000000");
+    assert!(!symbol.is_undefined());
+    assert_eq!(object.symbols().filter(|symbol| symbol.name() == Ok("func_02000000")).count(), 1);
+
+    Ok(())
+}
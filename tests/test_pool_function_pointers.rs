@@ -0,0 +1,64 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::data::{self, FindLocalDataOptions},
+    config::{
+        module::{AnalysisOptions, Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{InstructionMode, Symbol, SymbolMaps},
+    },
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x14] = [
+    0x00, 0x00, 0x9f, 0xe5, // func_02000000: ldr r0, [pc, #0x0]
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x0c, 0x00, 0x00, 0x02, // .word 0x0200000c
+    0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1, never called
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+#[test]
+fn test_pool_function_pointers() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 0xc));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000014, 4)?)?;
+    let mut module =
+        Module::new_arm9("main".to_string(), symbol_map, Relocations::new(), sections, &CODE, &NamePrefixes::default())?;
+
+    let options = AnalysisOptions { find_pool_function_pointers: true, ..Default::default() };
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    module.find_pool_function_pointers(symbol_map, &options)?;
+
+    assert!(module.get_function(0x0200000c).is_some(), "pointed-to function was not added to the module");
+    let (function, symbol) = symbol_map.get_function(0x0200000c)?.expect("pointed-to function has no symbol");
+    assert_eq!(symbol.name, "func_0200000c");
+    assert_eq!(function.size, 8);
+
+    // The pool constant is now relocated like a pointer to any known function
+    let mut relocations = Relocations::new();
+    for function in module.sections().functions() {
+        data::find_local_data_from_pools(
+            function,
+            FindLocalDataOptions {
+                sections: module.sections(),
+                module_kind: ModuleKind::Arm9,
+                symbol_map,
+                relocations: &mut relocations,
+                name_prefix: "data_",
+                code: module.code(),
+                base_address: module.base_address(),
+                address_range: None,
+            },
+            &options,
+        )?;
+    }
+    let relocations = relocations.iter().map(|relocation| (relocation.from_address(), relocation.to_address()));
+    assert_eq!(relocations.collect::<Vec<_>>(), [(0x02000008, 0x0200000c)]);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::symbol::{InstructionMode, Symbol, SymbolKind, SymbolMap},
+};
+use unarm::Endian;
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+#[rustfmt::skip]
+const CODE: [u8; 0xc] = [
+    0x00, 0xf0, 0x00, 0x02, // .word 0x0200f000, looks like a Thumb bl
+    0x0c, 0x00, 0x1f, 0xe5, // ldr r0, [pc, #-0xc]
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+fn find_functions(symbol_map: &mut SymbolMap) -> Result<BTreeMap<u32, Function>> {
+    Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address: BASE_ADDRESS,
+        module_code: &CODE,
+        symbol_map,
+        module_start_address: BASE_ADDRESS,
+        module_end_address: BASE_ADDRESS + CODE.len() as u32,
+        search_options: FunctionSearchOptions::default(),
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })
+}
+
+#[test]
+fn test_function_starting_with_pool() -> Result<()> {
+    let mut symbol_map = SymbolMap::new();
+    let functions = find_functions(&mut symbol_map)?;
+
+    // Parsing starts on the pool, but the function is found after it and includes it
+    assert_eq!(functions.len(), 1);
+    let function = functions.values().next().unwrap();
+    assert_eq!(function.start_address(), BASE_ADDRESS);
+    assert_eq!(function.first_instruction_address(), BASE_ADDRESS + 0x4);
+    assert_eq!(function.end_address(), BASE_ADDRESS + CODE.len() as u32);
+    assert!(function.pool_constants().contains(&BASE_ADDRESS));
+    assert!(!function.is_thumb());
+    assert_eq!(function.name(), "func_02000000");
+
+    Ok(())
+}
+
+#[test]
+fn test_function_starting_with_named_pool() -> Result<()> {
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_function("GetPoolValue".to_string(), BASE_ADDRESS, InstructionMode::Arm, CODE.len() as u32));
+    let functions = find_functions(&mut symbol_map)?;
+
+    // The function keeps the name of the symbol at the pool, and no other function is added there
+    assert_eq!(functions.len(), 1);
+    let function = functions.values().next().unwrap();
+    assert_eq!(function.start_address(), BASE_ADDRESS);
+    assert_eq!(function.name(), "GetPoolValue");
+    let functions_at_pool = symbol_map
+        .for_address(BASE_ADDRESS)
+        .into_iter()
+        .flatten()
+        .filter(|(_, symbol)| matches!(symbol.kind, SymbolKind::Function(_)))
+        .count();
+    assert_eq!(functions_at_pool, 1);
+
+    Ok(())
+}
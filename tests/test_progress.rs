@@ -0,0 +1,24 @@
+use anyhow::Result;
+use ds_decomp::util::progress::Progress;
+
+#[test]
+fn test_progress_lines() -> Result<()> {
+    let mut progress = Progress::new(vec![], 3, false);
+    for _ in 0..3 {
+        progress.advance()?;
+    }
+    let output = String::from_utf8(progress.into_writer().unwrap())?;
+    assert_eq!(output, "1/3 modules\n2/3 modules\n3/3 modules\n");
+    Ok(())
+}
+
+#[test]
+fn test_progress_bar() -> Result<()> {
+    let mut progress = Progress::new(vec![], 2, true);
+    progress.advance()?;
+    progress.advance()?;
+    let output = String::from_utf8(progress.into_writer().unwrap())?;
+    let last_line = output.rsplit('\r').next().unwrap();
+    assert_eq!(last_line, "[##############################] 100% 2/2 modules\n");
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::config::{
+    module::ModuleKind,
+    relocation::Relocations,
+    symbol::{SymData, Symbol, SymbolMaps},
+};
+
+fn write_relocs(name: &str, relocs: &str) -> Result<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("relocation_names");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(name);
+    fs::write(&path, relocs)?;
+    Ok(path)
+}
+
+fn overlay_symbols(symbols: &[(u16, &str, u32)]) -> SymbolMaps {
+    let mut symbol_maps = SymbolMaps::new();
+    for &(id, name, address) in symbols {
+        symbol_maps.get_mut(ModuleKind::Overlay(id)).add(Symbol::new_data(name.to_string(), address, SymData::Any, false));
+    }
+    symbol_maps
+}
+
+#[test]
+fn test_relocation_names_round_trip() -> Result<()> {
+    let symbol_maps = overlay_symbols(&[(1, "gShared", 0x02100000), (2, "gShared", 0x02100000)]);
+    let relocs = "from:0x02000000 kind:load to:gShared module:overlays(1,2)\n";
+    let path = write_relocs("round_trip.txt", relocs)?;
+
+    let relocations = Relocations::from_file(&path, &symbol_maps)?;
+    assert_eq!(relocations.get(0x02000000).unwrap().to_address(), 0x02100000);
+
+    // The name is written back instead of the resolved address
+    relocations.to_file(&path)?;
+    assert_eq!(fs::read_to_string(&path)?, relocs);
+
+    Ok(())
+}
+
+#[test]
+fn test_relocation_names_resolve_every_overlay() -> Result<()> {
+    let missing = overlay_symbols(&[(1, "gShared", 0x02100000)]);
+    let path = write_relocs("missing.txt", "from:0x02000000 kind:load to:gShared module:overlays(1,2)\n")?;
+    assert!(Relocations::from_file(&path, &missing).is_err());
+
+    let moved = overlay_symbols(&[(1, "gShared", 0x02100000), (2, "gShared", 0x02100040)]);
+    let path = write_relocs("moved.txt", "from:0x02000000 kind:load to:gShared module:overlays(1,2)\n")?;
+    assert!(Relocations::from_file(&path, &moved).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use ds_decomp::{
+    cmd::ModuleLayout,
+    config::section::{Section, SectionKind, Sections},
+};
+
+const BASE_ADDRESS: u32 = 0x02100000;
+
+/// Sections of an overlay with base address 0x02100000, code size 0x120, BSS size 0x60 and .ctor at 0x02100100..0x02100108
+fn overlay_sections(text_end: u32) -> Result<Sections> {
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, BASE_ADDRESS, text_end, 32)?)?;
+    sections.add(Section::new(".rodata".to_string(), SectionKind::Data, text_end, BASE_ADDRESS + 0x100, 4)?)?;
+    sections.add(Section::new(".ctor".to_string(), SectionKind::Data, BASE_ADDRESS + 0x100, BASE_ADDRESS + 0x108, 4)?)?;
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, BASE_ADDRESS + 0x108, BASE_ADDRESS + 0x120, 4)?)?;
+    sections.add(Section::new(".bss".to_string(), SectionKind::Bss, BASE_ADDRESS + 0x120, BASE_ADDRESS + 0x180, 32)?)?;
+    Ok(sections)
+}
+
+#[test]
+fn test_module_layout_from_sections() -> Result<()> {
+    let layout = ModuleLayout::new(&overlay_sections(BASE_ADDRESS + 0xc0)?, "OV000", None)?;
+    assert_eq!(
+        layout,
+        ModuleLayout {
+            code: BASE_ADDRESS..BASE_ADDRESS + 0x120,
+            bss: BASE_ADDRESS + 0x120..BASE_ADDRESS + 0x180,
+            ctor: Some(BASE_ADDRESS + 0x100..BASE_ADDRESS + 0x108),
+        }
+    );
+    assert_eq!(layout.base_address(), BASE_ADDRESS);
+    assert_eq!(layout.code_size(), 0x120);
+    assert_eq!(layout.bss_size(), 0x60);
+
+    // Moving a boundary between sections by hand keeps the module layout
+    let edited = ModuleLayout::new(&overlay_sections(BASE_ADDRESS + 0xe0)?, "OV000", None)?;
+    assert_eq!(edited, layout);
+
+    Ok(())
+}
+
+#[test]
+fn test_module_layout_without_bss() -> Result<()> {
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x01ff8000, 0x01ff8400, 32)?)?;
+    let layout = ModuleLayout::new(&sections, "ITCM", None)?;
+    assert_eq!(layout.bss, 0x01ff8400..0x01ff8400);
+    assert_eq!(layout.bss_size(), 0);
+    assert_eq!(layout.ctor, None);
+
+    assert!(ModuleLayout::new(&Sections::new(), "ITCM", None).is_err());
+    Ok(())
+}
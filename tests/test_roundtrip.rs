@@ -11,9 +11,12 @@ use std::{
 use anyhow::Result;
 use ds_decomp::{
     analysis::data::AddFunctionCallAsRelocationsError,
-    cmd::{CheckModules, ConfigRom, Delink, Disassemble, Init, Lcf},
-    config::config::Config,
-    util::io::{open_file, read_to_string},
+    cmd::{CheckModules, ConfigRom, Delink, Disassemble, Init, Lcf, LcfFormat},
+    config::{config::Config, name_prefixes::NamePrefixes, relocation::RelocCommentTemplate},
+    util::{
+        address::AddressFormat,
+        io::{open_file, read_to_string},
+    },
 };
 use ds_rom::{
     crypto::blowfish::BlowfishKey,
@@ -77,18 +80,35 @@ fn test_roundtrip() -> Result<()> {
         assert!(directory_equals(&target_config_dir, &dsd_config_dir)?);
 
         // Disassemble
-        let disassemble = Disassemble { config_path: dsd_config_yaml.clone(), asm_path: project_path.join("asm"), ual: false };
+        let disassemble = Disassemble {
+            config_path: dsd_config_yaml.clone(),
+            asm_path: project_path.join("asm"),
+            ual: false,
+            module: None,
+            start: None,
+            end: None,
+            jobs: 1,
+            incbin_threshold: None,
+            encoding: None,
+            single_file: false,
+            address_format: AddressFormat::default(),
+        };
         disassemble.run()?;
 
         // Delink modules
-        let delink = Delink { config_path: dsd_config_yaml.clone() };
+        let delink = Delink { config_path: dsd_config_yaml.clone(), jobs: 1, incremental: false };
         delink.run()?;
 
         // Generate LCF
         let build_path = dsd_config_yaml.parent().unwrap().join(dsd_config.build_path);
         let lcf_file = build_path.join("linker_script.lcf");
         let objects_file = build_path.join("objects.txt");
-        let lcf = Lcf { config_path: dsd_config_yaml.clone(), lcf_file: lcf_file.clone(), objects_file: objects_file.clone() };
+        let lcf = Lcf {
+            config_path: dsd_config_yaml.clone(),
+            lcf_file: lcf_file.clone(),
+            objects_file: objects_file.clone(),
+            format: LcfFormat::Mwld,
+        };
         lcf.run()?;
 
         // Run linker
@@ -123,11 +143,18 @@ fn test_roundtrip() -> Result<()> {
         assert!(linker_output.status.success());
 
         // Check modules
-        let check_modules = CheckModules { config_path: dsd_config_yaml.clone(), fail: true };
+        let check_modules = CheckModules {
+            config_path: dsd_config_yaml.clone(),
+            fail: true,
+            color: false,
+            watch: false,
+            fix_alignment: false,
+        };
         check_modules.run()?;
 
         // Configure ds-rom
-        let config_rom = ConfigRom { elf: linker_out_file.clone(), config: dsd_config_yaml.clone() };
+        let config_rom =
+            ConfigRom { elf: Some(linker_out_file.clone()), from_project: false, config: dsd_config_yaml.clone() };
         config_rom.run()?;
 
         fs::remove_dir_all(project_path)?;
@@ -144,9 +171,27 @@ fn dsd_init(project_path: &Path, rom_config: &Path, allow_unknown_function_calls
         output_path: dsd_config_dir.clone(),
         dry: false,
         build_path,
+        function_overrides: None,
+        data_regions: vec![],
+        handwritten_code: vec![],
+        instruction_modes: vec![],
+        no_epilogue_fallbacks: vec![],
+        overlay_groups: vec![],
+        overlay_base_addresses: vec![],
+        small_data_sections: vec![],
+        name_prefixes: NamePrefixes::default(),
         skip_reloc_analysis: false,
         allow_unknown_function_calls,
-        provide_reloc_source: false,
+        find_vtables: false,
+        find_rtti: false,
+        find_pool_function_pointers: false,
+        min_function_size: 0,
+        check_defs_uses: false,
+        relocate_function_branches: false,
+        no_secure_area: false,
+        arm9_bios_calls: false,
+        annotate_relocs: false,
+        reloc_comment: RelocCommentTemplate::default(),
     };
     init.run()?;
     Ok(dsd_config_dir)
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use ds_decomp::config::{
+    module::{AnalysisOptions, Module, ModuleKind},
+    name_prefixes::NamePrefixes,
+    relocation::Relocations,
+    section::{Section, SectionKind, Sections},
+    symbol::{InstructionMode, SymData, Symbol, SymbolMaps},
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x40] = [
+    0x00, 0x00, 0xa0, 0xe3, // func_02000000: mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x20, 0x00, 0x00, 0x02, // data_02000008: .word 0x02000020
+    0x00, 0x00, 0x00, 0x00, // .word 0x0
+    0x28, 0x00, 0x00, 0x02, // data_02000010: .word 0x02000028
+    0x18, 0x00, 0x00, 0x02, // .word 0x02000018
+    0x08, 0x00, 0x00, 0x02, // .word 0x02000008
+    0x00, 0x00, 0x00, 0x00, // .word 0x0
+    b'4', b'B', b'a', b's', b'e', 0x00, 0x00, 0x00,
+    b'Q', b'2', b'3', b'F', b'o', b'o', b'7', b'D', b'e', b'r', b'i', b'v', b'e', b'd', 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+];
+
+#[test]
+fn test_rtti() -> Result<()> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 8));
+    for address in [0x02000008, 0x02000010] {
+        symbol_map.add_data(Some(format!("data_{address:08x}")), address, SymData::Any)?;
+    }
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000008, 4)?)?;
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000008, 0x02000040, 4)?)?;
+    let mut module =
+        Module::new_arm9("main".to_string(), symbol_map, Relocations::new(), sections, &CODE, &NamePrefixes::default())?;
+
+    let options = AnalysisOptions { find_rtti: true, ..Default::default() };
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    module.find_type_info(symbol_map, &options)?;
+
+    let relocations = module.relocations().iter().map(|relocation| (relocation.from_address(), relocation.to_address()));
+    assert_eq!(
+        relocations.collect::<Vec<_>>(),
+        [(0x02000008, 0x02000020), (0x02000010, 0x02000028), (0x02000014, 0x02000018)]
+    );
+
+    let (_, symbol) = symbol_map.by_address(0x02000008)?.unwrap();
+    assert_eq!(symbol.name, "typeinfo_Base");
+    let (_, symbol) = symbol_map.by_address(0x02000010)?.unwrap();
+    assert_eq!(symbol.name, "typeinfo_Foo_Derived");
+    // The base class list has no symbol, so it is not mistaken for type info
+    assert!(symbol_map.by_address(0x02000018)?.is_none());
+
+    let (data, symbol) = symbol_map.get_data(0x02000020)?.expect("type name symbol was not added");
+    assert_eq!(symbol.name, "typename_Base");
+    assert!(*data == SymData::Byte { count: Some(6) });
+    let (data, symbol) = symbol_map.get_data(0x02000028)?.expect("type name symbol was not added");
+    assert_eq!(symbol.name, "typename_Foo_Derived");
+    assert!(*data == SymData::Byte { count: Some(15) });
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use ds_decomp::config::{
+    config::Config,
+    module::{AnalysisOptions, Module, SECURE_AREA_SIZE},
+    name_prefixes::NamePrefixes,
+    relocation::Relocations,
+    section::{Section, SectionKind, Sections},
+    symbol::SymbolMap,
+};
+
+const BASE_ADDRESS: u32 = 0x02000000;
+
+/// Finds entry functions in code which has a function at the start and another one right after the secure area.
+fn find_entry_functions(has_secure_area: bool) -> Result<Vec<u32>> {
+    let mut code = vec![0; SECURE_AREA_SIZE as usize + 0x10];
+    code[0x0..0x4].copy_from_slice(&0xe3a00000u32.to_le_bytes()); // mov r0, #0x0
+    code[0x4..0x8].copy_from_slice(&0xe12fff1eu32.to_le_bytes()); // bx lr
+    code[0x800..0x804].copy_from_slice(&0xe12fff1eu32.to_le_bytes()); // bx lr
+    let (functions, _) = analyze(code, has_secure_area, false)?;
+    Ok(functions)
+}
+
+/// Finds entry functions in `code` and returns the addresses of the functions which were found, along with the symbols.
+fn analyze(code: Vec<u8>, has_secure_area: bool, arm9_bios_calls: bool) -> Result<(Vec<u32>, SymbolMap)> {
+    let end_address = BASE_ADDRESS + code.len() as u32;
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, BASE_ADDRESS, end_address, 4)?)?;
+    let mut symbol_map = SymbolMap::new();
+    let mut module =
+        Module::new_arm9("main".to_string(), &mut symbol_map, Relocations::new(), sections, &code, &NamePrefixes::default())?;
+
+    let options = AnalysisOptions { has_secure_area, arm9_bios_calls, ..Default::default() };
+    let functions = module.find_entry_functions(&mut symbol_map, BASE_ADDRESS + 0x804, &options)?;
+    let addresses = functions.keys().copied().collect();
+    Ok((addresses, symbol_map))
+}
+
+#[test]
+fn test_secure_area() -> Result<()> {
+    // The first 0x800 bytes are skipped, and only software interrupt functions would be found there
+    assert_eq!(find_entry_functions(true)?, [BASE_ADDRESS + SECURE_AREA_SIZE]);
+
+    Ok(())
+}
+
+#[test]
+fn test_no_secure_area() -> Result<()> {
+    let functions = find_entry_functions(false)?;
+    assert_eq!(functions.first(), Some(&BASE_ADDRESS), "function at the start of the module was not found");
+
+    Ok(())
+}
+
+#[test]
+fn test_secure_area_config() -> Result<()> {
+    const CONFIG: &str = "\
+version: 2
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays: []
+";
+
+    // Retail ROMs have a secure area, which is left out of the config
+    let mut config: Config = serde_yml::from_str(CONFIG)?;
+    assert!(config.has_secure_area);
+    assert!(!serde_yml::to_string(&config)?.contains("has_secure_area"));
+
+    config.has_secure_area = false;
+    let yaml = serde_yml::to_string(&config)?;
+    assert!(yaml.contains("\nhas_secure_area: false\n"), "{yaml}");
+    let config: Config = serde_yml::from_str(&yaml)?;
+    assert!(!config.has_secure_area);
+
+    Ok(())
+}
+
+#[test]
+fn test_arm9_bios_calls() -> Result<()> {
+    let mut code = vec![0; SECURE_AREA_SIZE as usize + 0x10];
+    code[0x0..0x2].copy_from_slice(&0xdf09u16.to_le_bytes()); // swi #0x9
+    code[0x2..0x4].copy_from_slice(&0x4770u16.to_le_bytes()); // bx lr
+    code[0x4..0x6].copy_from_slice(&0xdf03u16.to_le_bytes()); // swi #0x3
+    code[0x6..0x8].copy_from_slice(&0x4770u16.to_le_bytes()); // bx lr
+    code[0x800..0x804].copy_from_slice(&0xe12fff1eu32.to_le_bytes()); // bx lr
+
+    let (_, symbol_map) = analyze(code.clone(), true, true)?;
+    assert_eq!(symbol_map.by_address(BASE_ADDRESS)?.unwrap().1.name, "SVC_Div");
+    assert_eq!(symbol_map.by_address(BASE_ADDRESS + 0x4)?.unwrap().1.name, "SVC_WaitByLoop");
+
+    let (_, symbol_map) = analyze(code, true, false)?;
+    assert_eq!(symbol_map.by_address(BASE_ADDRESS)?.unwrap().1.name, "Div");
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use ds_decomp::config::{
+    module::{Module, ModuleKind},
+    name_prefixes::NamePrefixes,
+    relocation::Relocations,
+    section::{Section, SectionKind, Sections},
+    small_data::SmallDataSection,
+    symbol::{SymBss, SymData, Symbol, SymbolMap},
+};
+
+const BASE_ADDRESS: u32 = 0x02100000;
+
+fn section_names(sections: &Sections) -> Vec<(&str, u32, u32)> {
+    sections.sorted_by_address().into_iter().map(|s| (s.name(), s.start_address(), s.end_address())).collect()
+}
+
+#[test]
+fn test_small_data_sections() -> Result<()> {
+    let sdata = SmallDataSection::from_str("overlay:1:.sdata:0x021000c0:0x02100100")?;
+    let sbss = SmallDataSection::from_str("overlay:1:.sbss:0x02100100:0x02100120")?;
+
+    let mut sections = Sections::new();
+    let data = Section::new(".data".to_string(), SectionKind::Data, BASE_ADDRESS, BASE_ADDRESS + 0x100, 32)?;
+    sections.add_with_small_data(data, &sdata)?;
+    let bss = Section::new(".bss".to_string(), SectionKind::Bss, BASE_ADDRESS + 0x100, BASE_ADDRESS + 0x200, 32)?;
+    sections.add_with_small_data(bss, &sbss)?;
+    assert_eq!(
+        section_names(&sections),
+        vec![
+            (".data", 0x02100000, 0x021000c0),
+            (".sdata", 0x021000c0, 0x02100100),
+            (".sbss", 0x02100100, 0x02100120),
+            (".bss", 0x02100120, 0x02100200),
+        ]
+    );
+
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_data("data_ov001_02100010".to_string(), 0x02100010, SymData::Any, false));
+    symbol_map.add(Symbol::new_data("gSmallData".to_string(), 0x021000c4, SymData::Any, false));
+    symbol_map.add(Symbol::new_bss("gSmallBss".to_string(), 0x02100100, SymBss { size: Some(4) }, false));
+    symbol_map.add(Symbol::new_bss("data_ov001_02100180".to_string(), 0x02100180, SymBss { size: Some(4) }, false));
+
+    let code = [0u8; 0x100];
+    let module = Module::new_overlay(
+        "ov001".to_string(),
+        &mut symbol_map,
+        Relocations::new(),
+        sections,
+        1,
+        &code,
+        &NamePrefixes::default(),
+    )?;
+    let symbol_sections = symbol_map
+        .iter()
+        .map(|symbol| {
+            let (_, section) = module.sections().get_by_contained_address(symbol.addr).unwrap();
+            (symbol.name.as_str(), section.name())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        symbol_sections,
+        vec![
+            ("data_ov001_02100010", ".data"),
+            ("gSmallData", ".sdata"),
+            ("gSmallBss", ".sbss"),
+            ("data_ov001_02100180", ".bss"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_small_data_must_be_at_section_edge() -> Result<()> {
+    let sdata = SmallDataSection::from_str("main:.sdata:0x02000040:0x02000080")?;
+    let data = Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000100, 32)?;
+    assert!(Sections::new().add_with_small_data(data, &sdata).is_err());
+
+    let outside = SmallDataSection::from_str("main:.sdata:0x020000c0:0x02000140")?;
+    let data = Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000100, 32)?;
+    assert!(Sections::new().add_with_small_data(data, &outside).is_err());
+
+    let bss = Section::new(".bss".to_string(), SectionKind::Bss, 0x02000000, 0x02000100, 32)?;
+    assert!(Sections::new().add_with_small_data(bss, &sdata).is_err(), ".sdata can't be split from .bss");
+    Ok(())
+}
+
+#[test]
+fn test_parse_small_data_section() -> Result<()> {
+    let section = SmallDataSection::from_str("overlay:5:.sbss:0x020a0000:0x020a0100")?;
+    assert_eq!(
+        section,
+        SmallDataSection { module: ModuleKind::Overlay(5), kind: SectionKind::Bss, start: 0x020a0000, end: 0x020a0100 }
+    );
+    assert_eq!(section.to_string(), "overlay:5:.sbss:0x020a0000:0x020a0100");
+
+    assert!(SmallDataSection::from_str("main:.data:0x02000000:0x02000010").is_err());
+    assert!(SmallDataSection::from_str("main:.sdata:0x02000010:0x02000010").is_err());
+
+    let duplicate = SmallDataSection::from_str("overlay:5:.sbss:0x020a0200:0x020a0300")?;
+    assert!(SmallDataSection::check_unique(&[section, duplicate]).is_err());
+    Ok(())
+}
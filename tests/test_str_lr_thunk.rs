@@ -0,0 +1,54 @@
+use anyhow::Result;
+use ds_decomp::{
+    analysis::functions::{FindFunctionsOptions, Function, FunctionSearchOptions},
+    config::symbol::SymbolMap,
+};
+use unarm::Endian;
+
+#[test]
+fn test_str_lr_prologue() -> Result<()> {
+    let base_address = 0x02000000;
+    let thunk_address = 0x0200000c;
+    #[rustfmt::skip]
+    let code = [
+        0x00, 0x00, 0x50, 0xe3, // cmp r0, #0x0
+        0x1e, 0xff, 0x2f, 0x01, // bxeq lr
+        0xff, 0xff, 0xff, 0x1a, // bne thunk, conditional tail call
+        // thunk
+        0x04, 0xe0, 0x2d, 0xe5, // str lr, [sp, #-0x4]!
+        0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1
+        0x04, 0xf0, 0x9d, 0xe4, // ldr pc, [sp], #0x4
+        // not a prologue, only stores r0
+        0x04, 0x00, 0x2d, 0xe5, // str r0, [sp, #-0x4]!
+        0x04, 0x00, 0x9d, 0xe4, // ldr r0, [sp], #0x4
+        0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    ];
+
+    let mut symbol_map = SymbolMap::new();
+    let functions = Function::find_functions(FindFunctionsOptions {
+        default_name_prefix: "func_",
+        base_address,
+        module_code: &code,
+        symbol_map: &mut symbol_map,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        search_options: FunctionSearchOptions::default(),
+        function_overrides: None,
+        data_regions: None,
+        handwritten_code: None,
+        instruction_modes: None,
+        no_epilogue_fallback: None,
+        endian: Endian::Little,
+    })?;
+
+    let function = functions.get(&base_address).expect("function was not found");
+    assert_eq!(function.end_address(), thunk_address, "function must end before the thunk's prologue");
+
+    let thunk = functions.get(&thunk_address).expect("thunk was not found");
+    assert_eq!(thunk.end_address(), thunk_address + 0xc, "ldr pc, [sp], #0x4 must end the thunk");
+
+    let last = functions.get(&(thunk_address + 0xc)).expect("last function was not found");
+    assert_eq!(last.end_address(), base_address + code.len() as u32);
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use ds_decomp::{
+    analysis::strings::{decode_string, StringEncoding},
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::Relocations,
+        section::{Section, SectionKind, Sections},
+        symbol::{SymData, Symbol, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x10] = [
+    // data_02000000: "こんにちは" in Shift-JIS
+    0x82, 0xb1, 0x82, 0xf1, 0x82, 0xc9, 0x82, 0xbf, 0x82, 0xcd, 0x00, 0x00,
+    // data_0200000c: truncated double-byte character
+    0x82, 0x00, 0x00, 0x00,
+];
+
+fn disassemble(string_encoding: Option<StringEncoding>) -> Result<String> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_data("data_02000000".to_string(), 0x02000000, SymData::Any, false));
+    symbol_map.add(Symbol::new_data("data_0200000c".to_string(), 0x0200000c, SymData::Any, false));
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000010, 4)?)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        sections,
+        &CODE,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x02000010, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let disassemble = Disassemble {
+        config_path: PathBuf::new(),
+        asm_path: PathBuf::new(),
+        ual: false,
+        module: None,
+        start: None,
+        end: None,
+        jobs: 1,
+        incbin_threshold: None,
+        encoding: None,
+        single_file: false,
+        address_format: AddressFormat::default(),
+    };
+    let mut output = vec![];
+    disassemble.disassemble(&module, &delink_file, &mut output, &symbol_maps, string_encoding)?;
+    Ok(String::from_utf8(output)?)
+}
+
+#[test]
+fn test_shift_jis_string_comment() -> Result<()> {
+    let assembly = disassemble(Some(StringEncoding::ShiftJis))?;
+    assert!(
+        assembly.contains(
+            "data_02000000:
+    ; \"こんにちは\"
+    .byte 0x82, 0xb1, 0x82, 0xf1, 0x82, 0xc9, 0x82, 0xbf, 0x82, 0xcd, 0x00, 0x00
+"
+        ),
+        "{assembly}"
+    );
+    assert!(
+        assembly.contains(
+            "data_0200000c:
+    .byte 0x82, 0x00, 0x00, 0x00
+"
+        ),
+        "invalid sequences must fall back to bytes:\n{assembly}"
+    );
+
+    let ascii = disassemble(Some(StringEncoding::Ascii))?;
+    assert!(!ascii.contains(';'), "{ascii}");
+    assert_eq!(disassemble(None)?, ascii);
+    Ok(())
+}
+
+#[test]
+fn test_decode_string() {
+    assert_eq!(decode_string(&CODE, StringEncoding::ShiftJis), Some((10, "こんにちは".to_string())));
+    assert_eq!(decode_string(&CODE, StringEncoding::Ascii), None);
+    assert_eq!(decode_string(b"Hello\0", StringEncoding::Ascii), Some((5, "Hello".to_string())));
+    assert_eq!(decode_string(b"\0", StringEncoding::Ascii), None);
+}
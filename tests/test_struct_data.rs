@@ -0,0 +1,126 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::{
+    cmd::Disassemble,
+    config::{
+        delinks::DelinkFile,
+        module::{Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        relocation::{RelocationModule, Relocations},
+        section::{Section, SectionKind, Sections},
+        struct_layouts::StructLayouts,
+        symbol::{SymData, SymbolKind, SymbolMap, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+
+fn write_file(name: &str, contents: &str) -> Result<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("struct_data");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(name);
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+#[test]
+fn test_struct_data() -> Result<()> {
+    #[rustfmt::skip]
+    let code = [
+        0x03, 0x00, 0x00, 0x00, // data_02000000.count
+        0x08, 0x00, 0x00, 0x02, // data_02000000.next
+        0x07, 0x00, 0x00, 0x00, // data_02000008
+    ];
+
+    let layouts_path = write_file(
+        "struct_layouts.txt",
+        "Node field:next offset:0x4 kind:word // Pointer to the next node
+Node field:count offset:0x0 kind:word
+",
+    )?;
+    let symbols_path = write_file(
+        "symbols.txt",
+        "data_02000000 kind:data(struct:Node) addr:0x02000000
+data_02000008 kind:data(word) addr:0x02000008
+",
+    )?;
+
+    let mut symbol_maps = SymbolMaps::new();
+    *symbol_maps.struct_layouts_mut() = StructLayouts::from_file(&layouts_path)?;
+    *symbol_maps.get_mut(ModuleKind::Arm9) = SymbolMap::from_file(&symbols_path)?;
+    symbol_maps.resolve_struct_sizes();
+
+    let (_, symbol) = symbol_maps.get(ModuleKind::Arm9).unwrap().by_address(0x02000000)?.unwrap();
+    assert!(symbol.kind == SymbolKind::Data(SymData::Struct { layout: "Node".to_string(), size: Some(8) }));
+    assert_eq!(symbol.to_string(), "data_02000000 kind:data(struct:Node) addr:0x02000000");
+    // The symbol spans its layout, not everything up to the end of the section
+    assert_eq!(symbol.size(0x0200000c), 8);
+
+    let layout = symbol_maps.struct_layouts().get("Node").unwrap();
+    assert_eq!(layout.size(), 8);
+    assert_eq!(layout.fields().iter().map(|field| field.name.as_str()).collect::<Vec<_>>(), ["count", "next"]);
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x0200000c, 4)?)?;
+    let mut relocations = Relocations::new();
+    relocations.add_load(0x02000004, 0x02000008, 0, RelocationModule::Main)?;
+    let module = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        relocations,
+        sections,
+        &code,
+        &NamePrefixes::default(),
+    )?;
+
+    let mut file_sections = Sections::new();
+    file_sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000000, 0x0200000c, 4)?)?;
+    let delink_file = DelinkFile::new("src/main.c".to_string(), file_sections, false);
+
+    let disassemble = Disassemble {
+        config_path: PathBuf::new(),
+        asm_path: PathBuf::new(),
+        ual: false,
+        module: None,
+        start: None,
+        end: None,
+        jobs: 1,
+        incbin_threshold: None,
+        encoding: None,
+        single_file: false,
+        address_format: AddressFormat::default(),
+    };
+    let mut output = vec![];
+    disassemble.disassemble(&module, &delink_file, &mut output, &symbol_maps, None)?;
+    let assembly = String::from_utf8(output)?;
+
+    assert!(
+        assembly.contains(
+            "data_02000000:
+    ; count
+    .word 0x3
+    ; next
+    .word data_02000008
+data_02000008:
+    .word 0x7
+"
+        ),
+        "{assembly}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_overlapping_struct_fields() -> Result<()> {
+    let path = write_file(
+        "overlapping_fields.txt",
+        "Pair field:first offset:0x0 kind:word
+Pair field:second offset:0x2 kind:short
+",
+    )?;
+    let Err(error) = StructLayouts::from_file(&path) else { panic!("overlapping fields should fail to load") };
+    let message = format!("{error:#}");
+    assert!(message.contains("second") && message.contains("first"), "{message}");
+    Ok(())
+}
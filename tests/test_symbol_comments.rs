@@ -0,0 +1,46 @@
+use std::fs;
+
+use anyhow::Result;
+use ds_decomp::config::symbol::SymbolMap;
+
+const SYMBOLS: &str = "\
+// Entry point
+Entry kind:function(arm,size=0x8) addr:0x02000000
+func_02000008 kind:function(thumb,size=0x4) addr:0x02000008 // Called from Entry
+// Lookup table
+//   indexed by item ID
+data_02000010 kind:data(word[4]) addr:0x02000010 // TODO: find a better name
+// End of the main module
+";
+
+#[test]
+fn test_symbol_comments_roundtrip() -> Result<()> {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_symbol_comments");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("symbols.txt");
+    fs::write(&path, SYMBOLS)?;
+
+    let symbol_map = SymbolMap::from_file(&path)?;
+    assert_eq!(symbol_map.iter().count(), 3);
+    symbol_map.to_file(&path)?;
+    assert_eq!(fs::read_to_string(&path)?, SYMBOLS);
+
+    // Comments stay with their symbol when it is renamed, and are removed with it
+    let mut symbol_map = SymbolMap::from_file(&path)?;
+    symbol_map.rename_by_address(0x02000010, "item_table")?;
+    symbol_map.remove_by_address(0x02000008)?;
+    symbol_map.to_file(&path)?;
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        "\
+// Entry point
+Entry kind:function(arm,size=0x8) addr:0x02000000
+// Lookup table
+//   indexed by item ID
+item_table kind:data(word[4]) addr:0x02000010 // TODO: find a better name
+// End of the main module
+"
+    );
+
+    Ok(())
+}
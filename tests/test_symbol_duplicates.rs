@@ -0,0 +1,67 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::config::symbol::SymbolMap;
+
+fn write_symbols(name: &str, symbols: &str) -> Result<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("symbol_duplicates");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(name);
+    fs::write(&path, symbols)?;
+    Ok(path)
+}
+
+#[test]
+fn test_duplicate_address() -> Result<()> {
+    let path = write_symbols(
+        "duplicate_address.txt",
+        "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:data(word) addr:0x02000100
+MyFunction kind:function(arm,size=0x8) addr:0x02000000
+",
+    )?;
+
+    let Err(error) = SymbolMap::from_file(&path) else { panic!("duplicate address should fail to load") };
+    let message = format!("{error:#}");
+    let path = path.display();
+    assert!(message.contains(&format!("{path}:3")), "{message}");
+    assert!(message.contains(&format!("{path}:1")), "{message}");
+    assert!(message.contains("MyFunction") && message.contains("func_02000000"), "{message}");
+
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_name() -> Result<()> {
+    let path = write_symbols(
+        "duplicate_name.txt",
+        "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+func_02000000 kind:function(arm,size=0x8) addr:0x02000008
+",
+    )?;
+
+    let Err(error) = SymbolMap::from_file(&path) else { panic!("duplicate name should fail to load") };
+    let message = format!("{error:#}");
+    let path = path.display();
+    assert!(message.contains(&format!("{path}:2")), "{message}");
+    assert!(message.contains(&format!("{path}:1")), "{message}");
+
+    Ok(())
+}
+
+#[test]
+fn test_identical_duplicates_are_merged() -> Result<()> {
+    let path = write_symbols(
+        "identical.txt",
+        "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+",
+    )?;
+
+    let symbol_map = SymbolMap::from_file(&path)?;
+    assert_eq!(symbol_map.iter().count(), 1);
+    let (_, symbol) = symbol_map.by_address(0x02000000)?.unwrap();
+    assert_eq!(symbol.name, "func_02000000");
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use ds_decomp::config::{
+    module::ModuleKind,
+    name_prefixes::NamePrefixes,
+    symbol::{MergePolicy, SymData, Symbol, SymbolMaps},
+};
+
+fn arm9_symbols(symbols: &[(&str, u32)]) -> SymbolMaps {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    for &(name, address) in symbols {
+        symbol_map.add(Symbol::new_data(name.to_string(), address, SymData::Any, false));
+    }
+    symbol_maps
+}
+
+fn merge(policy: MergePolicy) -> Result<Vec<(String, u32)>> {
+    let mut symbol_maps = arm9_symbols(&[("data_02000000", 0x02000000), ("gSelfName", 0x02000100)]);
+    let other = arm9_symbols(&[("gCounter", 0x02000000), ("gOtherName", 0x02000100), ("gAdded", 0x02000200)]);
+    symbol_maps.merge(&other, [ModuleKind::Arm9], policy, &NamePrefixes::default())?;
+
+    let symbol_map = symbol_maps.get(ModuleKind::Arm9).unwrap();
+    Ok(symbol_map.iter().map(|symbol| (symbol.name.clone(), symbol.addr)).collect())
+}
+
+#[test]
+fn test_merge_prefer_self() -> Result<()> {
+    assert_eq!(
+        merge(MergePolicy::PreferSelf)?,
+        vec![
+            ("data_02000000".to_string(), 0x02000000),
+            ("gSelfName".to_string(), 0x02000100),
+            ("gAdded".to_string(), 0x02000200)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_merge_prefer_other() -> Result<()> {
+    assert_eq!(
+        merge(MergePolicy::PreferOther)?,
+        vec![("gCounter".to_string(), 0x02000000), ("gOtherName".to_string(), 0x02000100), ("gAdded".to_string(), 0x02000200)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_merge_prefer_named() -> Result<()> {
+    assert_eq!(
+        merge(MergePolicy::PreferNamed)?,
+        vec![("gCounter".to_string(), 0x02000000), ("gSelfName".to_string(), 0x02000100), ("gAdded".to_string(), 0x02000200)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_merge_name_collision() {
+    let mut symbol_maps = arm9_symbols(&[("gCounter", 0x02000000)]);
+    let other = arm9_symbols(&[("gCounter", 0x02000004)]);
+    assert!(symbol_maps.merge(&other, [ModuleKind::Arm9], MergePolicy::PreferSelf, &NamePrefixes::default()).is_err());
+}
+
+#[test]
+fn test_merge_address_with_several_symbols() -> Result<()> {
+    // A data symbol and a label share the address, only the data symbol is renamed
+    let mut symbol_maps = arm9_symbols(&[("data_02000000", 0x02000000)]);
+    symbol_maps.get_mut(ModuleKind::Arm9).add(Symbol::new_label("_02000000".to_string(), 0x02000000, false));
+    let other = arm9_symbols(&[("gCounter", 0x02000000)]);
+
+    let renames = symbol_maps.merge(&other, [ModuleKind::Arm9], MergePolicy::PreferNamed, &NamePrefixes::default())?;
+    assert_eq!(renames.len(), 1);
+    assert_eq!((renames[0].old_name.as_str(), renames[0].new_name.as_str()), ("data_02000000", "gCounter"));
+
+    let symbol_map = symbol_maps.get(ModuleKind::Arm9).unwrap();
+    let names = symbol_map.iter().map(|symbol| symbol.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["gCounter", "_02000000"]);
+    Ok(())
+}
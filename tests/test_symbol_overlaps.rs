@@ -0,0 +1,76 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::config::{
+    module::ModuleKind,
+    name_prefixes::NamePrefixes,
+    symbol::{InstructionMode, MergePolicy, Symbol, SymbolMap, SymbolMaps},
+};
+
+#[test]
+fn test_function_overlap_check() {
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 0x10));
+    symbol_map.add(Symbol::new_function("func_02000020".to_string(), 0x02000020, InstructionMode::Arm, 0x10));
+
+    // Fits in the gap between the two functions
+    assert!(symbol_map
+        .check_function_overlap(&Symbol::new_function("func_02000010".to_string(), 0x02000010, InstructionMode::Arm, 0x10))
+        .is_ok());
+    // Starts inside the previous function
+    assert!(symbol_map
+        .check_function_overlap(&Symbol::new_function("func_02000008".to_string(), 0x02000008, InstructionMode::Arm, 0x8))
+        .is_err());
+    // Extends past the start of the next function
+    assert!(symbol_map
+        .check_function_overlap(&Symbol::new_function("func_02000010".to_string(), 0x02000010, InstructionMode::Arm, 0x14))
+        .is_err());
+    // Same address as an existing function
+    assert!(symbol_map
+        .check_function_overlap(&Symbol::new_function("Other".to_string(), 0x02000020, InstructionMode::Arm, 0x4))
+        .is_err());
+
+    // Functions of unknown size can't overlap
+    let unknown = Symbol::new_unknown_function("func_02000004".to_string(), 0x02000004, false);
+    assert!(symbol_map.check_function_overlap(&unknown).is_ok());
+}
+
+#[test]
+fn test_load_overlapping_functions() -> Result<()> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("symbol_overlaps");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("symbols.txt");
+    fs::write(
+        &path,
+        "func_02000010 kind:function(arm,size=0x10) addr:0x02000010
+func_02000000 kind:function(arm,size=0x14) addr:0x02000000
+",
+    )?;
+
+    let error = SymbolMap::from_file(&path).err().expect("overlapping functions should fail to load");
+    let message = format!("{error:#}");
+    assert!(message.contains("func_02000000"), "{message}");
+    assert!(message.contains("func_02000010"), "{message}");
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_overlapping_functions() {
+    let mut symbol_maps = SymbolMaps::new();
+    symbol_maps.get_mut(ModuleKind::Arm9).add(Symbol::new_function(
+        "func_02000000".to_string(),
+        0x02000000,
+        InstructionMode::Arm,
+        0x10,
+    ));
+    let mut other = SymbolMaps::new();
+    other.get_mut(ModuleKind::Arm9).add(Symbol::new_function(
+        "func_02000008".to_string(),
+        0x02000008,
+        InstructionMode::Arm,
+        0x10,
+    ));
+
+    assert!(symbol_maps.merge(&other, MergePolicy::PreferSelf, &NamePrefixes::default()).is_err());
+}
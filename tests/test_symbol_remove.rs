@@ -0,0 +1,78 @@
+use anyhow::Result;
+use ds_decomp::config::symbol::{Symbol, SymbolMap};
+
+fn label(name: &str, addr: u32) -> Symbol {
+    Symbol::new_label(name.to_string(), addr, false)
+}
+
+fn names(symbol_map: &SymbolMap) -> Vec<&str> {
+    symbol_map.iter().map(|symbol| symbol.name.as_str()).collect()
+}
+
+#[test]
+fn test_remove() -> Result<()> {
+    let mut symbol_map = SymbolMap::new();
+    symbol_map.add(label("first", 0x02000000));
+    let (second, _) = symbol_map.add(label("second", 0x02000004));
+    let (third, _) = symbol_map.add(label("third", 0x02000008));
+
+    let removed = symbol_map.remove(second)?;
+    assert_eq!(removed.name, "second");
+    assert!(symbol_map.by_address(0x02000004)?.is_none());
+    assert!(symbol_map.by_name("second")?.is_none());
+    assert!(symbol_map.remove(second).is_err(), "removing twice should fail");
+
+    // Indices of later symbols are still valid
+    let (index, symbol) = symbol_map.by_name("third")?.unwrap();
+    assert!(index == third);
+    assert_eq!(symbol.addr, 0x02000008);
+    assert_eq!(names(&symbol_map), ["first", "third"]);
+
+    // The address can be reused
+    symbol_map.add(label("new_second", 0x02000004));
+    assert_eq!(symbol_map.by_address(0x02000004)?.unwrap().1.name, "new_second");
+    assert_eq!(names(&symbol_map), ["first", "new_second", "third"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_remove_multiple_at_address() -> Result<()> {
+    let mut symbol_map = SymbolMap::new();
+    let (first, _) = symbol_map.add(label("first", 0x02000000));
+    symbol_map.add(label("second", 0x02000000));
+    symbol_map.add(label("other", 0x02000004));
+
+    symbol_map.remove(first)?;
+    let (_, symbol) = symbol_map.by_address(0x02000000)?.expect("one symbol should be left");
+    assert_eq!(symbol.name, "second");
+
+    symbol_map.add(label("third", 0x02000000));
+    let removed = symbol_map.remove_by_address(0x02000000)?;
+    assert_eq!(removed.iter().map(|symbol| symbol.name.as_str()).collect::<Vec<_>>(), ["second", "third"]);
+    assert!(symbol_map.for_address(0x02000000).is_none());
+    assert!(symbol_map.by_name("second")?.is_none());
+    assert!(symbol_map.by_name("third")?.is_none());
+    assert_eq!(names(&symbol_map), ["other"]);
+
+    assert!(symbol_map.remove_by_address(0x02000000)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_remove_shared_name() -> Result<()> {
+    let mut symbol_map = SymbolMap::new();
+    let (first, _) = symbol_map.add(label("shared", 0x02000000));
+    symbol_map.add(label("shared", 0x02000004));
+
+    symbol_map.remove(first)?;
+    let (_, symbol) = symbol_map.by_name("shared")?.expect("other symbol with the same name should be left");
+    assert_eq!(symbol.addr, 0x02000004);
+
+    symbol_map.rename_by_address(0x02000004, "renamed")?;
+    assert!(symbol_map.by_name("shared")?.is_none());
+    assert_eq!(symbol_map.by_name("renamed")?.unwrap().1.addr, 0x02000004);
+
+    Ok(())
+}
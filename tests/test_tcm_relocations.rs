@@ -0,0 +1,87 @@
+use anyhow::Result;
+use ds_decomp::{
+    config::{
+        module::{AnalysisOptions, Module, ModuleKind},
+        name_prefixes::NamePrefixes,
+        program::Program,
+        relocation::{RelocationModule, Relocations},
+        section::{Section, SectionKind, Sections},
+        symbol::{SymbolLookup, SymbolMaps},
+    },
+    util::address::AddressFormat,
+};
+use ds_rom::rom::raw::AutoloadKind;
+use unarm::Endian;
+
+const ITCM: ModuleKind = ModuleKind::Autoload(AutoloadKind::Itcm);
+
+/// Main has a pointer to 0x01ff8010, which is inside both the ITCM and overlay 0
+static MAIN_CODE: [u8; 4] = 0x01ff8010u32.to_le_bytes();
+static ITCM_CODE: [u8; 0x20] = [0; 0x20];
+static OV000_CODE: [u8; 0x20] = [0; 0x20];
+
+fn data_sections(start: u32, end: u32) -> Result<Sections> {
+    let mut sections = Sections::new();
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, start, end, 4)?)?;
+    Ok(sections)
+}
+
+#[test]
+fn test_pointer_into_itcm() -> Result<()> {
+    let name_prefixes = NamePrefixes::default();
+    let mut symbol_maps = SymbolMaps::new();
+
+    let main = Module::new_arm9(
+        "main".to_string(),
+        symbol_maps.get_mut(ModuleKind::Arm9),
+        Relocations::new(),
+        data_sections(0x02000000, 0x02000004)?,
+        &MAIN_CODE,
+        &name_prefixes,
+    )?;
+    let overlay = Module::new_overlay(
+        "ov000".to_string(),
+        symbol_maps.get_mut(ModuleKind::Overlay(0)),
+        Relocations::new(),
+        data_sections(0x01ff8000, 0x01ff8020)?,
+        0,
+        &OV000_CODE,
+        &name_prefixes,
+    )?;
+    let itcm = Module::new_autoload(
+        "itcm".to_string(),
+        symbol_maps.get_mut(ITCM),
+        Relocations::new(),
+        data_sections(0x01ff8000, 0x01ff8020)?,
+        AutoloadKind::Itcm,
+        &ITCM_CODE,
+        &name_prefixes,
+    )?;
+
+    let mut program = Program::new(main, vec![overlay], vec![itcm], symbol_maps);
+    program.analyze_cross_references(&AnalysisOptions { name_prefixes, ..Default::default() })?;
+
+    let relocations = program.main().relocations();
+    let relocation = relocations.get(0x02000000).expect("pointer was not relocated");
+    assert!(*relocation.module() == RelocationModule::Itcm, "{}", relocation.module());
+
+    let symbol_maps = program.symbol_maps();
+    let (_, symbol) = symbol_maps.get(ITCM).unwrap().get_data(0x01ff8010)?.expect("no symbol in ITCM");
+    assert!(!symbol.ambiguous);
+    assert!(symbol_maps.get(ModuleKind::Overlay(0)).unwrap().get_data(0x01ff8010)?.is_none());
+
+    let symbol_lookup = SymbolLookup {
+        module_kind: ModuleKind::Arm9,
+        symbol_map: symbol_maps.get(ModuleKind::Arm9).unwrap(),
+        symbol_maps,
+        relocations,
+        endian: Endian::Little,
+        string_encoding: None,
+        address_format: AddressFormat::default(),
+    };
+    let mut output = vec![];
+    assert!(symbol_lookup.write_symbol(&mut output, 0x02000000, 0x01ff8010, &mut false, "    ")?);
+    assert_eq!(String::from_utf8(output)?, format!("    .word {}\n", symbol.name));
+
+    Ok(())
+}
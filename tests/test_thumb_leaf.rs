@@ -0,0 +1,66 @@
+use anyhow::Result;
+use ds_decomp::analysis::functions::{Function, FunctionParseOptions, ParseFunctionOptions, ParseFunctionResult};
+
+fn parse_thumb(code: &[u8], base_address: u32, start_address: u32) -> Result<Function> {
+    let result = Function::parse_function(FunctionParseOptions {
+        name: format!("func_{start_address:08x}"),
+        start_address,
+        base_address,
+        module_code: code,
+        known_end_address: None,
+        module_start_address: base_address,
+        module_end_address: base_address + code.len() as u32,
+        parse_options: ParseFunctionOptions { thumb: Some(true), ..Default::default() },
+    })?;
+    let ParseFunctionResult::Found(function) = result else {
+        panic!("function at {start_address:#010x} was not found: {result:?}");
+    };
+    Ok(function)
+}
+
+#[test]
+fn test_thumb_getter_after_tail_call() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        // tail call
+        0x00, 0x20, // movs r0, #0x0
+        0x7d, 0xe0, // b 0x02000100
+        // getter
+        0x01, 0x20, // movs r0, #0x1
+        0x70, 0x47, // bx lr
+    ];
+
+    let tail_call = parse_thumb(&code, base_address, base_address)?;
+    assert_eq!(tail_call.end_address(), base_address + 4, "getter must not be part of the previous function");
+    assert_eq!(tail_call.function_calls().get(&(base_address + 2)).map(|call| call.address), Some(base_address + 0x100));
+
+    let getter = parse_thumb(&code, base_address, base_address + 4)?;
+    assert_eq!(getter.end_address(), base_address + 8);
+    assert!(getter.is_thumb());
+
+    Ok(())
+}
+
+#[test]
+fn test_thumb_pop_pc_after_tail_call() -> Result<()> {
+    let base_address = 0x02000000;
+    #[rustfmt::skip]
+    let code = [
+        // tail call
+        0x00, 0x20, // movs r0, #0x0
+        0x7d, 0xe0, // b 0x02000100
+        // getter
+        0x00, 0xb5, // push {lr}
+        0x01, 0x20, // movs r0, #0x1
+        0x00, 0xbd, // pop {pc}
+    ];
+
+    let tail_call = parse_thumb(&code, base_address, base_address)?;
+    assert_eq!(tail_call.end_address(), base_address + 4, "getter must not be part of the previous function");
+
+    let getter = parse_thumb(&code, base_address, base_address + 4)?;
+    assert_eq!(getter.end_address(), base_address + 10);
+
+    Ok(())
+}
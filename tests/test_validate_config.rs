@@ -0,0 +1,140 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use ds_decomp::cmd::ValidateConfig;
+
+const CONFIG: &str = "\
+rom_config: rom/config.yaml
+build_path: build
+delinks_path: build/delinks
+main_module:
+  name: main
+  object: build/arm9.bin
+  hash: '0000000000000000'
+  delinks: delinks.txt
+  symbols: symbols.txt
+  relocations: relocs.txt
+autoloads: []
+overlays:
+- id: 0
+  name: ov000
+  object: build/ov000.bin
+  hash: '0000000000000000'
+  delinks: overlays/ov000/delinks.txt
+  symbols: overlays/ov000/symbols.txt
+  relocations: overlays/ov000/relocs.txt
+";
+
+const MAIN_DELINKS: &str = "    .text       start:0x02000000 end:0x02000100 kind:code align:32
+    .bss        start:0x02000100 end:0x02000200 kind:bss align:32
+
+";
+const MAIN_SYMBOLS: &str = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+data_02000100 kind:bss addr:0x02000100
+";
+const MAIN_RELOCS: &str = "from:0x02000004 kind:load to:0x02100000 module:overlay(0)
+";
+
+const OVERLAY_DELINKS: &str = "    .text       start:0x02100000 end:0x02100100 kind:code align:32
+
+";
+const OVERLAY_SYMBOLS: &str = "func_ov000_02100000 kind:function(arm,size=0x8) addr:0x02100000
+";
+
+struct TestConfig<'a> {
+    main_delinks: &'a str,
+    main_symbols: &'a str,
+    main_relocs: &'a str,
+    overlay_delinks: &'a str,
+    overlay_symbols: &'a str,
+}
+
+impl Default for TestConfig<'_> {
+    fn default() -> Self {
+        Self {
+            main_delinks: MAIN_DELINKS,
+            main_symbols: MAIN_SYMBOLS,
+            main_relocs: MAIN_RELOCS,
+            overlay_delinks: OVERLAY_DELINKS,
+            overlay_symbols: OVERLAY_SYMBOLS,
+        }
+    }
+}
+
+impl TestConfig<'_> {
+    fn validate(&self, name: &str) -> Result<Vec<String>> {
+        let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("validate_config").join(name);
+        let overlay_path = path.join("overlays/ov000");
+        fs::create_dir_all(path.join("rom"))?;
+        fs::create_dir_all(&overlay_path)?;
+
+        fs::write(path.join("config.yaml"), CONFIG)?;
+        fs::write(path.join("rom/config.yaml"), "")?;
+        fs::write(path.join("delinks.txt"), self.main_delinks)?;
+        fs::write(path.join("symbols.txt"), self.main_symbols)?;
+        fs::write(path.join("relocs.txt"), self.main_relocs)?;
+        fs::write(overlay_path.join("delinks.txt"), self.overlay_delinks)?;
+        fs::write(overlay_path.join("symbols.txt"), self.overlay_symbols)?;
+        fs::write(overlay_path.join("relocs.txt"), "")?;
+
+        ValidateConfig { config_path: path.join("config.yaml") }.validate()
+    }
+}
+
+#[test]
+fn test_valid_config() -> Result<()> {
+    let problems = TestConfig::default().validate("valid")?;
+    assert!(problems.is_empty(), "{problems:#?}");
+    Ok(())
+}
+
+#[test]
+fn test_bad_symbols() -> Result<()> {
+    let main_symbols = "func_02000000 kind:function(arm,size=0x8) addr:0x02000000
+func_02000010 kind:function(arm) addr:0x02000010
+func_02000300 kind:function(arm,size=0x8) addr:0x02000300
+func_020001fc kind:function(arm,size=0x10) addr:0x020001fc
+";
+    let problems = TestConfig { main_symbols, ..Default::default() }.validate("bad_symbols")?;
+    assert_eq!(problems.len(), 3, "{problems:#?}");
+    assert!(problems.iter().any(|p| p.contains("symbols.txt:2") && p.contains("must have a size")), "{problems:#?}");
+    assert!(problems.iter().any(|p| p.contains("symbols.txt:3") && p.contains("outside of ARM9 main")), "{problems:#?}");
+    assert!(problems.iter().any(|p| p.contains("symbols.txt:4") && p.contains("ends after ARM9 main")), "{problems:#?}");
+    Ok(())
+}
+
+#[test]
+fn test_missing_overlay() -> Result<()> {
+    let main_relocs = "from:0x02000004 kind:load to:0x02100000 module:overlay(5)
+";
+    let problems = TestConfig { main_relocs, ..Default::default() }.validate("missing_overlay")?;
+    assert_eq!(problems.len(), 1, "{problems:#?}");
+    assert!(problems[0].contains("overlay 5"), "{problems:#?}");
+    Ok(())
+}
+
+#[test]
+fn test_bad_relocations() -> Result<()> {
+    let main_relocs = "from:0x02000004 kind:load to:0x02100000 module:overlay(0)
+from:0x02000008 kind:bogus to:0x02100000 module:overlay(0)
+from:0x0200000c kind:load to:0x02100000 module:nowhere
+";
+    let problems = TestConfig { main_relocs, ..Default::default() }.validate("bad_relocations")?;
+    assert_eq!(problems.len(), 2, "{problems:#?}");
+    assert!(problems.iter().any(|p| p.contains("relocs.txt:2")), "{problems:#?}");
+    assert!(problems.iter().any(|p| p.contains("relocs.txt:3")), "{problems:#?}");
+    Ok(())
+}
+
+#[test]
+fn test_overlapping_modules() -> Result<()> {
+    let overlay_delinks = "    .text       start:0x02000100 end:0x02000200 kind:code align:32
+
+";
+    let overlay_symbols = "func_ov000_02000100 kind:function(arm,size=0x8) addr:0x02000100
+";
+    let problems = TestConfig { overlay_delinks, overlay_symbols, ..Default::default() }.validate("overlapping_modules")?;
+    assert_eq!(problems.len(), 1, "{problems:#?}");
+    assert!(problems[0].contains("overlaps with overlay 0"), "{problems:#?}");
+    Ok(())
+}
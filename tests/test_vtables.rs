@@ -0,0 +1,84 @@
+use anyhow::Result;
+use ds_decomp::config::{
+    module::{AnalysisOptions, Module, ModuleKind},
+    name_prefixes::NamePrefixes,
+    relocation::Relocations,
+    section::{Section, SectionKind, Sections},
+    symbol::{InstructionMode, SymData, Symbol, SymbolMaps},
+};
+
+#[rustfmt::skip]
+const CODE: [u8; 0x28] = [
+    0x00, 0x00, 0xa0, 0xe3, // func_02000000: mov r0, #0x0
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x01, 0x00, 0xa0, 0xe3, // mov r0, #0x1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x02, 0x00, 0xa0, 0xe3, // mov r0, #0x2
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    0x00, 0x00, 0x00, 0x00, // header
+    0x00, 0x00, 0x00, 0x02, // .word func_02000000
+    0x08, 0x00, 0x00, 0x02, // .word 0x02000008
+    0x10, 0x00, 0x00, 0x02, // .word 0x02000010
+];
+
+/// Finds vtables in [`CODE`], after adding a data symbol at `data_address` if given. Checks that the entries are relocated
+/// and returns the symbols.
+fn find_vtables(data_address: Option<u32>) -> Result<SymbolMaps> {
+    let mut symbol_maps = SymbolMaps::new();
+    let symbol_map = symbol_maps.get_mut(ModuleKind::Arm9);
+    symbol_map.add(Symbol::new_function("func_02000000".to_string(), 0x02000000, InstructionMode::Arm, 8));
+    if let Some(data_address) = data_address {
+        symbol_map.add_data(Some(format!("data_{data_address:08x}")), data_address, SymData::Any)?;
+    }
+
+    let mut sections = Sections::new();
+    sections.add(Section::new(".text".to_string(), SectionKind::Code, 0x02000000, 0x02000018, 4)?)?;
+    sections.add(Section::new(".data".to_string(), SectionKind::Data, 0x02000018, 0x02000028, 4)?)?;
+    let mut module =
+        Module::new_arm9("main".to_string(), symbol_map, Relocations::new(), sections, &CODE, &NamePrefixes::default())?;
+
+    let options = AnalysisOptions { find_vtables: true, ..Default::default() };
+    module.find_vtables(symbol_maps.get_mut(ModuleKind::Arm9), &options)?;
+
+    for address in [0x02000008, 0x02000010] {
+        assert!(module.get_function(address).is_some(), "function at {address:#010x} was not added to the module");
+    }
+    let relocations = module.relocations().iter().map(|relocation| (relocation.from_address(), relocation.to_address()));
+    assert_eq!(
+        relocations.collect::<Vec<_>>(),
+        [(0x0200001c, 0x02000000), (0x02000020, 0x02000008), (0x02000024, 0x02000010)]
+    );
+
+    Ok(symbol_maps)
+}
+
+#[test]
+fn test_vtable_functions() -> Result<()> {
+    let symbol_maps = find_vtables(None)?;
+    let symbol_map = symbol_maps.get(ModuleKind::Arm9).unwrap();
+
+    for address in [0x02000008, 0x02000010] {
+        let (function, symbol) = symbol_map.get_function(address)?.expect("vtable target was not promoted to a function");
+        assert_eq!(symbol.name, format!("func_{address:08x}"));
+        assert_eq!(function.size, 8);
+    }
+
+    let (data, symbol) = symbol_map.get_data(0x0200001c)?.expect("vtable symbol was not added");
+    assert_eq!(symbol.name, "vtable_0200001c");
+    assert!(*data == SymData::Word { count: Some(3) });
+
+    Ok(())
+}
+
+#[test]
+fn test_vtable_header() -> Result<()> {
+    // The header is referenced instead of the first entry
+    let symbol_maps = find_vtables(Some(0x02000018))?;
+    let symbol_map = symbol_maps.get(ModuleKind::Arm9).unwrap();
+
+    let (_, symbol) = symbol_map.by_address(0x02000018)?.unwrap();
+    assert_eq!(symbol.name, "vtable_02000018");
+    assert!(symbol_map.by_address(0x0200001c)?.is_none());
+
+    Ok(())
+}